@@ -0,0 +1,295 @@
+/*!
+ * Governance Smart Contract
+ * 
+ * Lets holders of a governance token vote on a P2P marketplace's fee rate and trade
+ * limits. The marketplace registers this contract as its governance executor, after
+ * which it accepts those parameter updates only from here.
+ * 
+ * Business Logic:
+ * 1. Admin deploys governance for a market, configured by its constructor, and registers
+ *    it with `set_governance_executor`
+ * 2. Holders of at least the proposal threshold open proposals for a parameter change
+ * 3. Token holders vote by locking tokens for or against until voting closes
+ * 4. Passed proposals are executed against the marketplace by anyone, within the
+ *    execution window and unless a newer proposal for the same parameter ran first
+ * 5. Voters withdraw their locked tokens once voting has closed
+ */
+
+#![no_std]
+use soroban_sdk::{contract, contractimpl, token, Address, Env, Symbol, symbol_short};
+
+pub mod marketplace;
+mod types;
+use marketplace::MarketplaceClient;
+use types::{Error, ParameterChange, Proposal, PROPOSAL_CREATED, PROPOSAL_EXECUTED, VOTE_CAST};
+
+// Storage keys for contract state
+const TOKEN_KEY: Symbol = symbol_short!("TOKEN");                    // Governance token votes are weighted by (persistent)
+const MARKET_KEY: Symbol = symbol_short!("MARKET");                  // Marketplace the proposals execute against (persistent)
+const PERIOD_KEY: Symbol = symbol_short!("PERIOD");                  // Voting period in seconds (persistent)
+const QUORUM_KEY: Symbol = symbol_short!("QUORUM");                  // Minimum tokens voting for a proposal to pass (persistent)
+const THRESHOLD_KEY: Symbol = symbol_short!("THRESHOLD");            // Minimum token balance to open a proposal (persistent)
+const PROPOSAL_KEY: Symbol = symbol_short!("PROP");                  // Proposal, keyed (PROP, proposal_id) (persistent)
+const LOCK_KEY: Symbol = symbol_short!("LOCK");                      // Tokens locked, keyed (LOCK, proposal_id, voter) (persistent)
+const EXECUTED_KEY: Symbol = symbol_short!("EXECUTED");              // Last executed proposal ID, keyed (EXECUTED, parameter) (persistent)
+const NEXT_ID_KEY: Symbol = symbol_short!("NEXT_ID");                // Counter for proposal IDs (instance)
+
+const EXECUTION_WINDOW: u64 = 7 * 86_400;                            // 7 days - How long a passed proposal can be executed after voting closes
+const LEDGERS_PER_DAY: u32 = 17_280;                                 // ~5 second ledgers
+const ENTRY_TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;               // 30 days - Proposals and locks are extended once their TTL drops below this
+const ENTRY_TTL_EXTEND: u32 = 120 * LEDGERS_PER_DAY;                 // 120 days - TTL a proposal or lock is extended to on each write
+
+#[contract]
+pub struct Governance;
+
+#[contractimpl]
+impl Governance {
+    /// Configures governance for a marketplace at deployment.
+    /// Runs atomically with the deployment, so nobody can pin the token, market or quorum
+    /// before the admin registers this contract as the marketplace's executor.
+    /// 
+    /// # Arguments
+    /// * `token` - Governance token whose holders vote
+    /// * `market` - Marketplace that executes passed proposals
+    /// * `voting_period` - Seconds a proposal stays open for voting
+    /// * `quorum` - Minimum tokens voting in favour for a proposal to pass
+    /// * `proposal_threshold` - Minimum token balance needed to open a proposal
+    /// 
+    /// # Errors
+    /// - InvalidAmount: If the voting period, quorum or proposal threshold is zero
+    pub fn __constructor(
+        env: Env,
+        token: Address,
+        market: Address,
+        voting_period: u64,
+        quorum: i128,
+        proposal_threshold: i128,
+    ) -> Result<(), Error> {
+        if voting_period == 0 || quorum <= 0 || proposal_threshold <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        env.storage().persistent().set(&TOKEN_KEY, &token);
+        env.storage().persistent().set(&MARKET_KEY, &market);
+        env.storage().persistent().set(&PERIOD_KEY, &voting_period);
+        env.storage().persistent().set(&QUORUM_KEY, &quorum);
+        env.storage().persistent().set(&THRESHOLD_KEY, &proposal_threshold);
+        env.storage().instance().set(&NEXT_ID_KEY, &0u64);
+
+        Ok(())
+    }
+
+    /// Opens a vote on a marketplace parameter change.
+    /// 
+    /// # Arguments
+    /// * `proposer` - The address opening the proposal (must sign transaction)
+    /// * `change` - The parameter change applied if the proposal passes
+    /// 
+    /// # Returns
+    /// The ID of the new proposal
+    /// 
+    /// # Errors
+    /// - NotInitialized: If governance was never initialized
+    /// - BelowThreshold: If the proposer holds fewer tokens than the proposal threshold
+    pub fn propose(env: Env, proposer: Address, change: ParameterChange) -> Result<u64, Error> {
+        // Verify the proposer has signed this transaction
+        proposer.require_auth();
+
+        let voting_period: u64 = env.storage().persistent().get(&PERIOD_KEY).ok_or(Error::NotInitialized)?;
+
+        // Only token holders with a real stake can put changes to a vote
+        let threshold: i128 = env.storage().persistent().get(&THRESHOLD_KEY).unwrap();
+        let token_id: Address = env.storage().persistent().get(&TOKEN_KEY).unwrap();
+        if token::Client::new(&env, &token_id).balance(&proposer) < threshold {
+            return Err(Error::BelowThreshold);
+        }
+
+        let proposal_id: u64 = env.storage().instance().get(&NEXT_ID_KEY).unwrap();
+        let ends_at = env.ledger().timestamp() + voting_period;
+
+        Self::_set_proposal(&env, proposal_id, &Proposal {
+            proposer,
+            change: change.clone(),
+            votes_for: 0,
+            votes_against: 0,
+            ends_at,
+            executed: false,
+        });
+        env.storage().instance().set(&NEXT_ID_KEY, &(proposal_id + 1));
+
+        env.events().publish((PROPOSAL_CREATED,), (proposal_id, change, ends_at));
+
+        Ok(proposal_id)
+    }
+
+    /// Votes on a proposal by locking governance tokens until voting closes.
+    /// 
+    /// # Arguments
+    /// * `voter` - The token holder voting (must sign transaction)
+    /// * `proposal_id` - The proposal voted on
+    /// * `support` - True to vote for, false to vote against
+    /// * `amount` - Tokens to lock; the vote's weight
+    /// 
+    /// # Errors
+    /// - ProposalNotFound: If the proposal doesn't exist
+    /// - VotingClosed: If voting on the proposal has ended
+    /// - InvalidAmount: If amount is not positive
+    pub fn vote(env: Env, voter: Address, proposal_id: u64, support: bool, amount: i128) -> Result<(), Error> {
+        // Verify the voter has signed this transaction
+        voter.require_auth();
+
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id).ok_or(Error::ProposalNotFound)?;
+        if env.ledger().timestamp() >= proposal.ends_at {
+            return Err(Error::VotingClosed);
+        }
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Lock the tokens so they can't vote again from another account
+        let token_id: Address = env.storage().persistent().get(&TOKEN_KEY).unwrap();
+        token::Client::new(&env, &token_id).transfer(&voter, &env.current_contract_address(), &amount);
+
+        let key = (LOCK_KEY, proposal_id, voter.clone());
+        let locked = Self::get_locked(env.clone(), proposal_id, voter.clone());
+        env.storage().persistent().set(&key, &(locked + amount));
+        env.storage().persistent().extend_ttl(&key, ENTRY_TTL_THRESHOLD, ENTRY_TTL_EXTEND);
+
+        if support {
+            proposal.votes_for += amount;
+        } else {
+            proposal.votes_against += amount;
+        }
+        Self::_set_proposal(&env, proposal_id, &proposal);
+
+        env.events().publish((VOTE_CAST, voter), (proposal_id, support, amount));
+
+        Ok(())
+    }
+
+    /// Applies a passed proposal to the marketplace. Anyone can call this once voting has closed.
+    /// 
+    /// # Business Logic
+    /// - Passes when votes for reach the quorum and outnumber votes against
+    /// - Must be executed within the execution window after voting closes
+    /// - Can't run once a newer proposal for the same parameter has been executed,
+    ///   so a stale result can't revert a more recent decision
+    /// - The marketplace validates the new values exactly as for an admin update
+    /// 
+    /// # Errors
+    /// - ProposalNotFound: If the proposal doesn't exist
+    /// - VotingOpen: If voting has not yet closed
+    /// - AlreadyExecuted: If the proposal was already applied
+    /// - ProposalRejected: If the proposal did not pass
+    /// - ProposalExpired: If the execution window has passed
+    /// - Superseded: If a newer proposal for the same parameter was already executed
+    pub fn execute(env: Env, proposal_id: u64) -> Result<(), Error> {
+        let mut proposal = Self::get_proposal(env.clone(), proposal_id).ok_or(Error::ProposalNotFound)?;
+        if env.ledger().timestamp() < proposal.ends_at {
+            return Err(Error::VotingOpen);
+        }
+        if proposal.executed {
+            return Err(Error::AlreadyExecuted);
+        }
+        let quorum: i128 = env.storage().persistent().get(&QUORUM_KEY).unwrap();
+        if proposal.votes_for < quorum || proposal.votes_for <= proposal.votes_against {
+            return Err(Error::ProposalRejected);
+        }
+        if env.ledger().timestamp() > proposal.ends_at + EXECUTION_WINDOW {
+            return Err(Error::ProposalExpired);
+        }
+        let executed_key = (EXECUTED_KEY, Self::_parameter(&proposal.change));
+        let last_executed: Option<u64> = env.storage().persistent().get(&executed_key);
+        if last_executed.is_some_and(|last| last > proposal_id) {
+            return Err(Error::Superseded);
+        }
+
+        // Mark executed before calling out to the marketplace
+        proposal.executed = true;
+        Self::_set_proposal(&env, proposal_id, &proposal);
+        env.storage().persistent().set(&executed_key, &proposal_id);
+
+        let market: Address = env.storage().persistent().get(&MARKET_KEY).unwrap();
+        let client = MarketplaceClient::new(&env, &market);
+        match proposal.change {
+            ParameterChange::FeeRate(new_fee_rate) => client.update_fee_rate(&new_fee_rate),
+            ParameterChange::TradeLimits(min_amount, max_amount) => client.update_trade_limits(&min_amount, &max_amount),
+            ParameterChange::GovernanceExecutor(executor) => client.set_governance_executor(&executor),
+        }
+
+        env.events().publish((PROPOSAL_EXECUTED,), (proposal_id,));
+
+        Ok(())
+    }
+
+    /// Returns a voter's locked tokens once voting on the proposal has closed.
+    /// 
+    /// # Returns
+    /// The amount of tokens returned
+    /// 
+    /// # Errors
+    /// - ProposalNotFound: If the proposal doesn't exist
+    /// - VotingOpen: If voting has not yet closed
+    /// - NothingLocked: If the voter has no tokens locked on the proposal
+    pub fn withdraw(env: Env, voter: Address, proposal_id: u64) -> Result<i128, Error> {
+        // Verify the voter has signed this transaction
+        voter.require_auth();
+
+        let proposal = Self::get_proposal(env.clone(), proposal_id).ok_or(Error::ProposalNotFound)?;
+        if env.ledger().timestamp() < proposal.ends_at {
+            return Err(Error::VotingOpen);
+        }
+
+        let key = (LOCK_KEY, proposal_id, voter.clone());
+        let amount: i128 = env.storage().persistent().get(&key).ok_or(Error::NothingLocked)?;
+        env.storage().persistent().remove(&key);
+
+        let token_id: Address = env.storage().persistent().get(&TOKEN_KEY).unwrap();
+        token::Client::new(&env, &token_id).transfer(&env.current_contract_address(), &voter, &amount);
+
+        Ok(amount)
+    }
+
+    // ========== INTERNAL HELPERS ==========
+
+    /// Internal helper to store a proposal, keeping it alive until its voters have withdrawn
+    fn _set_proposal(env: &Env, proposal_id: u64, proposal: &Proposal) {
+        let key = (PROPOSAL_KEY, proposal_id);
+        env.storage().persistent().set(&key, proposal);
+        env.storage().persistent().extend_ttl(&key, ENTRY_TTL_THRESHOLD, ENTRY_TTL_EXTEND);
+    }
+
+    /// Internal helper to name the marketplace parameter a change sets, so proposals for
+    /// the same parameter can be ordered against each other
+    fn _parameter(change: &ParameterChange) -> Symbol {
+        match change {
+            ParameterChange::FeeRate(_) => symbol_short!("fee_rate"),
+            ParameterChange::TradeLimits(..) => symbol_short!("limits"),
+            ParameterChange::GovernanceExecutor(_) => symbol_short!("executor"),
+        }
+    }
+
+    // ========== VIEW FUNCTIONS ==========
+
+    /// Returns a proposal by ID.
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Option<Proposal> {
+        env.storage().persistent().get(&(PROPOSAL_KEY, proposal_id))
+    }
+
+    /// Returns the tokens a voter has locked on a proposal.
+    pub fn get_locked(env: Env, proposal_id: u64, voter: Address) -> i128 {
+        env.storage().persistent().get(&(LOCK_KEY, proposal_id, voter)).unwrap_or(0)
+    }
+
+    /// Returns the minimum token balance needed to open a proposal.
+    pub fn get_proposal_threshold(env: Env) -> i128 {
+        env.storage().persistent().get(&THRESHOLD_KEY).unwrap_or(0)
+    }
+
+    /// Returns the marketplace proposals execute against.
+    pub fn get_market(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&MARKET_KEY)
+    }
+}
+
+mod test;
@@ -0,0 +1,18 @@
+//! The subset of the P2P marketplace interface governance executes against.
+//!
+//! The marketplace accepts these calls only from its registered governance executor,
+//! see `set_governance_executor` on the marketplace.
+
+use soroban_sdk::{contractclient, Address, Env};
+
+#[contractclient(name = "MarketplaceClient")]
+pub trait Marketplace {
+    /// Sets the flat trading fee rate in basis points
+    fn update_fee_rate(env: Env, new_fee_rate: u32);
+
+    /// Sets the minimum and maximum trade amounts
+    fn update_trade_limits(env: Env, min_amount: i128, max_amount: i128);
+
+    /// Hands governed parameters to another executor, or back to the admin with None
+    fn set_governance_executor(env: Env, executor: Option<Address>);
+}
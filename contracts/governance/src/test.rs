@@ -0,0 +1,141 @@
+#![cfg(test)]
+
+use super::*;
+use p2p_marketplace::testutils::setup_marketplace;
+use soroban_sdk::{
+    testutils::{Address as TestAddress, Ledger, MockAuth, MockAuthInvoke},
+    Address, Env, IntoVal,
+};
+
+const VOTING_PERIOD: u64 = 3 * 86_400;
+const QUORUM: i128 = 1_000;
+const PROPOSAL_THRESHOLD: i128 = 100;
+
+fn setup_governance<'a>(env: &Env) -> (GovernanceClient<'a>, p2p_marketplace::testutils::Marketplace<'a>, token::StellarAssetClient<'a>) {
+    let market = setup_marketplace(env);
+    let token_admin = <Address as TestAddress>::generate(env);
+    let gov_token_id = env.register_stellar_asset_contract_v2(token_admin).address();
+
+    let contract_id = env.register(Governance, (gov_token_id.clone(), market.contract_id.clone(), VOTING_PERIOD, QUORUM, PROPOSAL_THRESHOLD));
+    let client = GovernanceClient::new(env, &contract_id);
+    market.client.set_governance_executor(&Some(contract_id));
+
+    (client, market, token::StellarAssetClient::new(env, &gov_token_id))
+}
+
+#[test]
+fn test_passed_proposal_updates_marketplace_fee_rate() {
+    let env = Env::default();
+    let (client, market, gov_token) = setup_governance(&env);
+    let (alice, bob) = (<Address as TestAddress>::generate(&env), <Address as TestAddress>::generate(&env));
+    gov_token.mint(&alice, &1_500);
+    gov_token.mint(&bob, &400);
+
+    let proposal_id = client.propose(&alice, &ParameterChange::FeeRate(10));
+    client.vote(&alice, &proposal_id, &true, &1_500);
+    client.vote(&bob, &proposal_id, &false, &400);
+    assert_eq!(client.try_execute(&proposal_id), Err(Ok(Error::VotingOpen)));
+
+    env.ledger().with_mut(|li| li.timestamp += VOTING_PERIOD);
+    assert_eq!(client.try_vote(&bob, &proposal_id, &false, &1), Err(Ok(Error::VotingClosed)));
+    client.execute(&proposal_id);
+    assert_eq!(market.client.get_fee_rate(), 10);
+    assert_eq!(client.try_execute(&proposal_id), Err(Ok(Error::AlreadyExecuted)));
+
+    // Locked tokens come back after voting
+    assert_eq!(client.withdraw(&alice, &proposal_id), 1_500);
+    assert_eq!(token::Client::new(&env, &gov_token.address).balance(&alice), 1_500);
+    assert_eq!(client.try_withdraw(&alice, &proposal_id), Err(Ok(Error::NothingLocked)));
+}
+
+#[test]
+fn test_rejected_proposal_leaves_limits_unchanged() {
+    let env = Env::default();
+    let (client, market, gov_token) = setup_governance(&env);
+    let voter = <Address as TestAddress>::generate(&env);
+    gov_token.mint(&voter, &QUORUM);
+    let limits = market.client.get_trade_limits();
+
+    // Votes below quorum don't pass
+    let proposal_id = client.propose(&voter, &ParameterChange::TradeLimits(1, 2));
+    client.vote(&voter, &proposal_id, &true, &(QUORUM - 1));
+    env.ledger().with_mut(|li| li.timestamp += VOTING_PERIOD);
+    assert_eq!(client.try_execute(&proposal_id), Err(Ok(Error::ProposalRejected)));
+    assert_eq!(market.client.get_trade_limits(), limits);
+}
+
+#[test]
+fn test_marketplace_parameters_owned_by_executor() {
+    let env = Env::default();
+    let (client, market, _) = setup_governance(&env);
+    assert_eq!(market.client.get_governance_executor(), Some(client.address.clone()));
+
+    // The admin alone can no longer change governed parameters
+    env.mock_auths(&[MockAuth {
+        address: &market.admin,
+        invoke: &MockAuthInvoke {
+            contract: &market.contract_id,
+            fn_name: "update_fee_rate",
+            args: (10u32,).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(market.client.try_update_fee_rate(&10).is_err());
+}
+
+#[test]
+fn test_proposals_need_a_stake_and_expire_unexecuted() {
+    let env = Env::default();
+    let (client, market, gov_token) = setup_governance(&env);
+    let voter = <Address as TestAddress>::generate(&env);
+    gov_token.mint(&voter, &QUORUM);
+
+    // Opening a vote takes more than a signature
+    let stranger = <Address as TestAddress>::generate(&env);
+    assert_eq!(client.try_propose(&stranger, &ParameterChange::FeeRate(10)), Err(Ok(Error::BelowThreshold)));
+
+    // A passed proposal left unexecuted goes stale
+    let fee_rate = market.client.get_fee_rate();
+    let proposal_id = client.propose(&voter, &ParameterChange::FeeRate(10));
+    client.vote(&voter, &proposal_id, &true, &QUORUM);
+    env.ledger().with_mut(|li| li.timestamp += VOTING_PERIOD + EXECUTION_WINDOW + 1);
+    assert_eq!(client.try_execute(&proposal_id), Err(Ok(Error::ProposalExpired)));
+    assert_eq!(market.client.get_fee_rate(), fee_rate);
+    assert_eq!(client.withdraw(&voter, &proposal_id), QUORUM);
+}
+
+#[test]
+fn test_older_proposal_cannot_revert_newer_one() {
+    let env = Env::default();
+    let (client, market, gov_token) = setup_governance(&env);
+    let voter = <Address as TestAddress>::generate(&env);
+    gov_token.mint(&voter, &(QUORUM * 2));
+
+    let older = client.propose(&voter, &ParameterChange::FeeRate(10));
+    let newer = client.propose(&voter, &ParameterChange::FeeRate(20));
+    client.vote(&voter, &older, &true, &QUORUM);
+    client.vote(&voter, &newer, &true, &QUORUM);
+    env.ledger().with_mut(|li| li.timestamp += VOTING_PERIOD);
+
+    client.execute(&newer);
+    assert_eq!(client.try_execute(&older), Err(Ok(Error::Superseded)));
+    assert_eq!(market.client.get_fee_rate(), 20);
+}
+
+#[test]
+fn test_passed_proposal_hands_control_back_to_admin() {
+    let env = Env::default();
+    let (client, market, gov_token) = setup_governance(&env);
+    let voter = <Address as TestAddress>::generate(&env);
+    gov_token.mint(&voter, &QUORUM);
+
+    let proposal_id = client.propose(&voter, &ParameterChange::GovernanceExecutor(None));
+    client.vote(&voter, &proposal_id, &true, &QUORUM);
+    env.ledger().with_mut(|li| li.timestamp += VOTING_PERIOD);
+
+    // The marketplace admin co-signs the handover
+    client.execute(&proposal_id);
+    assert_eq!(market.client.get_governance_executor(), None);
+    market.client.update_fee_rate(&10);
+    assert_eq!(market.client.get_fee_rate(), 10);
+}
@@ -0,0 +1,116 @@
+/*!
+ * Type Definitions for the Governance Contract
+ * 
+ * This module defines the proposals, errors, and events used by the token-voting
+ * contract that executes fee and limit changes on a P2P marketplace.
+ */
+
+use soroban_sdk::{contracterror, contracttype, Address, Symbol, symbol_short};
+
+// ================================================================================================
+// CORE DATA STRUCTURES
+// ================================================================================================
+
+/// A marketplace parameter change that token holders can vote on.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParameterChange {
+    /// Set the flat trading fee rate in basis points
+    FeeRate(u32),
+
+    /// Set the (minimum, maximum) trade amounts in the escrow token's smallest unit
+    TradeLimits(i128, i128),
+
+    /// Hand the marketplace's governed parameters to another executor, or back to its admin.
+    /// The marketplace also requires its admin's authorization for the handover.
+    GovernanceExecutor(Option<Address>),
+}
+
+/// A parameter change put to a vote.
+/// 
+/// Votes are weighted by governance tokens locked in the contract until voting ends,
+/// so the same tokens can't be moved between accounts to vote twice.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    /// Address that opened the proposal
+    pub proposer: Address,
+
+    /// The change applied to the marketplace if the proposal passes
+    pub change: ParameterChange,
+
+    /// Governance tokens locked in favour
+    pub votes_for: i128,
+
+    /// Governance tokens locked against
+    pub votes_against: i128,
+
+    /// Ledger timestamp when voting closes
+    pub ends_at: u64,
+
+    /// Whether the change has been applied to the marketplace
+    pub executed: bool,
+}
+
+// ================================================================================================
+// ERROR DEFINITIONS
+// ================================================================================================
+
+/// Error codes returned by the governance contract.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// Governance has already been initialized
+    /// No longer returned: governance is configured by its constructor
+    AlreadyInitialized = 1,
+    
+    /// Governance has not been initialized
+    NotInitialized = 2,
+    
+    /// Requested proposal does not exist
+    ProposalNotFound = 3,
+    
+    /// Voting on the proposal has closed
+    VotingClosed = 4,
+    
+    /// Voting on the proposal is still open
+    VotingOpen = 5,
+    
+    /// Proposal did not pass: quorum missed or more votes against than for
+    ProposalRejected = 6,
+    
+    /// Proposal was already executed
+    AlreadyExecuted = 7,
+    
+    /// Vote amount, voting period, quorum or proposal threshold is invalid
+    InvalidAmount = 8,
+    
+    /// Address has no tokens locked on the proposal
+    NothingLocked = 9,
+
+    /// Proposer holds fewer governance tokens than the proposal threshold
+    BelowThreshold = 10,
+
+    /// Passed proposal was not executed within the execution window
+    ProposalExpired = 11,
+
+    /// A newer proposal changing the same parameter has already been executed
+    Superseded = 12,
+}
+
+// ================================================================================================
+// EVENT DEFINITIONS
+// ================================================================================================
+
+/// Event emitted when a proposal is opened
+/// Data: (proposal_id, change, ends_at)
+pub const PROPOSAL_CREATED: Symbol = symbol_short!("gov_prop");
+
+/// Event emitted when governance tokens are locked as a vote
+/// Data: (proposal_id, support, amount)
+pub const VOTE_CAST: Symbol = symbol_short!("gov_vote");
+
+/// Event emitted when a passed proposal is applied to the marketplace
+/// Data: (proposal_id)
+pub const PROPOSAL_EXECUTED: Symbol = symbol_short!("gov_exec");
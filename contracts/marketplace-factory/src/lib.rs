@@ -0,0 +1,192 @@
+/*!
+ * Marketplace Factory Smart Contract
+ * 
+ * Deploys and initializes one P2P marketplace contract per token/fiat corridor
+ * (USDC/KES, USDT/NGN, ...) and keeps an on-chain registry of deployed instances.
+ * 
+ * Business Logic:
+ * 1. The factory is deployed with its admin and the uploaded marketplace Wasm hash
+ * 2. Admin deploys a market per corridor; the factory initializes it atomically
+ * 3. Existing manual deployments can be registered so the registry is complete
+ * 4. Clients discover markets through `get_markets` and `get_market`
+ */
+
+#![no_std]
+use soroban_sdk::{
+    contract, contractimpl, xdr::ToXdr, Address, BytesN, Env, Symbol, Vec,
+    symbol_short,
+};
+
+mod types;
+use types::{Error, FeePolicy, Market, MARKET_DEPLOYED, MARKET_REGISTERED};
+
+// Storage keys for contract state
+const ADMIN_KEY: Symbol = symbol_short!("ADMIN");                    // Factory administrator (persistent)
+const WASM_HASH_KEY: Symbol = symbol_short!("WASM");                 // Marketplace Wasm hash used for deployments (persistent)
+const MARKETS_KEY: Symbol = symbol_short!("MARKETS");                // Registered markets in creation order (instance)
+
+#[contract]
+pub struct MarketplaceFactory;
+
+#[contractimpl]
+impl MarketplaceFactory {
+    /// Sets the factory's admin and the marketplace Wasm hash at deployment.
+    /// Runs atomically with the deployment, so nobody can claim the factory first.
+    /// 
+    /// # Arguments
+    /// * `admin` - Address allowed to deploy markets; also becomes admin of each market
+    /// * `wasm_hash` - Hash of the uploaded P2P marketplace Wasm
+    pub fn __constructor(env: Env, admin: Address, wasm_hash: BytesN<32>) {
+        env.storage().persistent().set(&ADMIN_KEY, &admin);
+        env.storage().persistent().set(&WASM_HASH_KEY, &wasm_hash);
+        env.storage().instance().set(&MARKETS_KEY, &Vec::<Market>::new(&env));
+    }
+
+    /// Internal helper to verify admin authorization
+    fn _require_admin(env: &Env) -> Result<Address, Error> {
+        let admin: Address = env.storage().persistent().get(&ADMIN_KEY).ok_or(Error::Unauthorized)?;
+        admin.require_auth();
+        Ok(admin)
+    }
+
+    /// Internal helper to record a market, rejecting duplicate corridors
+    fn _add_market(env: &Env, token: Address, fiat: Symbol, market: Address) -> Result<(), Error> {
+        let mut markets: Vec<Market> = env.storage().instance().get(&MARKETS_KEY).unwrap_or(Vec::new(env));
+        if markets.iter().any(|m| m.token == token && m.fiat == fiat) {
+            return Err(Error::MarketExists);
+        }
+
+        markets.push_back(Market { token, fiat, market, created_at: env.ledger().timestamp() });
+        env.storage().instance().set(&MARKETS_KEY, &markets);
+
+        Ok(())
+    }
+
+    /// Deploys and initializes a new P2P marketplace for a token/fiat corridor.
+    /// 
+    /// # Business Flow
+    /// 1. Verifies admin authorization
+    /// 2. Deploys the marketplace Wasm with a salt derived from the corridor, passing the
+    ///    factory admin, token, fee collector and fee policy to the market's constructor
+    /// 3. Records the market in the registry, unless the corridor is already registered
+    /// 
+    /// # Arguments
+    /// * `token` - The token escrowed by the market (e.g. USDC)
+    /// * `fiat` - The fiat currency code settled off-chain (e.g. KES)
+    /// * `fee_collector` - Address that receives the market's trading fees
+    /// * `fee_policy` - Whether the market collects fees and the fee range its admin may set
+    /// 
+    /// # Returns
+    /// The address of the deployed marketplace
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - MarketExists: If the corridor already has a market
+    pub fn deploy_market(
+        env: Env,
+        token: Address,
+        fiat: Symbol,
+        fee_collector: Address,
+        fee_policy: FeePolicy,
+    ) -> Result<Address, Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        // Deterministic salt per corridor keeps market addresses predictable
+        let salt = env.crypto().sha256(&(token.clone(), fiat.clone()).to_xdr(&env));
+        let wasm_hash: BytesN<32> = env.storage().persistent().get(&WASM_HASH_KEY).unwrap();
+        // The constructor initializes the market atomically, so it can't be front-run
+        let market = env.deployer()
+            .with_current_contract(salt.to_bytes())
+            .deploy_v2(wasm_hash, (admin, token.clone(), fee_collector, fee_policy));
+
+        // A duplicate corridor fails here, rolling the deployment back
+        Self::_add_market(&env, token.clone(), fiat.clone(), market.clone())?;
+
+        env.events().publish((MARKET_DEPLOYED,), (token, fiat, market.clone()));
+
+        Ok(market)
+    }
+
+    /// Adds an existing marketplace deployment to the registry.
+    /// Used for corridors that were deployed manually before the factory existed.
+    /// 
+    /// # Arguments
+    /// * `token` - The token escrowed by the market
+    /// * `fiat` - The fiat currency code settled off-chain
+    /// * `market` - Address of the existing marketplace contract
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - MarketExists: If the corridor already has a market
+    pub fn register_market(env: Env, token: Address, fiat: Symbol, market: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        Self::_require_admin(&env)?;
+
+        Self::_add_market(&env, token.clone(), fiat.clone(), market.clone())?;
+
+        env.events().publish((MARKET_REGISTERED,), (token, fiat, market));
+
+        Ok(())
+    }
+
+    /// Updates the marketplace Wasm hash used for future deployments.
+    /// Existing markets are unaffected and upgrade through their own admin.
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    pub fn update_wasm_hash(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        // Verify admin authorization
+        Self::_require_admin(&env)?;
+
+        env.storage().persistent().set(&WASM_HASH_KEY, &new_wasm_hash);
+
+        Ok(())
+    }
+
+    /// Transfers factory administration to a new address.
+    /// The new admin must sign too, so control can't be handed to an address nobody holds.
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    pub fn update_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        Self::_require_admin(&env)?;
+        new_admin.require_auth();
+
+        env.storage().persistent().set(&ADMIN_KEY, &new_admin);
+
+        Ok(())
+    }
+
+    // ========== VIEW FUNCTIONS ==========
+
+    /// Returns all registered markets in creation order.
+    pub fn get_markets(env: Env) -> Vec<Market> {
+        env.storage().instance().get(&MARKETS_KEY).unwrap_or(Vec::new(&env))
+    }
+
+    /// Looks up the marketplace for a token/fiat corridor.
+    /// 
+    /// # Errors
+    /// - MarketNotFound: If no market is registered for the corridor
+    pub fn get_market(env: Env, token: Address, fiat: Symbol) -> Result<Address, Error> {
+        Self::get_markets(env)
+            .iter()
+            .find(|m| m.token == token && m.fiat == fiat)
+            .map(|m| m.market)
+            .ok_or(Error::MarketNotFound)
+    }
+
+    /// Returns the factory admin address.
+    pub fn get_admin(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ADMIN_KEY)
+    }
+
+    /// Returns the marketplace Wasm hash used for deployments.
+    pub fn get_wasm_hash(env: Env) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&WASM_HASH_KEY)
+    }
+}
+
+mod test;
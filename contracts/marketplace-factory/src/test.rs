@@ -0,0 +1,61 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::{testutils::Address as TestAddress, Address, BytesN, Env};
+
+fn setup_factory<'a>() -> (Env, MarketplaceFactoryClient<'a>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = <Address as TestAddress>::generate(&env);
+    let contract_id = env.register(MarketplaceFactory, (admin.clone(), BytesN::from_array(&env, &[1u8; 32])));
+    let client = MarketplaceFactoryClient::new(&env, &contract_id);
+    (env, client, admin)
+}
+
+#[test]
+fn test_register_and_lookup_markets() {
+    let (env, client, admin) = setup_factory();
+    assert_eq!(client.get_admin(), Some(admin));
+    assert_eq!(client.get_markets().len(), 0);
+
+    let usdc = <Address as TestAddress>::generate(&env);
+    let kes_market = <Address as TestAddress>::generate(&env);
+    let ngn_market = <Address as TestAddress>::generate(&env);
+    client.register_market(&usdc, &symbol_short!("KES"), &kes_market);
+    client.register_market(&usdc, &symbol_short!("NGN"), &ngn_market);
+
+    assert_eq!(client.get_markets().len(), 2);
+    assert_eq!(client.get_market(&usdc, &symbol_short!("KES")), kes_market);
+    assert_eq!(client.get_market(&usdc, &symbol_short!("NGN")), ngn_market);
+    assert_eq!(client.try_get_market(&usdc, &symbol_short!("UGX")), Err(Ok(Error::MarketNotFound)));
+}
+
+#[test]
+fn test_duplicate_corridor_rejected() {
+    let (env, client, admin) = setup_factory();
+    let usdc = <Address as TestAddress>::generate(&env);
+    let market = <Address as TestAddress>::generate(&env);
+    client.register_market(&usdc, &symbol_short!("KES"), &market);
+
+    assert_eq!(
+        client.try_register_market(&usdc, &symbol_short!("KES"), &market),
+        Err(Ok(Error::MarketExists))
+    );
+
+    // The registry rejects the corridor after deployment, so with this placeholder Wasm the
+    // deployment fails first; either way the whole call rolls back
+    assert!(client.try_deploy_market(&usdc, &symbol_short!("KES"), &admin, &FeePolicy { fees_enabled: false, fee_floor: 0, fee_ceiling: 0 }).is_err());
+    assert_eq!(client.get_markets().len(), 1);
+}
+
+#[test]
+fn test_admin_handover_needs_both_signatures() {
+    let (env, client, admin) = setup_factory();
+    let new_admin = <Address as TestAddress>::generate(&env);
+
+    client.update_admin(&new_admin);
+    let auths = env.auths();
+    assert!(auths.iter().any(|(address, _)| *address == admin));
+    assert!(auths.iter().any(|(address, _)| *address == new_admin));
+    assert_eq!(client.get_admin(), Some(new_admin));
+}
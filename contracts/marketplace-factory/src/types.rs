@@ -0,0 +1,83 @@
+/*!
+ * Type Definitions for the Marketplace Factory Contract
+ * 
+ * This module defines the registry record, errors, and events used by the factory
+ * that deploys one P2P marketplace instance per token/fiat corridor.
+ */
+
+use soroban_sdk::{contracterror, contracttype, Address, Symbol, symbol_short};
+
+// ================================================================================================
+// CORE DATA STRUCTURES
+// ================================================================================================
+
+/// A marketplace instance registered with the factory.
+/// 
+/// Each corridor (e.g. USDC/KES, USDT/NGN) maps to exactly one marketplace contract.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Market {
+    /// The escrowed token traded on this marketplace (e.g. USDC)
+    pub token: Address,
+    
+    /// Fiat currency code settled off-chain (e.g. KES, NGN)
+    pub fiat: Symbol,
+    
+    /// Address of the deployed P2P marketplace contract
+    pub market: Address,
+    
+    /// Ledger timestamp when the market was deployed or registered
+    pub created_at: u64,
+}
+
+/// Protocol fee policy passed to each deployed marketplace's constructor.
+/// 
+/// Mirrors the marketplace's `FeePolicy`, so fee-free community instances can be
+/// deployed without code changes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeePolicy {
+    /// Whether protocol fees are collected; the market admin can toggle this later
+    pub fees_enabled: bool,
+
+    /// Lowest fee rate in basis points the market admin may set
+    pub fee_floor: u32,
+
+    /// Highest fee rate in basis points the market admin may set (max 1000 = 10%)
+    pub fee_ceiling: u32,
+}
+
+// ================================================================================================
+// ERROR DEFINITIONS
+// ================================================================================================
+
+/// Error codes returned by the factory contract.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// Factory has already been initialized
+    /// No longer returned: the factory is configured by its constructor
+    AlreadyInitialized = 1,
+    
+    /// Caller is not authorized to perform this operation
+    Unauthorized = 2,
+    
+    /// A market for this token/fiat pair is already registered
+    MarketExists = 3,
+    
+    /// No market is registered for this token/fiat pair
+    MarketNotFound = 4,
+}
+
+// ================================================================================================
+// EVENT DEFINITIONS
+// ================================================================================================
+
+/// Event emitted when the factory deploys a new marketplace
+/// Data: (token, fiat, market)
+pub const MARKET_DEPLOYED: Symbol = symbol_short!("mkt_dep");
+
+/// Event emitted when an existing marketplace is added to the registry
+/// Data: (token, fiat, market)
+pub const MARKET_REGISTERED: Symbol = symbol_short!("mkt_reg");
@@ -0,0 +1,870 @@
+//! Typed interface of the P2P marketplace for other Soroban contracts.
+//!
+//! Aggregators, smart wallets and other integrators depend on this crate instead of the
+//! marketplace implementation: it holds the public types (offers, trades, errors, events)
+//! and `P2PMarketplaceTrait`, whose generated `P2PMarketplaceClient` calls a deployed
+//! marketplace. Functions returning `Result` get a panicking method and a `try_` variant
+//! on the client. The marketplace contract implements the trait, so a signature change here
+//! fails to compile until the contract follows. See the marketplace crate for the full
+//! documentation of each function.
+
+#![no_std]
+// Mirrors the contract's functions, which take their arguments flat
+#![allow(clippy::too_many_arguments)]
+
+pub mod types;
+
+use soroban_sdk::{contractclient, Address, Bytes, BytesN, Env, Map, String, Vec};
+
+use types::{
+    AdminAction, AdminLogEntry, BuyerRequirements, Dispute, DisputeResolution, Error, ErrorInfo, FeeBracket,
+    FeePayer, FeeSplit, Offer, PendingAction, Proposal, Quote, Reputation,
+    SettlementMode, TokenConfig, Trade, TradeDetails,
+};
+
+#[contractclient(name = "P2PMarketplaceClient")]
+pub trait P2PMarketplaceTrait {
+    // ========== Offers and trades ==========
+
+    /// Creates a new offer to sell USDC for KES with escrow protection.
+    /// The seller authorizes the escrow transfer as part of this invocation; no prior
+    /// `approve()` transaction is needed.
+    fn create_offer(
+        env: Env,
+        seller: Address,
+        usdc_amount: i128,
+        kes_amount: i128,
+        terms_hash: Option<BytesN<32>>,
+        settlement: SettlementMode,
+        refund_to: Option<Address>,
+        metadata_uri: Option<String>,
+        idempotency_key: Option<BytesN<32>>,
+    ) -> Result<u64, Error>;
+
+    /// Runs every check `create_offer` would, without changing state or moving funds.
+    fn simulate_create_offer(
+        env: Env,
+        seller: Address,
+        usdc_amount: i128,
+        kes_amount: i128,
+        settlement: SettlementMode,
+    ) -> Result<(), Error>;
+
+    /// Sets the conditions a buyer must meet to trade against an offer.
+    /// Passing all-zero values and an empty allowlist removes the requirements.
+    fn set_buyer_requirements(
+        env: Env,
+        seller: Address,
+        offer_id: u64,
+        min_completed_trades: u32,
+        min_reputation: i128,
+        allowed_buyers: Vec<Address>,
+    ) -> Result<(), Error>;
+
+    /// Limits how much USDC a single buyer can fill from an offer within a time window.
+    /// Spreads fills of recurring offers across counterparties. Passing a zero cap removes it.
+    fn set_buyer_cap(
+        env: Env,
+        seller: Address,
+        offer_id: u64,
+        max_per_buyer: i128,
+        window: u64,
+    ) -> Result<(), Error>;
+
+    /// Stores encrypted payment details (e.g. an M-Pesa paybill) on an offer.
+    /// The details are disclosed in an event when a trade is initiated, instead of
+    /// being broadcast with the offer.
+    fn set_payment_details(env: Env, seller: Address, offer_id: u64, details: Bytes) -> Result<(), Error>;
+
+    /// Chooses who bears the protocol fee when a trade against the offer settles.
+    /// Lets sellers competing on exact face value absorb the fee themselves.
+    fn set_fee_payer(env: Env, seller: Address, offer_id: u64, fee_payer: FeePayer) -> Result<(), Error>;
+
+    /// Makes an offer recurring: after each completed trade it is re-escrowed from the
+    /// seller's token allowance and listed again, until the total cap is used up or the
+    /// seller cancels.
+    fn set_recurring(env: Env, seller: Address, offer_id: u64, total_cap: i128) -> Result<(), Error>;
+
+    /// Creates several offers at different rates with a single escrow transfer.
+    /// Lets market makers post a ladder of price levels in one transaction.
+    fn create_offers(env: Env, seller: Address, levels: Vec<(i128, i128)>) -> Result<Vec<u64>, Error>;
+
+    /// Creates an offer and a trade in one shot, bound to a named seller and buyer.
+    /// OTC escrow for brokers who arrange deals off-chain and only need the escrow rails,
+    /// and for pre-negotiated deals (e.g. agreed over Telegram): both parties sign the same
+    /// invocation, so there is no window for another buyer to take the offer.
+    fn create_direct_escrow(
+        env: Env,
+        seller: Address,
+        buyer: Address,
+        usdc_amount: i128,
+        kes_amount: i128,
+    ) -> Result<(u64, u64), Error>;
+
+    /// Initiates a trade by a buyer against an existing offer.
+    /// This begins the escrow process where USDC is held while payment confirmation occurs.
+    fn initiate_trade(
+        env: Env,
+        buyer: Address,
+        offer_id: u64,
+        receive_to: Option<Address>,
+        operator: Option<Address>,
+        operator_fee_bps: u32,
+        external_ref: Option<BytesN<32>>,
+    ) -> Result<u64, Error>;
+
+    /// Initiates a trade only if the offer still meets the buyer's constraints.
+    fn initiate_trade_fok(
+        env: Env,
+        buyer: Address,
+        offer_id: u64,
+        max_rate: i128,
+        max_usdc: i128,
+    ) -> Result<u64, Error>;
+
+    /// Runs every check `initiate_trade` would, without changing state or moving funds.
+    fn simulate_initiate_trade(
+        env: Env,
+        buyer: Address,
+        offer_id: u64,
+        operator: Option<Address>,
+        operator_fee_bps: u32,
+        external_ref: Option<BytesN<32>>,
+    ) -> Result<(), Error>;
+
+    /// Proposes a different KES price for an existing offer.
+    /// The seller can accept the proposal, which initiates a trade at the negotiated amount.
+    fn propose_price(
+        env: Env,
+        buyer: Address,
+        offer_id: u64,
+        kes_amount: i128,
+        receive_to: Option<Address>,
+    ) -> Result<u64, Error>;
+
+    /// Accepts a buyer's price proposal, initiating a trade at the negotiated KES amount.
+    fn accept_proposal(env: Env, seller: Address, proposal_id: u64) -> Result<u64, Error>;
+
+    /// Rejects a price proposal (seller) or withdraws it (buyer).
+    fn reject_proposal(env: Env, caller: Address, proposal_id: u64) -> Result<(), Error>;
+
+    /// Reclaims a proposal deposit whose refund couldn't be delivered.
+    fn claim_proposal_deposit(env: Env, buyer: Address, proposal_id: u64) -> Result<i128, Error>;
+
+    /// Marks a partial fiat payment (tranche) on an installment trade.
+    /// Used when a trade's KES amount exceeds what the buyer can send in one payment.
+    fn mark_tranche(env: Env, trade_id: u64, buyer: Address, kes_amount: i128) -> Result<u32, Error>;
+
+    /// Confirms receipt of a tranche; releases USDC once the full KES amount is covered.
+    fn confirm_tranche(env: Env, trade_id: u64, seller: Address, index: u32) -> Result<(), Error>;
+
+    /// Allows trade participants to confirm payment completion.
+    /// Both buyer and seller must confirm before USDC is released.
+    fn confirm_payment(env: Env, trade_id: u64, participant: Address) -> Result<(), Error>;
+
+    /// Lets the seller release escrow to the buyer immediately.
+    /// For trades where the seller verifies the KES payment out-of-band before the buyer confirms.
+    fn release_to_buyer(env: Env, trade_id: u64, seller: Address) -> Result<(), Error>;
+
+    /// Confirms the buyer's payment with a receipt signed by the registered attester.
+    /// Replaces the buyer's bare boolean confirmation with cryptographic backing.
+    fn confirm_payment_with_receipt(
+        env: Env,
+        trade_id: u64,
+        buyer: Address,
+        payment_ref: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error>;
+
+    /// Locks the seller's confirmation to a secret the buyer put in the KES payment reference.
+    /// Committing doubles as the buyer's payment confirmation.
+    fn commit_payment_hash(env: Env, trade_id: u64, buyer: Address, payment_hash: BytesN<32>) -> Result<(), Error>;
+
+    /// Confirms receipt of a hash-locked payment by revealing the buyer's secret.
+    fn confirm_payment_with_preimage(env: Env, trade_id: u64, seller: Address, preimage: Bytes) -> Result<(), Error>;
+
+    /// Records a payment oracle's attestation that the buyer's KES payment was received.
+    /// The attestation substitutes for the seller's confirmation once the grace period passes.
+    fn attest_payment(env: Env, trade_id: u64, attestation: BytesN<32>) -> Result<(), Error>;
+
+    /// Releases an oracle-attested trade once its grace period has elapsed.
+    /// Anyone can call this, so a seller going offline after being paid can't block the buyer.
+    fn release_attested_trade(env: Env, trade_id: u64) -> Result<(), Error>;
+
+    /// Releases a buyer's first trade once its cooling-off period has elapsed.
+    /// Anyone can call this, so the buyer isn't left waiting on the seller.
+    fn release_held_trade(env: Env, trade_id: u64) -> Result<(), Error>;
+
+    /// Releases a trade to the buyer after the seller stayed silent through the auto-release window.
+    /// Anyone can call this, so an offline seller can't hold a paying buyer hostage.
+    fn auto_release_trade(env: Env, trade_id: u64) -> Result<(), Error>;
+
+    /// Allows trade participants to cancel an initiated trade.
+    /// This returns the escrowed USDC to the seller.
+    fn cancel_trade(env: Env, trade_id: u64, participant: Address) -> Result<(), Error>;
+
+    /// Restores a trade cancelled by mistake, when both parties sign within an hour of the cancellation.
+    fn reopen_trade(env: Env, trade_id: u64) -> Result<i128, Error>;
+
+    /// Resolves expired trades by returning escrowed USDC to sellers.
+    /// Anyone can call this function to clean up expired trades.
+    fn resolve_expired_trade(env: Env, trade_id: u64, caller: Address) -> Result<(), Error>;
+
+    /// Allows sellers to cancel their offers and recover escrowed USDC.
+    /// Offers can only be cancelled if no active trade exists.
+    fn cancel_offer(env: Env, seller: Address, offer_id: u64) -> Result<(), Error>;
+
+    /// Expires an offer that has seen no trade activity for the offer TTL, refunding its seller.
+    /// Permissionless so abandoned listings and their escrow don't accumulate forever.
+    fn expire_offer(env: Env, offer_id: u64) -> Result<i128, Error>;
+
+    /// Withdraws USDC payouts that were parked because they couldn't be delivered at release.
+    fn withdraw(env: Env, recipient: Address) -> Result<i128, Error>;
+
+    /// Removes a completed or cancelled trade from storage after the retention period.
+    /// Permissionless so anyone can keep storage and rent costs bounded.
+    fn prune_trade(env: Env, trade_id: u64) -> Result<(), Error>;
+
+    /// Removes a filled or expired offer from storage after the retention period.
+    /// Permissionless so anyone can keep storage and rent costs bounded.
+    fn prune_offer(env: Env, offer_id: u64) -> Result<(), Error>;
+
+    /// Emergency function to pause all trading activities.
+    /// Only admin can pause the contract for security or maintenance.
+    fn pause(env: Env) -> Result<(), Error>;
+
+    /// Emergency brake for the guardian role.
+    /// The guardian (e.g. a monitoring bot) can pause but holds no other power:
+    /// it cannot unpause or change any configuration.
+    fn guardian_pause(env: Env) -> Result<(), Error>;
+
+    /// Assigns the guardian role, which may only pause the contract.
+    fn set_guardian(env: Env, guardian: Address) -> Result<(), Error>;
+
+    /// Resumes trading activities after a pause.
+    /// Only admin can unpause the contract.
+    fn unpause(env: Env) -> Result<(), Error>;
+
+    /// Starts the wind-down of a deprecated or compromised marketplace.
+    /// The contract is paused for good, and anyone can then return each offer's escrow
+    /// to its seller with `emergency_refund`.
+    fn begin_wind_down(env: Env) -> Result<(), Error>;
+
+    /// Returns an offer's escrow and fee reserve to the seller during a wind-down.
+    /// Callable by anyone, since the funds can only go to the seller's refund address.
+    fn emergency_refund(env: Env, offer_id: u64) -> Result<i128, Error>;
+
+    // ========== Disputes ==========
+
+    /// Raises a dispute for a trade when payment confirmation conflicts arise.
+    /// This function allows trade participants to escalate issues that cannot be resolved
+    /// through normal payment confirmation flow.
+    fn raise_dispute(
+        env: Env,
+        trade_id: u64,
+        caller: Address,
+        reason: String,
+        evidence: Vec<BytesN<32>>,
+    ) -> Result<(), Error>;
+
+    /// Adds evidence to an open dispute during its evidence window.
+    fn submit_evidence(
+        env: Env,
+        trade_id: u64,
+        participant: Address,
+        evidence: Vec<BytesN<32>>,
+        complete: bool,
+    ) -> Result<(), Error>;
+
+    /// Resolves a disputed trade with admin intervention.
+    /// Only the admin can resolve disputes by choosing to release USDC to buyer or refund to seller.
+    fn resolve_dispute(env: Env, trade_id: u64, resolution: DisputeResolution) -> Result<(), Error>;
+
+    /// Applies the ruling of the arbitration contract disputes are delegated to.
+    fn submit_ruling(env: Env, trade_id: u64, resolution: DisputeResolution) -> Result<(), Error>;
+
+    /// Resolves a disputed trade assigned to the calling panel arbitrator.
+    fn arbitrate_dispute(
+        env: Env,
+        arbitrator: Address,
+        trade_id: u64,
+        resolution: DisputeResolution,
+    ) -> Result<(), Error>;
+
+    /// Withdraws the fees a panel arbitrator has accrued for resolving disputes.
+    fn claim_arbitrator_fees(env: Env, arbitrator: Address) -> Result<i128, Error>;
+
+    // ========== Relayed calls ==========
+
+    /// Creates an offer on behalf of a seller who has no XLM for fees.
+    fn relay_create_offer(
+        env: Env,
+        relayer: Address,
+        seller: Address,
+        usdc_amount: i128,
+        kes_amount: i128,
+        terms_hash: Option<BytesN<32>>,
+        settlement: SettlementMode,
+    ) -> Result<u64, Error>;
+
+    /// Initiates a trade on behalf of a buyer who has no XLM for fees.
+    fn relay_initiate_trade(
+        env: Env,
+        relayer: Address,
+        buyer: Address,
+        offer_id: u64,
+        operator: Option<Address>,
+        operator_fee_bps: u32,
+        external_ref: Option<BytesN<32>>,
+    ) -> Result<u64, Error>;
+
+    /// Confirms payment on behalf of a trade participant who has no XLM for fees.
+    fn relay_confirm_payment(
+        env: Env,
+        relayer: Address,
+        trade_id: u64,
+        participant: Address,
+    ) -> Result<(), Error>;
+
+    // ========== Administration ==========
+
+    /// Upgrades the contract to a new Wasm hash.
+    /// This function can only be called by the contract admin.
+    fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error>;
+
+    /// Schedules escrow migration to a successor contract, or cancels it with None.
+    /// Starts a two-day timelock so users can exit before their escrow moves.
+    fn schedule_migration(env: Env, successor: Option<Address>) -> Result<(), Error>;
+
+    /// Moves the escrow of every active offer to the scheduled successor contract.
+    /// For upgrades that can't go through `upgrade`, e.g. storage layout changes.
+    fn migrate_escrow(env: Env, successor: Address) -> Result<i128, Error>;
+
+    /// Updates the admin address to a new address.
+    /// This is a critical security function that transfers administrative control.
+    fn update_admin(env: Env, new_admin: Address) -> Result<(), Error>;
+
+    /// Enables multisig admin by configuring an M-of-N signer set.
+    /// Once enabled, `update_admin`, `upgrade` and `resolve_dispute` must go through
+    /// `propose_admin_action` / `approve_action` / `execute_action`.
+    fn set_admin_signers(env: Env, signers: Vec<Address>, threshold: u32) -> Result<(), Error>;
+
+    /// Proposes a high-impact admin action for multisig approval.
+    /// The proposer's approval is counted immediately.
+    fn propose_admin_action(env: Env, signer: Address, action: AdminAction) -> Result<u64, Error>;
+
+    /// Approves a pending admin action. Repeat approvals by the same signer are ignored.
+    fn approve_action(env: Env, signer: Address, action_id: u64) -> Result<(), Error>;
+
+    /// Executes an admin action once it has enough approvals.
+    /// Callable by anyone; the approvals are the authorization.
+    fn execute_action(env: Env, action_id: u64) -> Result<(), Error>;
+
+    /// Updates the fee collector address where trading fees are sent.
+    /// This allows admin to change where marketplace fees are collected.
+    fn update_fee_collector(env: Env, new_fee_collector: Address) -> Result<(), Error>;
+
+    /// Switches fees to a size-based curve where the rate falls as trades grow.
+    /// Distinct from the flat fee rate, which applies again once the curve is cleared.
+    fn update_fee_curve(env: Env, curve: Vec<FeeBracket>) -> Result<(), Error>;
+
+    /// Sets the maximum operator fee integrating wallets may add to a trade.
+    fn update_operator_fee_cap(env: Env, new_cap: u32) -> Result<(), Error>;
+
+    /// Replaces the single fee collector with a weighted list of fee recipients.
+    /// Applies to the part of each fee not routed to the insurance fund.
+    fn update_fee_splits(env: Env, splits: Vec<FeeSplit>) -> Result<(), Error>;
+
+    /// Updates the trading fee rate charged on completed trades.
+    /// Fee rate is specified in basis points (1/100th of a percent).
+    fn update_fee_rate(env: Env, new_fee_rate: u32) -> Result<(), Error>;
+
+    /// Registers the cKES token used for on-chain settlement.
+    /// Once registered, sellers may create offers whose KES leg settles on-chain.
+    fn set_kes_token(env: Env, kes_token_id: Address) -> Result<(), Error>;
+
+    /// Adds a token to the registry with default settings: enabled, no limits, no fee override.
+    /// Decimals are read from the token contract.
+    fn register_token(env: Env, token_id: Address) -> Result<(), Error>;
+
+    /// Updates a registered token's limits, fee override and enabled flag.
+    /// Disabling a token stops new offers from using it; existing trades settle normally.
+    fn set_token_config(
+        env: Env,
+        token_id: Address,
+        min_amount: i128,
+        max_amount: i128,
+        fee_rate: Option<u32>,
+        enabled: bool,
+    ) -> Result<(), Error>;
+
+    /// Registers the payment oracle allowed to attest off-chain KES payments.
+    fn set_payment_oracle(env: Env, oracle: Address) -> Result<(), Error>;
+
+    /// Adds or removes a relayer from the gasless-trading allowlist.
+    fn set_relayer(env: Env, relayer: Address, allowed: bool) -> Result<(), Error>;
+
+    /// Registers the Ed25519 public key that signs buyer payment receipts.
+    fn set_receipt_attester(env: Env, public_key: BytesN<32>) -> Result<(), Error>;
+
+    /// Updates the grace period between an oracle attestation and release.
+    fn update_attestation_grace_period(env: Env, grace_seconds: u64) -> Result<(), Error>;
+
+    /// Enables or tunes auto-release of buyer-confirmed trades the seller never acts on.
+    fn update_auto_release_window(env: Env, window_seconds: u64) -> Result<(), Error>;
+
+    /// Sets the cooling-off period applied to each buyer's first completed trade.
+    fn update_first_trade_hold(env: Env, hold_seconds: u64) -> Result<(), Error>;
+
+    /// Sets the cooldown imposed on buyers who cancel or let trades expire.
+    fn update_walkaway_cooldown(env: Env, base_seconds: u64) -> Result<(), Error>;
+
+    /// Updates the share of trading fees routed into the insurance fund.
+    /// Share is specified in basis points of the fee, not of the trade amount.
+    fn update_insurance_share(env: Env, new_share: u32) -> Result<(), Error>;
+
+    /// Updates the fee a panel arbitrator accrues per dispute they resolve.
+    fn update_arbitrator_fee(env: Env, fee: i128) -> Result<(), Error>;
+
+    /// Updates the reputation score deducted from the party a dispute is resolved against.
+    fn update_dispute_penalty(env: Env, penalty: i128) -> Result<(), Error>;
+
+    /// Updates when repeat dispute losers are suspended.
+    fn update_suspension_policy(env: Env, max_losses: u32, window: u64) -> Result<(), Error>;
+
+    /// Lifts a suspension, clearing the address's recent dispute losses.
+    /// Reputation and the lifetime `disputes_lost` count are kept.
+    fn reinstate(env: Env, address: Address) -> Result<(), Error>;
+
+    /// Updates how long both parties can submit evidence after a dispute is raised.
+    fn update_evidence_window(env: Env, window_seconds: u64) -> Result<(), Error>;
+
+    /// Assigns the compliance officer role.
+    /// The compliance officer can freeze individual trades and offers during AML reviews.
+    fn set_compliance_officer(env: Env, officer: Address) -> Result<(), Error>;
+
+    /// Freezes a trade pending a compliance review.
+    /// Blocks every state transition on the trade without pausing the contract.
+    fn freeze_trade(env: Env, trade_id: u64) -> Result<(), Error>;
+
+    /// Lifts a compliance freeze from a trade.
+    fn unfreeze_trade(env: Env, trade_id: u64) -> Result<(), Error>;
+
+    /// Freezes an offer pending a compliance review.
+    /// Blocks new trades against the offer and its cancellation.
+    fn freeze_offer(env: Env, offer_id: u64) -> Result<(), Error>;
+
+    /// Lifts a compliance freeze from an offer.
+    fn unfreeze_offer(env: Env, offer_id: u64) -> Result<(), Error>;
+
+    /// Updates the threshold above which completed trades emit a `large_trd` event.
+    /// Lets compliance pipelines subscribe to large trades only.
+    fn update_large_trade_threshold(env: Env, threshold: i128) -> Result<(), Error>;
+
+    /// Sets the refundable native XLM deposit buyers post when initiating a trade.
+    fn set_initiation_deposit(env: Env, xlm_token: Address, amount: i128) -> Result<(), Error>;
+
+    /// Updates the per-address daily cap on traded USDC.
+    fn update_daily_limit(env: Env, daily_limit: i128) -> Result<(), Error>;
+
+    /// Updates the reward points emission rate for completed trades.
+    fn update_points_rate(env: Env, new_rate: u32) -> Result<(), Error>;
+
+    /// Pays compensation out of the insurance fund.
+    /// Used to make victims whole when a dispute reveals fraud that escrow alone can't cover.
+    fn compensate(env: Env, recipient: Address, amount: i128) -> Result<(), Error>;
+
+    /// Sweeps USDC held above the contract's liabilities to the given address.
+    /// Recovers tokens that were accidentally sent directly to the contract.
+    fn sweep_surplus(env: Env, to: Address) -> Result<i128, Error>;
+
+    /// Switches protocol fee collection on or off for this instance.
+    /// Operator fees chosen by integrators are unaffected.
+    fn set_fees_enabled(env: Env, enabled: bool) -> Result<(), Error>;
+
+    /// Hands fee and trade-limit parameters to a governance executor, e.g. a token-voting
+    /// contract. Once set, `update_fee_rate`, `update_fee_curve` and `update_trade_limits`
+    /// accept updates only from the executor.
+    fn set_governance_executor(env: Env, executor: Option<Address>) -> Result<(), Error>;
+
+    /// Delegates dispute resolution to an external arbitration contract, or returns it to the admin.
+    fn set_arbitrator(env: Env, arbitrator: Option<Address>) -> Result<(), Error>;
+
+    /// Adds or removes an arbitrator from the dispute panel.
+    fn set_panel_arbitrator(env: Env, arbitrator: Address, allowed: bool) -> Result<(), Error>;
+
+    /// Sets or clears the yield adapter that idle escrow can be deposited with.
+    fn set_yield_adapter(env: Env, adapter: Option<Address>) -> Result<(), Error>;
+
+    /// Deposits idle escrow with the yield adapter.
+    /// Deposits are withdrawn just in time whenever a release or refund needs them.
+    fn deposit_to_yield(env: Env, amount: i128) -> Result<(), Error>;
+
+    /// Withdraws yield accrued on deposited escrow into the insurance fund.
+    /// Permissionless, so anyone can keep the fund topped up.
+    fn harvest_yield(env: Env) -> Result<i128, Error>;
+
+    /// Updates the minimum and maximum trade amounts for USDC trades.
+    /// These limits help prevent spam trades and excessive exposure.
+    fn update_trade_limits(env: Env, min_amount: i128, max_amount: i128) -> Result<(), Error>;
+
+    /// Updates the KES-side limits applied to fiat offers.
+    /// Swap offers are bounded by the token registry instead.
+    fn update_fiat_limits(
+        env: Env,
+        min_kes_amount: i128,
+        min_rate: i128,
+        max_rate: i128,
+    ) -> Result<(), Error>;
+
+    /// Updates the caps on active offers, bounding instance storage growth and query cost.
+    fn update_offer_caps(env: Env, max_total: u32, max_per_seller: u32) -> Result<(), Error>;
+
+    /// Switches how new offer and trade IDs are assigned.
+    fn update_hashed_ids(env: Env, enabled: bool) -> Result<(), Error>;
+
+    /// Updates the ceiling on concurrent open trades across the marketplace.
+    /// A safety valve for abuse incidents: new trades are rejected while the ceiling is reached.
+    fn update_max_open_trades(env: Env, max_open_trades: u32) -> Result<(), Error>;
+
+    /// Updates the trade expiration time for new trades.
+    /// This controls how long buyers have to confirm payment before trades expire.
+    fn update_trade_expiration(env: Env, expiration_seconds: u64) -> Result<(), Error>;
+
+    /// Updates the grace period after a trade's nominal deadline.
+    fn update_expiry_grace(env: Env, grace_seconds: u64) -> Result<(), Error>;
+
+    /// Updates how long completed and cancelled records are kept before they can be pruned.
+    fn update_record_retention(env: Env, retention_seconds: u64) -> Result<(), Error>;
+
+    /// Sets how long an offer may go without trade activity before anyone can expire it.
+    fn update_offer_ttl(env: Env, ttl_seconds: u64) -> Result<(), Error>;
+
+    // ========== Queries ==========
+
+    /// Returns the current admin address.
+    fn get_admin(env: Env) -> Result<Address, Error>;
+
+    /// Returns the USDC token contract address.
+    fn get_usdc_token_id(env: Env) -> Result<Address, Error>;
+
+    /// Returns the fee collector address.
+    fn get_fee_collector(env: Env) -> Result<Address, Error>;
+
+    /// Returns the current trading fee rate in basis points.
+    fn get_fee_rate(env: Env) -> u32;
+
+    /// Returns the maximum operator fee in basis points; 0 means operator fees are disabled.
+    fn get_operator_fee_cap(env: Env) -> u32;
+
+    /// Returns the size-based fee curve.
+    fn get_fee_curve(env: Env) -> Vec<FeeBracket>;
+
+    /// Returns the fee rate that would apply to a trade of the given size.
+    fn get_fee_rate_for(env: Env, usdc_amount: i128) -> u32;
+
+    /// Returns the weighted fee recipients.
+    fn get_fee_splits(env: Env) -> Vec<FeeSplit>;
+
+    /// Returns the share of each trading fee routed to the insurance fund.
+    fn get_insurance_share(env: Env) -> u32;
+
+    /// Returns the multisig admin signer set and approval threshold.
+    fn get_admin_signers(env: Env) -> (Vec<Address>, u32);
+
+    /// Returns a pending multisig admin action by its ID.
+    fn get_admin_action(env: Env, action_id: u64) -> Option<PendingAction>;
+
+    /// Returns the guardian address, if assigned.
+    fn get_guardian(env: Env) -> Option<Address>;
+
+    /// Returns the compliance officer address, if assigned.
+    fn get_compliance_officer(env: Env) -> Option<Address>;
+
+    /// Returns whether a trade is frozen pending compliance review.
+    fn is_trade_frozen(env: Env, trade_id: u64) -> bool;
+
+    /// Returns whether an offer is frozen pending compliance review.
+    fn is_offer_frozen(env: Env, offer_id: u64) -> bool;
+
+    /// Returns the large-trade reporting threshold.
+    fn get_large_trade_threshold(env: Env) -> i128;
+
+    /// Returns the initiation deposit buyers currently post.
+    fn get_initiation_deposit(env: Env) -> Option<(Address, i128)>;
+
+    /// Returns the initiation deposit held for a trade.
+    fn get_trade_deposit(env: Env, trade_id: u64) -> i128;
+
+    /// Returns the per-address daily cap on traded USDC.
+    fn get_daily_limit(env: Env) -> i128;
+
+    /// Returns the USDC an address has traded so far today.
+    fn get_daily_volume(env: Env, address: Address) -> i128;
+
+    /// Returns the reward points emission rate.
+    fn get_points_rate(env: Env) -> u32;
+
+    /// Returns the reward points balance of an address.
+    fn get_points(env: Env, address: Address) -> i128;
+
+    /// Returns the parked USDC an address can claim with `withdraw`.
+    fn get_claimable(env: Env, address: Address) -> i128;
+
+    /// Returns the reputation score deducted per dispute lost.
+    fn get_dispute_penalty(env: Env) -> i128;
+
+    /// Returns the repeat-offender suspension policy.
+    fn get_suspension_policy(env: Env) -> (u32, u64);
+
+    /// Returns when an address was suspended, or None if it isn't suspended.
+    fn get_suspended_at(env: Env, address: Address) -> Option<u64>;
+
+    /// Returns the evidence window opened when a dispute is raised.
+    fn get_evidence_window(env: Env) -> u64;
+
+    /// Returns the fee a panel arbitrator accrues per resolved dispute.
+    fn get_arbitrator_fee(env: Env) -> i128;
+
+    /// Returns a panel arbitrator's track record and unclaimed fees.
+    fn get_arbitrator_stats(env: Env, arbitrator: Address) -> (u32, i128);
+
+    /// Returns the USDC currently held in the insurance fund.
+    fn get_insurance_fund(env: Env) -> i128;
+
+    /// Returns whether protocol fees are collected on this instance.
+    fn get_fees_enabled(env: Env) -> bool;
+
+    /// Returns the (floor, ceiling) fee rate bounds fixed at deployment, in basis points.
+    fn get_fee_bounds(env: Env) -> (u32, u32);
+
+    /// Returns the governance executor that owns fee and limit parameters, if any.
+    fn get_governance_executor(env: Env) -> Option<Address>;
+
+    /// Returns the arbitration contract disputes are delegated to, if any.
+    fn get_arbitrator(env: Env) -> Option<Address>;
+
+    /// Returns the arbitrators new disputes are assigned to, in assignment order.
+    fn get_arbitrator_panel(env: Env) -> Vec<Address>;
+
+    /// Returns the scheduled escrow migration, if any.
+    fn get_pending_migration(env: Env) -> Option<(Address, u64)>;
+
+    /// Returns when the wind-down started, if it has.
+    fn get_wind_down(env: Env) -> Option<u64>;
+
+    /// Returns the successor contract escrow was migrated to, if the migration has run.
+    fn get_successor(env: Env) -> Option<Address>;
+
+    /// Returns the yield adapter idle escrow can be deposited with, if any.
+    fn get_yield_adapter(env: Env) -> Option<Address>;
+
+    /// Returns the USDC principal currently deposited with the yield adapter.
+    fn get_yield_deposited(env: Env) -> i128;
+
+    /// Reconciles the contract's USDC balance against what it owes.
+    /// Cheap on-chain solvency check for auditors and monitoring.
+    fn verify_escrow(env: Env) -> Result<i128, Error>;
+
+    /// Previews the settlement of a trade against an offer without changing state.
+    /// Uses the same fee calculation as trade completion.
+    fn quote_trade(env: Env, offer_id: u64, usdc_amount: i128) -> Result<Quote, Error>;
+
+    /// Returns the current minimum and maximum trade amounts.
+    fn get_trade_limits(env: Env) -> (i128, i128);
+
+    /// Returns the active-offer caps.
+    fn get_offer_caps(env: Env) -> (u32, u32);
+
+    /// Returns the ceiling on concurrent open trades.
+    fn get_max_open_trades(env: Env) -> u32;
+
+    /// Returns the KES-side limits for fiat offers.
+    fn get_fiat_limits(env: Env) -> (i128, i128, i128);
+
+    /// Returns the registered payment oracle, if any.
+    fn get_payment_oracle(env: Env) -> Option<Address>;
+
+    /// Returns the relayers allowed to submit trades on behalf of users.
+    fn get_relayers(env: Env) -> Vec<Address>;
+
+    /// Returns the registered receipt attester public key, if any.
+    fn get_receipt_attester(env: Env) -> Option<BytesN<32>>;
+
+    /// Returns the cooling-off period for a buyer's first trade.
+    fn get_first_trade_hold(env: Env) -> u64;
+
+    /// Returns the base cooldown applied after a buyer walks away from a trade.
+    fn get_walkaway_cooldown(env: Env) -> u64;
+
+    /// Returns a buyer's walkaway streak and when their cooldown ends.
+    fn get_buyer_cooldown(env: Env, buyer: Address) -> (u32, u64);
+
+    /// Returns the auto-release window for buyer-confirmed trades.
+    fn get_auto_release_window(env: Env) -> u64;
+
+    /// Returns the grace period between an oracle attestation and release.
+    fn get_attestation_grace_period(env: Env) -> u64;
+
+    /// Returns the registered cKES token used for on-chain settlement, if any.
+    fn get_kes_token(env: Env) -> Option<Address>;
+
+    /// Returns a token's registry entry.
+    fn get_token_config(env: Env, token_id: Address) -> Option<TokenConfig>;
+
+    /// Returns the decimals of the USDC token, cached at initialization.
+    fn get_token_decimals(env: Env) -> u32;
+
+    /// Returns the current trade expiration time in seconds.
+    fn get_trade_expiration(env: Env) -> u64;
+
+    /// Returns the grace period after a trade's nominal deadline before it counts as expired.
+    fn get_expiry_grace(env: Env) -> u64;
+
+    /// Returns the next offer ID that will be assigned, or None while hashed IDs are on.
+    fn get_next_offer_id(env: Env) -> Option<u64>;
+
+    /// Returns the next trade ID that will be assigned, or None while hashed IDs are on.
+    fn get_next_trade_id(env: Env) -> Option<u64>;
+
+    /// Returns whether new offer and trade IDs are derived from a hash.
+    fn get_hashed_ids(env: Env) -> bool;
+
+    /// Returns the nonce the next hashed offer or trade ID of an owner will be derived from.
+    fn get_id_nonce(env: Env, owner: Address) -> u64;
+
+    /// Resolves the offer a seller created with a given nonce, under either ID scheme.
+    fn find_offer_id(env: Env, seller: Address, nonce: u64, ledger: u32) -> Option<u64>;
+
+    /// Resolves the trade a buyer opened with a given nonce, under either ID scheme.
+    fn find_trade_id(env: Env, buyer: Address, nonce: u64, ledger: u32) -> Option<u64>;
+
+    /// Returns all offers in the marketplace.
+    /// Warning: This function can be expensive for large datasets.
+    fn get_offers(env: Env) -> Map<u64, Offer>;
+
+    /// Returns a specific offer by its ID.
+    fn get_offer(env: Env, offer_id: u64) -> Option<Offer>;
+
+    /// Returns who bears the protocol fee on an offer (Buyer unless the seller chose otherwise).
+    fn get_fee_payer(env: Env, offer_id: u64) -> FeePayer;
+
+    /// Returns the buyer requirements set on an offer, if any.
+    fn get_buyer_requirements(env: Env, offer_id: u64) -> Option<BuyerRequirements>;
+
+    /// Returns the per-buyer purchase cap set on an offer, if any.
+    fn get_buyer_cap(env: Env, offer_id: u64) -> Option<(i128, u64)>;
+
+    /// Returns how much USDC a buyer has filled from a capped offer in the current window.
+    fn get_buyer_fill(env: Env, offer_id: u64, buyer: Address) -> i128;
+
+    /// Returns an address's reputation; zero for addresses with no completed trades or lost disputes.
+    fn get_reputation(env: Env, address: Address) -> Reputation;
+
+    /// Returns the USDC a recurring offer may still re-escrow; 0 if it isn't recurring.
+    fn get_recurring_cap(env: Env, offer_id: u64) -> i128;
+
+    /// Returns the implied exchange rate of an offer.
+    fn get_offer_rate(env: Env, offer_id: u64) -> Option<i128>;
+
+    /// Returns the volume-weighted average rate of trades completed in the last 24 hours.
+    fn get_reference_rate(env: Env) -> Option<i128>;
+
+    /// Returns available offers whose implied rate falls within a range.
+    fn get_offers_by_rate_range(env: Env, min_rate: i128, max_rate: i128, limit: u32) -> Vec<u64>;
+
+    /// Returns an order-book depth snapshot: available USDC aggregated per price level.
+    fn get_depth(env: Env, levels: u32) -> Vec<(i128, i128)>;
+
+    /// Returns a page of offers that can be traded right now.
+    /// Applies the same availability rules `initiate_trade` enforces, so frontends
+    /// don't need to replicate them client-side.
+    fn get_available_offers(env: Env, start: u64, limit: u32) -> Vec<(u64, Offer)>;
+
+    /// Returns an open price proposal by its ID.
+    fn get_proposal(env: Env, proposal_id: u64) -> Option<Proposal>;
+
+    /// Returns all trades in the marketplace.
+    /// Warning: This function can be expensive for large datasets.
+    fn get_trades(env: Env) -> Map<u64, Trade>;
+
+    /// Returns a specific trade by its ID.
+    fn get_trade(env: Env, trade_id: u64) -> Option<Trade>;
+
+    /// Returns the payment hash the buyer committed for a trade, if any.
+    fn get_payment_hash(env: Env, trade_id: u64) -> Option<BytesN<32>>;
+
+    /// Returns the trade a client-supplied external reference was attached to.
+    fn get_trade_by_ref(env: Env, external_ref: BytesN<32>) -> Option<Trade>;
+
+    /// Returns the offer a seller created with a client idempotency key.
+    fn get_offer_by_key(env: Env, seller: Address, idempotency_key: BytesN<32>) -> Option<u64>;
+
+    /// Returns when a trade expires and how long is left.
+    /// Applies the contract's own expiration rules, including the extension
+    /// installment trades get from each confirmed tranche.
+    fn get_trade_deadline(env: Env, trade_id: u64) -> Result<(u64, u64), Error>;
+
+    /// Returns the sequence number of the last event addressed to an address.
+    fn get_event_seq(env: Env, address: Address) -> u64;
+
+    /// Returns a trade together with everything needed to render it.
+    fn get_trade_details(env: Env, trade_id: u64) -> Result<TradeDetails, Error>;
+
+    /// Returns the dispute record for a trade.
+    fn get_dispute(env: Env, trade_id: u64) -> Option<Dispute>;
+
+    /// Returns all disputes that have not yet been resolved.
+    fn get_open_disputes(env: Env) -> Vec<Dispute>;
+
+    /// Returns a page of disputed trade IDs ordered by dispute age (oldest first).
+    fn get_disputed_trades(env: Env, start: u32, limit: u32) -> Vec<u64>;
+
+    /// Returns a page of the on-chain admin activity log.
+    fn get_admin_log(env: Env, start: u64, limit: u32) -> Vec<AdminLogEntry>;
+
+    /// Returns the mapping of sellers to their active offer IDs.
+    fn get_active_offers(env: Env) -> Map<Address, Vec<u64>>;
+
+    /// Returns the oldest active offer ID for a specific seller.
+    fn get_seller_active_offer(env: Env, seller: Address) -> Option<u64>;
+
+    /// Returns every active offer ID for a specific seller.
+    /// Market makers may hold several price levels created through `create_offers`.
+    fn get_seller_active_offers(env: Env, seller: Address) -> Vec<u64>;
+
+    /// Returns the total USDC a seller has locked in escrow.
+    fn get_escrowed_balance(env: Env, seller: Address) -> i128;
+
+    /// Returns the number of active offers.
+    /// Maintained as a counter, so it is cheap for dashboards and monitoring.
+    fn get_active_offer_count(env: Env) -> u32;
+
+    /// Returns the number of trades that are initiated, payment-confirmed, or disputed.
+    /// Maintained as a counter, so it is cheap for dashboards and monitoring.
+    fn get_open_trade_count(env: Env) -> u32;
+
+    /// Returns the USDC escrowed across all active offers.
+    /// Maintained as a counter; `verify_escrow` remains the full reconciliation.
+    fn get_total_escrowed(env: Env) -> i128;
+
+    /// Returns whether the contract is currently paused.
+    fn is_paused(env: Env) -> bool;
+
+    /// Returns how long final-state records are kept before they can be pruned.
+    fn get_record_retention(env: Env) -> u64;
+
+    /// Returns how long an offer may go without trade activity before anyone can expire it.
+    fn get_offer_ttl(env: Env) -> u64;
+
+    /// Returns when an active offer can be expired with `expire_offer`.
+    fn get_offer_expiry(env: Env, offer_id: u64) -> Option<u64>;
+
+    /// Returns comprehensive contract configuration and status.
+    /// This is a convenience function that aggregates multiple config values.
+    #[allow(clippy::type_complexity)] // Mirrors the contract, which can't return type aliases of tuples
+    fn get_contract_info(env: Env) -> Result<(Address, Address, Address, u32, i128, i128, u64, bool, u64), Error>;
+
+    /// Returns machine-readable metadata for an error code.
+    fn get_error_info(env: Env, code: u32) -> Option<ErrorInfo>;
+}
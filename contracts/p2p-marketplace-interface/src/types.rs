@@ -0,0 +1,1231 @@
+/*!
+ * Type Definitions for P2P Marketplace Smart Contract
+ * 
+ * This module defines all the data structures, enums, and constants in the marketplace's
+ * public interface. Each type is carefully designed to represent specific
+ * aspects of the trading system with clear semantics and efficient storage.
+ */
+
+use soroban_sdk::{contracterror, contracttype, Address, Bytes, BytesN, String, Symbol, Vec, symbol_short};
+
+// ================================================================================================
+// CORE DATA STRUCTURES
+// ================================================================================================
+
+/// Represents a sell offer in the marketplace.
+/// 
+/// An offer is created when a seller wants to exchange USDC for KES. The seller deposits
+/// USDC into the contract as escrow, and the offer becomes available for buyers to trade against.
+/// 
+/// # Design Decisions
+/// - Seller address identifies who created the offer and owns the escrowed USDC
+/// - USDC amount is stored with 6 decimal precision (Stellar USDC standard)
+/// - KES amount represents the off-chain currency amount expected in return
+/// - No expiration field yet - could be added in future versions
+/// - No partial fulfillment support - offers are atomic (all-or-nothing)
+/// 
+/// # Business Logic
+/// - One offer per seller (enforced by contract logic)
+/// - USDC is held in escrow until trade completion or offer cancellation
+/// - Exchange rate is implicitly defined by usdc_amount / kes_amount ratio
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Offer {
+    /// The address of the seller who created this offer
+    /// This address owns the escrowed USDC and will receive KES payment off-chain
+    pub seller: Address,
+    
+    /// Amount of USDC being offered for sale (with 6 decimal places)
+    /// This amount is held in escrow by the contract until trade completion
+    /// Example: 1_000_000 = 1 USDC, 500_000 = 0.5 USDC
+    pub usdc_amount: i128,
+    
+    /// Amount of KES (Kenyan Shillings) expected in return
+    /// This is settled off-chain through traditional payment methods
+    /// The ratio usdc_amount/kes_amount defines the exchange rate
+    /// Example: 150_000 = 150 KES (assuming 3 decimal precision)
+    pub kes_amount: i128,
+    
+    /// Optional hash of the seller's off-chain terms of sale
+    /// e.g. "payment within 15 min, M-Pesa only"; copied onto every trade
+    pub terms_hash: Option<BytesN<32>>,
+    
+    /// How the KES leg is settled: off-chain (M-Pesa etc.) or on-chain in cKES
+    pub settlement: SettlementMode,
+    
+    /// Optional address that receives refunds of this offer's escrow instead of the seller
+    /// e.g. a cold wallet different from the seller's transacting key
+    pub refund_to: Option<Address>,
+    
+    /// Optional IPFS CID or HTTPS URL of richer listing content
+    /// e.g. seller bio, terms, supported hours; set at creation
+    pub metadata_uri: Option<String>,
+    
+    /// ID of the trade currently holding this offer, if any
+    /// Reserved offers are hidden from availability queries and cannot be edited
+    pub reserved_by: Option<u64>,
+    
+    /// Buyer this offer is bound to when created as a direct (OTC) escrow
+    /// Direct offers are never listed and only the named buyer can trade them
+    pub counterparty: Option<Address>,
+    
+    /// Where the offer is in its lifecycle; only Active offers can be traded
+    pub status: OfferStatus,
+}
+
+/// Represents an active trade between a buyer and seller.
+/// 
+/// A trade is initiated when a buyer chooses to trade against an existing offer.
+/// The trade tracks the progress through various states until completion or cancellation.
+/// 
+/// # Trade Lifecycle
+/// 1. Initiated: Trade created, waiting for payment confirmations
+/// 2. PaymentConfirmed: Both parties confirmed payment, USDC ready for release
+/// 3. Completed: USDC released to buyer, trade successful
+/// 4. Cancelled: Trade cancelled, USDC returned to seller
+/// 5. Disputed: Conflict raised, requires admin intervention
+/// 
+/// # Security Features
+/// - Start time enables expiration checking to prevent indefinite escrow
+/// - Separate confirmation flags prevent single-party manipulation
+/// - Status tracking ensures proper state transitions
+/// - Immutable offer_id links trade to specific offer terms
+/// - Economic terms are snapshotted so settlement never depends on the live offer
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trade {
+    /// The ID of the offer this trade is executing against
+    /// Links this trade to specific offer terms (amounts, seller, etc.)
+    pub offer_id: u64,
+    
+    /// The address of the buyer initiating this trade
+    /// This address will receive the USDC upon successful completion
+    pub buyer: Address,
+    
+    /// The seller of the underlying offer, snapshotted at initiation
+    /// This address receives any refund of the escrowed USDC
+    pub seller: Address,
+    
+    /// Amount of USDC locked in this trade, snapshotted from the offer at initiation
+    /// Used for release, refund, and fee calculation regardless of later offer changes
+    pub usdc_amount: i128,
+    
+    /// Amount of KES the buyer owes off-chain, snapshotted from the offer at initiation
+    pub kes_amount: i128,
+    
+    /// Timestamp when the trade was initiated (in seconds since epoch)
+    /// Used for calculating trade expiration and timeout handling
+    /// Prevents trades from staying active indefinitely
+    pub start_time: u64,
+    
+    /// Current status of the trade in its lifecycle
+    /// Determines what operations are allowed and what happens next
+    pub status: TradeStatus,
+    
+    /// Whether the buyer has confirmed sending KES payment off-chain
+    /// Buyer sets this to true after sending KES via traditional payment methods
+    /// Part of the dual-confirmation system for trade completion
+    pub buyer_confirmed_payment: bool,
+    
+    /// Whether the seller has confirmed receiving KES payment off-chain
+    /// Seller sets this to true after receiving and verifying KES payment
+    /// When both buyer and seller confirm, USDC is automatically released
+    pub seller_confirmed_payment: bool,
+    
+    /// Hash of the offer's terms of sale at the time the trade was initiated
+    /// Gives disputes an immutable reference to what both parties agreed to
+    pub terms_hash: Option<BytesN<32>>,
+    
+    /// Timestamp when the buyer confirmed sending KES, if they have
+    pub buyer_confirmed_at: Option<u64>,
+    
+    /// Timestamp when the seller confirmed receiving KES, if they have
+    pub seller_confirmed_at: Option<u64>,
+    
+    /// Timestamp when the USDC was released and the trade completed
+    pub completed_at: Option<u64>,
+    
+    /// Timestamp when the trade was cancelled (by a participant, expiry, or dispute refund)
+    pub cancelled_at: Option<u64>,
+    
+    /// Settlement mode copied from the offer
+    /// On-chain trades escrow the buyer's cKES and swap both legs atomically
+    pub settlement: SettlementMode,
+    
+    /// Payment oracle's reference for the buyer's KES payment, if attested
+    pub attestation: Option<BytesN<32>>,
+    
+    /// Timestamp of the oracle attestation; release is allowed after the grace period
+    pub attested_at: Option<u64>,
+    
+    /// Payment reference from an attester-signed receipt backing the buyer's confirmation
+    pub buyer_receipt: Option<BytesN<32>>,
+    
+    /// Optional address that receives the buyer's USDC payout instead of the buyer
+    pub receive_to: Option<Address>,
+    
+    /// Seller's refund address, snapshotted from the offer
+    pub refund_to: Option<Address>,
+    
+    /// Partial fiat payments marked by the buyer, in order
+    /// USDC is released once confirmed tranches cover the full KES amount
+    pub tranches: Vec<Tranche>,
+    
+    /// Timestamp of the seller's latest tranche confirmation
+    /// Expiration is measured from here so multi-day installments stay open
+    pub last_tranche_at: Option<u64>,
+    
+    /// Integrating wallet that receives an operator fee at completion, if any
+    pub operator: Option<Address>,
+    
+    /// Operator fee in basis points, withheld from the buyer's payout on top of the protocol fee
+    pub operator_fee_bps: u32,
+    
+    /// Client-supplied reference (e.g. a back-office order ID) for reconciliation, if any
+    pub external_ref: Option<BytesN<32>>,
+}
+
+/// One step of the size-based fee curve.
+/// 
+/// Trades of at least `min_amount` pay `rate` until the next bracket starts,
+/// so small remittances and treasury-sized trades can be priced differently.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeBracket {
+    /// Smallest USDC trade amount (in token base units) this bracket applies to
+    pub min_amount: i128,
+
+    /// Fee rate in basis points for trades in this bracket
+    pub rate: u32,
+}
+
+/// Admin-managed settings for one token the marketplace handles.
+/// 
+/// Registered tokens can be used as swap settlement assets; an entry for the escrow
+/// token itself can override the flat fee rate.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenConfig {
+    /// Token decimals, read from the token contract at registration
+    pub decimals: u32,
+
+    /// Smallest amount accepted per trade in token base units, 0 = no minimum
+    pub min_amount: i128,
+
+    /// Largest amount accepted per trade in token base units, 0 = no maximum
+    pub max_amount: i128,
+
+    /// Fee rate in basis points overriding the flat rate, if any
+    pub fee_rate: Option<u32>,
+
+    /// Whether new offers may use the token
+    pub enabled: bool,
+}
+
+/// Per-instance protocol fee policy, fixed at deployment.
+/// 
+/// Lets white-label operators run fee-free community instances, or pin the fee range
+/// the admin may later choose from.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeePolicy {
+    /// Whether protocol fees are collected; the admin can toggle this later
+    pub fees_enabled: bool,
+
+    /// Lowest fee rate in basis points the admin may set
+    pub fee_floor: u32,
+
+    /// Highest fee rate in basis points the admin may set (max 1000 = 10%)
+    pub fee_ceiling: u32,
+}
+
+/// One recipient's weighted slice of collected trading fees.
+/// 
+/// Configured by admin as a list whose weights sum to 10000 basis points,
+/// e.g. 7000 treasury / 2000 operations / 1000 arbitrator pool.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeSplit {
+    /// Address receiving this slice of fees
+    pub recipient: Address,
+
+    /// Share of the distributable fee in basis points
+    pub weight: u32,
+}
+
+/// A partial fiat payment within an installment trade.
+/// 
+/// Large trades can exceed daily mobile-money limits, so the buyer pays in several
+/// tranches and the seller confirms each one as it arrives.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Tranche {
+    /// KES amount the buyer reports having sent in this tranche
+    pub kes_amount: i128,
+
+    /// Timestamp when the buyer marked the tranche as paid
+    pub marked_at: u64,
+
+    /// Whether the seller has confirmed receiving this tranche
+    pub confirmed: bool,
+}
+
+/// A trader's on-chain track record, built up from completed trades and dispute rulings.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Reputation {
+    /// Number of trades completed as buyer or seller
+    pub completed_trades: u32,
+
+    /// Reputation score, +1 per completed trade, minus the dispute penalty per dispute lost
+    pub score: i128,
+
+    /// Number of disputes resolved against this trader
+    pub disputes_lost: u32,
+}
+
+/// Conditions a buyer must meet to trade against an offer.
+/// 
+/// Lets experienced sellers keep fresh accounts away from large offers.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BuyerRequirements {
+    /// Minimum completed trades the buyer needs, 0 = none
+    pub min_completed_trades: u32,
+
+    /// Minimum reputation score the buyer needs, 0 = none
+    pub min_reputation: i128,
+
+    /// Buyers allowed to trade, empty = anyone meeting the other requirements
+    pub allowed_buyers: Vec<Address>,
+}
+
+/// Represents a dispute raised against a trade.
+///
+/// A dispute record is created when a trade participant escalates a trade and is kept
+/// after resolution so the outcome can be audited on-chain.
+///
+/// # Lifecycle
+/// 1. Created by `raise_dispute` with the reason and any initial evidence
+/// 2. Resolved by the admin, recording who resolved it and the decision taken
+///
+/// # Design Decisions
+/// - Keyed by trade ID, so a trade can have at most one dispute
+/// - Evidence is stored as 32-byte hashes; the documents themselves live off-chain
+/// - `resolved_by` stays `None` and `resolution` stays `Pending` while the dispute is open
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dispute {
+    /// The ID of the disputed trade
+    pub trade_id: u64,
+
+    /// The participant (buyer or seller) who raised the dispute
+    pub raised_by: Address,
+
+    /// Timestamp when the dispute was raised (in seconds since epoch)
+    pub raised_at: u64,
+
+    /// Free-form explanation of the dispute provided by the participant
+    pub reason: String,
+
+    /// Hashes of off-chain evidence (receipts, screenshots, chat logs)
+    pub evidence: Vec<BytesN<32>>,
+
+    /// The panel arbitrator assigned to rule on the dispute, if a panel is registered
+    pub arbitrator: Option<Address>,
+
+    /// Timestamp until which the parties can submit evidence; resolution waits for it
+    pub evidence_until: u64,
+
+    /// Participants who declared their evidence complete; once both have, resolution can proceed early
+    pub evidence_complete: Vec<Address>,
+
+    /// The admin address that resolved the dispute, if resolved
+    pub resolved_by: Option<Address>,
+
+    /// The admin's decision, or `Pending` while the dispute is open
+    pub resolution: DisputeOutcome,
+}
+
+/// A buyer's counter-offer on the price of an existing offer.
+/// 
+/// # Lifecycle
+/// 1. Created by `propose_price` with the KES amount the buyer is willing to pay
+/// 2. Accepted by the seller, which initiates a trade at the negotiated amount,
+///    or rejected/withdrawn by either party
+/// 3. Dropped, with any deposit refunded, once the offer is filled, cancelled, expired or pruned,
+///    or once it has expired and its slot on the offer is needed for a new proposal
+/// 
+/// # Design Decisions
+/// - Only the KES side is negotiable; the escrowed USDC amount is fixed by the offer
+/// - Several buyers may hold open proposals on the same offer, up to a per-offer cap
+/// - Proposals expire, so a seller can't accept a price the buyer offered long ago
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proposal {
+    /// The offer being negotiated
+    pub offer_id: u64,
+
+    /// The buyer proposing the price
+    pub buyer: Address,
+
+    /// KES amount the buyer proposes to pay for the whole offer
+    pub kes_amount: i128,
+
+    /// Optional payout address carried into the trade on acceptance
+    pub receive_to: Option<Address>,
+
+    /// Timestamp when the proposal was made
+    pub created_at: u64,
+
+    /// Timestamp after which the seller can no longer accept the proposal
+    pub expires_at: u64,
+}
+
+/// Read-only preview of a trade's settlement amounts.
+/// 
+/// Returned by `quote_trade` so wallets can show exact take-home numbers before
+/// the buyer initiates. Uses the same fee calculation as settlement.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Quote {
+    /// USDC amount being quoted, before fees
+    pub gross: i128,
+
+    /// Trading fee withheld from the gross amount at settlement (the buyer's share only)
+    pub fee: i128,
+
+    /// USDC the buyer receives on completion (gross - fee)
+    pub net_to_buyer: i128,
+
+    /// KES the buyer must pay for the quoted amount at the offer's rate
+    pub kes_due: i128,
+
+    /// Seconds the buyer has to complete payment once the trade is initiated
+    pub expires_in: u64,
+}
+
+/// Everything a client needs to render a trade screen, from a single call.
+/// 
+/// Returned by `get_trade_details`. Amounts in the fee preview use the fee
+/// configuration in effect now, which is what settlement would apply.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TradeDetails {
+    /// The stored trade record, including the offer terms snapshotted at initiation
+    pub trade: Trade,
+
+    /// Exchange rate implied by the trade amounts (KES per USDC, scaled by 10^7)
+    pub rate: i128,
+
+    /// Timestamp after which the trade can be resolved as expired
+    pub expires_at: u64,
+
+    /// Seconds left before expiry, 0 once expired
+    pub expires_in: u64,
+
+    /// Protocol fee that would be withheld from the buyer at settlement
+    pub fee: i128,
+
+    /// Operator fee that would be withheld at settlement
+    pub operator_fee: i128,
+
+    /// USDC the buyer would receive at settlement
+    pub net_to_buyer: i128,
+
+    /// Dispute record, if the trade was ever disputed
+    pub dispute: TradeDispute,
+}
+
+// ================================================================================================
+// ENUMERATIONS
+// ================================================================================================
+
+/// Represents the current state of a trade in its lifecycle.
+/// 
+/// The status determines which operations are allowed and guides the trade flow.
+/// State transitions are carefully controlled to prevent invalid operations.
+/// 
+/// # State Transition Rules
+/// - Initiated → PaymentConfirmed (when both parties confirm)
+/// - Initiated → Cancelled (by participant request or expiration)
+/// - Initiated → Disputed (when conflicts arise)
+/// - PaymentConfirmed → Completed (automatic USDC release)
+/// - Disputed → Completed or Cancelled (by admin resolution)
+/// 
+/// # Security Considerations
+/// - Final states (Completed, Cancelled) prevent further modifications
+/// - Disputed state requires admin intervention to resolve
+/// - State changes are irreversible to maintain audit trail
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TradeStatus {
+    /// Trade has been created and is waiting for payment confirmations
+    /// Both buyer and seller can still cancel at this stage
+    /// Trade will expire if confirmations don't happen within time limit
+    Initiated,
+    
+    /// Both buyer and seller have confirmed payment completion
+    /// USDC is ready to be released to buyer automatically
+    /// This is a brief transitional state before Completed
+    PaymentConfirmed,
+    
+    /// Trade has been successfully completed
+    /// USDC has been transferred to buyer, fees collected
+    /// This is a final state - no further changes allowed
+    Completed,
+    
+    /// Trade has been cancelled by participants or due to expiration
+    /// USDC has been returned to seller
+    /// This is a final state - no further changes allowed
+    Cancelled,
+    
+    /// A dispute has been raised and requires admin intervention
+    /// No automatic operations can occur until admin resolves the dispute
+    /// Admin can choose to complete trade or cancel it
+    Disputed,
+}
+
+/// Enumeration of possible offer states throughout the offer lifecycle.
+/// 
+/// # State Transitions
+/// - Active → Reserved (a buyer initiates a trade)
+/// - Reserved → Active (the trade is cancelled)
+/// - Reserved → Filled (the trade completes; recurring offers return to Active)
+/// - Reserved → Expired (the trade expires and the escrow is refunded)
+/// - Active → Cancelled (the seller withdraws the offer)
+/// - Active or Reserved → Paused (compliance freeze) and back when unfrozen
+/// 
+/// Filled, Cancelled and Expired offers keep their record until pruned.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OfferStatus {
+    /// Listed and open to new trades
+    Active,
+    
+    /// Held by the trade in `reserved_by`
+    Reserved,
+    
+    /// Frozen by the compliance officer; no trades or cancellation until unfrozen
+    Paused,
+    
+    /// Sold through a completed trade, escrow released to the buyer
+    Filled,
+    
+    /// Withdrawn by the seller or refunded through a dispute, escrow returned
+    Cancelled,
+    
+    /// Its trade expired, or it sat untouched past the offer TTL, and the escrow was returned to the seller
+    Expired,
+}
+
+/// Determines how the KES leg of a trade is settled.
+/// 
+/// # Modes
+/// - OffChain: Buyer pays KES through traditional rails; both parties confirm
+/// - OnChain: Buyer escrows the registered cKES token at initiation and the
+///   contract swaps both legs atomically, with no confirmations required
+/// - Swap: Like OnChain, but against any other on-chain asset (e.g. EURC);
+///   `kes_amount` is then denominated in that asset
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SettlementMode {
+    /// KES is paid off-chain and confirmed by both participants
+    OffChain,
+    
+    /// KES is paid on-chain in the registered cKES token
+    OnChain,
+    
+    /// The counter leg is paid on-chain in the given token contract
+    Swap(Address),
+}
+
+/// Determines who bears the protocol fee when an offer's trade settles.
+/// 
+/// # Settlement
+/// - Buyer: The fee is withheld from the buyer's payout (default)
+/// - Seller: The buyer receives the full amount; the fee comes from the seller's fee reserve
+/// - Split: Each side bears half, the buyer's half rounded up
+/// 
+/// The seller's share is backed by a reserve escrowed alongside the offer, sized for
+/// the instance's fee ceiling; whatever the settled fee doesn't use is returned.
+/// Operator fees are always withheld from the buyer.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FeePayer {
+    /// Fee withheld from the buyer's payout
+    Buyer,
+
+    /// Fee paid by the seller from the offer's fee reserve
+    Seller,
+
+    /// Fee shared equally between buyer and seller
+    Split,
+}
+
+/// Represents admin's decision when resolving a disputed trade.
+/// 
+/// When trades are disputed, only the admin can resolve them by choosing
+/// one of two outcomes based on off-chain investigation.
+/// 
+/// # Resolution Logic
+/// - ReleaseToBuyer: Admin determined payment was successful, complete the trade
+/// - RefundToSeller: Admin determined payment failed or was fraudulent, cancel trade
+/// 
+/// # Fee Handling
+/// - ReleaseToBuyer: Normal fees are collected as if trade completed normally
+/// - RefundToSeller: No fees collected, full amount returned to seller
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DisputeResolution {
+    /// Release escrowed USDC to buyer (minus fees)
+    /// Used when admin determines the trade should complete successfully
+    /// Fees are collected normally as this counts as a successful trade
+    ReleaseToBuyer,
+    
+    /// Refund full USDC amount to seller (no fees)
+    /// Used when admin determines the trade should be cancelled
+    /// No fees collected as this is treated as a failed/fraudulent trade
+    RefundToSeller,
+}
+
+/// Represents the outcome recorded on a dispute.
+/// 
+/// Wraps the admin's `DisputeResolution` so an open dispute can be represented
+/// explicitly on the stored record.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DisputeOutcome {
+    /// Dispute is open and awaiting admin resolution
+    Pending,
+
+    /// Dispute was resolved with the given decision
+    Resolved(DisputeResolution),
+
+    /// Dispute was closed without a ruling when the marketplace wound down
+    Closed,
+}
+
+/// Dispute section of `TradeDetails`.
+/// 
+/// Explicit variants instead of `Option<Dispute>` so the record stays a plain contract type.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[allow(clippy::large_enum_variant)] // Contract types are plain values; boxing isn't supported
+pub enum TradeDispute {
+    /// The trade has never been disputed
+    NotDisputed,
+
+    /// The trade was disputed; carries the dispute record
+    Disputed(Dispute),
+}
+
+/// High-impact admin operations that require M-of-N approval when multisig admin is enabled.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AdminAction {
+    /// Transfer admin control to a new address
+    UpdateAdmin(Address),
+
+    /// Upgrade the contract to a new Wasm hash
+    Upgrade(BytesN<32>),
+
+    /// Resolve a disputed trade with the given decision
+    ResolveDispute(u64, DisputeResolution),
+
+    /// Replace the admin signer set and approval threshold (empty set disables multisig)
+    SetSigners(Vec<Address>, u32),
+}
+
+/// An admin action awaiting approval from the signer set.
+/// 
+/// # Lifecycle
+/// 1. Proposed by a signer, whose proposal counts as the first approval
+/// 2. Approved by further signers until the threshold is met
+/// 3. Executed by anyone once approved; the record is then removed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingAction {
+    /// The operation to perform
+    pub action: AdminAction,
+
+    /// Signer that proposed the action
+    pub proposer: Address,
+
+    /// Signers that have approved, including the proposer
+    pub approvals: Vec<Address>,
+
+    /// Timestamp when the action was proposed
+    pub created_at: u64,
+}
+
+/// A single entry in the on-chain admin activity log.
+/// 
+/// # Value Encoding
+/// - `old_value` and `new_value` hold the XDR encoding of the affected setting
+/// - Empty bytes mean the setting was unset (getters fall back to their defaults)
+/// - Multi-value settings are encoded as a tuple, in the setter's argument order
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AdminLogEntry {
+    /// Sequential log ID, never reused after older entries are dropped
+    pub id: u64,
+
+    /// Address whose authorization performed the action
+    pub actor: Address,
+
+    /// Short name of the action, e.g. "fee_rate" or "pause"
+    pub action: Symbol,
+
+    /// Ledger timestamp of the action
+    pub timestamp: u64,
+
+    /// Value before the action
+    pub old_value: Bytes,
+
+    /// Value after the action
+    pub new_value: Bytes,
+}
+
+// ================================================================================================
+// ERROR DEFINITIONS
+// ================================================================================================
+
+/// Comprehensive error types for all possible failure scenarios in the marketplace.
+/// 
+/// Each error is assigned a unique numeric code for easy identification in logs
+/// and client applications. Error codes are grouped logically by function area.
+/// 
+/// # Error Code Ranges
+/// - 1-5: Entity not found errors
+/// - 6-10: Authorization and access control errors  
+/// - 11-15: Business logic and validation errors
+/// - 16-20: Technical and system errors
+/// 
+/// # Design Principles
+/// - Descriptive names that clearly indicate the problem
+/// - Unique numeric codes for programmatic handling
+/// - Comprehensive coverage of all failure scenarios
+/// - Grouped by logical categories for maintainability
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    // ========== Entity Not Found Errors (1-5) ==========
+    
+    /// Requested offer ID does not exist in the marketplace
+    /// This can happen if offer was never created, already cancelled, or completed
+    OfferNotFound = 1,
+    
+    /// Requested trade ID does not exist in the marketplace
+    /// This can happen if trade was never created or ID is invalid
+    TradeNotFound = 2,
+    
+    // ========== Business Rule Violations (3-7) ==========
+    
+    /// Seller already has an active offer and cannot create another
+    /// Business rule: one active offer per seller to prevent liquidity fragmentation
+    AlreadyHasActiveOffer = 3,
+    
+    /// Trade has exceeded its time limit and is no longer valid
+    /// Trades expire to prevent indefinite escrow situations
+    TradeExpired = 4,
+    
+    /// Operation is not allowed for the current trade status
+    /// Each trade status has specific allowed operations
+    InvalidTradeStatus = 5,
+    
+    /// Caller is not authorized to perform this operation
+    /// Used for access control and participant validation
+    Unauthorized = 6,
+    
+    /// Offer already has an active trade and cannot accept another
+    /// Business rule: one trade per offer to maintain order
+    TradeAlreadyInitiated = 7,
+    
+    /// Contract is paused and trading operations are disabled
+    /// Emergency mechanism for maintenance or security issues
+    ContractPaused = 8,
+    
+    /// Trade has not yet expired (opposite of TradeExpired)
+    /// Used when trying to resolve non-expired trades
+    TradeNotExpired = 9,
+    
+    // ========== Token and Financial Errors (10-14) ==========
+    
+    /// Token allowance is too low for the requested transfer
+    /// Code kept stable; balance shortfalls now return InsufficientBalance
+    InsufficientAllowance = 10,
+    
+    /// Input amount or configuration value is invalid (negative, zero, or malformed)
+    /// Trade-limit violations use BelowMinimum / AboveMaximum instead
+    InvalidAmount = 11,
+    
+    /// Token transfer operation failed for technical reasons
+    /// Could indicate network issues, token contract problems, or insufficient gas
+    TokenTransferFailed = 12,
+    
+    /// Provided token address is not a valid token contract
+    /// Used during initialization to validate USDC token address
+    InvalidTokenAddress = 13,
+    
+    /// User has exceeded rate limits for operations
+    /// Anti-spam mechanism, e.g. too many open price proposals on one offer
+    RateLimitExceeded = 14,
+    
+    // ========== Timing Errors (15+) ==========
+    
+    /// A waiting period (e.g. attestation grace period) has not yet elapsed
+    /// The operation can be retried once the period is over
+    GracePeriodActive = 15,
+    
+    // ========== Compliance Errors (16+) ==========
+    
+    /// Address would exceed its daily traded USDC cap
+    /// Cap resets at the start of the next UTC day (ledger time)
+    DailyLimitExceeded = 16,
+    
+    /// Trade or offer is frozen pending a compliance review
+    /// No state transitions are allowed until the compliance officer unfreezes it
+    Frozen = 17,
+    
+    // ========== Negotiation Errors (18+) ==========
+    
+    /// Requested price proposal does not exist
+    /// It may never have been made, or was already accepted, rejected or withdrawn
+    ProposalNotFound = 18,
+    
+    // ========== Governance Errors (19+) ==========
+    
+    /// Requested admin action does not exist or was already executed
+    ActionNotFound = 19,
+    
+    /// Admin action does not yet have enough signer approvals to execute
+    ThresholdNotMet = 20,
+    
+    // ========== Confirmation Errors (21+) ==========
+    
+    /// Buyer has already confirmed sending payment for this trade
+    /// Repeat confirmations are rejected so clients get a precise reason
+    BuyerAlreadyConfirmed = 21,
+    
+    /// Seller has already confirmed receiving payment for this trade
+    SellerAlreadyConfirmed = 22,
+    
+    // ========== Precise Validation Errors (23+) ==========
+    
+    /// Provided address failed validation
+    InvalidAddress = 23,
+    
+    /// Sender doesn't hold enough tokens for the transfer
+    /// Returned when the token contract rejects a transfer for lack of funds
+    InsufficientBalance = 24,
+    
+    /// Amount is below the configured minimum trade amount
+    BelowMinimum = 25,
+    
+    /// Amount is above the configured maximum trade amount
+    AboveMaximum = 26,
+    
+    /// Offer is past its expiry and can no longer be traded
+    OfferExpired = 27,
+    
+    /// Contract configuration has not been set up
+    NotInitialized = 28,
+    
+    /// Offer's implied KES/USDC rate is outside the configured sanity range
+    /// Usually a mistyped amount on one side of the offer
+    RateOutOfBounds = 29,
+    
+    /// Marketplace or seller already has the maximum number of active offers
+    /// Cancel or fill existing offers before listing more
+    OfferLimitReached = 30,
+    
+    /// Buyer doesn't meet the requirements the seller set on the offer
+    RequirementsNotMet = 31,
+    
+    /// External reference is already attached to another trade
+    DuplicateReference = 32,
+    
+    /// Buyer has already filled as much of this offer as the seller allows per window
+    BuyerCapExceeded = 33,
+
+    /// Buyer is cooling down after cancelling or letting trades expire
+    CooldownActive = 34,
+
+    /// Marketplace already has the maximum number of open trades
+    TradeLimitReached = 35,
+
+    /// The dispute's evidence window has closed, or the participant declared their evidence complete
+    EvidenceWindowClosed = 36,
+
+    /// Address was suspended after repeatedly losing disputes and needs admin reinstatement
+    Suspended = 37,
+
+    /// Operation is only allowed while the contract is paused
+    ContractNotPaused = 38,
+
+    /// Emergency refunds are only available once the marketplace is winding down
+    WindDownInactive = 39,
+
+    /// Offer's rate or amount no longer meets the buyer's fill-or-kill constraints
+    FillConditionsNotMet = 40,
+
+    /// Seller's preimage doesn't match the buyer's payment hash
+    PreimageMismatch = 41,
+
+    /// Price proposal is past its expiry and can no longer be accepted
+    ProposalExpired = 42,
+}
+
+/// Broad class of an `Error`, for grouping messages in client UIs.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorCategory {
+    /// The referenced offer, trade, proposal or action doesn't exist
+    NotFound,
+
+    /// The record isn't in a state that allows the operation
+    State,
+
+    /// The caller may not perform the operation
+    Authorization,
+
+    /// An argument is malformed or out of range
+    Validation,
+
+    /// A token balance, allowance or transfer is the problem
+    Funds,
+
+    /// A deadline has passed or a waiting period hasn't
+    Timing,
+
+    /// A marketplace, seller or buyer limit is reached
+    Limits,
+
+    /// A compliance freeze or suspension applies
+    Compliance,
+
+    /// The marketplace is paused, winding down or not set up
+    Availability,
+}
+
+/// How a client should surface an `Error` to the user.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorSeverity {
+    /// Nothing is wrong with the request; refresh state or wait
+    Info,
+
+    /// The user can fix the request themselves
+    Warning,
+
+    /// Support or an admin has to step in
+    Critical,
+}
+
+/// Machine-readable metadata for one `Error` code.
+/// 
+/// Returned by `get_error_info` so wallets can render actionable messages
+/// without hardcoding the error table; `contract_errors.json` mirrors it with English messages.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ErrorInfo {
+    /// The `Error` discriminant
+    pub code: u32,
+
+    /// The `Error` variant name, e.g. `OfferNotFound`, usable as a localization key
+    pub name: Symbol,
+
+    /// Broad class of the error
+    pub category: ErrorCategory,
+
+    /// How prominently to surface the error
+    pub severity: ErrorSeverity,
+
+    /// Whether the same call can succeed later without changing its arguments
+    pub retryable: bool,
+
+    /// Argument names that usually cause the error, for highlighting form fields
+    pub params: Vec<Symbol>,
+}
+
+// ================================================================================================
+// EVENT CONSTANTS
+// ================================================================================================
+// These symbols are used for emitting events that provide transparency and enable
+// off-chain indexing and monitoring of marketplace activities.
+//
+// Topic scheme: the first topic is the event symbol and the second is the address the
+// event affects, so RPC topic filters can select every event involving an address.
+// - Trade and proposal events are emitted twice, once per party: (symbol, party, counterparty, seq)
+// - Offer events go to the seller, payout events to the recipient: (symbol, address, seq)
+// - Protocol-level events (admin actions, yield, compliance reports) carry the symbol only
+//
+// `seq` is the addressed party's event sequence number: it starts at 1 and increases by one
+// per event addressed to that party, so gaps reveal missed events.
+
+/// Event emitted when a new offer is created
+/// Topics: (symbol, seller, seq)
+/// Contains: (offer_id, usdc_amount, kes_amount, metadata_uri)
+/// Used by: create_offer, create_offers and create_direct_escrow functions
+pub const OFFER_CREATED: Symbol = symbol_short!("offr_crt");
+
+/// Event emitted when a trade is initiated against an offer
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, offer_id, usdc_amount, kes_amount)
+/// Used by: initiate_trade function
+pub const TRADE_INITIATED: Symbol = symbol_short!("trd_init");
+
+/// Event emitted when a participant confirms payment
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, confirmed_by)
+/// Used by: confirm_payment function
+pub const PAYMENT_CONFIRMED: Symbol = symbol_short!("pay_conf");
+
+/// Event emitted when a trade is successfully completed
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, offer_id, usdc_amount, kes_amount)
+/// Used by: release_usdc function (internal)
+pub const TRADE_COMPLETED: Symbol = symbol_short!("trd_comp");
+
+/// Event emitted when a trade is cancelled
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, cancelled_by)
+/// Used by: cancel_trade function
+pub const TRADE_CANCELLED: Symbol = symbol_short!("trd_canc");
+
+/// Event emitted when an offer is cancelled
+/// Topics: (symbol, seller, seq)
+/// Contains: (offer_id)
+/// Used by: cancel_offer function
+pub const OFFER_CANCELLED: Symbol = symbol_short!("offr_canc");
+
+/// Event emitted when an untouched offer is expired past the offer TTL
+/// Topics: (symbol, seller, seq)
+/// Contains: (offer_id, refund_amount)
+/// Used by: expire_offer function
+pub const OFFER_EXPIRED: Symbol = symbol_short!("offr_exp");
+
+/// Event emitted when a dispute is raised for a trade
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, raised_by)
+/// Used by: raise_dispute function
+pub const DISPUTE_RAISED: Symbol = symbol_short!("dis_rais");
+
+/// Event emitted when a dispute is assigned to a panel arbitrator
+/// Topics: (symbol, arbitrator, seq)
+/// Contains: (trade_id)
+/// Used by: raise_dispute function
+pub const DISPUTE_ASSIGNED: Symbol = symbol_short!("dis_asgn");
+
+/// Event emitted when a participant submits evidence on an open dispute
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, participant, evidence, complete)
+/// Used by: submit_evidence function
+pub const EVIDENCE_SUBMITTED: Symbol = symbol_short!("evid_sub");
+
+/// Event emitted when both parties reopen a trade cancelled by mistake
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, re_escrowed_amount)
+/// Used by: reopen_trade function
+pub const TRADE_REOPENED: Symbol = symbol_short!("trd_reopn");
+
+/// Event emitted when escrow is migrated to a successor contract
+/// Topics: (symbol, successor, seq)
+/// Contains: (offers, trades, amount)
+/// Used by: migrate_escrow function
+pub const ESCROW_MIGRATED: Symbol = symbol_short!("migrated");
+
+/// Event emitted when an offer's escrow is returned during a wind-down
+/// Topics: (symbol, seller, seq)
+/// Contains: (offer_id, amount)
+/// Used by: emergency_refund function
+pub const EMERGENCY_REFUND: Symbol = symbol_short!("emrg_rfnd");
+
+/// Event emitted when an admin resolves a dispute
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, resolution)
+/// Used by: resolve_dispute function
+pub const DISPUTE_RESOLVED: Symbol = symbol_short!("dis_resl");
+
+/// Event emitted when the insurance fund compensates an address
+/// Topics: (symbol, recipient, seq)
+/// Contains: (amount)
+/// Used by: compensate function
+pub const INSURANCE_COMPENSATED: Symbol = symbol_short!("ins_comp");
+
+/// Event emitted when surplus USDC is swept out of the contract
+/// Topics: (symbol, recipient, seq)
+/// Contains: (amount)
+/// Used by: sweep_surplus function
+pub const SURPLUS_SWEPT: Symbol = symbol_short!("surp_swp");
+
+/// Event emitted when the payment oracle attests a buyer's KES payment
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, attestation)
+/// Used by: attest_payment function
+pub const PAYMENT_ATTESTED: Symbol = symbol_short!("pay_attst");
+
+/// Event emitted when the seller reveals the preimage of the buyer's payment hash
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, preimage)
+/// Used by: confirm_payment_with_preimage function
+pub const PAYMENT_REVEALED: Symbol = symbol_short!("pay_rvl");
+
+/// Event emitted when the compliance officer freezes a trade
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id)
+/// Used by: freeze_trade function
+pub const TRADE_FROZEN: Symbol = symbol_short!("trd_frz");
+
+/// Event emitted when the compliance officer unfreezes a trade
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id)
+/// Used by: unfreeze_trade function
+pub const TRADE_UNFROZEN: Symbol = symbol_short!("trd_unfrz");
+
+/// Event emitted when the compliance officer freezes an offer
+/// Topics: (symbol, seller, seq)
+/// Contains: (offer_id)
+/// Used by: freeze_offer function
+pub const OFFER_FROZEN: Symbol = symbol_short!("ofr_frz");
+
+/// Event emitted when an address is suspended for repeatedly losing disputes
+/// Topics: (symbol, address, seq)
+/// Contains: (disputes_lost_in_window)
+/// Used by: dispute resolution
+pub const ADDRESS_SUSPENDED: Symbol = symbol_short!("suspended");
+
+/// Event emitted when the admin reinstates a suspended address
+/// Topics: (symbol, address, seq)
+/// Contains: ()
+/// Used by: reinstate function
+pub const ADDRESS_REINSTATED: Symbol = symbol_short!("reinstate");
+
+/// Event emitted when the compliance officer unfreezes an offer
+/// Topics: (symbol, seller, seq)
+/// Contains: (offer_id)
+/// Used by: unfreeze_offer function
+pub const OFFER_UNFROZEN: Symbol = symbol_short!("ofr_unfrz");
+
+/// Event emitted when a completed trade meets the large-trade reporting threshold
+/// Contains: (trade_id, usdc_amount, buyer, seller)
+/// Used by: release_usdc function (internal)
+pub const LARGE_TRADE: Symbol = symbol_short!("large_trd");
+
+/// Event emitted when a buyer proposes a price on an offer
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (proposal_id, offer_id, kes_amount)
+/// Used by: propose_price function
+pub const PROPOSAL_MADE: Symbol = symbol_short!("prop_new");
+
+/// Event emitted when a seller accepts a price proposal
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (proposal_id, trade_id)
+/// Used by: accept_proposal function
+pub const PROPOSAL_ACCEPTED: Symbol = symbol_short!("prop_acc");
+
+/// Event emitted when a proposal is rejected by the seller or withdrawn by the buyer
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (proposal_id, rejected_by)
+/// Used by: reject_proposal function
+pub const PROPOSAL_REJECTED: Symbol = symbol_short!("prop_rej");
+
+/// Event emitted when a buyer marks a partial fiat payment
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, tranche_index, kes_amount)
+/// Used by: mark_tranche function
+pub const TRANCHE_MARKED: Symbol = symbol_short!("trnch_mk");
+
+/// Event emitted when a seller confirms a partial fiat payment
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, tranche_index, confirmed_kes_total)
+/// Used by: confirm_tranche function
+pub const TRANCHE_CONFIRMED: Symbol = symbol_short!("trnch_cf");
+
+/// Event emitted when a final-state trade is pruned from storage
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, trade) - the full record for off-chain archival
+/// Used by: prune_trade function
+pub const TRADE_PRUNED: Symbol = symbol_short!("trd_prune");
+
+/// Event emitted when a filled or expired offer is pruned from storage
+/// Topics: (symbol, seller, seq)
+/// Contains: (offer_id, offer) - the full record for off-chain archival
+/// Used by: prune_offer function
+pub const OFFER_PRUNED: Symbol = symbol_short!("ofr_prune");
+
+/// Event emitted when a signer proposes a multisig admin action
+/// Contains: (action_id, action)
+/// Used by: propose_admin_action function
+pub const ACTION_PROPOSED: Symbol = symbol_short!("act_prop");
+
+/// Event emitted when a signer approves a multisig admin action
+/// Contains: (action_id, approval_count)
+/// Used by: approve_action function
+pub const ACTION_APPROVED: Symbol = symbol_short!("act_appr");
+
+/// Event emitted when an approved multisig admin action is executed
+/// Contains: (action_id)
+/// Used by: execute_action function
+pub const ACTION_EXECUTED: Symbol = symbol_short!("act_exec");
+
+/// Event emitted when an expired trade is resolved and its escrow returned
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, offer_id, usdc_returned, triggered_by)
+/// Used by: resolve_expired_trade function
+pub const TRADE_EXPIRED: Symbol = symbol_short!("trd_exp");
+
+/// Event emitted when a recurring offer is re-escrowed and listed again after completing
+/// Topics: (symbol, seller, seq)
+/// Contains: (offer_id, usdc_amount, remaining_cap)
+/// Used by: release_usdc function
+pub const OFFER_RELISTED: Symbol = symbol_short!("ofr_relst");
+
+/// Event emitted when accrued yield is withdrawn into the insurance fund
+/// Contains: (amount)
+/// Used by: harvest_yield function
+pub const YIELD_HARVESTED: Symbol = symbol_short!("yld_harv");
+
+/// Event emitted when a payout can't be delivered and is parked for later withdrawal
+/// Topics: (symbol, recipient, seq)
+/// Contains: (trade_id, amount)
+/// Used by: release_usdc, resolve_dispute functions
+pub const PAYOUT_PARKED: Symbol = symbol_short!("pay_park");
+
+/// Event emitted when a panel arbitrator claims their accrued fees
+/// Topics: (symbol, arbitrator, seq)
+/// Contains: (amount)
+/// Used by: claim_arbitrator_fees function
+pub const ARBITRATOR_PAID: Symbol = symbol_short!("arb_paid");
+
+/// Event emitted when a parked payout is withdrawn
+/// Topics: (symbol, recipient, seq)
+/// Contains: (amount)
+/// Used by: withdraw function
+pub const PAYOUT_CLAIMED: Symbol = symbol_short!("pay_claim");
+
+/// Event emitted when a dropped proposal's deposit can't be refunded and is parked
+/// Topics: (symbol, buyer, seq)
+/// Contains: (proposal_id, amount)
+/// Used by: functions that drop proposals (reject_proposal, settlement, offer cancellation and expiry)
+pub const DEPOSIT_PARKED: Symbol = symbol_short!("dep_park");
+
+/// Event emitted when a parked proposal deposit is withdrawn
+/// Topics: (symbol, buyer, seq)
+/// Contains: (proposal_id, amount)
+/// Used by: claim_proposal_deposit function
+pub const DEPOSIT_CLAIMED: Symbol = symbol_short!("dep_claim");
+
+/// Event emitted when a buyer's first completed trade is held for the cooling-off period
+/// Topics: (symbol, party, counterparty, seq), once for the buyer and once for the seller
+/// Contains: (trade_id, release_at)
+/// Used by: confirm_payment and other confirmation functions
+pub const RELEASE_HELD: Symbol = symbol_short!("rel_held");
+
+/// Event disclosing an offer's encrypted payment details to the buyer who opened a trade
+/// Topics: (symbol, buyer, seq)
+/// Contains: (trade_id, details)
+/// Used by: initiate_trade function
+pub const PAYMENT_DETAILS: Symbol = symbol_short!("pay_dtls");
@@ -0,0 +1,13 @@
+//! Interface the marketplace expects from an external arbitration contract.
+//!
+//! When the admin delegates dispute resolution, every new dispute is forwarded to the
+//! arbitration contract with `open_case`. The arbitrator later calls the marketplace's
+//! `submit_ruling` with its decision, e.g. once a juror pool has voted.
+
+use soroban_sdk::{contractclient, Address, Env};
+
+#[contractclient(name = "ArbitrationClient")]
+pub trait Arbitration {
+    /// Opens a case for a disputed trade; `raised_by` is the participant who disputed
+    fn open_case(env: Env, trade_id: u64, raised_by: Address, usdc_amount: i128);
+}
@@ -0,0 +1,73 @@
+//! Metadata table behind `get_error_info`.
+//!
+//! One row per `Error` variant, in code order. Keep it in step with the enum and with
+//! `contract_errors.json`, which carries the same rows plus English messages.
+
+use soroban_sdk::{Env, Symbol, Vec};
+
+use crate::types::{ErrorCategory, ErrorInfo, ErrorSeverity};
+
+/// Looks up the metadata for an `Error` code, or `None` if no variant has that code.
+pub fn error_info(env: &Env, code: u32) -> Option<ErrorInfo> {
+    use ErrorCategory::*;
+    use ErrorSeverity::*;
+
+    let (name, category, severity, retryable, params): (&str, ErrorCategory, ErrorSeverity, bool, &[&str]) = match code {
+        1 => ("OfferNotFound", NotFound, Warning, false, &["offer_id"]),
+        2 => ("TradeNotFound", NotFound, Warning, false, &["trade_id"]),
+        3 => ("AlreadyHasActiveOffer", State, Warning, false, &["seller"]),
+        4 => ("TradeExpired", Timing, Warning, false, &["trade_id"]),
+        5 => ("InvalidTradeStatus", State, Warning, false, &["trade_id"]),
+        6 => ("Unauthorized", Authorization, Warning, false, &[]),
+        7 => ("TradeAlreadyInitiated", State, Info, false, &["offer_id"]),
+        8 => ("ContractPaused", Availability, Info, true, &[]),
+        9 => ("TradeNotExpired", Timing, Info, true, &["trade_id"]),
+        10 => ("InsufficientAllowance", Funds, Warning, true, &["usdc_amount"]),
+        11 => ("InvalidAmount", Validation, Warning, false, &["usdc_amount", "kes_amount"]),
+        12 => ("TokenTransferFailed", Funds, Critical, true, &[]),
+        13 => ("InvalidTokenAddress", Validation, Warning, false, &["settlement", "token"]),
+        14 => ("RateLimitExceeded", Limits, Info, true, &[]),
+        15 => ("GracePeriodActive", Timing, Info, true, &[]),
+        16 => ("DailyLimitExceeded", Limits, Warning, true, &["usdc_amount"]),
+        17 => ("Frozen", Compliance, Critical, true, &["trade_id", "offer_id"]),
+        18 => ("ProposalNotFound", NotFound, Warning, false, &["proposal_id"]),
+        19 => ("ActionNotFound", NotFound, Warning, false, &["action_id"]),
+        20 => ("ThresholdNotMet", Authorization, Info, true, &["action_id"]),
+        21 => ("BuyerAlreadyConfirmed", State, Info, false, &["trade_id"]),
+        22 => ("SellerAlreadyConfirmed", State, Info, false, &["trade_id"]),
+        23 => ("InvalidAddress", Validation, Warning, false, &[]),
+        24 => ("InsufficientBalance", Funds, Warning, true, &["usdc_amount"]),
+        25 => ("BelowMinimum", Limits, Warning, false, &["usdc_amount"]),
+        26 => ("AboveMaximum", Limits, Warning, false, &["usdc_amount"]),
+        27 => ("OfferExpired", Timing, Warning, false, &["offer_id"]),
+        28 => ("NotInitialized", Availability, Critical, false, &[]),
+        29 => ("RateOutOfBounds", Validation, Warning, false, &["usdc_amount", "kes_amount"]),
+        30 => ("OfferLimitReached", Limits, Warning, true, &[]),
+        31 => ("RequirementsNotMet", Authorization, Warning, false, &["offer_id"]),
+        32 => ("DuplicateReference", Validation, Warning, false, &["external_ref"]),
+        33 => ("BuyerCapExceeded", Limits, Warning, true, &["offer_id"]),
+        34 => ("CooldownActive", Timing, Info, true, &[]),
+        35 => ("TradeLimitReached", Limits, Info, true, &[]),
+        36 => ("EvidenceWindowClosed", Timing, Warning, false, &["trade_id"]),
+        37 => ("Suspended", Compliance, Critical, false, &[]),
+        38 => ("ContractNotPaused", Availability, Warning, false, &[]),
+        39 => ("WindDownInactive", Availability, Warning, false, &[]),
+        40 => ("FillConditionsNotMet", Validation, Warning, false, &["max_rate", "max_usdc"]),
+        41 => ("PreimageMismatch", Validation, Warning, false, &["preimage"]),
+        42 => ("ProposalExpired", Timing, Warning, false, &["proposal_id"]),
+        _ => return None,
+    };
+
+    let mut param_names = Vec::new(env);
+    for param in params {
+        param_names.push_back(Symbol::new(env, param));
+    }
+    Some(ErrorInfo {
+        code,
+        name: Symbol::new(env, name),
+        category,
+        severity,
+        retryable,
+        params: param_names,
+    })
+}
@@ -24,62 +24,108 @@
  */
 
 #![no_std]
+// Contract functions take their arguments flat, and the generated client mirrors them
+#![allow(clippy::too_many_arguments)]
 
-mod types;
+pub mod arbitration;
+pub mod error_info;
+pub mod migration;
+pub mod state_machine;
+pub mod types;
+pub mod yield_adapter;
 
 #[cfg(test)]
 mod test;
 
+#[cfg(any(test, feature = "testutils"))]
+pub mod testutils;
+
 use soroban_sdk::{
     contract,
     contractimpl,
     token,
-    Address, Env, Map, Symbol, log, symbol_short, BytesN
+    xdr::ToXdr,
+    Address, Bytes, Env, IntoVal, Map, log, symbol_short, BytesN, String, Symbol, TryFromVal, Val, Vec
 };
 
 use types::{
-    Error, Offer, Trade, TradeStatus, DisputeResolution,
-    OFFER_CREATED, TRADE_INITIATED, PAYMENT_CONFIRMED, TRADE_COMPLETED,
-    TRADE_CANCELLED, OFFER_CANCELLED, DISPUTE_RAISED, DISPUTE_RESOLVED
+    ConfigKey, DataKey, Error, Offer, OfferStatus, Trade, TradeStatus, Dispute, DisputeOutcome, DisputeResolution, SettlementMode, Quote, Proposal, Tranche, AdminAction, PendingAction, AdminLogEntry, FeeSplit, FeeBracket, FeePolicy, FeePayer, TokenConfig, Reputation, BuyerRequirements, TradeDetails, TradeDispute, ErrorInfo,
+    OFFER_CREATED, TRADE_INITIATED, PAYMENT_CONFIRMED, TRADE_COMPLETED, TRADE_REOPENED,
+    TRADE_CANCELLED, OFFER_CANCELLED, DISPUTE_RAISED, DISPUTE_ASSIGNED, EVIDENCE_SUBMITTED, DISPUTE_RESOLVED,
+    INSURANCE_COMPENSATED, SURPLUS_SWEPT, PAYMENT_ATTESTED,
+    TRADE_FROZEN, TRADE_UNFROZEN, OFFER_FROZEN, OFFER_UNFROZEN, LARGE_TRADE,
+    PROPOSAL_MADE, PROPOSAL_ACCEPTED, PROPOSAL_REJECTED, TRANCHE_MARKED, TRANCHE_CONFIRMED,
+    TRADE_PRUNED, OFFER_PRUNED, ACTION_PROPOSED, ACTION_APPROVED, ACTION_EXECUTED, TRADE_EXPIRED,
+    OFFER_RELISTED, YIELD_HARVESTED, PAYOUT_PARKED, PAYOUT_CLAIMED, RELEASE_HELD, PAYMENT_DETAILS,
+    ARBITRATOR_PAID, ADDRESS_SUSPENDED, ADDRESS_REINSTATED, ESCROW_MIGRATED, EMERGENCY_REFUND,
+    PAYMENT_REVEALED, OFFER_EXPIRED, DEPOSIT_PARKED, DEPOSIT_CLAIMED
 };
+use p2p_marketplace_interface::P2PMarketplaceTrait;
+use arbitration::ArbitrationClient;
+use migration::SuccessorClient;
+use state_machine::transition;
+use yield_adapter::YieldAdapterClient;
 
 #[contract]
 pub struct P2PMarketplaceContract;
 
-// Storage keys - Using short symbols for gas efficiency
-// Persistent storage is used for configuration that should survive contract upgrades
-// Instance storage is used for runtime data that can be reset
-const ADMIN_KEY: Symbol = symbol_short!("ADMIN");                    // Admin address (persistent)
-const USDC_TOKEN_KEY: Symbol = symbol_short!("USDC_TKN");            // USDC token contract address (persistent)
-const OFFERS_KEY: Symbol = symbol_short!("OFFERS");                  // Map of all offers (instance)
-const ACTIVE_OFFERS: Symbol = symbol_short!("ACTV_OFRS");           // Maps seller Address to their active offer_id (instance)
-const TRADES_KEY: Symbol = symbol_short!("TRADES");                  // Map of all trades (instance)
-const NEXT_OFFER_ID: Symbol = symbol_short!("NEXT_O_ID");           // Counter for generating unique offer IDs (instance)
-const NEXT_TRADE_ID: Symbol = symbol_short!("NEXT_T_ID");           // Counter for generating unique trade IDs (instance)
-const PAUSED_KEY: Symbol = symbol_short!("PAUSED");                  // Contract pause state (instance)
-const FEE_RATE_KEY: Symbol = symbol_short!("FEE_RATE");             // Trading fee rate in basis points (persistent)
-const FEE_COLLECTOR_KEY: Symbol = symbol_short!("FEE_COLL");        // Address that receives trading fees (persistent)
-const MIN_TRADE_AMOUNT_KEY: Symbol = symbol_short!("MIN_AMT");      // Minimum USDC amount per trade (persistent)
-const MAX_TRADE_AMOUNT_KEY: Symbol = symbol_short!("MAX_AMT");      // Maximum USDC amount per trade (persistent)
-const TRADE_EXPIRATION_KEY: Symbol = symbol_short!("TRD_EXP");      // Trade timeout in seconds (persistent)
-const EXECUTING: Symbol = symbol_short!("EXEC");                         // Reentrancy guard flag (instance)
-
 // Default configuration values - These are fallbacks if storage is not set
 const DEFAULT_TRADE_EXPIRATION: u64 = 600;                          // 10 minutes - Reasonable time for payment confirmation
-const DEFAULT_MIN_TRADE_AMOUNT: i128 = 1_000_000;                   // 1 USDC (6 decimals) - Prevents spam with tiny trades
-const DEFAULT_MAX_TRADE_AMOUNT: i128 = 1_000_000_000_000;          // 1M USDC - Prevents excessively large trades
+const DEFAULT_MIN_TRADE_UNITS: i128 = 1;                            // 1 whole USDC - Prevents spam with tiny trades
+const DEFAULT_MAX_TRADE_UNITS: i128 = 1_000_000;                    // 1M USDC - Prevents excessively large trades
+const MAX_REASONABLE_UNITS: i128 = 1_000_000_000_000;               // 1 trillion USDC - Upper bound for admin-set limits
+const DEFAULT_TOKEN_DECIMALS: u32 = 6;                              // Stellar USDC standard, used if decimals were never cached
+const MAX_TOKEN_DECIMALS: u32 = 18;                                 // Keeps unit scaling well inside i128 range
 const DEFAULT_FEE_RATE: u32 = 25;                                   // 0.25% = 25 basis points - Competitive marketplace fee
+const DEFAULT_OPERATOR_FEE_CAP: u32 = 0;                            // Operator fees disabled unless configured
+const DEFAULT_INSURANCE_SHARE: u32 = 0;                             // No fees routed to insurance unless configured
+const DEFAULT_ATTESTATION_GRACE: u64 = 900;                         // 15 minutes for the seller to dispute an attestation
+const DEFAULT_DISPUTE_PENALTY: i128 = 5;                            // A lost dispute outweighs five completed trades
+const DEFAULT_POINTS_RATE: u32 = 0;                                 // No reward points emitted unless configured
+const DEFAULT_RECORD_RETENTION: u64 = 30 * 86_400;                  // 30 days - Final-state records are kept before pruning
+const MAX_RECORD_RETENTION: u64 = 10 * 365 * 86_400;                // 10 years - Upper bound for admin-set retention
+const MAX_FIRST_TRADE_HOLD: u64 = 7 * 86_400;                       // 7 days - Upper bound for the first-trade cooling-off period
+const MAX_WALKAWAY_COOLDOWN: u64 = 7 * 86_400;                     // 7 days - Upper bound for the escalating walkaway cooldown
+const MAX_EVIDENCE_WINDOW: u64 = 14 * 86_400;                      // 14 days - Upper bound for the dispute evidence window
+const MAX_DISPUTE_EVIDENCE: u32 = 50;                               // Evidence hashes per dispute - Bounds storage
+const MAX_DISPUTE_REASON_LEN: u32 = 256;                            // Bytes of a dispute's reason - Bounds storage
+const MAX_TRADE_TRANCHES: u32 = 20;                                 // Installments per trade - Bounds storage
+const MAX_EXPIRY_GRACE: u64 = 600;                                  // 10 minutes - Upper bound for the grace period after a trade's deadline
+const REOPEN_WINDOW: u64 = 3_600;                                   // 1 hour - How long a cancelled trade can be reopened by both parties
+const MIGRATION_DELAY: u64 = 2 * 86_400;                            // 2 days - Notice users get before escrow can move to a successor
+const MIN_OFFER_TTL: u64 = 86_400;                                  // 1 day - Lower bound for a non-zero offer TTL, so fresh offers can't be expired
+const MAX_OFFER_TTL: u64 = 365 * 86_400;                            // 1 year - Upper bound for the offer TTL
+const MAX_PAYMENT_DETAILS_LEN: u32 = 256;                           // Bytes of encrypted payment details per offer - Bounds storage
+const MAX_OPEN_PROPOSALS: u32 = 3;                                  // Open proposals per buyer on one offer - Bounds storage
+const MAX_OFFER_PROPOSALS: u32 = 10;                                // Open proposals on one offer - Bounds storage and the scans on settlement
+const PROPOSAL_LIFETIME: u64 = 86_400;                              // 1 day - How long the seller can accept a price proposal
+const MAX_METADATA_URI_LEN: u32 = 128;                              // Bytes of an offer's metadata URI (IPFS CID or HTTPS URL) - Bounds storage
+const LEDGERS_PER_DAY: u32 = 17_280;                                // ~5 second ledgers
+const ENTRY_TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;              // 30 days - Long-lived persistent entries are extended once their TTL drops below this
+const ENTRY_TTL_EXTEND: u32 = 120 * LEDGERS_PER_DAY;                // 120 days - TTL a long-lived persistent entry is extended to on each write
+const SECONDS_PER_DAY: u64 = 86_400;                                // Daily volume limits reset on ledger-time day boundaries
+const MAX_BATCH_OFFERS: u32 = 20;                                   // Price levels per create_offers call - Bounds invocation cost
+const MAX_FEE_BRACKETS: u32 = 10;                                   // Steps in the fee curve - Bounds lookup cost
+const MAX_ADMIN_LOG_ENTRIES: u64 = 200;                             // Admin log entries kept on-chain - Oldest are dropped beyond this
+const MAX_FEE_RATE: u32 = 1_000;                                    // 10% - Upper bound for any admin-set fee rate
+const MAX_FEE_SPLITS: u32 = 10;                                     // Fee recipients per split - Bounds transfers per settlement
+const RATE_SCALE: i128 = 10_000_000;                                // Fixed-point scale for exchange rates (7 decimal places)
+const VWAP_WINDOW: u64 = 86_400;                                    // 24 hours of completed trades feed the reference rate
+const VWAP_BUCKET: u64 = 3_600;                                     // Volume is aggregated into hourly buckets
 const BASIS_POINTS_DIVISOR: u32 = 10_000;                          // Standard basis points denominator
+const TOKEN_BALANCE_ERROR: u32 = 10;                                // Stellar Asset Contract error code for insufficient balance
 
 #[contractimpl]
 impl P2PMarketplaceContract {
-    /// Initializes the P2P marketplace contract with essential configuration.
-    /// This function can only be called once and sets up the foundational parameters.
+    /// Initializes the P2P marketplace contract at deployment.
+    /// Runs atomically with contract creation, so there is no window in which
+    /// another party could initialize the contract first.
     /// 
     /// # Arguments
     /// * `admin` - The address that will have administrative privileges (pause, fees, disputes)
     /// * `usdc_token_id` - The contract address of the USDC token to be traded
     /// * `fee_collector` - The address that will receive trading fees
+    /// * `fee_policy` - Whether fees are collected and the fee range the admin may set
     /// 
     /// # Security Considerations
     /// - Validates that USDC token address is a valid token contract
@@ -89,9 +135,42 @@ impl P2PMarketplaceContract {
     /// 
     /// # Returns
     /// Result indicating success or failure of initialization
+    pub fn __constructor(
+        env: Env,
+        admin: Address,
+        usdc_token_id: Address,
+        fee_collector: Address,
+        fee_policy: FeePolicy,
+    ) -> Result<(), Error> {
+        Self::_initialize(&env, admin, usdc_token_id, fee_collector, fee_policy)
+    }
+
+    /// Deprecated: configuration is set by the constructor at deployment.
+    /// Kept so existing integrations get a clear failure instead of a missing function;
+    /// it only succeeds on a contract whose storage was never initialized.
+    /// 
+    /// # Arguments
+    /// * `admin` - The address that will have administrative privileges
+    /// * `usdc_token_id` - The contract address of the USDC token to be traded
+    /// * `fee_collector` - The address that will receive trading fees
+    /// 
+    /// Fees are enabled with the full 0-10% range available to the admin.
     pub fn initialize(env: Env, admin: Address, usdc_token_id: Address, fee_collector: Address) -> Result<(), Error> {
+        let fee_policy = FeePolicy { fees_enabled: true, fee_floor: 0, fee_ceiling: MAX_FEE_RATE };
+        Self::_initialize(&env, admin, usdc_token_id, fee_collector, fee_policy)
+    }
+
+    /// Internal helper that writes the initial configuration.
+    /// Shared by the constructor and the deprecated `initialize` shim.
+    fn _initialize(
+        env: &Env,
+        admin: Address,
+        usdc_token_id: Address,
+        fee_collector: Address,
+        fee_policy: FeePolicy,
+    ) -> Result<(), Error> {
         // ✅ SECURITY FIX: Use persistent storage for initialization check
-        if env.storage().persistent().has(&ADMIN_KEY) {
+        if env.storage().persistent().has(&ConfigKey::Admin) {
             panic!("Contract already initialized");
         }
         
@@ -102,27 +181,48 @@ impl P2PMarketplaceContract {
         
         // Validate USDC token is a legitimate token contract by calling decimals()
         // This will panic if the address doesn't implement the token interface
-        let usdc_client = token::Client::new(&env, &usdc_token_id);
-        let _ = usdc_client.decimals(); // This will panic if not a valid token
+        let usdc_client = token::Client::new(env, &usdc_token_id);
+        let decimals = usdc_client.decimals(); // This will panic if not a valid token
+        if decimals > MAX_TOKEN_DECIMALS {
+            return Err(Error::InvalidTokenAddress);
+        }
+
+        // The fee range is fixed for the lifetime of the instance
+        if fee_policy.fee_floor > fee_policy.fee_ceiling || fee_policy.fee_ceiling > MAX_FEE_RATE {
+            return Err(Error::InvalidAmount);
+        }
+        
+        // Default limits are expressed in whole tokens and scaled to the token's precision
+        // so a 7-decimal asset gets the same limits as a 6-decimal one
+        let unit = 10i128.pow(decimals);
         
         // Store critical configuration in persistent storage
         // This ensures configuration survives contract upgrades
-        env.storage().persistent().set(&ADMIN_KEY, &admin);
-        env.storage().persistent().set(&USDC_TOKEN_KEY, &usdc_token_id);
-        env.storage().persistent().set(&FEE_COLLECTOR_KEY, &fee_collector);
-        env.storage().persistent().set(&FEE_RATE_KEY, &DEFAULT_FEE_RATE);
-        env.storage().persistent().set(&MIN_TRADE_AMOUNT_KEY, &DEFAULT_MIN_TRADE_AMOUNT);
-        env.storage().persistent().set(&MAX_TRADE_AMOUNT_KEY, &DEFAULT_MAX_TRADE_AMOUNT);
-        env.storage().persistent().set(&TRADE_EXPIRATION_KEY, &DEFAULT_TRADE_EXPIRATION);
+        env.storage().persistent().set(&ConfigKey::Admin, &admin);
+        env.storage().persistent().set(&ConfigKey::UsdcToken, &usdc_token_id);
+        env.storage().persistent().set(&ConfigKey::FeeCollector, &fee_collector);
+        env.storage().persistent().set(&ConfigKey::FeesEnabled, &fee_policy.fees_enabled);
+        env.storage().persistent().set(&ConfigKey::FeeBounds, &(fee_policy.fee_floor, fee_policy.fee_ceiling));
+        env.storage().persistent().set(&ConfigKey::FeeRate, &DEFAULT_FEE_RATE.clamp(fee_policy.fee_floor, fee_policy.fee_ceiling));
+        env.storage().persistent().set(&ConfigKey::TokenDecimals, &decimals);
+        env.storage().persistent().set(&ConfigKey::MinTradeAmount, &(DEFAULT_MIN_TRADE_UNITS * unit));
+        env.storage().persistent().set(&ConfigKey::MaxTradeAmount, &(DEFAULT_MAX_TRADE_UNITS * unit));
+        env.storage().persistent().set(&ConfigKey::TradeExpiration, &DEFAULT_TRADE_EXPIRATION);
+        env.storage().persistent().set(&ConfigKey::RecordRetention, &DEFAULT_RECORD_RETENTION);
+        env.storage().persistent().set(&ConfigKey::InsuranceShare, &DEFAULT_INSURANCE_SHARE);
+        env.storage().persistent().set(&ConfigKey::InsuranceFund, &0i128);
+        env.storage().persistent().set(&ConfigKey::PointsRate, &DEFAULT_POINTS_RATE);
         
         // Initialize runtime data structures in instance storage
         // These can be reset during contract upgrades if needed
-        env.storage().instance().set(&NEXT_OFFER_ID, &0u64);
-        env.storage().instance().set(&NEXT_TRADE_ID, &0u64);
-        env.storage().instance().set(&OFFERS_KEY, &Map::<u64, Offer>::new(&env));
-        env.storage().instance().set(&TRADES_KEY, &Map::<u64, Trade>::new(&env));
-        env.storage().instance().set(&ACTIVE_OFFERS, &Map::<Address, u64>::new(&env));
-        env.storage().instance().set(&PAUSED_KEY, &false);
+        env.storage().instance().set(&DataKey::NextOfferId, &0u64);
+        env.storage().instance().set(&DataKey::NextTradeId, &0u64);
+        env.storage().instance().set(&DataKey::Offers, &Map::<u64, Offer>::new(env));
+        env.storage().instance().set(&DataKey::Trades, &Map::<u64, Trade>::new(env));
+        env.storage().instance().set(&DataKey::Disputes, &Map::<u64, Dispute>::new(env));
+        env.storage().instance().set(&DataKey::DisputeQueue, &Vec::<u64>::new(env));
+        env.storage().instance().set(&DataKey::ActiveOffers, &Map::<Address, Vec<u64>>::new(env));
+        env.storage().instance().set(&DataKey::Paused, &false);
         
         Ok(())
     }
@@ -136,10 +236,144 @@ impl P2PMarketplaceContract {
     /// - Fails fast if admin is not properly authenticated
     /// 
     /// # Returns
-    /// Result indicating if the caller is authorized as admin
-    fn _require_admin(env: &Env) -> Result<(), Error> {
-        let admin: Address = env.storage().persistent().get(&ADMIN_KEY).unwrap();
+    /// The authorized admin address, for the admin log
+    fn _require_admin(env: &Env) -> Result<Address, Error> {
+        let admin: Address = env.storage().persistent().get(&ConfigKey::Admin).ok_or(Error::NotInitialized)?;
         admin.require_auth(); // This will fail if the admin hasn't signed the transaction
+        Ok(admin)
+    }
+
+    /// Internal helper to reject calls on a contract whose configuration was never written,
+    /// instead of panicking on missing storage. The admin key doubles as the initialized
+    /// flag: the constructor writes it first thing and it is only ever replaced.
+    /// 
+    /// # Errors
+    /// - NotInitialized: If the contract has not been initialized
+    fn _require_initialized(env: &Env) -> Result<(), Error> {
+        if !env.storage().persistent().has(&ConfigKey::Admin) {
+            return Err(Error::NotInitialized);
+        }
+        Ok(())
+    }
+
+    /// Internal helper to verify authorization for fee and limit parameter updates.
+    /// Once a governance executor is set it replaces the admin for these parameters.
+    fn _require_parameter_authority(env: &Env) -> Result<Address, Error> {
+        match env.storage().persistent().get::<_, Address>(&ConfigKey::GovernanceExecutor) {
+            Some(executor) => {
+                executor.require_auth();
+                Ok(executor)
+            }
+            None => Self::_require_admin(env),
+        }
+    }
+
+    /// Internal helper to append an entry to the on-chain admin log.
+    /// Once the log is full the oldest entry is dropped; IDs keep increasing.
+    fn _log_admin_action(env: &Env, actor: &Address, action: Symbol, old_value: Bytes, new_value: Bytes) {
+        let id: u64 = env.storage().instance().get(&DataKey::NextAdminLogId).unwrap_or(0);
+        let entry = AdminLogEntry {
+            id,
+            actor: actor.clone(),
+            action,
+            timestamp: env.ledger().timestamp(),
+            old_value,
+            new_value,
+        };
+        env.storage().persistent().set(&DataKey::AdminLog(id), &entry);
+        if id >= MAX_ADMIN_LOG_ENTRIES {
+            env.storage().persistent().remove(&DataKey::AdminLog(id - MAX_ADMIN_LOG_ENTRIES));
+        }
+        env.storage().instance().set(&DataKey::NextAdminLogId, &(id + 1));
+    }
+
+    /// Internal helper to XDR-encode a value for the admin log
+    fn _log_value<V: IntoVal<Env, Val>>(env: &Env, value: &V) -> Bytes {
+        let value: Val = value.into_val(env);
+        value.to_xdr(env)
+    }
+
+    /// Internal helper to XDR-encode a stored config value for the admin log (empty if unset)
+    fn _stored_config(env: &Env, key: &ConfigKey) -> Bytes {
+        env.storage().persistent().get::<_, Val>(key)
+            .map(|value| value.to_xdr(env))
+            .unwrap_or(Bytes::new(env))
+    }
+
+    /// Internal helper to store a config value and record the change in the admin log
+    fn _set_config<V: IntoVal<Env, Val>>(env: &Env, actor: &Address, action: Symbol, key: &ConfigKey, value: &V) {
+        let old_value = Self::_stored_config(env, key);
+        env.storage().persistent().set(key, value);
+        Self::_log_admin_action(env, actor, action, old_value, Self::_log_value(env, value));
+    }
+
+    /// Internal helper to clear a config value and record the change in the admin log
+    fn _remove_config(env: &Env, actor: &Address, action: Symbol, key: &ConfigKey) {
+        let old_value = Self::_stored_config(env, key);
+        env.storage().persistent().remove(key);
+        Self::_log_admin_action(env, actor, action, old_value, Bytes::new(env));
+    }
+
+    /// Internal helper to check an admin-set fee rate against the 10% maximum
+    /// and the instance's fee bounds
+    fn _validate_fee_rate(env: &Env, rate: u32) -> Result<(), Error> {
+        let (floor, ceiling) = Self::get_fee_bounds(env.clone());
+        if rate > MAX_FEE_RATE || rate < floor || rate > ceiling {
+            return Err(Error::InvalidAmount);
+        }
+        Ok(())
+    }
+
+    /// Internal helper to reject single-admin calls to high-impact operations
+    /// while a multisig signer set is configured.
+    /// 
+    /// # Errors
+    /// - Unauthorized: If multisig admin is enabled
+    fn _require_single_admin(env: &Env) -> Result<(), Error> {
+        let signers: Vec<Address> = env.storage().persistent().get(&ConfigKey::AdminSigners).unwrap_or(Vec::new(env));
+        if !signers.is_empty() {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Internal helper to verify a multisig signer's authorization
+    fn _require_signer(env: &Env, signer: &Address) -> Result<(), Error> {
+        signer.require_auth();
+        let signers: Vec<Address> = env.storage().persistent().get(&ConfigKey::AdminSigners).unwrap_or(Vec::new(env));
+        if !signers.contains(signer) {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Internal helper to validate and store a multisig signer set.
+    /// An empty set disables multisig and returns control to the single admin.
+    /// 
+    /// # Errors
+    /// - InvalidAmount: If threshold is outside 1..=signers, or signers contain duplicates
+    fn _set_signers(env: &Env, actor: &Address, signers: Vec<Address>, threshold: u32) -> Result<(), Error> {
+        if !signers.is_empty() && (threshold == 0 || threshold > signers.len()) {
+            return Err(Error::InvalidAmount);
+        }
+        for (i, signer) in signers.iter().enumerate() {
+            Self::_validate_address(&signer)?;
+            if signers.first_index_of(&signer) != Some(i as u32) {
+                return Err(Error::InvalidAmount);
+            }
+        }
+
+        let old_signers: Vec<Address> = env.storage().persistent().get(&ConfigKey::AdminSigners).unwrap_or(Vec::new(env));
+        let old_threshold: u32 = env.storage().persistent().get(&ConfigKey::AdminThreshold).unwrap_or(0);
+        env.storage().persistent().set(&ConfigKey::AdminSigners, &signers);
+        env.storage().persistent().set(&ConfigKey::AdminThreshold, &threshold);
+        Self::_log_admin_action(
+            env,
+            actor,
+            symbol_short!("signers"),
+            Self::_log_value(env, &(old_signers, old_threshold)),
+            Self::_log_value(env, &(signers, threshold)),
+        );
         Ok(())
     }
 
@@ -153,7 +387,33 @@ impl P2PMarketplaceContract {
     /// # Returns
     /// Boolean indicating if contract is paused
     fn _is_paused(env: &Env) -> bool {
-        env.storage().instance().get(&PAUSED_KEY).unwrap_or(false)
+        env.storage().instance().get(&DataKey::Paused).unwrap_or(false)
+    }
+
+    /// Internal helper to set the pause flag and record the change in the admin log
+    fn _set_paused(env: &Env, actor: &Address, paused: bool) {
+        let was_paused = Self::_is_paused(env);
+        env.storage().instance().set(&DataKey::Paused, &paused);
+        let action = if paused { symbol_short!("pause") } else { symbol_short!("unpause") };
+        Self::_log_admin_action(env, actor, action, Self::_log_value(env, &was_paused), Self::_log_value(env, &paused));
+    }
+
+    /// Internal helper to reject calls made while an external token call is in flight.
+    /// A token contract that re-enters the marketplace during a transfer would otherwise
+    /// observe trade state that has been updated but not yet settled.
+    /// 
+    /// # Errors
+    /// - Unauthorized: If a guarded token call is in progress
+    fn _require_not_executing(env: &Env) -> Result<(), Error> {
+        if env.storage().instance().get(&DataKey::Executing).unwrap_or(false) {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Internal helper to set or clear the in-progress guard around external token calls
+    fn _set_executing(env: &Env, executing: bool) {
+        env.storage().instance().set(&DataKey::Executing, &executing);
     }
 
     /// Internal helper to validate that an address is not zero/empty.
@@ -174,11 +434,22 @@ impl P2PMarketplaceContract {
         // In Soroban, addresses should have proper structure
         // This is a basic check - the SDK handles most validation
         if addr.to_string().is_empty() {
-            return Err(Error::InvalidAmount); // Using InvalidAmount for now, could add InvalidAddress error
+            return Err(Error::InvalidAddress);
         }
         Ok(())
     }
 
+    /// Internal helper returning one whole token in the token's smallest unit.
+    /// Used to scale limits defined in whole tokens to the cached token decimals.
+    /// 
+    /// # Returns
+    /// 10^decimals of the traded token
+    fn _token_unit(env: &Env) -> i128 {
+        let decimals: u32 = env.storage().persistent().get(&ConfigKey::TokenDecimals)
+            .unwrap_or(DEFAULT_TOKEN_DECIMALS);
+        10i128.pow(decimals)
+    }
+
     /// Internal helper to determine if a trade has exceeded its time limit.
     /// Trades have expiration times to prevent indefinite escrow situations.
     /// 
@@ -186,6 +457,8 @@ impl P2PMarketplaceContract {
     /// - Trades expire if payment confirmation takes too long
     /// - Expired trades can be resolved to return funds to seller
     /// - Configurable timeout allows for different confirmation requirements
+    /// - Each confirmed installment tranche restarts the timeout
+    /// - The expiry grace period extends the deadline, absorbing clock skew and submission latency
     /// 
     /// # Arguments
     /// * `trade` - The trade to check for expiration
@@ -193,9 +466,16 @@ impl P2PMarketplaceContract {
     /// # Returns
     /// Boolean indicating if the trade has expired
     fn _is_trade_expired(env: &Env, trade: &Trade) -> bool {
-        let trade_expiration: u64 = env.storage().persistent().get(&TRADE_EXPIRATION_KEY)
+        env.ledger().timestamp() >= Self::_trade_deadline(env, trade) + Self::get_expiry_grace(env.clone())
+    }
+
+    /// Internal helper to compute when a trade expires.
+    /// Installment trades measure from the latest confirmed tranche.
+    fn _trade_deadline(env: &Env, trade: &Trade) -> u64 {
+        let trade_expiration: u64 = env.storage().persistent().get(&ConfigKey::TradeExpiration)
             .unwrap_or(DEFAULT_TRADE_EXPIRATION);
-        env.ledger().timestamp() >= trade.start_time + trade_expiration
+        let last_activity = trade.last_tranche_at.unwrap_or(trade.start_time).max(trade.start_time);
+        last_activity + trade_expiration
     }
 
     /// Internal helper to calculate trading fees using basis points.
@@ -231,1163 +511,7101 @@ impl P2PMarketplaceContract {
         }
     }
 
-    /// Creates a new offer to sell USDC for KES with escrow protection.
-    /// The seller must approve the contract to spend their USDC before calling this function.
-    /// 
-    /// # Business Flow
-    /// 1. Validates seller authorization and input parameters
-    /// 2. Checks trading limits and seller doesn't have active offer
-    /// 3. Verifies seller has sufficient USDC balance and allowance
-    /// 4. Transfers USDC from seller to contract (escrow)
-    /// 5. Creates offer record and updates active offers mapping
-    /// 6. Emits event for transparency
-    /// 
-    /// # Security Checks
-    /// - Requires seller authorization
-    /// - Validates amount ranges to prevent spam/large trades
-    /// - Checks USDC balance and allowance before transfer
-    /// - Uses safe transfer with error handling
-    /// - Enforces one active offer per seller rule
+    /// Internal helper to credit reward points to both sides of a completed trade.
+    /// Points are proportional to the USDC volume at the configured emission rate.
     /// 
     /// # Arguments
-    /// * `seller` - The address creating the offer (must sign transaction)
-    /// * `usdc_amount` - Amount of USDC to sell (with 6 decimals)
-    /// * `kes_amount` - Amount of KES expected in return (off-chain settlement)
+    /// * `trade` - The completed trade
+    fn _award_points(env: &Env, trade: &Trade) {
+        let rate: u32 = env.storage().persistent().get(&ConfigKey::PointsRate).unwrap_or(DEFAULT_POINTS_RATE);
+        let earned = trade.usdc_amount * rate as i128 / Self::_token_unit(env);
+        if earned <= 0 {
+            return;
+        }
+
+        for participant in [trade.buyer.clone(), trade.seller.clone()] {
+            let key = DataKey::Points(participant);
+            let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+            env.storage().persistent().set(&key, &(balance + earned));
+            Self::_extend_entry(env, &key);
+        }
+    }
+
+    /// Internal helper to credit both parties of a completed trade with reputation
+    fn _record_reputation(env: &Env, trade: &Trade) {
+        for participant in [trade.buyer.clone(), trade.seller.clone()] {
+            let key = DataKey::Reputation(participant);
+            let mut reputation: Reputation = env.storage().persistent().get(&key).unwrap_or_default();
+            reputation.completed_trades += 1;
+            reputation.score += 1;
+            env.storage().persistent().set(&key, &reputation);
+            Self::_extend_entry(env, &key);
+        }
+    }
+
+    /// Internal helper to count a dispute ruling against the losing party's reputation
+    fn _record_dispute_loss(env: &Env, loser: &Address) {
+        let key = DataKey::Reputation(loser.clone());
+        let mut reputation: Reputation = env.storage().persistent().get(&key).unwrap_or_default();
+        reputation.disputes_lost += 1;
+        reputation.score -= Self::get_dispute_penalty(env.clone());
+        env.storage().persistent().set(&key, &reputation);
+        Self::_extend_entry(env, &key);
+
+        // Repeat offenders are suspended once they lose more than the allowed disputes within the window
+        let (max_losses, window) = Self::get_suspension_policy(env.clone());
+        if max_losses == 0 {
+            return;
+        }
+        let now = env.ledger().timestamp();
+        let mut losses: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::DisputeLosses)
+            .unwrap_or(Map::new(env));
+        let mut recent = Vec::new(env);
+        for lost_at in losses.get(loser.clone()).unwrap_or(Vec::new(env)).iter() {
+            if lost_at.saturating_add(window) > now {
+                recent.push_back(lost_at);
+            }
+        }
+        recent.push_back(now);
+
+        if recent.len() > max_losses {
+            losses.remove(loser.clone());
+            let mut suspended: Map<Address, u64> = env.storage().instance().get(&DataKey::Suspended)
+                .unwrap_or(Map::new(env));
+            suspended.set(loser.clone(), now);
+            env.storage().instance().set(&DataKey::Suspended, &suspended);
+            Self::_publish_to(env, ADDRESS_SUSPENDED, loser, (recent.len(),));
+        } else {
+            losses.set(loser.clone(), recent);
+        }
+        env.storage().instance().set(&DataKey::DisputeLosses, &losses);
+    }
+
+    /// Internal helper to reject suspended addresses from listing offers or opening trades
     /// 
-    /// # Returns
-    /// The unique ID of the created offer
+    /// # Errors
+    /// - Suspended: If the address is suspended
+    fn _require_not_suspended(env: &Env, address: &Address) -> Result<(), Error> {
+        if Self::get_suspended_at(env.clone(), address.clone()).is_some() {
+            return Err(Error::Suspended);
+        }
+        Ok(())
+    }
+
+    /// Internal helper to check a buyer against the requirements set on an offer
     /// 
     /// # Errors
-    /// - ContractPaused: If trading is temporarily disabled
-    /// - InvalidAmount: If amounts are outside allowed ranges
-    /// - AlreadyHasActiveOffer: If seller already has an active offer
-    /// - InsufficientAllowance: If seller hasn't approved enough USDC
-    /// - TokenTransferFailed: If USDC transfer to escrow fails
-    pub fn create_offer(env: Env, seller: Address, usdc_amount: i128, kes_amount: i128) -> Result<u64, Error> {
-        // Emergency brake - halt all trading if contract is paused
-        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
-        
-        // Verify the seller has signed this transaction
-        seller.require_auth();
-        
-        // SECURITY FIX: Validate seller address
-        Self::_validate_address(&seller)?;
+    /// - RequirementsNotMet: If the buyer falls short of any requirement
+    fn _check_buyer_requirements(env: &Env, buyer: &Address, offer_id: u64) -> Result<(), Error> {
+        let requirements: Map<u64, BuyerRequirements> = env.storage().instance().get(&DataKey::OfferRequirements)
+            .unwrap_or(Map::new(env));
+        let Some(requirements) = requirements.get(offer_id) else { return Ok(()) };
 
-        // Input validation - prevent invalid or malicious amounts
-        if usdc_amount <= 0 || kes_amount <= 0 {
-            return Err(Error::InvalidAmount);
+        if !requirements.allowed_buyers.is_empty() && !requirements.allowed_buyers.contains(buyer) {
+            return Err(Error::RequirementsNotMet);
         }
-        
-        // Enforce trading limits to prevent spam (min) and excessive exposure (max)
-        let min_amount: i128 = env.storage().persistent().get(&MIN_TRADE_AMOUNT_KEY)
-            .unwrap_or(DEFAULT_MIN_TRADE_AMOUNT);
-        let max_amount: i128 = env.storage().persistent().get(&MAX_TRADE_AMOUNT_KEY)
-            .unwrap_or(DEFAULT_MAX_TRADE_AMOUNT);
-            
-        if usdc_amount < min_amount || usdc_amount > max_amount {
-            log!(&env, "Amount out of range. Min: {}, Max: {}, Provided: {}", 
-                min_amount, max_amount, usdc_amount);
-            return Err(Error::InvalidAmount);
+        let reputation = Self::get_reputation(env.clone(), buyer.clone());
+        if reputation.completed_trades < requirements.min_completed_trades
+            || reputation.score < requirements.min_reputation
+        {
+            return Err(Error::RequirementsNotMet);
         }
+        Ok(())
+    }
 
-        // Business rule: One active offer per seller to keep marketplace simple
-        // This prevents sellers from fragmenting liquidity across multiple offers
-        let mut active_offers: Map<Address, u64> = env.storage().instance().get(&ACTIVE_OFFERS).unwrap();
-        if active_offers.contains_key(seller.clone()) {
-            return Err(Error::AlreadyHasActiveOffer);
+    /// Internal helper to get how much a buyer has filled of an offer in the current cap window
+    /// 
+    /// # Returns
+    /// (window start, USDC filled), with the window restarting once it has elapsed
+    fn _buyer_fill(env: &Env, offer_id: u64, buyer: &Address, window: u64) -> (u64, i128) {
+        let fills: Map<u64, Map<Address, (u64, i128)>> = env.storage().instance().get(&DataKey::BuyerFills)
+            .unwrap_or(Map::new(env));
+        let now = env.ledger().timestamp();
+        match fills.get(offer_id).and_then(|buyers| buyers.get(buyer.clone())) {
+            Some((start, filled)) if now < start.saturating_add(window) => (start, filled),
+            _ => (now, 0),
         }
+    }
 
-        // Setup USDC token client for balance checks and transfers
-        let usdc_token_id: Address = env.storage().persistent().get(&USDC_TOKEN_KEY).unwrap();
-        let usdc_client = token::Client::new(&env, &usdc_token_id);
+    /// Internal helper to check a trade against the per-buyer cap set on its offer
+    /// 
+    /// # Errors
+    /// - BuyerCapExceeded: If the trade would take the buyer over the cap for the window
+    fn _check_buyer_cap(env: &Env, buyer: &Address, offer_id: u64, usdc_amount: i128) -> Result<(), Error> {
+        let caps: Map<u64, (i128, u64)> = env.storage().instance().get(&DataKey::BuyerCaps).unwrap_or(Map::new(env));
+        let Some((max_per_buyer, window)) = caps.get(offer_id) else { return Ok(()) };
 
-        // Security check: Verify seller actually has the USDC they want to sell
-        let seller_balance = usdc_client.balance(&seller);
-        if seller_balance < usdc_amount {
-            log!(&env, "Insufficient balance. Required: {}, Available: {}", usdc_amount, seller_balance);
-            return Err(Error::InsufficientAllowance);
+        let (_, filled) = Self::_buyer_fill(env, offer_id, buyer, window);
+        if filled + usdc_amount > max_per_buyer {
+            return Err(Error::BuyerCapExceeded);
         }
+        Ok(())
+    }
 
-        // Security check: Verify seller has approved the contract to spend their USDC
-        // This is a common DeFi pattern - users must explicitly approve token spending
-        let allowance = usdc_client.allowance(&seller, &env.current_contract_address());
-        if allowance < usdc_amount {
-            log!(&env, "Insufficient allowance. Required: {}, Available: {}", usdc_amount, allowance);
-            return Err(Error::InsufficientAllowance);
-        }
+    /// Internal helper to count a completed trade against its buyer's cap on the offer
+    fn _record_buyer_fill(env: &Env, trade: &Trade) {
+        let caps: Map<u64, (i128, u64)> = env.storage().instance().get(&DataKey::BuyerCaps).unwrap_or(Map::new(env));
+        let Some((_, window)) = caps.get(trade.offer_id) else { return };
 
-        // Transfer USDC from seller to contract for escrow
-        // Using try_transfer for proper error handling instead of panic-prone transfer()
-        match usdc_client.try_transfer(&seller, &env.current_contract_address(), &usdc_amount) {
-            Ok(_) => {},
-            Err(_) => {
-                log!(&env, "Token transfer failed for amount: {}", usdc_amount);
-                return Err(Error::TokenTransferFailed);
+        let (start, filled) = Self::_buyer_fill(env, trade.offer_id, &trade.buyer, window);
+        let mut fills: Map<u64, Map<Address, (u64, i128)>> = env.storage().instance().get(&DataKey::BuyerFills)
+            .unwrap_or(Map::new(env));
+        let mut buyers = fills.get(trade.offer_id).unwrap_or(Map::new(env));
+        buyers.set(trade.buyer.clone(), (start, filled + trade.usdc_amount));
+        fills.set(trade.offer_id, buyers);
+        env.storage().instance().set(&DataKey::BuyerFills, &fills);
+    }
+
+    /// Internal helper to get the time until which a buyer is cooling down after walking away
+    /// 
+    /// # Returns
+    /// Base cooldown doubled for each consecutive walkaway after the first (capped at 7 days)
+    /// past the latest one, or 0 if the buyer has no walkaways or cooldowns are disabled
+    fn _cooldown_until(env: &Env, buyer: &Address) -> u64 {
+        let base: u64 = env.storage().persistent().get(&ConfigKey::WalkawayCooldown).unwrap_or(0);
+        let walkaways: Option<(u32, u64)> = env.storage().persistent().get(&DataKey::Walkaways(buyer.clone()));
+        match walkaways {
+            Some((count, last_at)) if base > 0 => {
+                let cooldown = base.saturating_mul(1u64 << (count - 1).min(32)).min(MAX_WALKAWAY_COOLDOWN);
+                last_at.saturating_add(cooldown)
             }
+            _ => 0,
         }
+    }
 
-        // Create the offer record with all necessary information
-        let mut offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer_id: u64 = env.storage().instance().get(&NEXT_OFFER_ID).unwrap();
+    /// Internal helper to count a cancelled or expired trade against its buyer.
+    /// Nothing is tracked while cooldowns are disabled.
+    fn _record_walkaway(env: &Env, buyer: &Address) {
+        if Self::get_walkaway_cooldown(env.clone()) == 0 {
+            return;
+        }
+        let key = DataKey::Walkaways(buyer.clone());
+        let count = env.storage().persistent().get::<_, (u32, u64)>(&key).map_or(0, |(count, _)| count);
+        env.storage().persistent().set(&key, &(count.saturating_add(1), env.ledger().timestamp()));
+        Self::_extend_entry(env, &key);
+    }
 
-        let offer = Offer {
-            seller: seller.clone(),
-            usdc_amount,
-            kes_amount,
-        };
+    /// Internal helper to take back the walkaway a cancellation at `cancelled_at` counted
+    /// against a buyer, when the trade is reopened. Only the buyer's latest walkaway can
+    /// match, and seller cancellations never counted one.
+    fn _undo_walkaway(env: &Env, buyer: &Address, cancelled_at: u64) {
+        let key = DataKey::Walkaways(buyer.clone());
+        let Some((count, last)) = env.storage().persistent().get::<_, (u32, u64)>(&key) else { return };
+        if last != cancelled_at {
+            return;
+        }
+        if count > 1 {
+            env.storage().persistent().set(&key, &(count - 1, last));
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+    }
 
-        // Store the offer and update active offers mapping for efficient lookups
-        offers.set(offer_id, offer);
-        active_offers.set(seller.clone(), offer_id);
+    /// Internal helper to clear a buyer's walkaway streak once they complete a trade
+    fn _clear_walkaways(env: &Env, buyer: &Address) {
+        env.storage().persistent().remove(&DataKey::Walkaways(buyer.clone()));
+    }
 
-        // Persist changes to storage
-        env.storage().instance().set(&OFFERS_KEY, &offers);
-        env.storage().instance().set(&ACTIVE_OFFERS, &active_offers);
-        env.storage().instance().set(&NEXT_OFFER_ID, &(offer_id + 1));
+    /// Internal helper to add a completed trade to the rolling VWAP buckets.
+    /// Buckets that fall outside the window are pruned on every update.
+    /// 
+    /// # Arguments
+    /// * `trade` - The completed trade
+    fn _record_volume(env: &Env, trade: &Trade) {
+        let now = env.ledger().timestamp();
+        let bucket = now / VWAP_BUCKET;
+        let oldest = now.saturating_sub(VWAP_WINDOW) / VWAP_BUCKET;
 
-        // Emit event for transparency and off-chain indexing
-        // Events allow frontends and analytics to track marketplace activity
-        env.events().publish(
-            (OFFER_CREATED, seller.clone()),
-            (offer_id, usdc_amount, kes_amount),
-        );
+        let mut volume: Map<u64, (i128, i128)> = env.storage().instance().get(&DataKey::Volume)
+            .unwrap_or(Map::new(env));
+        for key in volume.keys() {
+            if key < oldest {
+                volume.remove(key);
+            }
+        }
 
-        Ok(offer_id)
+        let (usdc, kes) = volume.get(bucket).unwrap_or((0, 0));
+        volume.set(bucket, (usdc + trade.usdc_amount, kes + trade.kes_amount));
+        env.storage().instance().set(&DataKey::Volume, &volume);
     }
 
-    /// Initiates a trade by a buyer against an existing offer.
-    /// This begins the escrow process where USDC is held while payment confirmation occurs.
-    /// 
-    /// # Business Flow
-    /// 1. Validates buyer authorization and offer existence
-    /// 2. Prevents self-trading and checks offer is still active
-    /// 3. Ensures no existing active trade for the offer
-    /// 4. Creates trade record with initial status
-    /// 5. Emits event to notify participants
-    /// 
-    /// # Security Features
-    /// - Prevents buyers from trading with themselves
-    /// - Validates offer is still active and available
-    /// - Efficient lookup using active_offers mapping
-    /// - Checks for existing active trades to prevent conflicts
+    /// Internal helper to determine the fee rate for a trade of the given size.
+    /// Uses the fee curve when one is configured, otherwise the flat fee rate.
     /// 
     /// # Arguments
-    /// * `buyer` - The address initiating the trade (must sign transaction)
-    /// * `offer_id` - The ID of the offer to trade against
+    /// * `amount` - USDC trade amount in token base units
     /// 
     /// # Returns
-    /// The unique ID of the created trade
-    /// 
-    /// # Errors
-    /// - ContractPaused: If trading is disabled
-    /// - OfferNotFound: If offer doesn't exist or is no longer active
-    /// - Unauthorized: If buyer tries to trade with themselves
-    /// - TradeAlreadyInitiated: If offer already has an active trade
-    pub fn initiate_trade(env: Env, buyer: Address, offer_id: u64) -> Result<u64, Error> {
-        // Emergency brake - halt all trading if contract is paused
-        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
-        
-        // Verify the buyer has signed this transaction
-        buyer.require_auth();
-        
-        // SECURITY FIX: Validate buyer address
-        Self::_validate_address(&buyer)?;
+    /// Fee rate in basis points
+    fn _fee_rate_for(env: &Env, amount: i128) -> u32 {
+        // Fee-free instances collect nothing, whatever the configured rates
+        if !Self::get_fees_enabled(env.clone()) {
+            return 0;
+        }
+        let (floor, ceiling) = Self::get_fee_bounds(env.clone());
+        Self::_configured_fee_rate(env, amount).clamp(floor, ceiling)
+    }
 
-        // Retrieve the offer details to validate the trade
-        let offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
-        
-        // Business rule: Prevent self-trading to avoid manipulation
-        // Users should not be able to trade with their own offers
-        if buyer == offer.seller {
-            return Err(Error::Unauthorized);
+    /// Internal helper to look up who bears the protocol fee on an offer
+    fn _fee_payer(env: &Env, offer_id: u64) -> FeePayer {
+        let fee_payers: Map<u64, FeePayer> = env.storage().instance().get(&DataKey::FeePayers).unwrap_or(Map::new(env));
+        fee_payers.get(offer_id).unwrap_or(FeePayer::Buyer)
+    }
+
+    /// Internal helper to compute the seller's share of a protocol fee
+    fn _seller_fee_share(fee_payer: FeePayer, fee_amount: i128) -> i128 {
+        match fee_payer {
+            FeePayer::Buyer => 0,
+            FeePayer::Seller => fee_amount,
+            FeePayer::Split => fee_amount / 2,
         }
+    }
 
-        // Efficient validation: Check if offer is still active using the active_offers mapping
-        // This is much more gas-efficient than iterating through all offers
-        let active_offers: Map<Address, u64> = env.storage().instance().get(&ACTIVE_OFFERS).unwrap();
-        if !active_offers.contains_key(offer.seller.clone()) || 
-           active_offers.get(offer.seller.clone()).unwrap() != offer_id {
-            return Err(Error::OfferNotFound);
+    /// Internal helper to size the reserve a seller escrows for their fee share.
+    /// Sized at the fee ceiling, so it covers any fee configuration at settlement.
+    fn _fee_reserve(env: &Env, fee_payer: FeePayer, usdc_amount: i128) -> i128 {
+        let (_, ceiling) = Self::get_fee_bounds(env.clone());
+        Self::_seller_fee_share(fee_payer, Self::_calculate_fee(usdc_amount, ceiling))
+    }
+
+    /// Internal helper to look up the fee reserve escrowed alongside an offer
+    fn _offer_fee_reserve(env: &Env, offer_id: u64, usdc_amount: i128) -> i128 {
+        Self::_fee_reserve(env, Self::_fee_payer(env, offer_id), usdc_amount)
+    }
+
+    /// Internal helper to look up the configured fee rate for a trade amount,
+    /// before the instance's fee switch and bounds are applied
+    fn _configured_fee_rate(env: &Env, amount: i128) -> u32 {
+        let curve: Vec<FeeBracket> = env.storage().persistent().get(&ConfigKey::FeeCurve).unwrap_or(Vec::new(env));
+        if curve.is_empty() {
+            // A registry entry for the escrow token can override the flat rate
+            let usdc_token_id: Option<Address> = env.storage().persistent().get(&ConfigKey::UsdcToken);
+            if let Some(rate) = usdc_token_id.and_then(|token| Self::_token_config(env, &token)).and_then(|config| config.fee_rate) {
+                return rate;
+            }
+            return env.storage().persistent().get(&ConfigKey::FeeRate).unwrap_or(DEFAULT_FEE_RATE);
         }
 
-        // Check for existing active trades on this offer
-        // Only one trade can be active per offer to maintain order
-        let mut trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
-        
-        // ✅ SECURITY FIX: Optimized check with reverse iteration (newer trades first)
-        let mut has_active_trade = false;
-        for trade in trades.values().into_iter().rev() {
-            if trade.offer_id == offer_id && 
-               (trade.status == TradeStatus::Initiated || 
-                trade.status == TradeStatus::PaymentConfirmed ||
-                trade.status == TradeStatus::Disputed) {
-                has_active_trade = true;
+        // Brackets are sorted ascending, so the last one reached applies
+        let mut rate = curve.get(0).unwrap().rate;
+        for bracket in curve.iter() {
+            if amount < bracket.min_amount {
                 break;
             }
+            rate = bracket.rate;
         }
-        
-        if has_active_trade {
-            return Err(Error::TradeAlreadyInitiated);
+        rate
+    }
+
+    /// Internal helper to pay out a collected trading fee.
+    /// The configured insurance share stays in the contract and is credited to the
+    /// insurance fund; the remainder is split across the weighted fee recipients,
+    /// or sent to the single fee collector when no split is configured.
+    /// 
+    /// # Design Notes
+    /// - Insurance portion never leaves the contract, so it cannot fail to transfer
+    /// - Rounding dust from the split goes to the last recipient
+    /// - Fee recipient transfer failures are logged and do not block the trade
+    /// 
+    /// # Arguments
+    /// * `usdc_client` - Token client for the USDC contract
+    /// * `fee_amount` - The total fee withheld from the trade
+    fn _distribute_fee(env: &Env, usdc_client: &token::Client, fee_amount: i128) {
+        if fee_amount <= 0 {
+            return;
         }
 
-        // Generate unique trade ID for tracking
-        let trade_id: u64 = env.storage().instance().get(&NEXT_TRADE_ID).unwrap();
+        // Credit the insurance fund with its slice of the fee
+        let insurance_share: u32 = env.storage().persistent().get(&ConfigKey::InsuranceShare)
+            .unwrap_or(DEFAULT_INSURANCE_SHARE);
+        let insurance_amount = Self::_calculate_fee(fee_amount, insurance_share);
+        if insurance_amount > 0 {
+            let fund: i128 = env.storage().persistent().get(&ConfigKey::InsuranceFund).unwrap_or(0);
+            env.storage().persistent().set(&ConfigKey::InsuranceFund, &(fund + insurance_amount));
+        }
 
-        // Create trade record with initial state
-        // Trade starts in "Initiated" status, waiting for payment confirmations
-        let trade = Trade {
-            offer_id,
-            buyer: buyer.clone(),
-            start_time: env.ledger().timestamp(), // Used for expiration checking
-            status: TradeStatus::Initiated,
-            buyer_confirmed_payment: false,       // Buyer hasn't confirmed sending KES yet
-            seller_confirmed_payment: false,      // Seller hasn't confirmed receiving KES yet
-        };
+        // Send the rest to the fee recipients
+        let collector_amount = fee_amount - insurance_amount;
+        if collector_amount <= 0 {
+            return;
+        }
 
-        // Store the trade and update counters
-        trades.set(trade_id, trade);
-        env.storage().instance().set(&TRADES_KEY, &trades);
-        env.storage().instance().set(&NEXT_TRADE_ID, &(trade_id + 1));
+        let splits: Vec<FeeSplit> = env.storage().persistent().get(&ConfigKey::FeeSplits).unwrap_or(Vec::new(env));
+        if splits.is_empty() {
+            let fee_collector: Address = env.storage().persistent().get(&ConfigKey::FeeCollector).unwrap();
+            Self::_pay_fee(env, usdc_client, &fee_collector, collector_amount);
+            return;
+        }
 
-        // Emit event for notification and tracking
-        env.events().publish((TRADE_INITIATED, buyer.clone()), (trade_id, offer_id));
+        let mut remaining = collector_amount;
+        for (i, split) in splits.iter().enumerate() {
+            let amount = if i as u32 == splits.len() - 1 {
+                remaining
+            } else {
+                Self::_calculate_fee(collector_amount, split.weight)
+            };
+            remaining -= amount;
+            Self::_pay_fee(env, usdc_client, &split.recipient, amount);
+        }
+    }
 
-        Ok(trade_id)
+    /// Internal helper to transfer one fee payment, logging instead of failing the trade
+    fn _pay_fee(env: &Env, usdc_client: &token::Client, recipient: &Address, amount: i128) {
+        if amount > 0 && usdc_client.try_transfer(&env.current_contract_address(), recipient, &amount).is_err() {
+            log!(env, "Failed to transfer fee {} to collector", amount);
+        }
     }
 
-    /// Allows trade participants to confirm payment completion.
-    /// Both buyer and seller must confirm before USDC is released.
-    /// 
-    /// # Business Flow
-    /// 1. Validates participant authorization and trade existence
-    /// 2. Checks trade hasn't expired and is in correct status
-    /// 3. Records participant's payment confirmation
-    /// 4. If both parties confirm, automatically releases USDC
-    /// 5. Emits appropriate events for transparency
-    /// 
-    /// # Security Features
-    /// - Only trade participants can confirm
-    /// - Prevents confirmation on expired trades
-    /// - Validates trade is in correct status for confirmation
-    /// - Automatic execution when both parties confirm
-    /// 
-    /// # Arguments
-    /// * `trade_id` - The ID of the trade to confirm payment for
-    /// * `participant` - The address confirming (buyer or seller, must sign)
+    /// Internal helper to total the USDC the contract controls: on hand plus yield principal
+    fn _held_usdc(env: &Env, usdc_client: &token::Client) -> i128 {
+        let deposited: i128 = env.storage().instance().get(&DataKey::YieldDeposited).unwrap_or(0);
+        usdc_client.balance(&env.current_contract_address()) + deposited
+    }
+
+    /// Internal helper to make sure `amount` USDC is on hand before paying it out,
+    /// withdrawing the shortfall from the yield adapter just in time.
     /// 
     /// # Errors
-    /// - ContractPaused: If contract is paused
-    /// - TradeNotFound: If trade doesn't exist
-    /// - TradeExpired: If trade has exceeded time limit
-    /// - InvalidTradeStatus: If trade is not in confirmable state
-    /// - Unauthorized: If caller is not a trade participant
-    pub fn confirm_payment(env: Env, trade_id: u64, participant: Address) -> Result<(), Error> {
-        // Emergency brake - halt all operations if contract is paused
-        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
-        
-        // ✅ SECURITY FIX: Simple reentrancy guard
-        if env.storage().instance().get(&EXECUTING).unwrap_or(false) {
-            return Err(Error::Unauthorized);
+    /// - TokenTransferFailed: If the adapter withdrawal fails or comes back short
+    fn _ensure_liquid(env: &Env, usdc_client: &token::Client, amount: i128) -> Result<(), Error> {
+        let contract = env.current_contract_address();
+        let on_hand = usdc_client.balance(&contract);
+        let deposited: i128 = env.storage().instance().get(&DataKey::YieldDeposited).unwrap_or(0);
+        if on_hand >= amount || deposited == 0 {
+            return Ok(());
         }
-        
-        // Verify the participant has signed this transaction
-        participant.require_auth();
-
-        // Retrieve and validate the trade
-        let mut trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
-        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
 
-        // Business rule: Expired trades cannot be confirmed to prevent stale settlements
-        if Self::_is_trade_expired(&env, &trade) {
-            return Err(Error::TradeExpired);
+        let Some(adapter) = env.storage().persistent().get::<_, Address>(&ConfigKey::YieldAdapter) else {
+            return Ok(());
+        };
+        let shortfall = (amount - on_hand).min(deposited);
+        let adapter_client = YieldAdapterClient::new(env, &adapter);
+        if adapter_client.try_withdraw(&contract, &shortfall).is_err()
+            || usdc_client.balance(&contract) - on_hand != shortfall
+        {
+            log!(env, "Failed to withdraw {} from yield adapter", shortfall);
+            return Err(Error::TokenTransferFailed);
         }
+        env.storage().instance().set(&DataKey::YieldDeposited, &(deposited - shortfall));
 
-        // Get offer details to validate participant authorization
-        let offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer = offers.get(trade.offer_id).ok_or(Error::OfferNotFound)?;
-
-        // Only allow confirmations on initiated trades
-        if trade.status != TradeStatus::Initiated {
-            return Err(Error::InvalidTradeStatus);
-        }
+        Ok(())
+    }
 
-        // Update confirmation status based on who is confirming
-        // Buyer confirms they have sent KES payment
-        // Seller confirms they have received KES payment
-        if participant == trade.buyer {
-            trade.buyer_confirmed_payment = true;
-        } else if participant == offer.seller {
-            trade.seller_confirmed_payment = true;
-        } else {
-            // Security check: Only trade participants can confirm
-            return Err(Error::Unauthorized);
+    /// Internal helper to pay a trade's USDC payout, parking it as a claimable balance
+    /// when the recipient can't receive it (e.g. frozen account or missing trustline)
+    /// so the trade still completes.
+    fn _pay_or_park(env: &Env, usdc_client: &token::Client, trade_id: u64, recipient: &Address, amount: i128) {
+        if usdc_client.try_transfer(&env.current_contract_address(), recipient, &amount).is_ok() {
+            return;
         }
 
-        // Emit confirmation event for transparency
-        env.events().publish((PAYMENT_CONFIRMED, participant.clone()), (trade_id,));
+        log!(env, "Parking undeliverable payout of {}", amount);
+        let key = DataKey::Claimable(recipient.clone());
+        let balance: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &(balance + amount));
+        Self::_extend_entry(env, &key);
+        Self::_adjust_counter(env, &DataKey::ClaimableTotal, amount);
 
-        // Automatic execution: If both parties have confirmed, complete the trade
-        if trade.buyer_confirmed_payment && trade.seller_confirmed_payment {
-            trade.status = TradeStatus::PaymentConfirmed;
+        Self::_publish_to(env, PAYOUT_PARKED, recipient, (trade_id, amount));
+    }
 
-            // BUG FIX: Persist state change before cross-contract call
-            // This ensures release_usdc reads the correct trade status
-            trades.set(trade_id, trade.clone());
-            env.storage().instance().set(&TRADES_KEY, &trades);
+    /// Internal helper to total the USDC the contract owes to users.
+    /// Liabilities are the escrow of every active offer, parked payouts and the insurance fund.
+    /// 
+    /// # Returns
+    /// Total USDC the contract must hold to remain solvent
+    fn _total_liabilities(env: &Env) -> i128 {
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let active_offers: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::ActiveOffers).unwrap();
 
-            // ✅ SECURITY FIX: Set guard before external call
-            env.storage().instance().set(&EXECUTING, &true);
-            let result = Self::release_usdc(env.clone(), trade_id);
-            env.storage().instance().set(&EXECUTING, &false);
-            
-            // Return early to prevent overwriting the 'Completed' status
-            return result;
+        let mut total: i128 = env.storage().persistent().get(&ConfigKey::InsuranceFund).unwrap_or(0);
+        total += env.storage().instance().get::<_, i128>(&DataKey::ClaimableTotal).unwrap_or(0);
+        let arbitrator_fees: Map<Address, (u32, i128)> = env.storage().instance().get(&DataKey::ArbitratorFees)
+            .unwrap_or(Map::new(env));
+        for (_, accrued) in arbitrator_fees.values() {
+            total += accrued;
         }
+        for offer_ids in active_offers.values() {
+            for offer_id in offer_ids.iter() {
+                if let Some(offer) = offers.get(offer_id) {
+                    total += offer.usdc_amount + Self::_offer_fee_reserve(env, offer_id, offer.usdc_amount);
+                }
+            }
+        }
+        total
+    }
 
-        // Persist the updated trade state if the trade was not completed
-        trades.set(trade_id, trade);
-        env.storage().instance().set(&TRADES_KEY, &trades);
+    /// Internal helper to check whether an offer is listed as active for its seller
+    fn _is_active_offer(env: &Env, seller: &Address, offer_id: u64) -> bool {
+        let active_offers: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::ActiveOffers).unwrap();
+        active_offers.get(seller.clone())
+            .map(|offer_ids| offer_ids.contains(offer_id))
+            .unwrap_or(false)
+    }
 
-        Ok(())
+    /// Internal helper to check whether an offer can be traded right now:
+    /// Active (listed, not reserved by an open trade, not frozen) and open to any buyer.
+    fn _is_available_offer(env: &Env, offer_id: u64, offer: &Offer) -> bool {
+        offer.status == OfferStatus::Active
+            && offer.counterparty.is_none()
+            && Self::_is_active_offer(env, &offer.seller, offer_id)
     }
 
-    /// Internal function to release escrowed USDC to the buyer upon trade completion.
-    /// This is the core value transfer that completes a successful trade.
-    /// 
-    /// # Business Logic
-    /// 1. Validates trade is ready for USDC release
-    /// 2. Calculates and deducts trading fees
-    /// 3. Transfers USDC to buyer (amount minus fees)
-    /// 4. Transfers fees to fee collector
-    /// 5. Updates trade status and removes offer from active list
-    /// 6. Emits completion event
-    /// 
-    /// # Fee Structure
-    /// - Fees are calculated as basis points of trade amount
-    /// - Fees are sent to designated fee collector address
-    /// - Buyer receives trade amount minus fees
-    /// - Fee failures don't block trade completion
-    /// 
-    /// # Arguments
-    /// * `trade_id` - The ID of the trade to complete
-    /// 
-    /// # Returns
-    /// Result indicating success or failure of USDC release
-    fn release_usdc(env: Env, trade_id: u64) -> Result<(), Error> {
-        // Retrieve and validate trade state
-        let mut trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
-        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+    /// Internal helper to mark an offer as reserved by a trade, or release it with None.
+    /// Paused offers stay paused; unfreezing restores the status from the reservation.
+    fn _set_reservation(env: &Env, offer_id: u64, reserved_by: Option<u64>) {
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        if let Some(mut offer) = offers.get(offer_id) {
+            if offer.status != OfferStatus::Paused {
+                offer.status = if reserved_by.is_some() { OfferStatus::Reserved } else { OfferStatus::Active };
+            }
+            offer.reserved_by = reserved_by;
+            if Self::_is_active_offer(env, &offer.seller, offer_id) {
+                Self::_touch_offer(env, offer_id);
+            }
+            offers.set(offer_id, offer);
+            env.storage().instance().set(&DataKey::Offers, &offers);
+        }
+    }
 
-        // Security check: Only release USDC for properly confirmed trades
-        if trade.status != TradeStatus::PaymentConfirmed {
-            return Err(Error::InvalidTradeStatus);
+    /// Internal helper to record where an offer is in its lifecycle
+    fn _set_offer_status(env: &Env, offer_id: u64, status: OfferStatus) {
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        if let Some(mut offer) = offers.get(offer_id) {
+            offer.status = status;
+            offers.set(offer_id, offer);
+            env.storage().instance().set(&DataKey::Offers, &offers);
         }
+    }
 
-        // Get offer details for amount and seller information
-        let offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer = offers.get(trade.offer_id).ok_or(Error::OfferNotFound)?;
+    /// Internal helper to apply a delta to a monitoring counter
+    fn _adjust_counter(env: &Env, key: &DataKey, delta: i128) {
+        let value: i128 = env.storage().instance().get(key).unwrap_or(0);
+        env.storage().instance().set(key, &(value + delta));
+    }
 
-        // Calculate trading fee based on configured rate
-        let fee_rate: u32 = env.storage().persistent().get(&FEE_RATE_KEY)
-            .unwrap_or(DEFAULT_FEE_RATE);
-        let fee_amount = Self::_calculate_fee(offer.usdc_amount, fee_rate);
-        
-        // ✅ SECURITY FIX: Prevent fee calculation underflow
-        if fee_amount >= offer.usdc_amount {
-            return Err(Error::InvalidAmount);
-        }
-        
-        let amount_to_buyer = offer.usdc_amount - fee_amount;
-        
-        // CRITICAL SECURITY FIX: Update state BEFORE transfers to prevent reentrancy
-        // Following checks-effects-interactions pattern
-        
-        // Update trade status to completed BEFORE transfers
-        trade.status = TradeStatus::Completed;
-        trades.set(trade_id, trade.clone());
+    /// Internal helper to hash (owner, nonce, ledger sequence) into a 64-bit ID
+    fn _hashed_id(env: &Env, owner: &Address, nonce: u64, ledger: u32) -> u64 {
+        let digest = env.crypto().sha256(&(owner.clone(), nonce, ledger).to_xdr(env)).to_array();
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&digest[..8]);
+        u64::from_be_bytes(id)
+    }
 
-        // Remove offer from active offers BEFORE transfers
-        let mut active_offers: Map<Address, u64> = env.storage().instance().get(&ACTIVE_OFFERS).unwrap();
-        active_offers.remove(offer.seller.clone());
+    /// Internal helper to allocate the next offer or trade ID.
+    /// The global counter always advances, so sequential IDs are never reused. When hashed IDs
+    /// are on, the ID is derived from the owner's own nonce instead, so it says nothing about
+    /// marketplace volume; the nonce is bumped again on a (vanishingly rare) clash.
+    fn _next_id<V: IntoVal<Env, Val> + TryFromVal<Env, Val>>(env: &Env, counter: &DataKey, owner: &Address, taken: &Map<u64, V>) -> u64 {
+        let mut next: u64 = env.storage().instance().get(counter).unwrap_or(0);
+        let id = if Self::get_hashed_ids(env.clone()) {
+            let key = DataKey::IdNonce(owner.clone());
+            let mut nonce: u64 = env.storage().persistent().get(&key).unwrap_or(0);
+            let id = loop {
+                let id = Self::_hashed_id(env, owner, nonce, env.ledger().sequence());
+                nonce += 1;
+                if !taken.contains_key(id) {
+                    break id;
+                }
+            };
+            env.storage().persistent().set(&key, &nonce);
+            Self::_extend_entry(env, &key);
+            id
+        } else {
+            while taken.contains_key(next) {
+                next += 1;
+            }
+            next
+        };
+        env.storage().instance().set(counter, &(next + 1));
+        id
+    }
 
-        // Persist all state changes BEFORE transfers
-        env.storage().instance().set(&TRADES_KEY, &trades);
-        env.storage().instance().set(&ACTIVE_OFFERS, &active_offers);
+    /// Internal helper to list an offer as active for its seller
+    /// and add its escrow to the open-interest counters
+    fn _add_active_offer(env: &Env, seller: &Address, offer_id: u64, usdc_amount: i128) {
+        let mut active_offers: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::ActiveOffers).unwrap();
+        let mut offer_ids = active_offers.get(seller.clone()).unwrap_or(Vec::new(env));
+        offer_ids.push_back(offer_id);
+        active_offers.set(seller.clone(), offer_ids);
+        env.storage().instance().set(&DataKey::ActiveOffers, &active_offers);
+        Self::_touch_offer(env, offer_id);
 
-        // Emit completion event BEFORE transfers for consistency
-        env.events().publish((TRADE_COMPLETED, trade.buyer.clone()), (trade_id,));
+        Self::_adjust_counter(env, &DataKey::OfferCount, 1);
+        Self::_adjust_counter(env, &DataKey::TotalEscrow, usdc_amount);
+    }
 
-        // Now perform the external calls (transfers)
-        let usdc_token_id: Address = env.storage().persistent().get(&USDC_TOKEN_KEY).unwrap();
-        let usdc_client = token::Client::new(&env, &usdc_token_id);
-        
-        // Primary transfer: Send USDC to buyer (minus fees)
-        // This is the main value transfer that completes the trade
-        match usdc_client.try_transfer(&env.current_contract_address(), &trade.buyer, &amount_to_buyer) {
-            Ok(_) => {},
-            Err(_) => {
-                log!(&env, "Failed to transfer {} to buyer", amount_to_buyer);
-                // CRITICAL: Since we already updated state, we need to revert on failure
-                // Revert the trade status
-                trade.status = TradeStatus::PaymentConfirmed;
-                trades.set(trade_id, trade.clone());
-                env.storage().instance().set(&TRADES_KEY, &trades);
-                
-                // Revert the active offers
-                active_offers.set(offer.seller.clone(), trade.offer_id);
-                env.storage().instance().set(&ACTIVE_OFFERS, &active_offers);
-                
-                return Err(Error::TokenTransferFailed);
+    /// Internal helper to record listing or trade activity on an active offer, restarting its TTL
+    fn _touch_offer(env: &Env, offer_id: u64) {
+        let mut activity: Map<u64, u64> = env.storage().instance().get(&DataKey::OfferActivity).unwrap_or(Map::new(env));
+        activity.set(offer_id, env.ledger().timestamp());
+        env.storage().instance().set(&DataKey::OfferActivity, &activity);
+    }
+
+    /// Internal helper to check that listing `new_offers` more offers stays within
+    /// the global and per-seller active-offer caps
+    /// 
+    /// # Errors
+    /// - OfferLimitReached: If either cap would be exceeded
+    fn _check_offer_caps(env: &Env, seller: &Address, new_offers: u32) -> Result<(), Error> {
+        let (max_total, max_per_seller) = Self::get_offer_caps(env.clone());
+        if max_total > 0 && Self::get_active_offer_count(env.clone()) + new_offers > max_total {
+            return Err(Error::OfferLimitReached);
+        }
+        if max_per_seller > 0 {
+            let active_offers: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::ActiveOffers).unwrap();
+            let listed = active_offers.get(seller.clone()).map(|offer_ids| offer_ids.len()).unwrap_or(0);
+            if listed + new_offers > max_per_seller {
+                return Err(Error::OfferLimitReached);
             }
         }
-        
-        // Secondary transfer: Send fees to fee collector
-        // Fee transfer failure doesn't block trade completion
-        if fee_amount > 0 {
-            let fee_collector: Address = env.storage().persistent().get(&FEE_COLLECTOR_KEY).unwrap();
-            match usdc_client.try_transfer(&env.current_contract_address(), &fee_collector, &fee_amount) {
-                Ok(_) => {},
-                Err(_) => {
-                    log!(&env, "Failed to transfer fee {} to collector", fee_amount);
-                    // Continue - don't fail the trade because of fee transfer
-                    // The trader's experience is more important than fee collection
-                }
+        Ok(())
+    }
+
+    /// Internal helper to drop the per-offer settings of a cancelled offer,
+    /// ending any recurring relists
+    fn _clear_offer_settings(env: &Env, offer_id: u64) {
+        let mut recurring: Map<u64, i128> = env.storage().instance().get(&DataKey::RecurringOffers).unwrap_or(Map::new(env));
+        if recurring.remove(offer_id).is_some() {
+            env.storage().instance().set(&DataKey::RecurringOffers, &recurring);
+        }
+        let mut requirements: Map<u64, BuyerRequirements> = env.storage().instance().get(&DataKey::OfferRequirements)
+            .unwrap_or(Map::new(env));
+        if requirements.remove(offer_id).is_some() {
+            env.storage().instance().set(&DataKey::OfferRequirements, &requirements);
+        }
+        let mut payment_details: Map<u64, Bytes> = env.storage().instance().get(&DataKey::PaymentDetails)
+            .unwrap_or(Map::new(env));
+        if payment_details.remove(offer_id).is_some() {
+            env.storage().instance().set(&DataKey::PaymentDetails, &payment_details);
+        }
+        let mut fee_payers: Map<u64, FeePayer> = env.storage().instance().get(&DataKey::FeePayers)
+            .unwrap_or(Map::new(env));
+        if fee_payers.remove(offer_id).is_some() {
+            env.storage().instance().set(&DataKey::FeePayers, &fee_payers);
+        }
+        let mut buyer_caps: Map<u64, (i128, u64)> = env.storage().instance().get(&DataKey::BuyerCaps)
+            .unwrap_or(Map::new(env));
+        if buyer_caps.remove(offer_id).is_some() {
+            env.storage().instance().set(&DataKey::BuyerCaps, &buyer_caps);
+        }
+        let mut buyer_fills: Map<u64, Map<Address, (u64, i128)>> = env.storage().instance().get(&DataKey::BuyerFills)
+            .unwrap_or(Map::new(env));
+        if buyer_fills.remove(offer_id).is_some() {
+            env.storage().instance().set(&DataKey::BuyerFills, &buyer_fills);
+        }
+    }
+
+    /// Internal helper to delist an offer, dropping the seller entry once empty
+    fn _remove_active_offer(env: &Env, seller: &Address, offer_id: u64, usdc_amount: i128) {
+        let mut active_offers: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::ActiveOffers).unwrap();
+        if let Some(mut offer_ids) = active_offers.get(seller.clone()) {
+            if let Some(index) = offer_ids.first_index_of(offer_id) {
+                offer_ids.remove(index);
+                Self::_adjust_counter(env, &DataKey::OfferCount, -1);
+                Self::_adjust_counter(env, &DataKey::TotalEscrow, -usdc_amount);
             }
+            if offer_ids.is_empty() {
+                active_offers.remove(seller.clone());
+            } else {
+                active_offers.set(seller.clone(), offer_ids);
+            }
+            env.storage().instance().set(&DataKey::ActiveOffers, &active_offers);
+        }
+
+        let mut activity: Map<u64, u64> = env.storage().instance().get(&DataKey::OfferActivity).unwrap_or(Map::new(env));
+        if activity.remove(offer_id).is_some() {
+            env.storage().instance().set(&DataKey::OfferActivity, &activity);
         }
+    }
+
+    /// Internal helper to resolve where a trade's USDC payout is sent
+    fn _payout_address(trade: &Trade) -> Address {
+        trade.receive_to.clone().unwrap_or(trade.buyer.clone())
+    }
 
+    /// Internal helper to resolve where a trade's escrow refund is sent
+    fn _refund_address(trade: &Trade) -> Address {
+        trade.refund_to.clone().unwrap_or(trade.seller.clone())
+    }
+
+    /// Internal helper to publish an event to both counterparties of a trade or proposal.
+    /// Each copy carries the affected party as the second topic and the other party as
+    /// the third, so a topic filter on one address finds every event involving it.
+    /// The last topic is the party's event sequence number.
+    fn _publish_to_parties<D: IntoVal<Env, Val> + Clone>(env: &Env, topic: Symbol, buyer: &Address, seller: &Address, data: D) {
+        let buyer_seq = Self::_next_event_seq(env, buyer);
+        env.events().publish((topic.clone(), buyer.clone(), seller.clone(), buyer_seq), data.clone());
+        let seller_seq = Self::_next_event_seq(env, seller);
+        env.events().publish((topic, seller.clone(), buyer.clone(), seller_seq), data);
+    }
+
+    /// Internal helper to publish an event addressed to a single address,
+    /// followed by that address's event sequence number
+    fn _publish_to<D: IntoVal<Env, Val>>(env: &Env, topic: Symbol, address: &Address, data: D) {
+        let seq = Self::_next_event_seq(env, address);
+        env.events().publish((topic, address.clone(), seq), data);
+    }
+
+    /// Internal helper to advance an address's event sequence number.
+    /// Numbers start at 1 and increase by one per event addressed to it, so consumers
+    /// can spot missed events and order an address's activity deterministically.
+    fn _next_event_seq(env: &Env, address: &Address) -> u64 {
+        let key = DataKey::EventSeq(address.clone());
+        let seq = env.storage().persistent().get(&key).unwrap_or(0u64) + 1;
+        env.storage().persistent().set(&key, &seq);
+        Self::_extend_entry(env, &key);
+        seq
+    }
+
+    /// Internal helper to keep a long-lived persistent entry from being archived.
+    /// Called on every write, so an entry that stays in use never has to be restored.
+    fn _extend_entry(env: &Env, key: &DataKey) {
+        env.storage().persistent().extend_ttl(key, ENTRY_TTL_THRESHOLD, ENTRY_TTL_EXTEND);
+    }
+
+    /// Internal helper to pay out a trade's initiation deposit, if the buyer posted one.
+    /// Deposits are paid in the token recorded at initiation, so later config changes
+    /// don't affect trades already open.
+    fn _pay_initiation_deposit(env: &Env, trade_id: u64, recipient: &Address) -> Result<(), Error> {
+        let mut deposits: Map<u64, (Address, i128)> = env.storage().instance().get(&DataKey::TradeDeposits)
+            .unwrap_or(Map::new(env));
+        let Some((xlm_token_id, deposit)) = deposits.get(trade_id) else {
+            return Ok(());
+        };
+        deposits.remove(trade_id);
+        env.storage().instance().set(&DataKey::TradeDeposits, &deposits);
+
+        let xlm_client = token::Client::new(env, &xlm_token_id);
+        if xlm_client.try_transfer(&env.current_contract_address(), recipient, &deposit).is_err() {
+            log!(env, "Failed to pay the {} initiation deposit", deposit);
+            return Err(Error::TokenTransferFailed);
+        }
         Ok(())
     }
 
-    /// Allows trade participants to cancel an initiated trade.
-    /// This returns the escrowed USDC to the seller.
-    /// 
-    /// # Business Flow
-    /// 1. Validates participant authorization and trade state
-    /// 2. Ensures only initiated trades can be cancelled
-    /// 3. Returns escrowed USDC to seller
-    /// 4. Updates trade status and removes offer from active list
-    /// 5. Emits cancellation event
-    /// 
-    /// # Security Features
-    /// - Only trade participants can cancel
-    /// - Only initiated trades can be cancelled
-    /// - Safe transfer with error handling
-    /// - Proper cleanup of offer state
-    /// 
-    /// # Arguments
-    /// * `trade_id` - The ID of the trade to cancel
-    /// * `participant` - The address requesting cancellation (buyer or seller)
+    /// Internal helper to collect the configured initiation deposit from a buyer who signed
+    /// the invocation. Returns the (token, amount) taken, or None if no deposit is configured.
     /// 
     /// # Errors
-    /// - ContractPaused: If contract is paused
-    /// - TradeNotFound: If trade doesn't exist
-    /// - InvalidTradeStatus: If trade cannot be cancelled
-    /// - Unauthorized: If caller is not a trade participant
-    /// - TokenTransferFailed: If USDC return fails
-    pub fn cancel_trade(env: Env, trade_id: u64, participant: Address) -> Result<(), Error> {
-        // Emergency brake - halt all operations if contract is paused
-        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
-        
-        // Verify the participant has signed this transaction
-        participant.require_auth();
+    /// - InsufficientBalance: If the buyer doesn't hold the deposit
+    /// - TokenTransferFailed: If the transfer fails for another reason
+    fn _collect_initiation_deposit(env: &Env, buyer: &Address) -> Result<Option<(Address, i128)>, Error> {
+        let Some((xlm_token_id, deposit)) = env.storage().persistent().get::<_, (Address, i128)>(&ConfigKey::InitiationDeposit) else {
+            return Ok(None);
+        };
+        let xlm_client = token::Client::new(env, &xlm_token_id);
+        match xlm_client.try_transfer(buyer, &env.current_contract_address(), &deposit) {
+            Ok(Ok(())) => Ok(Some((xlm_token_id, deposit))),
+            Err(Ok(err)) if err == soroban_sdk::Error::from_contract_error(TOKEN_BALANCE_ERROR) => {
+                log!(env, "Insufficient XLM balance for the {} initiation deposit", deposit);
+                Err(Error::InsufficientBalance)
+            }
+            _ => {
+                log!(env, "Initiation deposit transfer failed for amount: {}", deposit);
+                Err(Error::TokenTransferFailed)
+            }
+        }
+    }
 
-        // Retrieve and validate the trade
-        let mut trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
-        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+    /// Internal helper to return the initiation deposit posted with a proposal that won't be accepted.
+    /// Best-effort: a proposer who can't receive (e.g. a merged account) must not block the
+    /// settlement dropping the proposal, so a failed refund stays parked under its
+    /// ProposalDeposit entry until the proposer reclaims it with `claim_proposal_deposit`.
+    fn _refund_proposal_deposit(env: &Env, proposal_id: u64) {
+        let key = DataKey::ProposalDeposit(proposal_id);
+        let Some((buyer, (xlm_token_id, deposit))) = env.storage().persistent().get::<_, (Address, (Address, i128))>(&key) else {
+            return;
+        };
+        env.storage().persistent().remove(&key);
 
-        // Get offer details for validation
-        let offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer = offers.get(trade.offer_id).ok_or(Error::OfferNotFound)?;
+        let xlm_client = token::Client::new(env, &xlm_token_id);
+        if xlm_client.try_transfer(&env.current_contract_address(), &buyer, &deposit).is_ok() {
+            return;
+        }
 
-        // Business rule: Only initiated trades can be cancelled
-        if trade.status != TradeStatus::Initiated {
-            return Err(Error::InvalidTradeStatus);
+        log!(env, "Parking undeliverable proposal deposit of {}", deposit);
+        env.storage().persistent().set(&key, &(buyer.clone(), (xlm_token_id, deposit)));
+        Self::_extend_entry(env, &key);
+        Self::_publish_to(env, DEPOSIT_PARKED, &buyer, (proposal_id, deposit));
+    }
+
+    /// Internal helper to list the IDs of the open proposals on an offer
+    fn _offer_proposals(env: &Env, offer_id: u64) -> Vec<u64> {
+        env.storage().persistent().get(&DataKey::OfferProposals(offer_id)).unwrap_or(Vec::new(env))
+    }
+
+    /// Internal helper to store the IDs of the open proposals on an offer
+    fn _set_offer_proposals(env: &Env, offer_id: u64, proposal_ids: &Vec<u64>) {
+        let key = DataKey::OfferProposals(offer_id);
+        if proposal_ids.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, proposal_ids);
+            Self::_extend_entry(env, &key);
         }
+    }
 
-        // Security check: Only trade participants can cancel
-        if participant != trade.buyer && participant != offer.seller {
-            return Err(Error::Unauthorized);
+    /// Internal helper to drop a proposal that was accepted, rejected or withdrawn
+    fn _remove_proposal(env: &Env, proposal_id: u64, offer_id: u64) {
+        env.storage().persistent().remove(&DataKey::Proposal(proposal_id));
+        let mut proposal_ids = Self::_offer_proposals(env, offer_id);
+        if let Some(index) = proposal_ids.first_index_of(proposal_id) {
+            proposal_ids.remove(index);
         }
+        Self::_set_offer_proposals(env, offer_id, &proposal_ids);
+    }
 
-        // Update trade status to cancelled
-        trade.status = TradeStatus::Cancelled;
-        trades.set(trade_id, trade.clone());
+    /// Internal helper to drop the open proposals on an offer leaving the book, refunding their deposits
+    fn _clear_proposals(env: &Env, offer_id: u64) {
+        let proposal_ids = Self::_offer_proposals(env, offer_id);
+        if proposal_ids.is_empty() {
+            return;
+        }
+        env.storage().persistent().remove(&DataKey::OfferProposals(offer_id));
+        Self::_drop_proposals(env, &proposal_ids);
+    }
 
-        // Persist state changes
-        env.storage().instance().set(&TRADES_KEY, &trades);
+    /// Internal helper to delete proposals already taken off their offer, refunding their deposits
+    fn _drop_proposals(env: &Env, proposal_ids: &Vec<u64>) {
+        for proposal_id in proposal_ids.iter() {
+            env.storage().persistent().remove(&DataKey::Proposal(proposal_id));
+        }
+        for proposal_id in proposal_ids.iter() {
+            Self::_refund_proposal_deposit(env, proposal_id);
+        }
+    }
 
-        // Emit cancellation event for transparency
-        env.events().publish((TRADE_CANCELLED, participant.clone()), (trade_id,));
+    /// Internal helper to block state transitions on entities under compliance review.
+    /// 
+    /// # Arguments
+    /// * `key` - DataKey::FrozenTrades or DataKey::FrozenOffers
+    /// * `id` - The trade or offer ID
+    /// 
+    /// # Errors
+    /// - Frozen: If the entity is frozen
+    fn _require_not_frozen(env: &Env, key: &DataKey, id: u64) -> Result<(), Error> {
+        let frozen: Map<u64, u64> = env.storage().instance().get(key).unwrap_or(Map::new(env));
+        if frozen.contains_key(id) {
+            return Err(Error::Frozen);
+        }
+        Ok(())
+    }
 
+    /// Internal helper to verify compliance officer authorization
+    fn _require_compliance_officer(env: &Env) -> Result<(), Error> {
+        let officer: Address = env.storage().persistent().get(&ConfigKey::ComplianceOfficer).ok_or(Error::Unauthorized)?;
+        officer.require_auth();
         Ok(())
     }
 
-    /// Resolves expired trades by returning escrowed USDC to sellers.
-    /// Anyone can call this function to clean up expired trades.
-    /// 
-    /// # Business Logic
-    /// - Trades have time limits to prevent indefinite escrow
-    /// - Expired trades are automatically cancelled
-    /// - USDC is returned to seller when trade expires
-    /// - This prevents buyer griefing by not confirming payment
-    /// 
-    /// # Public Access
-    /// - Any address can call this function
-    /// - Helps maintain marketplace hygiene
-    /// - Incentivizes community participation in cleanup
+    /// Internal helper to add or remove an entity from a frozen set
+    fn _set_frozen(env: &Env, key: &DataKey, id: u64, frozen: bool) {
+        let mut entries: Map<u64, u64> = env.storage().instance().get(key).unwrap_or(Map::new(env));
+        if frozen {
+            entries.set(id, env.ledger().timestamp());
+        } else {
+            entries.remove(id);
+        }
+        env.storage().instance().set(key, &entries);
+    }
+
+    /// Internal helper to count USDC volume against an address's daily cap.
+    /// Usage is bucketed by ledger-time day and resets when the day changes.
     /// 
     /// # Arguments
-    /// * `trade_id` - The ID of the expired trade to resolve
+    /// * `address` - The seller or buyer whose volume is being recorded
+    /// * `amount` - USDC amount being escrowed or traded
     /// 
     /// # Errors
-    /// - ContractPaused: If contract is paused
-    /// - TradeNotFound: If trade doesn't exist
-    /// - TradeNotExpired: If trade hasn't actually expired
-    /// - InvalidTradeStatus: If trade is not in expirable state
-    /// - TokenTransferFailed: If USDC return fails
-    pub fn resolve_expired_trade(env: Env, trade_id: u64) -> Result<(), Error> {
-        // Emergency brake - halt all operations if contract is paused
-        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
-        
-        // Retrieve and validate the trade
-        let mut trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
-        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+    /// - DailyLimitExceeded: If the amount would take the address over the cap
+    fn _consume_daily_limit(env: &Env, address: &Address, amount: i128) -> Result<(), Error> {
+        let Some(used) = Self::_check_daily_limit(env, address, amount)? else {
+            return Ok(());
+        };
 
-        // Validate that the trade has actually expired
-        if !Self::_is_trade_expired(&env, &trade) {
-            return Err(Error::TradeNotExpired);
-        }
+        let today = env.ledger().timestamp() / SECONDS_PER_DAY;
+        let key = DataKey::DailyUsed(address.clone());
+        env.storage().persistent().set(&key, &(today, used + amount));
+        Self::_extend_entry(env, &key);
+        Ok(())
+    }
 
-        // Only initiated trades can expire - others are already resolved
-        if trade.status != TradeStatus::Initiated {
-            return Err(Error::InvalidTradeStatus);
+    /// Internal helper to check an amount against an address's daily cap without recording it.
+    /// 
+    /// # Returns
+    /// The address's usage so far today, or None if there is no cap
+    /// 
+    /// # Errors
+    /// - DailyLimitExceeded: If the amount would take the address over the cap
+    fn _check_daily_limit(env: &Env, address: &Address, amount: i128) -> Result<Option<i128>, Error> {
+        let limit: i128 = env.storage().persistent().get(&ConfigKey::DailyLimit).unwrap_or(0);
+        if limit == 0 {
+            return Ok(None);
         }
 
-        // Update trade status to cancelled due to expiration
-        trade.status = TradeStatus::Cancelled;
-        trades.set(trade_id, trade.clone());
-
-        // Get offer details for returning USDC to seller
-        let offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer = offers.get(trade.offer_id).ok_or(Error::OfferNotFound)?;
+        let today = env.ledger().timestamp() / SECONDS_PER_DAY;
+        let used = match env.storage().persistent().get::<_, (u64, i128)>(&DataKey::DailyUsed(address.clone())) {
+            Some((day, used)) if day == today => used,
+            _ => 0,
+        };
 
-        // Setup USDC client for returning funds
-        let usdc_token_id: Address = env.storage().persistent().get(&USDC_TOKEN_KEY).unwrap();
-        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        if used + amount > limit {
+            log!(env, "Daily limit exceeded. Limit: {}, Used: {}, Requested: {}", limit, used, amount);
+            return Err(Error::DailyLimitExceeded);
+        }
+        Ok(Some(used))
+    }
 
-        // Return the escrowed USDC to seller since trade expired
-        match usdc_client.try_transfer(&env.current_contract_address(), &offer.seller, &offer.usdc_amount) {
-            Ok(_) => {},
-            Err(_) => {
-                log!(&env, "Failed to return {} to seller", offer.usdc_amount);
-                return Err(Error::TokenTransferFailed);
-            }
+    /// Internal helper to validate the terms of a single offer or price level.
+    /// 
+    /// # Errors
+    /// - InvalidAmount: If amounts are non-positive
+    /// - BelowMinimum / AboveMaximum: If the USDC amount is outside trade limits
+    /// - RateOutOfBounds: If the implied rate is outside the fiat limits or too large to compute
+    /// - InvalidTokenAddress: If cKES settlement is requested but no cKES token is registered,
+    ///   or the swap asset is the USDC token
+    fn _validate_offer_terms(env: &Env, usdc_amount: i128, kes_amount: i128, settlement: &SettlementMode) -> Result<(), Error> {
+        // Input validation - prevent invalid or malicious amounts
+        if usdc_amount <= 0 || kes_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        // Enforce trading limits to prevent spam (min) and excessive exposure (max)
+        let (min_amount, max_amount) = Self::get_trade_limits(env.clone());
+            
+        if usdc_amount < min_amount {
+            log!(env, "Amount below minimum. Min: {}, Provided: {}", min_amount, usdc_amount);
+            return Err(Error::BelowMinimum);
+        }
+        if usdc_amount > max_amount {
+            log!(env, "Amount above maximum. Max: {}, Provided: {}", max_amount, usdc_amount);
+            return Err(Error::AboveMaximum);
         }
 
-        // Clean up: Remove offer from active offers
-        let mut active_offers: Map<Address, u64> = env.storage().instance().get(&ACTIVE_OFFERS).unwrap();
-        active_offers.remove(offer.seller.clone());
+        // On-chain settlement requires a usable counter asset
+        Self::_counter_token(env, settlement)?;
 
-        // Persist state changes
-        env.storage().instance().set(&TRADES_KEY, &trades);
-        env.storage().instance().set(&ACTIVE_OFFERS, &active_offers);
+        // The KES side gets its own floor and a rate sanity check against typos
+        if !matches!(settlement, SettlementMode::Swap(_)) {
+            let (min_kes_amount, min_rate, max_rate) = Self::get_fiat_limits(env.clone());
+            if kes_amount < min_kes_amount {
+                log!(env, "KES amount below minimum. Min: {}, Provided: {}", min_kes_amount, kes_amount);
+                return Err(Error::BelowMinimum);
+            }
+            let rate = Self::_implied_rate(kes_amount, usdc_amount)?;
+            if rate < min_rate || (max_rate > 0 && rate > max_rate) {
+                log!(env, "Implied rate out of bounds. Rate: {}", rate);
+                return Err(Error::RateOutOfBounds);
+            }
+        }
 
-        // Emit cancellation event (using contract address as emitter for expired trades)
-        env.events().publish((TRADE_CANCELLED, env.current_contract_address()), (trade_id,));
+        // Swap assets must be registered, enabled and within their own limits
+        if let SettlementMode::Swap(token_id) = settlement {
+            let config = Self::_token_config(env, token_id)
+                .filter(|config| config.enabled)
+                .ok_or(Error::InvalidTokenAddress)?;
+            if kes_amount < config.min_amount {
+                return Err(Error::BelowMinimum);
+            }
+            if config.max_amount > 0 && kes_amount > config.max_amount {
+                return Err(Error::AboveMaximum);
+            }
+        }
 
         Ok(())
     }
 
-    /// Allows sellers to cancel their offers and recover escrowed USDC.
-    /// Offers can only be cancelled if no active trade exists.
-    /// 
-    /// # Business Flow
-    /// 1. Validates seller authorization and offer ownership
-    /// 2. Checks no active trades exist for the offer
-    /// 3. Returns escrowed USDC to seller
-    /// 4. Removes offer from all mappings
-    /// 5. Emits cancellation event
-    /// 
-    /// # Security Features
-    /// - Only offer owner can cancel
-    /// - Prevents cancellation if active trade exists
-    /// - Safe transfer with error handling
-    /// - Proper cleanup of all offer references
+    /// Internal helper to look up a token's registry entry
+    fn _token_config(env: &Env, token_id: &Address) -> Option<TokenConfig> {
+        let configs: Map<Address, TokenConfig> = env.storage().persistent().get(&ConfigKey::TokenConfigs)?;
+        configs.get(token_id.clone())
+    }
+
+    /// Internal helper to resolve the token an on-chain settlement pays the counter leg in.
+    /// Returns None for off-chain settlement.
     /// 
-    /// # Arguments
-    /// * `seller` - The address that created the offer (must sign)
-    /// * `offer_id` - The ID of the offer to cancel
+    /// # Errors
+    /// - InvalidTokenAddress: If no cKES token is registered, or a swap asset is the USDC token
+    fn _counter_token(env: &Env, settlement: &SettlementMode) -> Result<Option<Address>, Error> {
+        match settlement {
+            SettlementMode::OffChain => Ok(None),
+            SettlementMode::OnChain => env.storage().persistent().get(&ConfigKey::KesToken)
+                .map(Some)
+                .ok_or(Error::InvalidTokenAddress),
+            SettlementMode::Swap(token_id) => {
+                let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+                if *token_id == usdc_token_id {
+                    return Err(Error::InvalidTokenAddress);
+                }
+                Ok(Some(token_id.clone()))
+            }
+        }
+    }
+
+    /// Internal helper to escrow USDC from a seller, returning the amount actually received.
+    /// The seller's require_auth authorizes the transfer as a sub-invocation.
     /// 
     /// # Errors
-    /// - ContractPaused: If contract is paused
-    /// - OfferNotFound: If offer doesn't exist
-    /// - Unauthorized: If caller is not the offer owner
-    /// - TradeAlreadyInitiated: If active trade exists for offer
-    /// - TokenTransferFailed: If USDC return fails
-    pub fn cancel_offer(env: Env, seller: Address, offer_id: u64) -> Result<(), Error> {
-        // Emergency brake - halt all operations if contract is paused
-        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
-        
-        // Verify the seller has signed this transaction
-        seller.require_auth();
+    /// - InsufficientBalance: If seller doesn't hold enough USDC
+    /// - TokenTransferFailed: If the transfer fails or nothing is received
+    fn _escrow_usdc(env: &Env, seller: &Address, amount: i128) -> Result<i128, Error> {
+        // Setup USDC token client for transfers
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(env, &usdc_token_id);
 
-        // Retrieve and validate the offer
-        let mut offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        // Record the contract balance so the amount actually received can be measured
+        let balance_before = usdc_client.balance(&env.current_contract_address());
 
-        // Security check: Only the offer owner can cancel their offer
-        if offer.seller != seller {
-            return Err(Error::Unauthorized);
+        // Transfer USDC from seller to contract for escrow
+        // No allowance or balance pre-check round trips are needed
+        match usdc_client.try_transfer(seller, &env.current_contract_address(), &amount) {
+            Ok(Ok(())) => {},
+            Err(Ok(err)) if err == soroban_sdk::Error::from_contract_error(TOKEN_BALANCE_ERROR) => {
+                log!(env, "Insufficient balance for escrow of {}", amount);
+                return Err(Error::InsufficientBalance);
+            }
+            _ => {
+                log!(env, "Token transfer failed for amount: {}", amount);
+                return Err(Error::TokenTransferFailed);
+            }
         }
 
-        // Business rule: Cannot cancel offer if there's an active trade
-        // This prevents disrupting ongoing trade processes
-        let trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
-        
-        // ✅ SECURITY FIX: Optimized check with reverse iteration (newer trades first)
-        // Completed and cancelled trades don't block offer cancellation
-        let mut has_active_trade = false;
-        for trade in trades.values().into_iter().rev() {
-            if trade.offer_id == offer_id && 
-               (trade.status == TradeStatus::Initiated || 
-                trade.status == TradeStatus::PaymentConfirmed || 
-                trade.status == TradeStatus::Disputed) {
-                has_active_trade = true;
-                break;
-            }
+        // SECURITY FIX: Escrow what was actually received, not what was requested
+        // Tokens with transfer fees or clawback would otherwise desynchronize escrow accounting
+        let received = usdc_client.balance(&env.current_contract_address()) - balance_before;
+        if received <= 0 {
+            log!(env, "No USDC received for escrow of {}", amount);
+            return Err(Error::TokenTransferFailed);
         }
-        
-        if has_active_trade {
-            return Err(Error::TradeAlreadyInitiated);
+
+        Ok(received)
+    }
+
+    /// Internal helper holding the offer checks shared by `create_offer` and its simulation.
+    /// Reads state only.
+    fn _check_new_offer(env: &Env, seller: &Address, usdc_amount: i128, kes_amount: i128, settlement: &SettlementMode) -> Result<(), Error> {
+        Self::_require_not_suspended(env, seller)?;
+        Self::_validate_offer_terms(env, usdc_amount, kes_amount, settlement)?;
+
+        // Business rule: One active offer per seller to keep marketplace simple
+        // This prevents retail sellers from fragmenting liquidity across multiple offers;
+        // market makers list multiple price levels through create_offers
+        let active_offers: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::ActiveOffers).unwrap();
+        if active_offers.contains_key(seller.clone()) {
+            return Err(Error::AlreadyHasActiveOffer);
         }
+        Self::_check_offer_caps(env, seller, 1)?;
+        Self::_check_daily_limit(env, seller, usdc_amount)?;
+        Ok(())
+    }
 
-        // Setup USDC client for returning escrowed funds
-        let usdc_token_id: Address = env.storage().persistent().get(&USDC_TOKEN_KEY).unwrap();
-        let usdc_client = token::Client::new(&env, &usdc_token_id);
+    /// Internal helper to re-escrow and relist a recurring offer after its trade completes.
+    /// Draws from the seller's token allowance to the marketplace, since the seller is
+    /// not part of the releasing invocation. Any failure ends the standing offer instead
+    /// of blocking the completed trade.
+    fn _relist_offer(env: &Env, usdc_client: &token::Client, offer_id: u64) {
+        let mut recurring: Map<u64, i128> = env.storage().instance().get(&DataKey::RecurringOffers).unwrap_or(Map::new(env));
+        let Some(remaining) = recurring.get(offer_id) else { return };
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let Some(mut offer) = offers.get(offer_id) else { return };
 
-        // Return the escrowed USDC to seller
-        match usdc_client.try_transfer(&env.current_contract_address(), &seller, &offer.usdc_amount) {
-            Ok(_) => {},
-            Err(_) => {
-                log!(&env, "Failed to return {} to seller", offer.usdc_amount);
-                return Err(Error::TokenTransferFailed);
+        // Stop once the cap is used up or the seller hits the daily limit. The limit is
+        // only consumed once the escrow has arrived, since a failed relist is swallowed
+        let amount = offer.usdc_amount;
+        let mut relisted = remaining >= amount && Self::_check_daily_limit(env, &offer.seller, amount).is_ok();
+
+        // Pull the next round of escrow and fee reserve, requiring the full amount to arrive
+        if relisted {
+            let contract = env.current_contract_address();
+            let pulled = amount + Self::_offer_fee_reserve(env, offer_id, amount);
+            let balance_before = usdc_client.balance(&contract);
+            relisted = usdc_client.try_transfer_from(&contract, &offer.seller, &contract, &pulled).is_ok()
+                && usdc_client.balance(&contract) - balance_before == pulled;
+            if !relisted {
+                log!(env, "Failed to re-escrow {} for recurring offer", amount);
             }
         }
 
-        // Remove offer from storage
-        offers.remove(offer_id);
-
-        // Remove from active offers mapping
-        let mut active_offers: Map<Address, u64> = env.storage().instance().get(&ACTIVE_OFFERS).unwrap();
-        active_offers.remove(seller.clone());
+        if !relisted {
+            recurring.remove(offer_id);
+            env.storage().instance().set(&DataKey::RecurringOffers, &recurring);
+            return;
+        }
+        // Checked above within this invocation, so the limit still has room
+        let _ = Self::_consume_daily_limit(env, &offer.seller, amount);
 
-        // Persist changes
-        env.storage().instance().set(&OFFERS_KEY, &offers);
-        env.storage().instance().set(&ACTIVE_OFFERS, &active_offers);
+        offer.reserved_by = None;
+        offer.status = OfferStatus::Active;
+        offers.set(offer_id, offer.clone());
+        env.storage().instance().set(&DataKey::Offers, &offers);
+        Self::_add_active_offer(env, &offer.seller, offer_id, amount);
 
-        // Emit cancellation event for transparency
-        env.events().publish((OFFER_CANCELLED, seller.clone()), (offer_id,));
+        let remaining = remaining - amount;
+        if remaining < amount {
+            recurring.remove(offer_id);
+        } else {
+            recurring.set(offer_id, remaining);
+        }
+        env.storage().instance().set(&DataKey::RecurringOffers, &recurring);
 
-        Ok(())
+        Self::_publish_to(env, OFFER_RELISTED, &offer.seller, (offer_id, amount, remaining));
     }
 
-    /// Emergency function to pause all trading activities.
-    /// Only admin can pause the contract for security or maintenance.
-    /// 
-    /// # Use Cases
-    /// - Security incidents requiring immediate halt
-    /// - Contract upgrades or maintenance
-    /// - Regulatory compliance requirements
-    /// - Market manipulation prevention
-    /// 
-    /// # Admin Only
-    /// - Requires admin authorization
-    /// - Immediate effect on all trading functions
-    /// - Does not affect existing trades, only new operations
+    /// Internal helper validating the operator fee and external reference of a new trade.
     /// 
-    /// # Returns
-    /// Result indicating success or failure of pause operation
-    pub fn pause(env: Env) -> Result<(), Error> {
-        // Verify admin authorization - only admin can pause
-        Self::_require_admin(&env)?;
-        
-        // Set pause flag to halt all trading operations
-        env.storage().instance().set(&PAUSED_KEY, &true);
-        
-        Ok(())
-    }
+    /// # Errors
+    /// - InvalidAmount: If the operator fee exceeds the admin cap or is set without an operator
+    /// - DuplicateReference: If the external reference is already attached to a trade
+    fn _check_trade_options(
+        env: &Env,
+        operator: &Option<Address>,
+        operator_fee_bps: u32,
+        external_ref: &Option<BytesN<32>>,
+    ) -> Result<(), Error> {
+        // Operator fee must come with an operator and stay within the admin cap
+        match operator {
+            Some(operator) => {
+                Self::_validate_address(operator)?;
+                let cap: u32 = env.storage().persistent().get(&ConfigKey::OperatorFeeCap)
+                    .unwrap_or(DEFAULT_OPERATOR_FEE_CAP);
+                if operator_fee_bps > cap {
+                    return Err(Error::InvalidAmount);
+                }
+            }
+            None if operator_fee_bps != 0 => return Err(Error::InvalidAmount),
+            None => {}
+        }
 
-    /// Resumes trading activities after a pause.
-    /// Only admin can unpause the contract.
-    /// 
-    /// # Security Consideration
-    /// - Admin should verify all issues are resolved before unpausing
-    /// - Existing trades continue normally after unpause
-    /// - New trading activities become available immediately
-    /// 
-    /// # Returns
-    /// Result indicating success or failure of unpause operation
-    pub fn unpause(env: Env) -> Result<(), Error> {
-        // Verify admin authorization - only admin can unpause
-        Self::_require_admin(&env)?;
-        
-        // Remove pause flag to resume trading operations
-        env.storage().instance().set(&PAUSED_KEY, &false);
-        
+        // References must identify a single trade
+        let trade_refs: Map<BytesN<32>, u64> = env.storage().instance().get(&DataKey::TradeRefs)
+            .unwrap_or(Map::new(env));
+        if external_ref.as_ref().is_some_and(|external_ref| trade_refs.contains_key(external_ref.clone())) {
+            return Err(Error::DuplicateReference);
+        }
         Ok(())
     }
 
-    // ================================================================================================
-    // DISPUTE RESOLUTION SYSTEM
-    // ================================================================================================
-    // Note: Dispute resolution functions should be implemented here
-    // For now, disputes must be handled off-chain by contacting the admin
-    
-    /// Raises a dispute for a trade when payment confirmation conflicts arise.
-    /// This function allows trade participants to escalate issues that cannot be resolved
-    /// through normal payment confirmation flow.
-    /// 
-    /// # Business Logic
-    /// - Either buyer or seller can raise a dispute
-    /// - Disputes can be raised on initiated or payment-confirmed trades
-    /// - Once disputed, trades require admin intervention to resolve
-    /// - Prevents automatic trade completion until dispute is resolved
-    /// 
-    /// # Security Features
-    /// - Only trade participants can raise disputes
-    /// - Validates trade exists and is in appropriate state
-    /// - Prevents abuse by limiting who can dispute
-    /// 
-    /// # Arguments
-    /// * `trade_id` - The ID of the trade to dispute
-    /// * `caller` - The address raising the dispute (buyer or seller)
+    /// Internal helper holding the trade checks shared by `_open_trade` and its simulation.
+    /// Reads state only.
     /// 
     /// # Returns
-    /// Result indicating success or failure of dispute creation
-    /// 
-    /// # Errors
-    /// - TradeNotFound: If trade doesn't exist
-    /// - Unauthorized: If caller is not a trade participant
-    /// - InvalidTradeStatus: If trade is not in disputable state
-    pub fn raise_dispute(env: Env, trade_id: u64, caller: Address) -> Result<(), Error> {
-        // Verify the caller has signed this transaction
-        caller.require_auth();
-
-        // Retrieve and validate the trade
-        let mut trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
-        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+    /// The offer the trade would be opened against
+    fn _check_new_trade(env: &Env, buyer: &Address, offer_id: u64) -> Result<Offer, Error> {
+        // Retrieve the offer details to validate the trade
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        Self::_require_not_frozen(env, &DataKey::FrozenOffers, offer_id)?;
+        
+        Self::_require_not_suspended(env, buyer)?;
 
-        // Get offer details to validate caller authorization
-        let offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer = offers.get(trade.offer_id).ok_or(Error::OfferNotFound)?;
+        // Business rule: Prevent self-trading to avoid manipulation
+        // Users should not be able to trade with their own offers
+        if *buyer == offer.seller {
+            return Err(Error::Unauthorized);
+        }
 
-        // Security check: Only trade participants can raise disputes
-        if caller != trade.buyer && caller != offer.seller {
+        // Direct escrows can only be traded by the buyer they were created for
+        if offer.counterparty.as_ref().is_some_and(|counterparty| counterparty != buyer) {
             return Err(Error::Unauthorized);
         }
+        Self::_check_buyer_requirements(env, buyer, offer_id)?;
+        Self::_check_buyer_cap(env, buyer, offer_id, offer.usdc_amount)?;
+        if env.ledger().timestamp() < Self::_cooldown_until(env, buyer) {
+            return Err(Error::CooldownActive);
+        }
 
-        // Business rule: Only initiated or payment-confirmed trades can be disputed
-        // Completed and cancelled trades cannot be disputed
-        if trade.status != TradeStatus::Initiated && trade.status != TradeStatus::PaymentConfirmed {
-            return Err(Error::InvalidTradeStatus);
+        // Efficient validation: Check if offer is still active using the active_offers mapping
+        // This is much more gas-efficient than iterating through all offers
+        if !Self::_is_active_offer(env, &offer.seller, offer_id) {
+            return Err(Error::OfferNotFound);
         }
 
-        // Update trade status to disputed
-        trade.status = TradeStatus::Disputed;
-        trades.set(trade_id, trade);
-        env.storage().instance().set(&TRADES_KEY, &trades);
+        // An offer past its TTL is waiting to be expired, so it can't be traded any more
+        if Self::get_offer_expiry(env.clone(), offer_id).is_some_and(|expires_at| env.ledger().timestamp() >= expires_at) {
+            return Err(Error::OfferExpired);
+        }
 
-        // Emit dispute event for admin notification and transparency
-        env.events().publish((DISPUTE_RAISED, caller.clone()), (trade_id,));
+        // Only one trade can be active per offer to maintain order
+        // The reservation replaces a scan over every trade
+        if offer.reserved_by.is_some() {
+            return Err(Error::TradeAlreadyInitiated);
+        }
+        Self::_check_daily_limit(env, buyer, offer.usdc_amount)?;
+        Self::_check_open_trade_ceiling(env)?;
+        Ok(offer)
+    }
 
+    /// Internal helper enforcing the safety valve on open trades: a global ceiling
+    /// bounds storage growth during abuse.
+    /// 
+    /// # Errors
+    /// - TradeLimitReached: If the ceiling is set and already reached
+    fn _check_open_trade_ceiling(env: &Env) -> Result<(), Error> {
+        let max_open_trades = Self::get_max_open_trades(env.clone());
+        if max_open_trades > 0 && Self::get_open_trade_count(env.clone()) >= max_open_trades {
+            return Err(Error::TradeLimitReached);
+        }
         Ok(())
     }
 
-    /// Resolves a disputed trade with admin intervention.
-    /// Only the admin can resolve disputes by choosing to release USDC to buyer or refund to seller.
+    /// Internal helper that opens a trade once the buyer's consent is established.
+    /// Shared by direct initiation and accepted price proposals.
     /// 
-    /// # Business Logic
-    /// - Admin reviews dispute details off-chain
-    /// - Admin decides whether buyer or seller is correct
-    /// - USDC is transferred based on admin's resolution decision
-    /// - Fees are still collected on successful trades (release to buyer)
-    /// - No fees on refunds to seller
+    /// # Arguments
+    /// * `buyer` - The buyer (already authorized)
+    /// * `offer_id` - The ID of the offer to trade against
+    /// * `receive_to` - Optional address for the USDC payout
+    /// * `negotiated_kes` - Negotiated KES amount overriding the offer's, if any
+    /// * `operator` - Optional integrating wallet earning an operator fee (already validated)
+    /// * `operator_fee_bps` - Operator fee in basis points
+    /// * `prepaid_deposit` - Initiation deposit already collected with a proposal (itself None if
+    ///   none was configured then); None collects the deposit from the signing buyer now
+    fn _open_trade(
+        env: Env,
+        buyer: Address,
+        offer_id: u64,
+        receive_to: Option<Address>,
+        negotiated_kes: Option<i128>,
+        operator: Option<Address>,
+        operator_fee_bps: u32,
+        prepaid_deposit: Option<Option<(Address, i128)>>,
+    ) -> Result<u64, Error> {
+        let offer = Self::_check_new_trade(&env, &buyer, offer_id)?;
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+
+        // Compliance: count the trade against the buyer's daily cap
+        Self::_consume_daily_limit(&env, &buyer, offer.usdc_amount)?;
+
+        // Generate unique trade ID for tracking
+        let trade_id = Self::_next_id(&env, &DataKey::NextTradeId, &buyer, &trades);
+
+        // Create trade record with initial state
+        // Trade starts in "Initiated" status, waiting for payment confirmations
+        let mut trade = Trade {
+            offer_id,
+            buyer: buyer.clone(),
+            seller: offer.seller.clone(),         // Snapshot offer terms so the trade is self-contained
+            usdc_amount: offer.usdc_amount,
+            kes_amount: negotiated_kes.unwrap_or(offer.kes_amount),
+            start_time: env.ledger().timestamp(), // Used for expiration checking
+            status: TradeStatus::Initiated,
+            buyer_confirmed_payment: false,       // Buyer hasn't confirmed sending KES yet
+            seller_confirmed_payment: false,      // Seller hasn't confirmed receiving KES yet
+            terms_hash: offer.terms_hash.clone(), // Freeze the agreed terms onto the trade
+            buyer_confirmed_at: None,
+            seller_confirmed_at: None,
+            completed_at: None,
+            cancelled_at: None,
+            settlement: offer.settlement.clone(),
+            attestation: None,
+            attested_at: None,
+            buyer_receipt: None,
+            tranches: Vec::new(&env),
+            last_tranche_at: None,
+            receive_to,
+            refund_to: offer.refund_to.clone(),
+            operator,
+            operator_fee_bps,
+            external_ref: None,
+        };
+
+        // On-chain settlement: the buyer escrows the counter asset (cKES or a swap asset)
+        // now, so both legs are held by the contract and there is nothing left to confirm
+        let counter_token = Self::_counter_token(&env, &trade.settlement)?;
+        let settle_now = counter_token.is_some();
+        if let Some(counter_token_id) = counter_token {
+            let counter_client = token::Client::new(&env, &counter_token_id);
+            match counter_client.try_transfer(&buyer, &env.current_contract_address(), &trade.kes_amount) {
+                Ok(Ok(())) => {},
+                Err(Ok(err)) if err == soroban_sdk::Error::from_contract_error(TOKEN_BALANCE_ERROR) => {
+                    log!(&env, "Insufficient counter asset balance for {}", trade.kes_amount);
+                    return Err(Error::InsufficientBalance);
+                }
+                _ => {
+                    log!(&env, "Counter asset transfer failed for amount: {}", trade.kes_amount);
+                    return Err(Error::TokenTransferFailed);
+                }
+            }
+
+            let now = env.ledger().timestamp();
+            trade.buyer_confirmed_payment = true;
+            trade.seller_confirmed_payment = true;
+            trade.buyer_confirmed_at = Some(now);
+            trade.seller_confirmed_at = Some(now);
+            transition(&env, &mut trade, TradeStatus::PaymentConfirmed)?;
+        } else {
+            // The buyer funds the cleanup bounty up front, refunded unless the trade expires.
+            // Accepted proposals carry the deposit the buyer posted when proposing, since
+            // the buyer doesn't sign the seller's acceptance
+            let deposit = match prepaid_deposit {
+                Some(prepaid_deposit) => prepaid_deposit,
+                None => Self::_collect_initiation_deposit(&env, &buyer)?,
+            };
+            if let Some(deposit) = deposit {
+                let mut deposits: Map<u64, (Address, i128)> = env.storage().instance().get(&DataKey::TradeDeposits)
+                    .unwrap_or(Map::new(&env));
+                deposits.set(trade_id, deposit);
+                env.storage().instance().set(&DataKey::TradeDeposits, &deposits);
+            }
+        }
+
+        // Store the trade and update counters
+        trades.set(trade_id, trade.clone());
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        // Reserve the offer so other buyers no longer see it as available
+        Self::_set_reservation(&env, offer_id, Some(trade_id));
+        Self::_adjust_counter(&env, &DataKey::OpenTrades, 1);
+
+        // Emit event for notification and tracking
+        // Both counterparties and the agreed terms, so feeds don't need to dereference the offer
+        Self::_publish_to_parties(
+            &env,
+            TRADE_INITIATED,
+            &buyer,
+            &trade.seller,
+            (trade_id, offer_id, trade.usdc_amount, trade.kes_amount),
+        );
+
+        // Disclose the seller's payment details now that a buyer is committed
+        let payment_details: Map<u64, Bytes> = env.storage().instance().get(&DataKey::PaymentDetails)
+            .unwrap_or(Map::new(&env));
+        if let Some(details) = payment_details.get(offer_id) {
+            Self::_publish_to(&env, PAYMENT_DETAILS, &buyer, (trade_id, details));
+        }
+
+        // Atomically swap both legs for on-chain settlement
+        if settle_now {
+            Self::release_usdc(env.clone(), trade_id)?;
+        }
+
+        Ok(trade_id)
+    }
+
+    /// Internal helper to persist a confirmation and release USDC once both parties confirmed.
+    /// Shared by every path that records a payment confirmation.
     /// 
-    /// # Admin Authority
-    /// - Only admin can resolve disputes
-    /// - Admin decisions are final and irreversible
-    /// - Admin should have off-chain verification process
+    /// # Arguments
+    /// * `trades` - The trades map the trade was read from
+    /// * `trade_id` - The ID of the confirmed trade
+    /// * `trade` - The trade with updated confirmation flags
+    fn _store_and_settle(env: &Env, mut trades: Map<u64, Trade>, trade_id: u64, mut trade: Trade) -> Result<(), Error> {
+        // Automatic execution: If both parties have confirmed, complete the trade
+        if trade.buyer_confirmed_payment && trade.seller_confirmed_payment {
+            transition(env, &mut trade, TradeStatus::PaymentConfirmed)?;
+
+            // BUG FIX: Persist state change before cross-contract call
+            // This ensures release_usdc reads the correct trade status
+            trades.set(trade_id, trade.clone());
+            env.storage().instance().set(&DataKey::Trades, &trades);
+
+            // A buyer's first trade waits out the cooling-off period, during which
+            // the seller can still dispute a reversed payment
+            let hold = Self::get_first_trade_hold(env.clone());
+            if hold > 0 && Self::get_reputation(env.clone(), trade.buyer.clone()).completed_trades == 0 {
+                let release_at = env.ledger().timestamp() + hold;
+                Self::_publish_to_parties(env, RELEASE_HELD, &trade.buyer, &trade.seller, (trade_id, release_at));
+                return Ok(());
+            }
+
+            // Return early to prevent overwriting the 'Completed' status
+            return Self::release_usdc(env.clone(), trade_id);
+        }
+
+        // Persist the updated trade state if the trade was not completed
+        trades.set(trade_id, trade);
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        Ok(())
+    }
+
+    /// Internal function to release escrowed USDC to the buyer upon trade completion.
+    /// This is the core value transfer that completes a successful trade.
+    /// 
+    /// # Business Logic
+    /// 1. Validates trade is ready for USDC release
+    /// 2. Calculates and deducts trading fees
+    /// 3. Transfers USDC to buyer (amount minus fees)
+    /// 4. Transfers fees to fee collector
+    /// 5. Updates trade status and removes offer from active list
+    /// 6. Emits completion event
+    /// 
+    /// # Fee Structure
+    /// - Fees are calculated as basis points of trade amount
+    /// - Fees are sent to designated fee collector address
+    /// - Buyer receives trade amount minus fees
+    /// - Fee failures don't block trade completion
+    /// 
+    /// # Reentrancy
+    /// The in-progress guard is held for the duration of the token calls, so a token
+    /// contract re-entering the marketplace is rejected by every guarded entry point.
     /// 
     /// # Arguments
-    /// * `trade_id` - The ID of the disputed trade to resolve
-    /// * `resolution` - The admin's decision (ReleaseToBuyer or RefundToSeller)
+    /// * `trade_id` - The ID of the trade to complete
     /// 
     /// # Returns
-    /// Result indicating success or failure of dispute resolution
+    /// Result indicating success or failure of USDC release
+    fn release_usdc(env: Env, trade_id: u64) -> Result<(), Error> {
+        Self::_set_executing(&env, true);
+        let result = Self::_release_usdc(env.clone(), trade_id);
+        Self::_set_executing(&env, false);
+        result
+    }
+
+    /// Internal body of `release_usdc`, run while the in-progress guard is held
+    fn _release_usdc(env: Env, trade_id: u64) -> Result<(), Error> {
+        // Retrieve and validate trade state
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        // Security check: Only release USDC for properly confirmed trades
+        if trade.status != TradeStatus::PaymentConfirmed {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+
+        // Calculate trading fee based on configured rate or fee curve
+        let fee_rate = Self::_fee_rate_for(&env, trade.usdc_amount);
+        let fee_amount = Self::_calculate_fee(trade.usdc_amount, fee_rate);
+        let operator_fee = Self::_calculate_fee(trade.usdc_amount, trade.operator_fee_bps);
+
+        // The seller's share of the fee comes out of the offer's fee reserve
+        let fee_reserve = Self::_offer_fee_reserve(&env, trade.offer_id, trade.usdc_amount);
+        let seller_fee = Self::_seller_fee_share(Self::_fee_payer(&env, trade.offer_id), fee_amount);
+        let buyer_fee = fee_amount - seller_fee;
+        
+        // ✅ SECURITY FIX: Prevent fee calculation underflow
+        if buyer_fee + operator_fee >= trade.usdc_amount {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let amount_to_buyer = trade.usdc_amount - buyer_fee - operator_fee;
+        
+        // CRITICAL SECURITY FIX: Update state BEFORE transfers to prevent reentrancy
+        // Following checks-effects-interactions pattern
+        
+        // Update trade status to completed BEFORE transfers
+        let confirmed = trade.clone();
+        transition(&env, &mut trade, TradeStatus::Completed)?;
+        trades.set(trade_id, trade.clone());
+        Self::_adjust_counter(&env, &DataKey::OpenTrades, -1);
+
+        // Remove offer from active offers BEFORE transfers
+        Self::_remove_active_offer(&env, &trade.seller, trade.offer_id, trade.usdc_amount);
+        Self::_set_offer_status(&env, trade.offer_id, OfferStatus::Filled);
+
+        // Persist all state changes BEFORE transfers
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        // Credit loyalty points and reputation, and feed the reference rate with the completed volume
+        Self::_award_points(&env, &trade);
+        Self::_record_reputation(&env, &trade);
+        Self::_record_volume(&env, &trade);
+        Self::_record_buyer_fill(&env, &trade);
+        Self::_clear_walkaways(&env, &trade.buyer);
+
+        // Emit completion event BEFORE transfers for consistency
+        Self::_publish_to_parties(
+            &env,
+            TRADE_COMPLETED,
+            &trade.buyer,
+            &trade.seller,
+            (trade_id, trade.offer_id, trade.usdc_amount, trade.kes_amount),
+        );
+
+        // Compliance reporting: flag trades at or above the large-trade threshold
+        let large_trade_threshold: i128 = env.storage().persistent().get(&ConfigKey::LargeTradeThreshold).unwrap_or(0);
+        if large_trade_threshold > 0 && trade.usdc_amount >= large_trade_threshold {
+            env.events().publish(
+                (LARGE_TRADE,),
+                (trade_id, trade.usdc_amount, trade.buyer.clone(), trade.seller.clone()),
+            );
+        }
+
+        // Now perform the external calls (transfers)
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        
+        // Primary transfer: Send USDC to buyer (minus fees)
+        // This is the main value transfer that completes the trade
+        // An undeliverable payout is parked for the buyer to withdraw later
+        if Self::_ensure_liquid(&env, &usdc_client, trade.usdc_amount + fee_reserve).is_err() {
+            log!(&env, "Failed to transfer {} to buyer", amount_to_buyer);
+            // CRITICAL: Since we already updated state, we need to revert on failure
+            // Restore the confirmed trade (a rollback, not a status transition)
+            trades.set(trade_id, confirmed);
+            env.storage().instance().set(&DataKey::Trades, &trades);
+            
+            // Revert the active offers and counters
+            Self::_add_active_offer(&env, &trade.seller, trade.offer_id, trade.usdc_amount);
+            Self::_adjust_counter(&env, &DataKey::OpenTrades, 1);
+            
+            return Err(Error::TokenTransferFailed);
+        }
+        Self::_pay_or_park(&env, &usdc_client, trade_id, &Self::_payout_address(&trade), amount_to_buyer);
+        if fee_reserve > seller_fee {
+            Self::_pay_or_park(&env, &usdc_client, trade_id, &Self::_refund_address(&trade), fee_reserve - seller_fee);
+        }
+        
+        // On-chain settlement: pay the escrowed counter leg to the seller
+        // Any failure here reverts the whole invocation, keeping the swap atomic
+        if let Some(counter_token_id) = Self::_counter_token(&env, &trade.settlement)? {
+            let counter_client = token::Client::new(&env, &counter_token_id);
+            if counter_client.try_transfer(&env.current_contract_address(), &trade.seller, &trade.kes_amount).is_err() {
+                log!(&env, "Failed to transfer {} of the counter asset to seller", trade.kes_amount);
+                return Err(Error::TokenTransferFailed);
+            }
+        }
+
+        // Secondary transfer: Send fees to fee collector and insurance fund
+        // Fee transfer failure doesn't block trade completion
+        // The trader's experience is more important than fee collection
+        Self::_distribute_fee(&env, &usdc_client, fee_amount);
+        if let Some(operator) = &trade.operator {
+            Self::_pay_fee(&env, &usdc_client, operator, operator_fee);
+        }
+
+        // The buyer saw the trade through, so their initiation deposit comes back
+        Self::_pay_initiation_deposit(&env, trade_id, &trade.buyer)?;
+
+        // Proposals on the filled offer can't be accepted anymore
+        Self::_clear_proposals(&env, trade.offer_id);
+
+        // Standing offers go straight back on the book
+        Self::_relist_offer(&env, &usdc_client, trade.offer_id);
+
+        Ok(())
+    }
+
+    /// Internal helper to get when a trade reached its final state, if it has
+    fn _finalized_at(trade: &Trade) -> Option<u64> {
+        match trade.status {
+            TradeStatus::Completed => trade.completed_at,
+            TradeStatus::Cancelled => trade.cancelled_at,
+            _ => None,
+        }
+    }
+
+    // ================================================================================================
+    // DISPUTE RESOLUTION SYSTEM
+    // ================================================================================================
+    // Note: Dispute resolution functions should be implemented here
+    // For now, disputes must be handled off-chain by contacting the admin
+    
+    /// Internal helper to record a dispute on a trade that was just moved to Disputed,
+    /// queue it for arbitration and notify the parties and the assigned arbitrator
+    fn _open_dispute(env: &Env, trade_id: u64, trade: &Trade, raised_by: &Address, reason: String, evidence: Vec<BytesN<32>>) {
+        // Assign a panel arbitrator in turn, so arbitrators can't pick their cases
+        let arbitrator = Self::_assign_arbitrator(env);
+
+        // Record the dispute details so the resolution can be audited later
+        let mut disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap();
+        disputes.set(trade_id, Dispute {
+            trade_id,
+            raised_by: raised_by.clone(),
+            raised_at: env.ledger().timestamp(),
+            reason,
+            evidence,
+            arbitrator: arbitrator.clone(),
+            evidence_until: env.ledger().timestamp() + Self::get_evidence_window(env.clone()),
+            evidence_complete: Vec::new(env),
+            resolved_by: None,
+            resolution: DisputeOutcome::Pending,
+        });
+        env.storage().instance().set(&DataKey::Disputes, &disputes);
+
+        // Append to the arbitration queue; appending keeps the queue ordered by dispute age
+        let mut queue: Vec<u64> = env.storage().instance().get(&DataKey::DisputeQueue).unwrap();
+        queue.push_back(trade_id);
+        env.storage().instance().set(&DataKey::DisputeQueue, &queue);
+
+        // Emit dispute event for admin notification and transparency
+        Self::_publish_to_parties(env, DISPUTE_RAISED, &trade.buyer, &trade.seller, (trade_id, raised_by.clone()));
+
+        if let Some(arbitrator) = &arbitrator {
+            Self::_publish_to(env, DISPUTE_ASSIGNED, arbitrator, trade_id);
+        }
+
+        // Forward the case when resolution is delegated to an arbitration contract
+        if let Some(arbitrator) = Self::get_arbitrator(env.clone()) {
+            ArbitrationClient::new(env, &arbitrator).open_case(&trade_id, raised_by, &trade.usdc_amount);
+        }
+    }
+
+    /// Internal helper to count a panel arbitrator's resolution and accrue their fee.
+    /// The fee is moved out of the insurance fund, paying what the fund holds if it runs short.
+    fn _accrue_arbitrator_fee(env: &Env, arbitrator: &Address) {
+        let fund: i128 = env.storage().persistent().get(&ConfigKey::InsuranceFund).unwrap_or(0);
+        let fee = Self::get_arbitrator_fee(env.clone()).min(fund);
+        if fee > 0 {
+            env.storage().persistent().set(&ConfigKey::InsuranceFund, &(fund - fee));
+        }
+
+        let mut arbitrator_fees: Map<Address, (u32, i128)> = env.storage().instance().get(&DataKey::ArbitratorFees)
+            .unwrap_or(Map::new(env));
+        let (resolved, accrued) = arbitrator_fees.get(arbitrator.clone()).unwrap_or((0, 0));
+        arbitrator_fees.set(arbitrator.clone(), (resolved + 1, accrued + fee));
+        env.storage().instance().set(&DataKey::ArbitratorFees, &arbitrator_fees);
+    }
+
+    /// Internal helper to pick the next panel arbitrator in round-robin order
     /// 
-    /// # Errors
-    /// - Unauthorized: If caller is not the admin
-    /// - TradeNotFound: If trade doesn't exist
-    /// - InvalidTradeStatus: If trade is not in disputed state
-    /// - TokenTransferFailed: If USDC transfer fails
-    pub fn resolve_dispute(env: Env, trade_id: u64, resolution: DisputeResolution) -> Result<(), Error> {
-        // Verify admin authorization - only admin can resolve disputes
-        Self::_require_admin(&env)?;
+    /// # Returns
+    /// The assigned arbitrator, or None if no panel is registered
+    fn _assign_arbitrator(env: &Env) -> Option<Address> {
+        let panel = Self::get_arbitrator_panel(env.clone());
+        if panel.is_empty() {
+            return None;
+        }
+        let cursor: u32 = env.storage().instance().get(&DataKey::NextArbitrator).unwrap_or(0);
+        env.storage().instance().set(&DataKey::NextArbitrator, &cursor.wrapping_add(1));
+        panel.get(cursor % panel.len())
+    }
+
+    /// Internal helper to reject admin resolution while an arbitration contract holds that authority
+    fn _require_not_delegated(env: &Env) -> Result<(), Error> {
+        if env.storage().persistent().has(&ConfigKey::Arbitrator) {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Internal helper that applies a dispute resolution once authorization is established.
+    /// Shared by the single-admin, multisig and arbitration paths.
+    fn _resolve_dispute(env: Env, trade_id: u64, resolution: DisputeResolution, resolver: Address) -> Result<(), Error> {
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
 
         // Retrieve and validate the trade
-        let mut trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
         let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
 
         // Security check: Only disputed trades can be resolved
         if trade.status != TradeStatus::Disputed {
             return Err(Error::InvalidTradeStatus);
         }
 
-        // Get offer details for transfer amounts and addresses
-        let offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
-        let offer = offers.get(trade.offer_id).ok_or(Error::OfferNotFound)?;
+        // No ruling before both sides had the chance to present their evidence
+        let disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap();
+        if let Some(dispute) = disputes.get(trade_id) {
+            let both_complete = dispute.evidence_complete.contains(&trade.buyer) && dispute.evidence_complete.contains(&trade.seller);
+            if env.ledger().timestamp() < dispute.evidence_until && !both_complete {
+                return Err(Error::GracePeriodActive);
+            }
+        }
 
         // Setup USDC client for resolution transfers
-        let usdc_token_id: Address = env.storage().persistent().get(&USDC_TOKEN_KEY).unwrap();
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
         let usdc_client = token::Client::new(&env, &usdc_token_id);
 
-        // Execute admin's resolution decision
+        // Execute admin's resolution decision, holding the guard for the token calls
+        Self::_set_executing(&env, true);
+        let settled = Self::_settle_resolution(&env, &usdc_client, trade_id, &mut trade, &resolution);
+        Self::_set_executing(&env, false);
+        settled?;
+
+        // Update trade with resolution outcome
+        trades.set(trade_id, trade.clone());
+        Self::_adjust_counter(&env, &DataKey::OpenTrades, -1);
+
+        // Clean up: Remove offer from active offers since dispute is resolved
+        Self::_remove_active_offer(&env, &trade.seller, trade.offer_id, trade.usdc_amount);
+        let offer_status = match resolution {
+            DisputeResolution::ReleaseToBuyer => OfferStatus::Filled,
+            DisputeResolution::RefundToSeller => OfferStatus::Cancelled,
+        };
+        Self::_set_offer_status(&env, trade.offer_id, offer_status);
+        // Proposals on an offer leaving the book can't be accepted, so their deposits go back
+        Self::_set_executing(&env, true);
+        Self::_clear_proposals(&env, trade.offer_id);
+        Self::_set_executing(&env, false);
+
+        // The party ruled against pays for it in reputation, which feeds buyer requirements
+        let loser = match resolution {
+            DisputeResolution::ReleaseToBuyer => &trade.seller,
+            DisputeResolution::RefundToSeller => &trade.buyer,
+        };
+        Self::_record_dispute_loss(&env, loser);
+
+        // Record the outcome on the dispute for the audit trail
+        let mut disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap();
+        if let Some(mut dispute) = disputes.get(trade_id) {
+            dispute.resolved_by = Some(resolver);
+            dispute.resolution = DisputeOutcome::Resolved(resolution.clone());
+            disputes.set(trade_id, dispute);
+        }
+
+        // Remove the trade from the arbitration queue
+        let mut queue: Vec<u64> = env.storage().instance().get(&DataKey::DisputeQueue).unwrap();
+        if let Some(index) = queue.first_index_of(trade_id) {
+            queue.remove(index);
+        }
+
+        // Persist all changes
+        env.storage().instance().set(&DataKey::Trades, &trades);
+        env.storage().instance().set(&DataKey::Disputes, &disputes);
+        env.storage().instance().set(&DataKey::DisputeQueue, &queue);
+
+        // Emit resolution event for transparency and audit trail
+        Self::_publish_to_parties(&env, DISPUTE_RESOLVED, &trade.buyer, &trade.seller, (trade_id, resolution));
+
+        Ok(())
+    }
+
+    /// Internal helper that performs the token transfers for a dispute resolution and
+    /// records the outcome on the trade. Runs while the in-progress guard is held.
+    fn _settle_resolution(
+        env: &Env,
+        usdc_client: &token::Client,
+        trade_id: u64,
+        trade: &mut Trade,
+        resolution: &DisputeResolution,
+    ) -> Result<(), Error> {
+        let fee_reserve = Self::_offer_fee_reserve(env, trade.offer_id, trade.usdc_amount);
+        Self::_ensure_liquid(env, usdc_client, trade.usdc_amount + fee_reserve)?;
         match resolution {
             DisputeResolution::ReleaseToBuyer => {
                 // Admin determined buyer is correct - complete the trade
                 // Calculate and collect fees even for disputed trades
-                let fee_rate: u32 = env.storage().persistent().get(&FEE_RATE_KEY)
-                    .unwrap_or(DEFAULT_FEE_RATE);
-                let fee_amount = Self::_calculate_fee(offer.usdc_amount, fee_rate);
+                let fee_rate = Self::_fee_rate_for(env, trade.usdc_amount);
+                let fee_amount = Self::_calculate_fee(trade.usdc_amount, fee_rate);
+                let operator_fee = Self::_calculate_fee(trade.usdc_amount, trade.operator_fee_bps);
+                let seller_fee = Self::_seller_fee_share(Self::_fee_payer(env, trade.offer_id), fee_amount);
+                let buyer_fee = fee_amount - seller_fee;
                 
                 // ✅ SECURITY FIX: Prevent fee calculation underflow
-                if fee_amount >= offer.usdc_amount {
+                if buyer_fee + operator_fee >= trade.usdc_amount {
                     return Err(Error::InvalidAmount);
                 }
                 
-                let amount_to_buyer = offer.usdc_amount - fee_amount;
+                let amount_to_buyer = trade.usdc_amount - buyer_fee - operator_fee;
                 
-                // Transfer USDC to buyer (minus fees)
-                match usdc_client.try_transfer(&env.current_contract_address(), &trade.buyer, &amount_to_buyer) {
-                    Ok(_) => {
-                        // Transfer fee to fee collector if applicable
-                        if fee_amount > 0 {
-                            let fee_collector: Address = env.storage().persistent().get(&FEE_COLLECTOR_KEY).unwrap();
-                            let _ = usdc_client.try_transfer(&env.current_contract_address(), &fee_collector, &fee_amount);
-                        }
-                        trade.status = TradeStatus::Completed;
-                    },
-                    Err(_) => {
-                        log!(&env, "Failed to transfer {} to buyer in dispute resolution", amount_to_buyer);
-                        return Err(Error::TokenTransferFailed);
-                    }
+                // Transfer USDC to buyer (minus fees), parking it if undeliverable
+                Self::_pay_or_park(env, usdc_client, trade_id, &Self::_payout_address(trade), amount_to_buyer);
+                if fee_reserve > seller_fee {
+                    Self::_pay_or_park(env, usdc_client, trade_id, &Self::_refund_address(trade), fee_reserve - seller_fee);
                 }
+
+                // Transfer fee to fee collector and insurance fund if applicable
+                Self::_distribute_fee(env, usdc_client, fee_amount);
+                if let Some(operator) = &trade.operator {
+                    Self::_pay_fee(env, usdc_client, operator, operator_fee);
+                }
+                transition(env, trade, TradeStatus::Completed)?;
+                Self::_record_buyer_fill(env, trade);
+                Self::_clear_walkaways(env, &trade.buyer);
             }
             DisputeResolution::RefundToSeller => {
-                // Admin determined seller is correct - refund the full amount (no fees)
-                match usdc_client.try_transfer(&env.current_contract_address(), &offer.seller, &offer.usdc_amount) {
-                    Ok(_) => {
-                        trade.status = TradeStatus::Cancelled;
-                    },
+                // Admin determined seller is correct - refund the full amount and fee reserve (no fees)
+                match usdc_client.try_transfer(&env.current_contract_address(), &Self::_refund_address(trade), &(trade.usdc_amount + fee_reserve)) {
+                    Ok(_) => transition(env, trade, TradeStatus::Cancelled)?,
                     Err(_) => {
-                        log!(&env, "Failed to refund {} to seller in dispute resolution", offer.usdc_amount);
+                        log!(env, "Failed to refund {} to seller in dispute resolution", trade.usdc_amount);
                         return Err(Error::TokenTransferFailed);
                     }
                 }
             }
         }
 
-        // Update trade with resolution outcome
-        trades.set(trade_id, trade.clone());
+        // Disputed trades are cleaned up by the resolver, so the deposit goes back to the buyer
+        Self::_pay_initiation_deposit(env, trade_id, &trade.buyer)
+    }
 
-        // Clean up: Remove offer from active offers since dispute is resolved
-        let mut active_offers: Map<Address, u64> = env.storage().instance().get(&ACTIVE_OFFERS).unwrap();
-        active_offers.remove(offer.seller.clone());
+    // ================================================================================================
+    // RELAYED FUNCTIONS
+    // ================================================================================================
+    // Gasless entry points: an allowlisted relayer submits and pays for the transaction, while
+    // the user only signs an authorization entry for the underlying call
 
-        // Persist all changes
-        env.storage().instance().set(&TRADES_KEY, &trades);
-        env.storage().instance().set(&ACTIVE_OFFERS, &active_offers);
+    /// Internal helper to verify the submitter is an allowlisted relayer
+    fn _require_relayer(env: &Env, relayer: &Address) -> Result<(), Error> {
+        relayer.require_auth();
+        let relayers: Vec<Address> = env.storage().persistent().get(&ConfigKey::Relayers).unwrap_or(Vec::new(env));
+        if !relayers.contains(relayer) {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+
+    // ================================================================================================
+    // ADMINISTRATIVE FUNCTIONS
+    // ================================================================================================
+    // These functions allow the admin to configure and manage the marketplace
+    
+    /// Internal helper that swaps the contract Wasm once authorization is established.
+    /// The previous hash isn't readable on-chain, so only the new one is logged.
+    fn _upgrade(env: &Env, actor: &Address, new_wasm_hash: BytesN<32>) {
+        Self::_log_admin_action(env, actor, symbol_short!("upgrade"), Bytes::new(env), Self::_log_value(env, &new_wasm_hash));
+        env.deployer().update_current_contract_wasm(new_wasm_hash);
+    }
+
+    /// Internal helper that transfers admin control once authorization is established.
+    fn _update_admin(env: &Env, actor: &Address, new_admin: Address) -> Result<(), Error> {
+        // Require new admin to sign transaction - prevents accidental transfers
+        // (an actor handing control to itself has already consented)
+        if new_admin != *actor {
+            new_admin.require_auth();
+        }
+        
+        // SECURITY FIX: Validate new admin address
+        Self::_validate_address(&new_admin)?;
+        
+        // Update admin address in persistent storage
+        Self::_set_config(env, actor, symbol_short!("admin"), &ConfigKey::Admin, &new_admin);
+        
+        // Emit event for security audit trail
+        Self::_publish_to(env, symbol_short!("adm_upd"), &new_admin, &new_admin);
+        
+        Ok(())
+    }
+    
+    // ================================================================================================
+    // QUERY FUNCTIONS (GETTERS)
+    // ================================================================================================
+    // These functions provide read-only access to contract state for external callers
+    
+    /// Internal helper to compute a KES/USDC pair's implied rate in RATE_SCALE fixed point.
+    /// KES amounts too large to scale have no representable rate.
+    fn _implied_rate(kes_amount: i128, usdc_amount: i128) -> Result<i128, Error> {
+        kes_amount.checked_mul(RATE_SCALE).map(|scaled| scaled / usdc_amount).ok_or(Error::RateOutOfBounds)
+    }
+
+    /// Internal helper to compute an offer's implied rate in RATE_SCALE fixed point
+    fn _offer_rate(offer: &Offer) -> Result<i128, Error> {
+        Self::_implied_rate(offer.kes_amount, offer.usdc_amount)
+    }
+}
+
+// The public entry points implement the interface crate's trait, so the contract and the
+// client other contracts build against can't drift apart without a compile error
+#[contractimpl]
+impl P2PMarketplaceTrait for P2PMarketplaceContract {
+    // ========== Offers and trades ==========
+
+    /// Creates a new offer to sell USDC for KES with escrow protection.
+    /// The seller authorizes the escrow transfer as part of this invocation; no prior
+    /// `approve()` transaction is needed.
+    /// 
+    /// # Business Flow
+    /// 1. Validates seller authorization and input parameters
+    /// 2. Checks trading limits and seller doesn't have active offer
+    /// 3. Transfers USDC from seller to contract (escrow), measuring the amount received
+    /// 4. Creates offer record for the received amount and updates active offers mapping
+    /// 5. Emits event for transparency
+    /// 
+    /// # Security Checks
+    /// - Requires seller authorization (covers the token transfer sub-invocation)
+    /// - Validates amount ranges to prevent spam/large trades
+    /// - Maps token transfer failures to precise contract errors
+    /// - Enforces one active offer per seller rule
+    /// 
+    /// # Arguments
+    /// * `seller` - The address creating the offer (must sign transaction)
+    /// * `usdc_amount` - Amount of USDC to sell (in the token's smallest unit)
+    /// * `kes_amount` - Amount of KES expected in return (off-chain settlement)
+    /// * `terms_hash` - Optional hash of the off-chain terms of sale, copied onto each trade
+    /// * `settlement` - Whether KES is paid off-chain, in the registered cKES token, or in a swap asset
+    /// * `refund_to` - Optional address for escrow refunds; defaults to the seller
+    /// * `metadata_uri` - Optional IPFS CID or HTTPS URL of richer listing content
+    ///   (seller bio, terms, supported hours), at most 128 bytes
+    /// * `idempotency_key` - Optional client nonce; retrying with the same key returns the
+    ///   offer the first attempt created instead of escrowing again
+    /// 
+    /// # Returns
+    /// The unique ID of the created offer
+    /// 
+    /// # Errors
+    /// - ContractPaused: If trading is temporarily disabled
+    /// - InvalidAmount: If amounts are non-positive or the metadata URI is empty or too long
+    /// - BelowMinimum / AboveMaximum: If the USDC amount is outside trade limits
+    /// - InvalidTokenAddress: If cKES settlement is requested but no cKES token is registered,
+    ///   or the swap asset is the USDC token
+    /// - AlreadyHasActiveOffer: If seller already has an active offer
+    /// - OfferLimitReached: If the active-offer caps are reached
+    /// - Suspended: If seller was suspended for repeatedly losing disputes
+    /// - InsufficientBalance: If seller doesn't hold enough USDC
+    /// - TokenTransferFailed: If USDC transfer to escrow fails
+    fn create_offer(
+        env: Env,
+        seller: Address,
+        usdc_amount: i128,
+        kes_amount: i128,
+        terms_hash: Option<BytesN<32>>,
+        settlement: SettlementMode,
+        refund_to: Option<Address>,
+        metadata_uri: Option<String>,
+        idempotency_key: Option<BytesN<32>>,
+    ) -> Result<u64, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all trading if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+        
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+        
+        // SECURITY FIX: Validate seller address
+        Self::_validate_address(&seller)?;
+        if let Some(refund_to) = &refund_to {
+            Self::_validate_address(refund_to)?;
+        }
+        if let Some(metadata_uri) = &metadata_uri {
+            if metadata_uri.is_empty() || metadata_uri.len() > MAX_METADATA_URI_LEN {
+                return Err(Error::InvalidAmount);
+            }
+        }
+
+        // A retried submission gets the offer its first attempt created, even if it has since
+        // been taken or cancelled; keys are scoped to the seller
+        if let Some(offer_id) = idempotency_key.clone().and_then(|idempotency_key| Self::get_offer_by_key(env.clone(), seller.clone(), idempotency_key)) {
+            return Ok(offer_id);
+        }
+
+        Self::_check_new_offer(&env, &seller, usdc_amount, kes_amount, &settlement)?;
+
+        // Compliance: count the listing against the seller's daily cap
+        Self::_consume_daily_limit(&env, &seller, usdc_amount)?;
+
+        // The seller's require_auth above authorizes the escrow transfer
+        let received = Self::_escrow_usdc(&env, &seller, usdc_amount)?;
+        let escrowed_amount = received.min(usdc_amount);
+        if escrowed_amount != usdc_amount {
+            log!(&env, "Escrowed {} of requested {}", escrowed_amount, usdc_amount);
+        }
+
+        // Create the offer record with all necessary information
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer_id = Self::_next_id(&env, &DataKey::NextOfferId, &seller, &offers);
+
+        let offer = Offer {
+            seller: seller.clone(),
+            usdc_amount: escrowed_amount,
+            kes_amount,
+            terms_hash,
+            settlement,
+            refund_to,
+            metadata_uri: metadata_uri.clone(),
+            reserved_by: None,
+            counterparty: None,
+            status: OfferStatus::Active,
+        };
+
+        // Store the offer and update active offers mapping for efficient lookups
+        offers.set(offer_id, offer);
+        Self::_add_active_offer(&env, &seller, offer_id, escrowed_amount);
+
+        // Persist changes to storage
+        env.storage().instance().set(&DataKey::Offers, &offers);
+        if let Some(idempotency_key) = idempotency_key {
+            let key = DataKey::OfferKey(seller.clone(), idempotency_key.clone());
+            let key_of = DataKey::OfferKeyOf(offer_id);
+            env.storage().persistent().set(&key, &offer_id);
+            env.storage().persistent().set(&key_of, &idempotency_key);
+            Self::_extend_entry(&env, &key);
+            Self::_extend_entry(&env, &key_of);
+        }
+
+        // Emit event for transparency and off-chain indexing
+        // Events allow frontends and analytics to track marketplace activity
+        Self::_publish_to(&env, OFFER_CREATED, &seller, (offer_id, escrowed_amount, kes_amount, metadata_uri));
+
+        Ok(offer_id)
+    }
+
+    /// Runs every check `create_offer` would, without changing state or moving funds.
+    /// 
+    /// # Usage
+    /// - Wallets call this before asking the seller to sign, to show the precise failure reason
+    /// 
+    /// # Arguments
+    /// * `seller` - The address that would create the offer (no signature needed)
+    /// * `usdc_amount` - Amount of USDC to sell
+    /// * `kes_amount` - Amount of KES expected in return
+    /// * `settlement` - The settlement mode the offer would use
+    /// 
+    /// # Errors
+    /// Any error `create_offer` would return before or during the escrow transfer, including
+    /// InsufficientBalance if the seller doesn't hold enough USDC
+    fn simulate_create_offer(
+        env: Env,
+        seller: Address,
+        usdc_amount: i128,
+        kes_amount: i128,
+        settlement: SettlementMode,
+    ) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+        Self::_validate_address(&seller)?;
+        Self::_check_new_offer(&env, &seller, usdc_amount, kes_amount, &settlement)?;
+
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        if token::Client::new(&env, &usdc_token_id).balance(&seller) < usdc_amount {
+            return Err(Error::InsufficientBalance);
+        }
+        Ok(())
+    }
+
+    /// Sets the conditions a buyer must meet to trade against an offer.
+    /// Passing all-zero values and an empty allowlist removes the requirements.
+    /// 
+    /// # Business Logic
+    /// - Checked when a trade is opened; trades already open are unaffected
+    /// - Reputation comes from completed trades and lost disputes, see `get_reputation`
+    /// - Requirements stay with the offer across recurring relists
+    /// 
+    /// # Arguments
+    /// * `seller` - The offer owner (must sign transaction)
+    /// * `offer_id` - The offer to restrict
+    /// * `min_completed_trades` - Minimum completed trades the buyer needs
+    /// * `min_reputation` - Minimum reputation score the buyer needs
+    /// * `allowed_buyers` - Buyers allowed to trade, empty = anyone
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - Unauthorized: If caller is not the offer owner
+    /// - InvalidAmount: If the minimum reputation is negative
+    fn set_buyer_requirements(
+        env: Env,
+        seller: Address,
+        offer_id: u64,
+        min_completed_trades: u32,
+        min_reputation: i128,
+        allowed_buyers: Vec<Address>,
+    ) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        if offer.seller != seller {
+            return Err(Error::Unauthorized);
+        }
+        if !Self::_is_active_offer(&env, &seller, offer_id) {
+            return Err(Error::OfferNotFound);
+        }
+        if min_reputation < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut requirements: Map<u64, BuyerRequirements> = env.storage().instance().get(&DataKey::OfferRequirements)
+            .unwrap_or(Map::new(&env));
+        if min_completed_trades == 0 && min_reputation == 0 && allowed_buyers.is_empty() {
+            requirements.remove(offer_id);
+        } else {
+            requirements.set(offer_id, BuyerRequirements { min_completed_trades, min_reputation, allowed_buyers });
+        }
+        env.storage().instance().set(&DataKey::OfferRequirements, &requirements);
+
+        Ok(())
+    }
+
+    /// Limits how much USDC a single buyer can fill from an offer within a time window.
+    /// Spreads fills of recurring offers across counterparties. Passing a zero cap removes it.
+    /// 
+    /// # Business Logic
+    /// - Checked when a trade is opened; fills are counted when trades complete
+    /// - The window starts with a buyer's first counted fill and restarts once it elapses
+    /// - Changing the cap keeps fills already counted in the current window
+    /// 
+    /// # Arguments
+    /// * `seller` - The offer owner (must sign transaction)
+    /// * `offer_id` - The offer to cap
+    /// * `max_per_buyer` - Maximum USDC a buyer may fill per window, 0 = no cap
+    /// * `window` - Window length in seconds
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - Unauthorized: If caller is not the offer owner
+    /// - InvalidAmount: If the cap is negative, or positive with a zero window
+    fn set_buyer_cap(env: Env, seller: Address, offer_id: u64, max_per_buyer: i128, window: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        if offer.seller != seller {
+            return Err(Error::Unauthorized);
+        }
+        if !Self::_is_active_offer(&env, &seller, offer_id) {
+            return Err(Error::OfferNotFound);
+        }
+        if max_per_buyer < 0 || (max_per_buyer > 0 && window == 0) {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut caps: Map<u64, (i128, u64)> = env.storage().instance().get(&DataKey::BuyerCaps).unwrap_or(Map::new(&env));
+        if max_per_buyer == 0 {
+            caps.remove(offer_id);
+        } else {
+            caps.set(offer_id, (max_per_buyer, window));
+        }
+        env.storage().instance().set(&DataKey::BuyerCaps, &caps);
+
+        Ok(())
+    }
+
+    /// Stores encrypted payment details (e.g. an M-Pesa paybill) on an offer.
+    /// The details are disclosed in an event when a trade is initiated, instead of
+    /// being broadcast with the offer.
+    /// 
+    /// # Business Logic
+    /// - The contract never decrypts the payload; encrypt it to the protocol or buyer key
+    /// - Contract storage is public, so the payload must be encrypted before submission
+    /// - Empty details remove the entry
+    /// 
+    /// # Arguments
+    /// * `seller` - The offer owner (must sign transaction)
+    /// * `offer_id` - The offer the details belong to
+    /// * `details` - Encrypted payload, at most 256 bytes
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - Unauthorized: If caller is not the offer owner
+    /// - InvalidAmount: If the payload is too long
+    fn set_payment_details(env: Env, seller: Address, offer_id: u64, details: Bytes) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        if offer.seller != seller {
+            return Err(Error::Unauthorized);
+        }
+        if !Self::_is_active_offer(&env, &seller, offer_id) {
+            return Err(Error::OfferNotFound);
+        }
+        if details.len() > MAX_PAYMENT_DETAILS_LEN {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut payment_details: Map<u64, Bytes> = env.storage().instance().get(&DataKey::PaymentDetails)
+            .unwrap_or(Map::new(&env));
+        if details.is_empty() {
+            payment_details.remove(offer_id);
+        } else {
+            payment_details.set(offer_id, details);
+        }
+        env.storage().instance().set(&DataKey::PaymentDetails, &payment_details);
+
+        Ok(())
+    }
+
+    /// Chooses who bears the protocol fee when a trade against the offer settles.
+    /// Lets sellers competing on exact face value absorb the fee themselves.
+    /// 
+    /// # Business Logic
+    /// - Seller and Split escrow a fee reserve sized for the instance's fee ceiling
+    /// - Changing the payer escrows or returns the difference in reserve immediately
+    /// - Whatever the settled fee doesn't use is returned to the seller at settlement
+    /// - The whole reserve is returned on cancellation, expiry or a refund to the seller
+    /// - Operator fees are always withheld from the buyer
+    /// 
+    /// # Arguments
+    /// * `seller` - The offer owner (must sign transaction)
+    /// * `offer_id` - The offer to configure
+    /// * `fee_payer` - Who bears the protocol fee
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - Unauthorized: If caller is not the offer owner, or a token call is in progress
+    /// - TradeAlreadyInitiated: If a trade is open against the offer
+    /// - InsufficientBalance: If the seller can't cover a larger reserve
+    /// - TokenTransferFailed: If the reserve can't be escrowed or returned in full
+    fn set_fee_payer(env: Env, seller: Address, offer_id: u64, fee_payer: FeePayer) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+        Self::_require_not_executing(&env)?;
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        if offer.seller != seller {
+            return Err(Error::Unauthorized);
+        }
+        if !Self::_is_active_offer(&env, &seller, offer_id) {
+            return Err(Error::OfferNotFound);
+        }
+
+        // An open trade was priced with the current payer
+        if offer.reserved_by.is_some() {
+            return Err(Error::TradeAlreadyInitiated);
+        }
+
+        // Escrow or return the difference in fee reserve
+        let current = Self::_offer_fee_reserve(&env, offer_id, offer.usdc_amount);
+        let required = Self::_fee_reserve(&env, fee_payer, offer.usdc_amount);
+        if required > current {
+            let shortfall = required - current;
+            if Self::_escrow_usdc(&env, &seller, shortfall)? < shortfall {
+                log!(&env, "Fee reserve short of {}", shortfall);
+                return Err(Error::TokenTransferFailed);
+            }
+        } else if current > required {
+            let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+            let usdc_client = token::Client::new(&env, &usdc_token_id);
+            let refund_to = offer.refund_to.clone().unwrap_or(seller.clone());
+            let excess = current - required;
+            Self::_set_executing(&env, true);
+            let refund = Self::_ensure_liquid(&env, &usdc_client, excess).map(|_| {
+                usdc_client.try_transfer(&env.current_contract_address(), &refund_to, &excess).is_ok()
+            });
+            Self::_set_executing(&env, false);
+            if refund != Ok(true) {
+                log!(&env, "Failed to return {} of fee reserve", excess);
+                return Err(Error::TokenTransferFailed);
+            }
+        }
+
+        let mut fee_payers: Map<u64, FeePayer> = env.storage().instance().get(&DataKey::FeePayers)
+            .unwrap_or(Map::new(&env));
+        if fee_payer == FeePayer::Buyer {
+            fee_payers.remove(offer_id);
+        } else {
+            fee_payers.set(offer_id, fee_payer);
+        }
+        env.storage().instance().set(&DataKey::FeePayers, &fee_payers);
+
+        Ok(())
+    }
+
+    /// Makes an offer recurring: after each completed trade it is re-escrowed from the
+    /// seller's token allowance and listed again, until the total cap is used up or the
+    /// seller cancels.
+    /// 
+    /// # Business Logic
+    /// - The seller must separately `approve` the marketplace on the USDC token, covering at
+    ///   least one relist; the token reports an allowance past its live-until ledger as zero
+    /// - `total_cap` bounds the USDC re-escrowed across all future relists
+    /// - Relisting stops silently if the allowance, balance or daily limit falls short
+    /// - Trades completed through dispute resolution don't relist
+    /// 
+    /// # Arguments
+    /// * `seller` - The offer owner (must sign transaction)
+    /// * `offer_id` - The offer to make recurring
+    /// * `total_cap` - Total USDC that may be re-escrowed, 0 to stop relisting
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - Unauthorized: If caller is not the offer owner
+    /// - InvalidAmount: If the cap is negative
+    /// - InsufficientAllowance: If the allowance is missing, expired or too low for one relist
+    fn set_recurring(env: Env, seller: Address, offer_id: u64, total_cap: i128) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        if offer.seller != seller {
+            return Err(Error::Unauthorized);
+        }
+        if !Self::_is_active_offer(&env, &seller, offer_id) {
+            return Err(Error::OfferNotFound);
+        }
+        if total_cap < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Catch a missing or lapsed approval now, instead of at the first relist
+        if total_cap > 0 {
+            let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+            let allowance = token::Client::new(&env, &usdc_token_id).allowance(&seller, &env.current_contract_address());
+            if allowance < offer.usdc_amount + Self::_offer_fee_reserve(&env, offer_id, offer.usdc_amount) {
+                return Err(Error::InsufficientAllowance);
+            }
+        }
+
+        let mut recurring: Map<u64, i128> = env.storage().instance().get(&DataKey::RecurringOffers).unwrap_or(Map::new(&env));
+        if total_cap == 0 {
+            recurring.remove(offer_id);
+        } else {
+            recurring.set(offer_id, total_cap);
+        }
+        env.storage().instance().set(&DataKey::RecurringOffers, &recurring);
+
+        Ok(())
+    }
+
+    /// Creates several offers at different rates with a single escrow transfer.
+    /// Lets market makers post a ladder of price levels in one transaction.
+    /// 
+    /// # Business Flow
+    /// 1. Validates seller authorization and every price level
+    /// 2. Transfers the sum of all levels from seller to contract (escrow)
+    /// 3. Creates one off-chain settlement offer per level
+    /// 4. Emits an offer event per level
+    /// 
+    /// # Business Logic
+    /// - Exempt from the one-active-offer rule; levels add to any existing offers
+    /// - Each level must individually respect the trade limits
+    /// - The full sum must arrive in escrow; fee-on-transfer shortfalls are rejected
+    /// 
+    /// # Arguments
+    /// * `seller` - The address creating the offers (must sign transaction)
+    /// * `levels` - `(usdc_amount, kes_amount)` pairs, one per offer
+    /// 
+    /// # Returns
+    /// The IDs of the created offers, in the order of `levels`
+    /// 
+    /// # Errors
+    /// - ContractPaused: If trading is temporarily disabled
+    /// - InvalidAmount: If no levels, too many levels, or any level is outside allowed ranges
+    /// - OfferLimitReached: If the ladder would exceed the active-offer caps
+    /// - Suspended: If seller was suspended for repeatedly losing disputes
+    /// - InsufficientBalance: If seller doesn't hold the summed USDC
+    /// - TokenTransferFailed: If the escrow transfer fails or arrives short
+    fn create_offers(env: Env, seller: Address, levels: Vec<(i128, i128)>) -> Result<Vec<u64>, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all trading if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+        
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+        Self::_validate_address(&seller)?;
+        Self::_require_not_suspended(&env, &seller)?;
+
+        if levels.is_empty() || levels.len() > MAX_BATCH_OFFERS {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut total: i128 = 0;
+        for (usdc_amount, kes_amount) in levels.iter() {
+            Self::_validate_offer_terms(&env, usdc_amount, kes_amount, &SettlementMode::OffChain)?;
+            total = total.checked_add(usdc_amount).ok_or(Error::InvalidAmount)?;
+        }
+        Self::_check_offer_caps(&env, &seller, levels.len())?;
+
+        // Compliance: count the whole ladder against the seller's daily cap
+        Self::_consume_daily_limit(&env, &seller, total)?;
+
+        // Single escrow transfer for the whole ladder
+        let received = Self::_escrow_usdc(&env, &seller, total)?;
+        if received < total {
+            log!(&env, "Escrowed {} of requested {}", received, total);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let mut offer_ids = Vec::new(&env);
+
+        for (usdc_amount, kes_amount) in levels.iter() {
+            let offer_id = Self::_next_id(&env, &DataKey::NextOfferId, &seller, &offers);
+            offers.set(offer_id, Offer {
+                seller: seller.clone(),
+                usdc_amount,
+                kes_amount,
+                terms_hash: None,
+                settlement: SettlementMode::OffChain,
+                refund_to: None,
+                metadata_uri: None,
+                reserved_by: None,
+                counterparty: None,
+                status: OfferStatus::Active,
+            });
+            Self::_add_active_offer(&env, &seller, offer_id, usdc_amount);
+            offer_ids.push_back(offer_id);
+
+            Self::_publish_to(&env, OFFER_CREATED, &seller, (offer_id, usdc_amount, kes_amount, None::<String>));
+        }
+
+        // Persist changes to storage
+        env.storage().instance().set(&DataKey::Offers, &offers);
+
+        Ok(offer_ids)
+    }
+
+    /// Creates an offer and a trade in one shot, bound to a named seller and buyer.
+    /// OTC escrow for brokers who arrange deals off-chain and only need the escrow rails,
+    /// and for pre-negotiated deals (e.g. agreed over Telegram): both parties sign the same
+    /// invocation, so there is no window for another buyer to take the offer.
+    /// 
+    /// # Business Flow
+    /// 1. Validates both parties' authorization and the trade terms
+    /// 2. Transfers USDC from seller to contract (escrow)
+    /// 3. Creates an unlisted offer bound to the buyer
+    /// 4. Opens the trade against it, which then follows the normal confirmation flow
+    /// 
+    /// # Business Logic
+    /// - The offer never appears in availability or rate queries
+    /// - Only the named buyer can trade the offer, including after a cancellation
+    /// - Exempt from the one-active-offer rule, like `create_offers`
+    /// - Off-chain settlement only, without payout redirection or operator fees
+    /// 
+    /// # Arguments
+    /// * `seller` - The address escrowing USDC (must sign transaction)
+    /// * `buyer` - The counterparty paying KES (must sign transaction)
+    /// * `usdc_amount` - Amount of USDC to escrow
+    /// * `kes_amount` - Amount of KES agreed off-chain
+    /// 
+    /// # Returns
+    /// The IDs of the created offer and trade
+    /// 
+    /// # Errors
+    /// - ContractPaused: If trading is temporarily disabled
+    /// - Unauthorized: If seller and buyer are the same address
+    /// - InvalidAmount: If amounts are non-positive
+    /// - BelowMinimum / AboveMaximum: If the USDC amount is outside trade limits
+    /// - OfferLimitReached: If the active-offer caps are reached
+    /// - Suspended: If seller was suspended for repeatedly losing disputes
+    /// - InsufficientBalance: If seller doesn't hold enough USDC
+    /// - TokenTransferFailed: If USDC transfer to escrow fails
+    fn create_direct_escrow(
+        env: Env,
+        seller: Address,
+        buyer: Address,
+        usdc_amount: i128,
+        kes_amount: i128,
+    ) -> Result<(u64, u64), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all trading if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Both named parties sign the deal
+        seller.require_auth();
+        buyer.require_auth();
+        Self::_validate_address(&seller)?;
+        Self::_validate_address(&buyer)?;
+        if seller == buyer {
+            return Err(Error::Unauthorized);
+        }
+        Self::_require_not_suspended(&env, &seller)?;
+
+        Self::_validate_offer_terms(&env, usdc_amount, kes_amount, &SettlementMode::OffChain)?;
+        Self::_check_offer_caps(&env, &seller, 1)?;
+
+        // Compliance: count the deal against the seller's daily cap
+        Self::_consume_daily_limit(&env, &seller, usdc_amount)?;
+
+        let received = Self::_escrow_usdc(&env, &seller, usdc_amount)?;
+        let escrowed_amount = received.min(usdc_amount);
+
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer_id = Self::_next_id(&env, &DataKey::NextOfferId, &seller, &offers);
+        offers.set(offer_id, Offer {
+            seller: seller.clone(),
+            usdc_amount: escrowed_amount,
+            kes_amount,
+            terms_hash: None,
+            settlement: SettlementMode::OffChain,
+            refund_to: None,
+            metadata_uri: None,
+            reserved_by: None,
+            counterparty: Some(buyer.clone()),
+            status: OfferStatus::Active,
+        });
+        Self::_add_active_offer(&env, &seller, offer_id, escrowed_amount);
+
+        // Persist the offer before the trade reserves it
+        env.storage().instance().set(&DataKey::Offers, &offers);
+
+        Self::_publish_to(&env, OFFER_CREATED, &seller, (offer_id, escrowed_amount, kes_amount, None::<String>));
+
+        let trade_id = Self::_open_trade(env, buyer, offer_id, None, None, None, 0, None)?;
+
+        Ok((offer_id, trade_id))
+    }
+
+    /// Initiates a trade by a buyer against an existing offer.
+    /// This begins the escrow process where USDC is held while payment confirmation occurs.
+    /// 
+    /// # Business Flow
+    /// 1. Validates buyer authorization and offer existence
+    /// 2. Prevents self-trading and checks offer is still active
+    /// 3. Ensures no existing active trade for the offer
+    /// 4. Creates trade record with initial status
+    /// 5. Emits event to notify participants
+    /// 6. For on-chain settlement offers, escrows the buyer's cKES and swaps both legs immediately
+    /// 
+    /// # Security Features
+    /// - Prevents buyers from trading with themselves
+    /// - Validates offer is still active and available
+    /// - Efficient lookup using active_offers mapping
+    /// - Checks for existing active trades to prevent conflicts
+    /// 
+    /// # Arguments
+    /// * `buyer` - The address initiating the trade (must sign transaction)
+    /// * `offer_id` - The ID of the offer to trade against
+    /// * `receive_to` - Optional address for the USDC payout; defaults to the buyer
+    /// * `operator` - Optional integrating wallet that earns an operator fee
+    /// * `operator_fee_bps` - Operator fee in basis points (0 without an operator)
+    /// * `external_ref` - Optional client reference (e.g. an order ID), unique per trade
+    /// 
+    /// # Returns
+    /// The unique ID of the created trade
+    /// 
+    /// # Errors
+    /// - ContractPaused: If trading is disabled
+    /// - InvalidAmount: If the operator fee exceeds the admin cap or is set without an operator
+    /// - DuplicateReference: If the external reference is already attached to a trade
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - OfferExpired: If the offer has gone without activity for the offer TTL
+    /// - Unauthorized: If buyer tries to trade with themselves
+    /// - RequirementsNotMet: If buyer doesn't meet the requirements set on the offer
+    /// - BuyerCapExceeded: If buyer has already filled the per-buyer cap on this offer for the window
+    /// - CooldownActive: If buyer is cooling down after recent cancellations or expiries
+    /// - Suspended: If buyer was suspended for repeatedly losing disputes
+    /// - TradeLimitReached: If the marketplace already has the maximum number of open trades
+    /// - TradeAlreadyInitiated: If offer already has an active trade
+    /// - InsufficientBalance: If the buyer doesn't hold enough cKES (on-chain settlement) or XLM for the initiation deposit
+    /// - TokenTransferFailed: If a settlement or initiation deposit transfer fails
+    fn initiate_trade(
+        env: Env,
+        buyer: Address,
+        offer_id: u64,
+        receive_to: Option<Address>,
+        operator: Option<Address>,
+        operator_fee_bps: u32,
+        external_ref: Option<BytesN<32>>,
+    ) -> Result<u64, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all trading if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+        
+        // Verify the buyer has signed this transaction
+        buyer.require_auth();
+        
+        // SECURITY FIX: Validate buyer address
+        Self::_validate_address(&buyer)?;
+        if let Some(receive_to) = &receive_to {
+            Self::_validate_address(receive_to)?;
+        }
+
+        Self::_check_trade_options(&env, &operator, operator_fee_bps, &external_ref)?;
+        let mut trade_refs: Map<BytesN<32>, u64> = env.storage().instance().get(&DataKey::TradeRefs)
+            .unwrap_or(Map::new(&env));
+
+        let trade_id = Self::_open_trade(env.clone(), buyer, offer_id, receive_to, None, operator, operator_fee_bps, None)?;
+
+        // Attach and index the reference for back-office reconciliation
+        if let Some(external_ref) = external_ref {
+            let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+            let mut trade = trades.get(trade_id).unwrap();
+            trade.external_ref = Some(external_ref.clone());
+            trades.set(trade_id, trade);
+            env.storage().instance().set(&DataKey::Trades, &trades);
+            trade_refs.set(external_ref, trade_id);
+            env.storage().instance().set(&DataKey::TradeRefs, &trade_refs);
+        }
+
+        Ok(trade_id)
+    }
+
+    /// Initiates a trade only if the offer still meets the buyer's constraints.
+    /// Lets routing contracts quote an offer, then take it atomically: if the seller repriced
+    /// or the offer changed in between, the call fails without side effects.
+    /// 
+    /// # Arguments
+    /// * `buyer` - The address initiating the trade (must sign transaction)
+    /// * `offer_id` - The ID of the offer to trade against
+    /// * `max_rate` - Highest KES per USDC the buyer accepts, in the `get_offer_rate` format
+    /// * `max_usdc` - Most USDC the buyer will take; offers fill all-or-nothing
+    /// 
+    /// # Returns
+    /// The unique ID of the created trade
+    /// 
+    /// # Errors
+    /// - InvalidAmount: If either constraint isn't positive
+    /// - OfferNotFound: If offer doesn't exist
+    /// - FillConditionsNotMet: If the offer's rate or amount exceeds the buyer's constraints
+    /// - RateOutOfBounds: If the offer's KES amount is too large to compute a rate
+    /// - Any error `initiate_trade` would return
+    fn initiate_trade_fok(env: Env, buyer: Address, offer_id: u64, max_rate: i128, max_usdc: i128) -> Result<u64, Error> {
+        Self::_require_initialized(&env)?;
+
+        if max_rate <= 0 || max_usdc <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Check the offer against the buyer's quote before touching any state
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        if Self::_offer_rate(&offer)? > max_rate || offer.usdc_amount > max_usdc {
+            return Err(Error::FillConditionsNotMet);
+        }
+
+        Self::initiate_trade(env, buyer, offer_id, None, None, 0, None)
+    }
+
+    /// Runs every check `initiate_trade` would, without changing state or moving funds.
+    /// 
+    /// # Usage
+    /// - Wallets call this before asking the buyer to sign, to show the precise failure reason
+    /// 
+    /// # Arguments
+    /// * `buyer` - The address that would initiate the trade (no signature needed)
+    /// * `offer_id` - The ID of the offer to trade against
+    /// * `operator` - Optional integrating wallet that would earn an operator fee
+    /// * `operator_fee_bps` - Operator fee in basis points (0 without an operator)
+    /// * `external_ref` - Optional client reference the trade would carry
+    /// 
+    /// # Errors
+    /// Any error `initiate_trade` would return, including InsufficientBalance if the buyer
+    /// can't cover the initiation deposit or the on-chain settlement leg
+    fn simulate_initiate_trade(
+        env: Env,
+        buyer: Address,
+        offer_id: u64,
+        operator: Option<Address>,
+        operator_fee_bps: u32,
+        external_ref: Option<BytesN<32>>,
+    ) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+        Self::_validate_address(&buyer)?;
+        Self::_check_trade_options(&env, &operator, operator_fee_bps, &external_ref)?;
+        let offer = Self::_check_new_trade(&env, &buyer, offer_id)?;
+
+        // The transfers made at initiation must be covered
+        let counter_token = Self::_counter_token(&env, &offer.settlement)?;
+        if let Some(counter_token_id) = counter_token {
+            if token::Client::new(&env, &counter_token_id).balance(&buyer) < offer.kes_amount {
+                return Err(Error::InsufficientBalance);
+            }
+        } else if let Some((xlm_token_id, deposit)) = Self::get_initiation_deposit(env.clone()) {
+            if token::Client::new(&env, &xlm_token_id).balance(&buyer) < deposit {
+                return Err(Error::InsufficientBalance);
+            }
+        }
+        Ok(())
+    }
+
+    /// Proposes a different KES price for an existing offer.
+    /// The seller can accept the proposal, which initiates a trade at the negotiated amount.
+    /// 
+    /// # Business Flow
+    /// 1. Validates buyer authorization and that the offer is open to trading
+    /// 2. Collects the initiation deposit, if configured, which moves to the trade on acceptance
+    /// 3. Records the proposal with the buyer's payout preference
+    /// 4. Emits event so the seller can respond
+    /// 
+    /// # Arguments
+    /// * `buyer` - The address proposing the price (must sign transaction)
+    /// * `offer_id` - The ID of the offer to negotiate
+    /// * `kes_amount` - KES amount the buyer proposes to pay for the whole offer
+    /// * `receive_to` - Optional address for the USDC payout if accepted
+    /// 
+    /// # Returns
+    /// The unique ID of the created proposal
+    /// 
+    /// # Errors
+    /// - ContractPaused: If trading is disabled
+    /// - InvalidAmount: If the proposed amount is not positive
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - Unauthorized: If buyer tries to negotiate with themselves
+    /// - InvalidTradeStatus: If the offer settles on-chain (no negotiation on atomic swaps)
+    /// - RateLimitExceeded: If the buyer, or the offer as a whole, already has the maximum open proposals
+    /// - InsufficientBalance: If the buyer can't post the initiation deposit
+    fn propose_price(
+        env: Env,
+        buyer: Address,
+        offer_id: u64,
+        kes_amount: i128,
+        receive_to: Option<Address>,
+    ) -> Result<u64, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all trading if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Verify the buyer has signed this transaction
+        buyer.require_auth();
+        Self::_validate_address(&buyer)?;
+        if let Some(receive_to) = &receive_to {
+            Self::_validate_address(receive_to)?;
+        }
+
+        if kes_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenOffers, offer_id)?;
+        if !Self::_is_active_offer(&env, &offer.seller, offer_id) {
+            return Err(Error::OfferNotFound);
+        }
+        if buyer == offer.seller {
+            return Err(Error::Unauthorized);
+        }
+
+        // On-chain offers swap at initiation with the buyer's signature, which a
+        // seller-side acceptance cannot provide
+        if offer.settlement != SettlementMode::OffChain {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // Expired proposals give up their slot on the offer, so stale ones can't keep it full
+        let now = env.ledger().timestamp();
+        let mut proposal_ids: Vec<u64> = Vec::new(&env);
+        let mut expired: Vec<u64> = Vec::new(&env);
+        let mut open_by_buyer = 0u32;
+        for proposal_id in Self::_offer_proposals(&env, offer_id).iter() {
+            let proposal: Proposal = env.storage().persistent().get(&DataKey::Proposal(proposal_id)).unwrap();
+            if now > proposal.expires_at {
+                expired.push_back(proposal_id);
+                continue;
+            }
+            if proposal.buyer == buyer {
+                open_by_buyer += 1;
+            }
+            proposal_ids.push_back(proposal_id);
+        }
+        if open_by_buyer >= MAX_OPEN_PROPOSALS || proposal_ids.len() >= MAX_OFFER_PROPOSALS {
+            return Err(Error::RateLimitExceeded);
+        }
+        let proposal_id: u64 = env.storage().instance().get(&DataKey::NextProposalId).unwrap_or(0);
+
+        // Collected now because the buyer doesn't sign the seller's acceptance
+        if let Some(deposit) = Self::_collect_initiation_deposit(&env, &buyer)? {
+            env.storage().persistent().set(&DataKey::ProposalDeposit(proposal_id), &(buyer.clone(), deposit));
+        }
+
+        let key = DataKey::Proposal(proposal_id);
+        env.storage().persistent().set(&key, &Proposal {
+            offer_id,
+            buyer: buyer.clone(),
+            kes_amount,
+            receive_to,
+            created_at: now,
+            expires_at: now + PROPOSAL_LIFETIME,
+        });
+        Self::_extend_entry(&env, &key);
+        proposal_ids.push_back(proposal_id);
+        Self::_set_offer_proposals(&env, offer_id, &proposal_ids);
+        env.storage().instance().set(&DataKey::NextProposalId, &(proposal_id + 1));
+
+        Self::_set_executing(&env, true);
+        Self::_drop_proposals(&env, &expired);
+        Self::_set_executing(&env, false);
+
+        Self::_publish_to_parties(&env, PROPOSAL_MADE, &buyer, &offer.seller, (proposal_id, offer_id, kes_amount));
+
+        Ok(proposal_id)
+    }
+
+    /// Accepts a buyer's price proposal, initiating a trade at the negotiated KES amount.
+    /// 
+    /// # Business Flow
+    /// 1. Validates the caller is the seller of the proposal's offer
+    /// 2. Consumes the proposal
+    /// 3. Initiates the trade on the buyer's behalf with the negotiated amount
+    /// 
+    /// # Arguments
+    /// * `seller` - The seller accepting (must sign transaction)
+    /// * `proposal_id` - The ID of the proposal to accept
+    /// 
+    /// # Returns
+    /// The unique ID of the created trade
+    /// 
+    /// # Errors
+    /// - ContractPaused: If trading is disabled
+    /// - ProposalNotFound: If proposal doesn't exist
+    /// - Unauthorized: If caller is not the offer's seller
+    /// - ProposalExpired: If the proposal is past its expiry
+    /// - Any error from trade initiation (e.g. TradeAlreadyInitiated, OfferNotFound)
+    fn accept_proposal(env: Env, seller: Address, proposal_id: u64) -> Result<u64, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all trading if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        let proposal: Proposal = env.storage().persistent().get(&DataKey::Proposal(proposal_id))
+            .ok_or(Error::ProposalNotFound)?;
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(proposal.offer_id).ok_or(Error::OfferNotFound)?;
+        if offer.seller != seller {
+            return Err(Error::Unauthorized);
+        }
+
+        // The buyer agreed to the price only for a limited time
+        if env.ledger().timestamp() > proposal.expires_at {
+            return Err(Error::ProposalExpired);
+        }
+
+        // Consume the proposal before opening the trade
+        Self::_remove_proposal(&env, proposal_id, proposal.offer_id);
+        let deposit = env.storage().persistent().get::<_, (Address, (Address, i128))>(&DataKey::ProposalDeposit(proposal_id))
+            .map(|(_, deposit)| deposit);
+        env.storage().persistent().remove(&DataKey::ProposalDeposit(proposal_id));
+
+        // The buyer consented to these terms when signing the proposal
+        let trade_id = Self::_open_trade(
+            env.clone(),
+            proposal.buyer.clone(),
+            proposal.offer_id,
+            proposal.receive_to,
+            Some(proposal.kes_amount),
+            None,
+            0,
+            Some(deposit),
+        )?;
+
+        Self::_publish_to_parties(&env, PROPOSAL_ACCEPTED, &proposal.buyer, &seller, (proposal_id, trade_id));
+
+        Ok(trade_id)
+    }
+
+    /// Rejects a price proposal (seller) or withdraws it (buyer).
+    /// Any initiation deposit posted with the proposal is refunded to the buyer.
+    /// Proposals are also dropped, with their deposits refunded, once the offer leaves the book.
+    /// A refund the buyer can't receive is parked for `claim_proposal_deposit`.
+    /// 
+    /// # Arguments
+    /// * `caller` - The offer's seller or the proposing buyer (must sign transaction)
+    /// * `proposal_id` - The ID of the proposal to remove
+    /// 
+    /// # Errors
+    /// - Unauthorized: If a token call is in progress
+    /// - ProposalNotFound: If proposal doesn't exist
+    /// - Unauthorized: If caller is neither the seller nor the proposing buyer
+    fn reject_proposal(env: Env, caller: Address, proposal_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify the caller has signed this transaction
+        caller.require_auth();
+        Self::_require_not_executing(&env)?;
+
+        let proposal: Proposal = env.storage().persistent().get(&DataKey::Proposal(proposal_id))
+            .ok_or(Error::ProposalNotFound)?;
+
+        // The buyer can always withdraw, even once the offer has been pruned
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let seller = offers.get(proposal.offer_id).map(|offer| offer.seller);
+        if caller != proposal.buyer && Some(&caller) != seller.as_ref() {
+            return Err(Error::Unauthorized);
+        }
+
+        Self::_remove_proposal(&env, proposal_id, proposal.offer_id);
+        Self::_set_executing(&env, true);
+        Self::_refund_proposal_deposit(&env, proposal_id);
+        Self::_set_executing(&env, false);
+
+        match &seller {
+            Some(seller) => Self::_publish_to_parties(&env, PROPOSAL_REJECTED, &proposal.buyer, seller, (proposal_id, caller)),
+            None => Self::_publish_to(&env, PROPOSAL_REJECTED, &proposal.buyer, (proposal_id, caller)),
+        }
+
+        Ok(())
+    }
+
+    /// Reclaims a proposal deposit whose refund couldn't be delivered.
+    /// 
+    /// # Business Logic
+    /// - Refunds of dropped proposals are best-effort so settlement never depends on the proposer,
+    ///   and a refund the proposer can't receive (e.g. a merged account) is parked
+    /// - The proposer withdraws the parked deposit once they can receive it again
+    /// 
+    /// # Arguments
+    /// * `buyer` - The proposer the deposit is owed to (must sign transaction)
+    /// * `proposal_id` - The ID of the dropped proposal
+    /// 
+    /// # Returns
+    /// The amount of the deposit token withdrawn
+    /// 
+    /// # Errors
+    /// - Unauthorized: If a token call is in progress, or the caller didn't make the proposal
+    /// - InvalidTradeStatus: If the proposal is still open; withdraw it with `reject_proposal`
+    /// - InvalidAmount: If no deposit is parked for the proposal
+    /// - TokenTransferFailed: If the buyer still can't receive the deposit
+    fn claim_proposal_deposit(env: Env, buyer: Address, proposal_id: u64) -> Result<i128, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify the buyer has signed this transaction
+        buyer.require_auth();
+        Self::_require_not_executing(&env)?;
+
+        if env.storage().persistent().has(&DataKey::Proposal(proposal_id)) {
+            return Err(Error::InvalidTradeStatus);
+        }
+        let key = DataKey::ProposalDeposit(proposal_id);
+        let (owner, (xlm_token_id, deposit)) = env.storage().persistent().get::<_, (Address, (Address, i128))>(&key)
+            .ok_or(Error::InvalidAmount)?;
+        if owner != buyer {
+            return Err(Error::Unauthorized);
+        }
+
+        // Clear the entry before the transfer (checks-effects-interactions)
+        env.storage().persistent().remove(&key);
+
+        let xlm_client = token::Client::new(&env, &xlm_token_id);
+        Self::_set_executing(&env, true);
+        let paid = xlm_client.try_transfer(&env.current_contract_address(), &buyer, &deposit).is_ok();
+        Self::_set_executing(&env, false);
+        if !paid {
+            log!(&env, "Failed to withdraw parked proposal deposit of {}", deposit);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        Self::_publish_to(&env, DEPOSIT_CLAIMED, &buyer, (proposal_id, deposit));
+
+        Ok(deposit)
+    }
+
+    /// Marks a partial fiat payment (tranche) on an installment trade.
+    /// Used when a trade's KES amount exceeds what the buyer can send in one payment.
+    /// 
+    /// # Business Flow
+    /// 1. Validates buyer authorization and trade state
+    /// 2. Appends the tranche awaiting seller confirmation
+    /// 3. Emits event so the seller can check their account
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade being paid
+    /// * `buyer` - The buyer reporting the payment (must sign)
+    /// * `kes_amount` - KES sent in this tranche
+    /// 
+    /// # Returns
+    /// Index of the tranche within the trade
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - InvalidAmount: If amount is not positive, or the trade already has the maximum tranches
+    /// - TradeNotFound: If trade doesn't exist
+    /// - TradeExpired: If trade has exceeded time limit
+    /// - InvalidTradeStatus: If trade is not awaiting payment
+    /// - Unauthorized: If caller is not the buyer
+    fn mark_tranche(env: Env, trade_id: u64, buyer: Address, kes_amount: i128) -> Result<u32, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Verify the buyer has signed this transaction
+        buyer.require_auth();
+
+        if kes_amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Retrieve and validate the trade
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        if Self::_is_trade_expired(&env, &trade) {
+            return Err(Error::TradeExpired);
+        }
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+        if buyer != trade.buyer {
+            return Err(Error::Unauthorized);
+        }
+        if trade.tranches.len() >= MAX_TRADE_TRANCHES {
+            return Err(Error::InvalidAmount);
+        }
+
+        let index = trade.tranches.len();
+        trade.tranches.push_back(Tranche {
+            kes_amount,
+            marked_at: env.ledger().timestamp(),
+            confirmed: false,
+        });
+
+        trades.set(trade_id, trade.clone());
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        Self::_publish_to_parties(&env, TRANCHE_MARKED, &buyer, &trade.seller, (trade_id, index, kes_amount));
+
+        Ok(index)
+    }
+
+    /// Confirms receipt of a tranche; releases USDC once the full KES amount is covered.
+    /// 
+    /// # Business Flow
+    /// 1. Validates seller authorization and the tranche
+    /// 2. Marks the tranche confirmed and restarts the trade timeout
+    /// 3. If confirmed tranches cover the trade's KES amount, completes the trade
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade
+    /// * `seller` - The seller confirming receipt (must sign)
+    /// * `index` - Index of the tranche to confirm
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - TradeExpired: If trade has exceeded time limit
+    /// - InvalidTradeStatus: If trade is not awaiting payment or tranche is already confirmed
+    /// - Unauthorized: If caller is not the seller
+    /// - InvalidAmount: If no tranche exists at the index
+    fn confirm_tranche(env: Env, trade_id: u64, seller: Address, index: u32) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        // Retrieve and validate the trade
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        if Self::_is_trade_expired(&env, &trade) {
+            return Err(Error::TradeExpired);
+        }
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+        if seller != trade.seller {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut tranche = trade.tranches.get(index).ok_or(Error::InvalidAmount)?;
+        if tranche.confirmed {
+            return Err(Error::InvalidTradeStatus);
+        }
+        tranche.confirmed = true;
+        trade.tranches.set(index, tranche);
+
+        let now = env.ledger().timestamp();
+        trade.last_tranche_at = Some(now);
+
+        let confirmed_kes: i128 = trade.tranches.iter()
+            .filter(|t| t.confirmed)
+            .map(|t| t.kes_amount)
+            .sum();
+
+        Self::_publish_to_parties(&env, TRANCHE_CONFIRMED, &trade.buyer, &seller, (trade_id, index, confirmed_kes));
+
+        // Fully paid: both sides' confirmations are implied by the confirmed tranches
+        if confirmed_kes >= trade.kes_amount {
+            trade.buyer_confirmed_payment = true;
+            trade.seller_confirmed_payment = true;
+            trade.buyer_confirmed_at = Some(now);
+            trade.seller_confirmed_at = Some(now);
+        }
+
+        Self::_store_and_settle(&env, trades, trade_id, trade)
+    }
+
+    /// Allows trade participants to confirm payment completion.
+    /// Both buyer and seller must confirm before USDC is released.
+    /// 
+    /// # Business Flow
+    /// 1. Validates participant authorization and trade existence
+    /// 2. Checks trade hasn't expired and is in correct status
+    /// 3. Records participant's payment confirmation
+    /// 4. If both parties confirm, automatically releases USDC
+    /// 5. Emits appropriate events for transparency
+    /// 
+    /// # Security Features
+    /// - Only trade participants can confirm
+    /// - Prevents confirmation on expired trades
+    /// - Validates trade is in correct status for confirmation
+    /// - Automatic execution when both parties confirm
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade to confirm payment for
+    /// * `participant` - The address confirming (buyer or seller, must sign)
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - TradeExpired: If trade has exceeded time limit
+    /// - InvalidTradeStatus: If trade is not in confirmable state, or the seller confirms a
+    ///   hash-locked trade (use `confirm_payment_with_preimage`)
+    /// - Unauthorized: If caller is not a trade participant
+    /// - BuyerAlreadyConfirmed / SellerAlreadyConfirmed: If the caller already confirmed
+    fn confirm_payment(env: Env, trade_id: u64, participant: Address) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+        
+        // ✅ SECURITY FIX: Simple reentrancy guard
+        Self::_require_not_executing(&env)?;
+        
+        // Verify the participant has signed this transaction
+        participant.require_auth();
+
+        // Retrieve and validate the trade
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        // Business rule: Expired trades cannot be confirmed to prevent stale settlements
+        if Self::_is_trade_expired(&env, &trade) {
+            return Err(Error::TradeExpired);
+        }
+
+
+        // Only allow confirmations on initiated trades
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // Update confirmation status based on who is confirming
+        // Buyer confirms they have sent KES payment
+        // Seller confirms they have received KES payment
+        if participant == trade.buyer {
+            if trade.buyer_confirmed_payment {
+                return Err(Error::BuyerAlreadyConfirmed);
+            }
+            trade.buyer_confirmed_payment = true;
+            trade.buyer_confirmed_at = Some(env.ledger().timestamp());
+        } else if participant == trade.seller {
+            if trade.seller_confirmed_payment {
+                return Err(Error::SellerAlreadyConfirmed);
+            }
+            // A hash-locked trade is confirmed by revealing the preimage, through its own entry point
+            if Self::get_payment_hash(env.clone(), trade_id).is_some() {
+                return Err(Error::InvalidTradeStatus);
+            }
+            trade.seller_confirmed_payment = true;
+            trade.seller_confirmed_at = Some(env.ledger().timestamp());
+        } else {
+            // Security check: Only trade participants can confirm
+            return Err(Error::Unauthorized);
+        }
+
+        // Emit confirmation event for transparency
+        Self::_publish_to_parties(&env, PAYMENT_CONFIRMED, &trade.buyer, &trade.seller, (trade_id, participant.clone()));
+
+        Self::_store_and_settle(&env, trades, trade_id, trade)
+    }
+
+    /// Lets the seller release escrow to the buyer immediately.
+    /// For trades where the seller verifies the KES payment out-of-band before the buyer confirms.
+    /// 
+    /// # Business Logic
+    /// - The buyer's confirmation is not required; the seller is the party at risk
+    /// - Fees are collected exactly as for a normally confirmed trade
+    /// - Works on expired trades too, since the seller is choosing not to take a refund
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade to release
+    /// * `seller` - The trade's seller (must sign transaction)
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - Unauthorized: If caller is not the trade's seller
+    /// - InvalidTradeStatus: If trade is not initiated
+    /// - TokenTransferFailed: If the USDC release fails
+    fn release_to_buyer(env: Env, trade_id: u64, seller: Address) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        // Retrieve and validate the trade
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        if seller != trade.seller {
+            return Err(Error::Unauthorized);
+        }
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // The seller's release doubles as their payment confirmation
+        trade.seller_confirmed_payment = true;
+        trade.seller_confirmed_at = Some(env.ledger().timestamp());
+        transition(&env, &mut trade, TradeStatus::PaymentConfirmed)?;
+        trades.set(trade_id, trade.clone());
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        Self::_publish_to_parties(&env, PAYMENT_CONFIRMED, &trade.buyer, &seller, (trade_id, seller.clone()));
+
+        Self::release_usdc(env.clone(), trade_id)
+    }
+
+    /// Confirms the buyer's payment with a receipt signed by the registered attester.
+    /// Replaces the buyer's bare boolean confirmation with cryptographic backing.
+    /// 
+    /// # Receipt Format
+    /// The attester signs `market (Address XDR) || buyer (Address XDR) || trade_id (8 bytes BE)
+    /// || kes_amount (16 bytes BE) || payment_ref (32 bytes)` with its Ed25519 key, where
+    /// `market` is this contract's address; the signature is verified on-chain.
+    /// 
+    /// # Security Features
+    /// - Signature verification binds the receipt to this market, buyer, trade and amount,
+    ///   so a receipt can't be replayed on another corridor's market with the same trade ID
+    /// - Invalid signatures abort the invocation
+    /// - Payment reference is stored on the trade for later disputes
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade being paid
+    /// * `buyer` - The buyer submitting the receipt (must sign)
+    /// * `payment_ref` - The payment provider's reference for the KES transfer
+    /// * `signature` - Attester's Ed25519 signature over the receipt message
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - Unauthorized: If no attester is registered or caller is not the buyer
+    /// - TradeNotFound: If trade doesn't exist
+    /// - TradeExpired: If trade has exceeded time limit
+    /// - InvalidTradeStatus: If trade is not in confirmable state
+    /// - BuyerAlreadyConfirmed: If a receipt was already recorded for this trade
+    fn confirm_payment_with_receipt(
+        env: Env,
+        trade_id: u64,
+        buyer: Address,
+        payment_ref: BytesN<32>,
+        signature: BytesN<64>,
+    ) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Verify the buyer has signed this transaction
+        buyer.require_auth();
+
+        let attester: BytesN<32> = env.storage().persistent().get(&ConfigKey::ReceiptAttester).ok_or(Error::Unauthorized)?;
+
+        // Retrieve and validate the trade
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        if Self::_is_trade_expired(&env, &trade) {
+            return Err(Error::TradeExpired);
+        }
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+        if buyer != trade.buyer {
+            return Err(Error::Unauthorized);
+        }
+        // A bare confirmation may be upgraded with a receipt, but only once
+        if trade.buyer_receipt.is_some() {
+            return Err(Error::BuyerAlreadyConfirmed);
+        }
+
+        // Rebuild the signed receipt message and verify the attester's signature
+        // ed25519_verify aborts the invocation if the signature is invalid
+        let mut message = env.current_contract_address().to_xdr(&env);
+        message.append(&buyer.clone().to_xdr(&env));
+        message.extend_from_array(&trade_id.to_be_bytes());
+        message.extend_from_array(&trade.kes_amount.to_be_bytes());
+        message.append(&Bytes::from(payment_ref.clone()));
+        env.crypto().ed25519_verify(&attester, &message, &signature);
+
+        trade.buyer_confirmed_payment = true;
+        trade.buyer_confirmed_at = Some(env.ledger().timestamp());
+        trade.buyer_receipt = Some(payment_ref);
+
+        // Emit confirmation event for transparency
+        Self::_publish_to_parties(&env, PAYMENT_CONFIRMED, &buyer, &trade.seller, (trade_id, buyer.clone()));
+
+        Self::_store_and_settle(&env, trades, trade_id, trade)
+    }
+
+    /// Locks the seller's confirmation to a secret the buyer put in the KES payment reference.
+    /// Committing doubles as the buyer's payment confirmation.
+    /// 
+    /// # Hash-Timelock Flow
+    /// 1. The buyer pays KES with a secret in the payment reference and commits `sha256(secret)`
+    /// 2. The seller reads the secret off the incoming payment and confirms with
+    ///    `confirm_payment_with_preimage`, publishing it on-chain
+    /// 3. The revealed preimage links the fiat transfer to the release for automated rails
+    /// 
+    /// If the seller never reveals, the trade deadline is the timelock: the buyer can dispute
+    /// and the trade expires as usual. The seller can still give up with `release_to_buyer`.
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade being paid
+    /// * `buyer` - The trade's buyer (must sign)
+    /// * `payment_hash` - sha256 of the secret in the payment reference
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - TradeExpired: If trade has exceeded time limit
+    /// - InvalidTradeStatus: If trade is not initiated or the seller already confirmed
+    /// - Unauthorized: If caller is not the buyer
+    /// - BuyerAlreadyConfirmed: If a payment hash was already committed for this trade
+    fn commit_payment_hash(env: Env, trade_id: u64, buyer: Address, payment_hash: BytesN<32>) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Verify the buyer has signed this transaction
+        buyer.require_auth();
+
+        // Retrieve and validate the trade
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        if Self::_is_trade_expired(&env, &trade) {
+            return Err(Error::TradeExpired);
+        }
+        if trade.status != TradeStatus::Initiated || trade.seller_confirmed_payment {
+            return Err(Error::InvalidTradeStatus);
+        }
+        if buyer != trade.buyer {
+            return Err(Error::Unauthorized);
+        }
+
+        let mut payment_hashes: Map<u64, BytesN<32>> = env.storage().instance().get(&DataKey::PaymentHashes)
+            .unwrap_or(Map::new(&env));
+        if payment_hashes.contains_key(trade_id) {
+            return Err(Error::BuyerAlreadyConfirmed);
+        }
+        payment_hashes.set(trade_id, payment_hash);
+        env.storage().instance().set(&DataKey::PaymentHashes, &payment_hashes);
+
+        // A bare confirmation may be upgraded with a commitment
+        if !trade.buyer_confirmed_payment {
+            trade.buyer_confirmed_payment = true;
+            trade.buyer_confirmed_at = Some(env.ledger().timestamp());
+            trades.set(trade_id, trade.clone());
+            env.storage().instance().set(&DataKey::Trades, &trades);
+        }
+
+        Self::_publish_to_parties(&env, PAYMENT_CONFIRMED, &buyer, &trade.seller, (trade_id, buyer.clone()));
+
+        Ok(())
+    }
+
+    /// Confirms receipt of a hash-locked payment by revealing the buyer's secret.
+    /// The preimage is published on-chain, then the trade settles as for any seller confirmation.
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade being confirmed
+    /// * `seller` - The trade's seller (must sign)
+    /// * `preimage` - The secret from the KES payment reference
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - TradeExpired: If trade has exceeded time limit
+    /// - InvalidTradeStatus: If trade is not initiated or has no payment hash
+    /// - Unauthorized: If caller is not the seller
+    /// - SellerAlreadyConfirmed: If the seller already confirmed
+    /// - PreimageMismatch: If the preimage doesn't hash to the buyer's commitment
+    fn confirm_payment_with_preimage(env: Env, trade_id: u64, seller: Address, preimage: Bytes) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        // Retrieve and validate the trade
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        if Self::_is_trade_expired(&env, &trade) {
+            return Err(Error::TradeExpired);
+        }
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+        if seller != trade.seller {
+            return Err(Error::Unauthorized);
+        }
+        if trade.seller_confirmed_payment {
+            return Err(Error::SellerAlreadyConfirmed);
+        }
+        let payment_hash = Self::get_payment_hash(env.clone(), trade_id).ok_or(Error::InvalidTradeStatus)?;
+        if env.crypto().sha256(&preimage).to_bytes() != payment_hash {
+            return Err(Error::PreimageMismatch);
+        }
+
+        trade.seller_confirmed_payment = true;
+        trade.seller_confirmed_at = Some(env.ledger().timestamp());
+
+        Self::_publish_to_parties(&env, PAYMENT_REVEALED, &trade.buyer, &seller, (trade_id, preimage));
+        Self::_publish_to_parties(&env, PAYMENT_CONFIRMED, &trade.buyer, &seller, (trade_id, seller.clone()));
+
+        Self::_store_and_settle(&env, trades, trade_id, trade)
+    }
+
+    /// Records a payment oracle's attestation that the buyer's KES payment was received.
+    /// The attestation substitutes for the seller's confirmation once the grace period passes.
+    /// 
+    /// # Business Flow
+    /// 1. Validates the caller is the registered payment oracle
+    /// 2. Checks the trade is still awaiting confirmation
+    /// 3. Stores the attestation reference and timestamp on the trade
+    /// 4. Emits attestation event so the seller can react within the grace period
+    /// 
+    /// # Security Features
+    /// - Only the registered oracle (e.g. an M-Pesa callback relayer) can attest
+    /// - Release is delayed by a grace period during which the seller can dispute
+    /// - Attested trades can no longer be refunded through expiry
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade being attested
+    /// * `attestation` - Hash of the oracle's payment record (e.g. M-Pesa receipt)
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - Unauthorized: If no oracle is registered
+    /// - TradeNotFound: If trade doesn't exist
+    /// - TradeExpired: If trade has exceeded time limit
+    /// - InvalidTradeStatus: If trade is not initiated or already attested
+    fn attest_payment(env: Env, trade_id: u64, attestation: BytesN<32>) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Only the registered payment oracle can attest
+        let oracle: Address = env.storage().persistent().get(&ConfigKey::PaymentOracle).ok_or(Error::Unauthorized)?;
+        oracle.require_auth();
+
+        // Retrieve and validate the trade
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        if Self::_is_trade_expired(&env, &trade) {
+            return Err(Error::TradeExpired);
+        }
+        if trade.status != TradeStatus::Initiated || trade.attested_at.is_some() {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // Record the attestation; release happens after the grace period
+        trade.attestation = Some(attestation.clone());
+        trade.attested_at = Some(env.ledger().timestamp());
+        trades.set(trade_id, trade.clone());
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        Self::_publish_to_parties(&env, PAYMENT_ATTESTED, &trade.buyer, &trade.seller, (trade_id, attestation));
+
+        Ok(())
+    }
+
+    /// Releases an oracle-attested trade once its grace period has elapsed.
+    /// Anyone can call this, so a seller going offline after being paid can't block the buyer.
+    /// 
+    /// # Business Logic
+    /// - The oracle attestation stands in for the seller's confirmation
+    /// - The seller can still raise a dispute during the grace period
+    /// - Fees are collected exactly as for a normally confirmed trade
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the attested trade to release
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If trade is not initiated or was never attested
+    /// - GracePeriodActive: If the grace period has not yet elapsed
+    /// - TokenTransferFailed: If the USDC release fails
+    fn release_attested_trade(env: Env, trade_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Retrieve and validate the trade
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        // Disputed or already settled trades are out of the oracle's hands
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+        let attested_at = trade.attested_at.ok_or(Error::InvalidTradeStatus)?;
+
+        let grace: u64 = Self::get_attestation_grace_period(env.clone());
+        if env.ledger().timestamp() < attested_at + grace {
+            return Err(Error::GracePeriodActive);
+        }
+
+        // The attestation substitutes for the seller's confirmation
+        trade.seller_confirmed_payment = true;
+        trade.seller_confirmed_at = Some(env.ledger().timestamp());
+        transition(&env, &mut trade, TradeStatus::PaymentConfirmed)?;
+        trades.set(trade_id, trade);
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        Self::release_usdc(env.clone(), trade_id)
+    }
+
+    /// Releases a buyer's first trade once its cooling-off period has elapsed.
+    /// Anyone can call this, so the buyer isn't left waiting on the seller.
+    /// 
+    /// # Business Logic
+    /// - The hold starts when the second confirmation lands
+    /// - The seller can raise a dispute during the hold, which stops the release
+    /// - Fees are collected exactly as for a normally confirmed trade
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the held trade to release
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If trade is not confirmed and held
+    /// - GracePeriodActive: If the cooling-off period has not yet elapsed
+    /// - TokenTransferFailed: If the USDC release fails
+    fn release_held_trade(env: Env, trade_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+
+        // Only held trades rest in PaymentConfirmed; every other path completes immediately
+        if trade.status != TradeStatus::PaymentConfirmed {
+            return Err(Error::InvalidTradeStatus);
+        }
+        let confirmed_at = trade.buyer_confirmed_at.max(trade.seller_confirmed_at).unwrap_or(0);
+        if env.ledger().timestamp() < confirmed_at + Self::get_first_trade_hold(env.clone()) {
+            return Err(Error::GracePeriodActive);
+        }
+
+        Self::release_usdc(env.clone(), trade_id)
+    }
+
+    /// Releases a trade to the buyer after the seller stayed silent through the auto-release window.
+    /// Anyone can call this, so an offline seller can't hold a paying buyer hostage.
+    /// 
+    /// # Business Logic
+    /// - Only available when the admin has enabled auto-release
+    /// - The buyer must have confirmed sending payment
+    /// - The seller neither confirmed nor disputed within the window
+    /// - Fees are collected exactly as for a normally confirmed trade
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade to release
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If auto-release is disabled, the trade is not initiated,
+    ///   or the buyer hasn't confirmed payment
+    /// - GracePeriodActive: If the auto-release window has not yet elapsed
+    /// - TokenTransferFailed: If the USDC release fails
+    fn auto_release_trade(env: Env, trade_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Retrieve and validate the trade
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        let window = Self::get_auto_release_window(env.clone());
+        if window == 0 || trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+        let buyer_confirmed_at = trade.buyer_confirmed_at.ok_or(Error::InvalidTradeStatus)?;
+        if env.ledger().timestamp() < buyer_confirmed_at + window {
+            return Err(Error::GracePeriodActive);
+        }
+
+        // The seller's silence through the window substitutes for their confirmation
+        trade.seller_confirmed_payment = true;
+        trade.seller_confirmed_at = Some(env.ledger().timestamp());
+        Self::_store_and_settle(&env, trades, trade_id, trade)
+    }
+
+    /// Allows trade participants to cancel an initiated trade.
+    /// This returns the escrowed USDC to the seller.
+    /// 
+    /// # Business Flow
+    /// 1. Validates participant authorization and trade state
+    /// 2. Ensures only initiated trades can be cancelled
+    /// 3. Returns escrowed USDC to seller
+    /// 4. Updates trade status and removes offer from active list
+    /// 5. Emits cancellation event
+    /// 
+    /// # Security Features
+    /// - Only trade participants can cancel
+    /// - Only initiated trades can be cancelled
+    /// - The seller can't cancel once the buyer has confirmed payment or it was attested
+    /// - Safe transfer with error handling
+    /// - Proper cleanup of offer state
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade to cancel
+    /// * `participant` - The address requesting cancellation (buyer or seller)
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If trade cannot be cancelled, or the seller cancels after the buyer confirmed payment
+    /// - Unauthorized: If caller is not a trade participant, or a token call is in progress
+    /// - TokenTransferFailed: If USDC return fails
+    fn cancel_trade(env: Env, trade_id: u64, participant: Address) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+        
+        // Verify the participant has signed this transaction
+        participant.require_auth();
+
+        // Retrieve and validate the trade
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+
+        // Business rule: Only initiated trades can be cancelled
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // Security check: Only trade participants can cancel
+        if participant != trade.buyer && participant != trade.seller {
+            return Err(Error::Unauthorized);
+        }
+
+        // Once the buyer says the KES moved, the seller can't walk away with the escrow:
+        // the trade can only be confirmed, disputed, or disputed on expiry
+        if participant == trade.seller && (trade.buyer_confirmed_payment || trade.attested_at.is_some()) {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // Update trade status to cancelled
+        transition(&env, &mut trade, TradeStatus::Cancelled)?;
+        trades.set(trade_id, trade.clone());
+        Self::_adjust_counter(&env, &DataKey::OpenTrades, -1);
+
+        // Persist state changes
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        // Release the reservation so the offer is available to other buyers again
+        Self::_set_reservation(&env, trade.offer_id, None);
+        if participant == trade.buyer {
+            Self::_record_walkaway(&env, &trade.buyer);
+        }
+
+        // A cancelled trade needs no cleanup, so the buyer's initiation deposit is returned
+        Self::_set_executing(&env, true);
+        let refund = Self::_pay_initiation_deposit(&env, trade_id, &trade.buyer);
+        Self::_set_executing(&env, false);
+        refund?;
+
+        // Emit cancellation event for transparency
+        Self::_publish_to_parties(&env, TRADE_CANCELLED, &trade.buyer, &trade.seller, (trade_id, participant.clone()));
+
+        Ok(())
+    }
+
+    /// Restores a trade cancelled by mistake, when both parties sign within an hour of the cancellation.
+    /// 
+    /// # Business Logic
+    /// - Covers trades cancelled with `cancel_trade` or expired with `resolve_expired_trade`
+    /// - Trades cancelled by a dispute ruling can't be reopened
+    /// - If the escrow was returned to the seller, the seller escrows it again, subject to
+    ///   the checks `create_offer` runs (one active offer per seller, caps, daily limit)
+    /// - The trade restarts its expiration timer; confirmations already given are kept
+    /// - The cancellation returned or paid out the initiation deposit, so the buyer posts it
+    ///   again if one is configured, and the walkaway it counted against the buyer is taken back
+    /// - The trade counts against the open-trade ceiling again
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the cancelled trade
+    /// 
+    /// # Returns
+    /// The USDC escrowed again from the seller, 0 if the escrow was still held
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If trade isn't cancelled or was cancelled by a dispute ruling
+    /// - TradeExpired: If the reopen window has passed
+    /// - TradeAlreadyInitiated: If another trade has reserved the offer since
+    /// - OfferNotFound: If the offer was filled or is frozen
+    /// - TradeLimitReached: If the open-trade ceiling has been reached
+    /// - AlreadyHasActiveOffer: If the escrow must be pulled again and the seller has listed another offer
+    /// - Any other error from `create_offer`'s checks when the escrow must be pulled again
+    /// - InsufficientBalance: If the seller can't escrow the USDC again, or the buyer can't post the deposit
+    /// - TokenTransferFailed: If the escrow or deposit transfer fails, or the escrow arrives short
+    fn reopen_trade(env: Env, trade_id: u64) -> Result<i128, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        // Both parties must agree to undo the cancellation
+        trade.buyer.require_auth();
+        trade.seller.require_auth();
+
+        if trade.status != TradeStatus::Cancelled {
+            return Err(Error::InvalidTradeStatus);
+        }
+        if env.ledger().timestamp() >= trade.cancelled_at.unwrap_or(0).saturating_add(REOPEN_WINDOW) {
+            return Err(Error::TradeExpired);
+        }
+
+        // Dispute rulings are final
+        if Self::get_dispute(env.clone(), trade_id).is_some() {
+            return Err(Error::InvalidTradeStatus);
+        }
+        Self::_check_open_trade_ceiling(&env)?;
+
+        let offer = Self::get_offer(env.clone(), trade.offer_id).ok_or(Error::OfferNotFound)?;
+        let re_escrowed = match offer.status {
+            // Cancelled before expiry: the offer still holds the escrow
+            OfferStatus::Active => 0,
+            // Expired or cancelled by the seller: the escrow went back and is pulled again in full,
+            // relisting the offer under the same checks as a new one
+            OfferStatus::Expired | OfferStatus::Cancelled => {
+                Self::_check_new_offer(&env, &trade.seller, offer.usdc_amount, offer.kes_amount, &offer.settlement)?;
+                Self::_consume_daily_limit(&env, &trade.seller, offer.usdc_amount)?;
+                let amount = trade.usdc_amount + Self::_offer_fee_reserve(&env, trade.offer_id, trade.usdc_amount);
+                if Self::_escrow_usdc(&env, &trade.seller, amount)? < amount {
+                    log!(&env, "Re-escrowed less than {}", amount);
+                    return Err(Error::TokenTransferFailed);
+                }
+                Self::_add_active_offer(&env, &trade.seller, trade.offer_id, trade.usdc_amount);
+                amount
+            }
+            OfferStatus::Reserved => return Err(Error::TradeAlreadyInitiated),
+            OfferStatus::Paused | OfferStatus::Filled => return Err(Error::OfferNotFound),
+        };
+
+        // The cancellation settled the initiation deposit, so the cleanup bounty is funded again
+        let mut deposits: Map<u64, (Address, i128)> = env.storage().instance().get(&DataKey::TradeDeposits)
+            .unwrap_or(Map::new(&env));
+        if !deposits.contains_key(trade_id) {
+            if let Some(deposit) = Self::_collect_initiation_deposit(&env, &trade.buyer)? {
+                deposits.set(trade_id, deposit);
+                env.storage().instance().set(&DataKey::TradeDeposits, &deposits);
+            }
+        }
+        Self::_undo_walkaway(&env, &trade.buyer, trade.cancelled_at.unwrap_or(0));
+
+        // Back to Initiated with a fresh expiration timer
+        transition(&env, &mut trade, TradeStatus::Initiated)?;
+        trade.start_time = env.ledger().timestamp();
+        trades.set(trade_id, trade.clone());
+        env.storage().instance().set(&DataKey::Trades, &trades);
+        Self::_adjust_counter(&env, &DataKey::OpenTrades, 1);
+        Self::_set_reservation(&env, trade.offer_id, Some(trade_id));
+
+        Self::_publish_to_parties(&env, TRADE_REOPENED, &trade.buyer, &trade.seller, (trade_id, re_escrowed));
+
+        Ok(re_escrowed)
+    }
+
+    /// Resolves expired trades by returning escrowed USDC to sellers.
+    /// Anyone can call this function to clean up expired trades.
+    /// 
+    /// # Business Logic
+    /// - Trades have time limits to prevent indefinite escrow
+    /// - Expired trades are automatically cancelled
+    /// - USDC is returned to seller when trade expires
+    /// - This prevents buyer griefing by not confirming payment
+    /// - Trades whose buyer confirmed paying are turned into disputes instead, since the
+    ///   buyer claims their KES already moved; no keeper bounty is paid for these
+    /// 
+    /// # Public Access
+    /// - Any address can call this function
+    /// - Helps maintain marketplace hygiene
+    /// - Incentivizes community participation in cleanup: the caller receives the
+    ///   buyer's initiation deposit, if one was posted
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the expired trade to resolve
+    /// * `caller` - The address triggering resolution (must sign transaction), recorded in the event
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - TradeNotFound: If trade doesn't exist
+    /// - TradeNotExpired: If trade hasn't actually expired
+    /// - InvalidTradeStatus: If trade is not in expirable state
+    /// - Unauthorized: If a token call is in progress
+    /// - TokenTransferFailed: If USDC return or the deposit payout fails
+    fn resolve_expired_trade(env: Env, trade_id: u64, caller: Address) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        // Verify the caller so the event attributes resolution correctly
+        caller.require_auth();
+        
+        // Retrieve and validate the trade
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        // Validate that the trade has actually expired
+        if !Self::_is_trade_expired(&env, &trade) {
+            return Err(Error::TradeNotExpired);
+        }
+
+        // Only initiated trades can expire - others are already resolved
+        if trade.status != TradeStatus::Initiated {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // Oracle-attested trades are released, not refunded - the buyer's money already moved
+        if trade.attested_at.is_some() {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // With auto-release enabled, a buyer-confirmed trade can only be released or disputed
+        if trade.buyer_confirmed_payment && Self::get_auto_release_window(env.clone()) > 0 {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // The buyer claims their KES already moved, so refunding the seller could take both legs:
+        // the trade goes to arbitration instead, with the initiation deposit left for the resolution
+        if trade.buyer_confirmed_payment {
+            transition(&env, &mut trade, TradeStatus::Disputed)?;
+            trades.set(trade_id, trade.clone());
+            env.storage().instance().set(&DataKey::Trades, &trades);
+
+            let reason = String::from_str(&env, "Trade expired after buyer confirmed payment");
+            Self::_open_dispute(&env, trade_id, &trade, &trade.buyer, reason, Vec::new(&env));
+            return Ok(());
+        }
+
+        // Update trade status to cancelled due to expiration
+        transition(&env, &mut trade, TradeStatus::Cancelled)?;
+        trades.set(trade_id, trade.clone());
+        Self::_adjust_counter(&env, &DataKey::OpenTrades, -1);
+
+
+        // Setup USDC client for returning funds
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+
+        // Return the escrowed USDC and fee reserve to seller since trade expired, holding the guard for the call
+        let refund_amount = trade.usdc_amount + Self::_offer_fee_reserve(&env, trade.offer_id, trade.usdc_amount);
+        Self::_set_executing(&env, true);
+        let refund = Self::_ensure_liquid(&env, &usdc_client, refund_amount).map(|_| {
+            usdc_client.try_transfer(&env.current_contract_address(), &Self::_refund_address(&trade), &refund_amount).is_ok()
+        });
+        Self::_set_executing(&env, false);
+        match refund {
+            Ok(true) => {},
+            _ => {
+                log!(&env, "Failed to return {} to seller", trade.usdc_amount);
+                return Err(Error::TokenTransferFailed);
+            }
+        }
+
+        // The buyer let the trade lapse, so their initiation deposit pays whoever cleaned it up
+        Self::_set_executing(&env, true);
+        let bounty = Self::_pay_initiation_deposit(&env, trade_id, &caller);
+        Self::_set_executing(&env, false);
+        bounty?;
+
+        // Clean up: Remove offer from active offers and release its reservation
+        Self::_remove_active_offer(&env, &trade.seller, trade.offer_id, trade.usdc_amount);
+        Self::_set_reservation(&env, trade.offer_id, None);
+        Self::_set_offer_status(&env, trade.offer_id, OfferStatus::Expired);
+        Self::_record_walkaway(&env, &trade.buyer);
+        // Proposals on an offer leaving the book can't be accepted, so their deposits go back
+        Self::_set_executing(&env, true);
+        Self::_clear_proposals(&env, trade.offer_id);
+        Self::_set_executing(&env, false);
+
+        // Persist state changes
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        // Emit a dedicated expiry event so indexers can tell it apart from a voluntary cancel
+        Self::_publish_to_parties(
+            &env,
+            TRADE_EXPIRED,
+            &trade.buyer,
+            &trade.seller,
+            (trade_id, trade.offer_id, trade.usdc_amount, caller),
+        );
+
+        Ok(())
+    }
+
+    /// Allows sellers to cancel their offers and recover escrowed USDC.
+    /// Offers can only be cancelled if no active trade exists.
+    /// 
+    /// # Business Flow
+    /// 1. Validates seller authorization and offer ownership
+    /// 2. Checks no active trades exist for the offer
+    /// 3. Returns escrowed USDC to seller
+    /// 4. Removes offer from all mappings
+    /// 5. Emits cancellation event
+    /// 
+    /// # Security Features
+    /// - Only offer owner can cancel
+    /// - Prevents cancellation if active trade exists
+    /// - Safe transfer with error handling
+    /// - Proper cleanup of all offer references
+    /// 
+    /// # Arguments
+    /// * `seller` - The address that created the offer (must sign)
+    /// * `offer_id` - The ID of the offer to cancel
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - OfferNotFound: If offer doesn't exist or is no longer active (filled, expired or cancelled)
+    /// - Unauthorized: If caller is not the offer owner
+    /// - TradeAlreadyInitiated: If active trade exists for offer
+    /// - Unauthorized: If a token call is in progress
+    /// - TokenTransferFailed: If USDC return fails
+    fn cancel_offer(env: Env, seller: Address, offer_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+        
+        // Verify the seller has signed this transaction
+        seller.require_auth();
+
+        // Retrieve and validate the offer
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenOffers, offer_id)?;
+
+        // Security check: Only the offer owner can cancel their offer
+        if offer.seller != seller {
+            return Err(Error::Unauthorized);
+        }
+
+        // Business rule: Cannot cancel offer if there's an active trade
+        // This prevents disrupting ongoing trade processes
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        
+        // ✅ SECURITY FIX: Optimized check with reverse iteration (newer trades first)
+        // Completed and cancelled trades don't block offer cancellation
+        let mut has_active_trade = false;
+        for trade in trades.values().into_iter().rev() {
+            if trade.offer_id == offer_id && 
+               !state_machine::is_final(&trade.status) {
+                has_active_trade = true;
+                break;
+            }
+        }
+        
+        if has_active_trade {
+            return Err(Error::TradeAlreadyInitiated);
+        }
+
+        // Filled, expired or already cancelled offers hold no escrow to return
+        if offer.status != OfferStatus::Active {
+            return Err(Error::OfferNotFound);
+        }
+
+        // Setup USDC client for returning escrowed funds
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+
+        // Return the escrowed USDC and any fee reserve to seller
+        let refund_to = offer.refund_to.clone().unwrap_or(seller.clone());
+        let refund_amount = offer.usdc_amount + Self::_offer_fee_reserve(&env, offer_id, offer.usdc_amount);
+        Self::_set_executing(&env, true);
+        let refund = Self::_ensure_liquid(&env, &usdc_client, refund_amount).map(|_| {
+            usdc_client.try_transfer(&env.current_contract_address(), &refund_to, &refund_amount).is_ok()
+        });
+        Self::_set_executing(&env, false);
+        match refund {
+            Ok(true) => {},
+            _ => {
+                log!(&env, "Failed to return {} to seller", refund_amount);
+                return Err(Error::TokenTransferFailed);
+            }
+        }
+
+        // Keep the record for the audit trail until pruned, ending any recurring relists
+        let mut offer = offer;
+        offer.status = OfferStatus::Cancelled;
+        offers.set(offer_id, offer.clone());
+        Self::_clear_offer_settings(&env, offer_id);
+
+        // Remove from active offers mapping
+        Self::_remove_active_offer(&env, &seller, offer_id, offer.usdc_amount);
+
+        // Persist changes
+        env.storage().instance().set(&DataKey::Offers, &offers);
+
+        // Proposals on an offer leaving the book can't be accepted, so their deposits go back
+        Self::_set_executing(&env, true);
+        Self::_clear_proposals(&env, offer_id);
+        Self::_set_executing(&env, false);
+
+        // Emit cancellation event for transparency
+        Self::_publish_to(&env, OFFER_CANCELLED, &seller, (offer_id,));
+
+        Ok(())
+    }
+
+    /// Expires an offer that has seen no trade activity for the offer TTL, refunding its seller.
+    /// Permissionless so abandoned listings and their escrow don't accumulate forever.
+    /// 
+    /// # Business Logic
+    /// - Listing, relisting, and opening or closing a trade on the offer restart its TTL
+    /// - Offers with a trade in progress can't be expired
+    /// - Enabling or changing the TTL never expires an offer sooner than one TTL after the change
+    /// - Escrow and any fee reserve go to the offer's refund address, as on cancellation
+    /// 
+    /// # Arguments
+    /// * `offer_id` - The ID of the offer to expire
+    /// 
+    /// # Returns
+    /// The USDC amount refunded
+    /// 
+    /// # Errors
+    /// - ContractPaused: If contract is paused
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - TradeAlreadyInitiated: If a trade is in progress on the offer
+    /// - GracePeriodActive: If the offer TTL is disabled or hasn't elapsed
+    /// - TokenTransferFailed: If the refund fails
+    fn expire_offer(env: Env, offer_id: u64) -> Result<i128, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Emergency brake - halt all operations if contract is paused
+        if Self::_is_paused(&env) { return Err(Error::ContractPaused); }
+
+        // Reject re-entry from a token contract mid-transfer
+        Self::_require_not_executing(&env)?;
+
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let mut offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenOffers, offer_id)?;
+
+        if offer.reserved_by.is_some() {
+            return Err(Error::TradeAlreadyInitiated);
+        }
+        if offer.status != OfferStatus::Active || !Self::_is_active_offer(&env, &offer.seller, offer_id) {
+            return Err(Error::OfferNotFound);
+        }
+        match Self::get_offer_expiry(env.clone(), offer_id) {
+            Some(expires_at) if env.ledger().timestamp() >= expires_at => {},
+            _ => return Err(Error::GracePeriodActive),
+        }
+
+        // Return the escrowed USDC and any fee reserve, as on cancellation
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        let refund_to = offer.refund_to.clone().unwrap_or(offer.seller.clone());
+        let refund_amount = offer.usdc_amount + Self::_offer_fee_reserve(&env, offer_id, offer.usdc_amount);
+        Self::_set_executing(&env, true);
+        let refund = Self::_ensure_liquid(&env, &usdc_client, refund_amount).map(|_| {
+            usdc_client.try_transfer(&env.current_contract_address(), &refund_to, &refund_amount).is_ok()
+        });
+        Self::_set_executing(&env, false);
+        if refund != Ok(true) {
+            log!(&env, "Failed to return {} to seller", refund_amount);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        // Keep the record for the audit trail until pruned, ending any recurring relists
+        offer.status = OfferStatus::Expired;
+        offers.set(offer_id, offer.clone());
+        Self::_clear_offer_settings(&env, offer_id);
+        Self::_remove_active_offer(&env, &offer.seller, offer_id, offer.usdc_amount);
+        env.storage().instance().set(&DataKey::Offers, &offers);
+
+        // Proposals on an offer leaving the book can't be accepted, so their deposits go back
+        Self::_set_executing(&env, true);
+        Self::_clear_proposals(&env, offer_id);
+        Self::_set_executing(&env, false);
+
+        Self::_publish_to(&env, OFFER_EXPIRED, &offer.seller, (offer_id, refund_amount));
+
+        Ok(refund_amount)
+    }
+
+    /// Withdraws USDC payouts that were parked because they couldn't be delivered at release.
+    /// 
+    /// # Business Logic
+    /// - Payouts are parked when the recipient can't receive USDC at release time
+    ///   (e.g. frozen account or missing trustline), so the trade still completes
+    /// - The full parked balance is withdrawn to the recipient once it can receive again
+    /// 
+    /// # Arguments
+    /// * `recipient` - The address the payouts were owed to (must sign transaction)
+    /// 
+    /// # Returns
+    /// The amount of USDC withdrawn
+    /// 
+    /// # Errors
+    /// - Unauthorized: If a token call is in progress
+    /// - InvalidAmount: If nothing is parked for the recipient
+    /// - TokenTransferFailed: If the recipient still can't receive USDC
+    fn withdraw(env: Env, recipient: Address) -> Result<i128, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify the recipient has signed this transaction
+        recipient.require_auth();
+        Self::_require_not_executing(&env)?;
+
+        let key = DataKey::Claimable(recipient.clone());
+        let amount: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Clear the balance before the transfer (checks-effects-interactions)
+        env.storage().persistent().remove(&key);
+        Self::_adjust_counter(&env, &DataKey::ClaimableTotal, -amount);
+
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        Self::_set_executing(&env, true);
+        let paid = Self::_ensure_liquid(&env, &usdc_client, amount).is_ok()
+            && usdc_client.try_transfer(&env.current_contract_address(), &recipient, &amount).is_ok();
+        Self::_set_executing(&env, false);
+        if !paid {
+            log!(&env, "Failed to withdraw parked payout of {}", amount);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        Self::_publish_to(&env, PAYOUT_CLAIMED, &recipient, (amount,));
+
+        Ok(amount)
+    }
+
+    /// Removes a completed or cancelled trade from storage after the retention period.
+    /// Permissionless so anyone can keep storage and rent costs bounded.
+    /// 
+    /// # Business Logic
+    /// - Only trades in a final state (Completed or Cancelled) can be pruned
+    /// - The retention period is measured from completion or cancellation
+    /// - The full record is emitted in an archival event before removal
+    /// - The trade's dispute record, if any, is pruned with it
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade to prune
+    /// 
+    /// # Errors
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If trade is not in a final state
+    /// - GracePeriodActive: If the retention period has not elapsed
+    fn prune_trade(env: Env, trade_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+        let finalized_at = Self::_finalized_at(&trade).ok_or(Error::InvalidTradeStatus)?;
+        let retention = Self::get_record_retention(env.clone());
+        if env.ledger().timestamp() < finalized_at + retention {
+            return Err(Error::GracePeriodActive);
+        }
+
+        trades.remove(trade_id);
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        if let Some(external_ref) = &trade.external_ref {
+            let mut trade_refs: Map<BytesN<32>, u64> = env.storage().instance().get(&DataKey::TradeRefs)
+                .unwrap_or(Map::new(&env));
+            trade_refs.remove(external_ref.clone());
+            env.storage().instance().set(&DataKey::TradeRefs, &trade_refs);
+        }
+
+        let mut disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap();
+        if disputes.contains_key(trade_id) {
+            disputes.remove(trade_id);
+            env.storage().instance().set(&DataKey::Disputes, &disputes);
+        }
+
+        let mut payment_hashes: Map<u64, BytesN<32>> = env.storage().instance().get(&DataKey::PaymentHashes)
+            .unwrap_or(Map::new(&env));
+        if payment_hashes.contains_key(trade_id) {
+            payment_hashes.remove(trade_id);
+            env.storage().instance().set(&DataKey::PaymentHashes, &payment_hashes);
+        }
+
+        // Final archival event carries the whole record for off-chain history
+        Self::_publish_to_parties(&env, TRADE_PRUNED, &trade.buyer, &trade.seller, (trade_id, trade.clone()));
+
+        Ok(())
+    }
+
+    /// Removes a filled or expired offer from storage after the retention period.
+    /// Permissionless so anyone can keep storage and rent costs bounded.
+    /// 
+    /// # Business Logic
+    /// - The offer must no longer be active and have no trade still in progress
+    /// - The retention period is measured from its latest trade's completion or cancellation
+    /// - The full record is emitted in an archival event before removal
+    /// 
+    /// # Arguments
+    /// * `offer_id` - The ID of the offer to prune
+    /// 
+    /// # Errors
+    /// - OfferNotFound: If offer doesn't exist
+    /// - InvalidTradeStatus: If the offer is still active or has a trade in progress
+    /// - GracePeriodActive: If the retention period has not elapsed
+    fn prune_offer(env: Env, offer_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        let mut offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenOffers, offer_id)?;
+
+        if Self::_is_active_offer(&env, &offer.seller, offer_id) {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // Find when the offer's last trade settled; any unfinished trade blocks pruning
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut last_final: Option<u64> = None;
+        for trade in trades.values() {
+            if trade.offer_id != offer_id {
+                continue;
+            }
+            let finalized_at = Self::_finalized_at(&trade).ok_or(Error::InvalidTradeStatus)?;
+            last_final = Some(last_final.unwrap_or(0).max(finalized_at));
+        }
+
+        // No remaining trades means they were already pruned after their own retention
+        let retention = Self::get_record_retention(env.clone());
+        if let Some(finalized_at) = last_final {
+            if env.ledger().timestamp() < finalized_at + retention {
+                return Err(Error::GracePeriodActive);
+            }
+        }
+
+        offers.remove(offer_id);
+        env.storage().instance().set(&DataKey::Offers, &offers);
+
+        // Offers that left the book already dropped their proposals; this catches any left behind
+        Self::_set_executing(&env, true);
+        Self::_clear_proposals(&env, offer_id);
+        Self::_set_executing(&env, false);
+
+        // Free the idempotency key; a retry this late would be a new offer anyway
+        let key_of = DataKey::OfferKeyOf(offer_id);
+        if let Some(idempotency_key) = env.storage().persistent().get::<_, BytesN<32>>(&key_of) {
+            env.storage().persistent().remove(&DataKey::OfferKey(offer.seller.clone(), idempotency_key));
+            env.storage().persistent().remove(&key_of);
+        }
+
+        // Final archival event carries the whole record for off-chain history
+        Self::_publish_to(&env, OFFER_PRUNED, &offer.seller, (offer_id, offer.clone()));
+
+        Ok(())
+    }
+
+    /// Emergency function to pause all trading activities.
+    /// Only admin can pause the contract for security or maintenance.
+    /// 
+    /// # Use Cases
+    /// - Security incidents requiring immediate halt
+    /// - Contract upgrades or maintenance
+    /// - Regulatory compliance requirements
+    /// - Market manipulation prevention
+    /// 
+    /// # Admin Only
+    /// - Requires admin authorization
+    /// - Immediate effect on all trading functions
+    /// - Does not affect existing trades, only new operations
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of pause operation
+    fn pause(env: Env) -> Result<(), Error> {
+        // Verify admin authorization - only admin can pause
+        let admin = Self::_require_admin(&env)?;
+        
+        // Set pause flag to halt all trading operations
+        Self::_set_paused(&env, &admin, true);
+        
+        Ok(())
+    }
+
+    /// Emergency brake for the guardian role.
+    /// The guardian (e.g. a monitoring bot) can pause but holds no other power:
+    /// it cannot unpause or change any configuration.
+    /// 
+    /// # Errors
+    /// - Unauthorized: If no guardian is set or caller is not the guardian
+    fn guardian_pause(env: Env) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify guardian authorization
+        let guardian: Address = env.storage().persistent().get(&ConfigKey::Guardian).ok_or(Error::Unauthorized)?;
+        guardian.require_auth();
+
+        // Set pause flag to halt all trading operations
+        Self::_set_paused(&env, &guardian, true);
+
+        Ok(())
+    }
+
+    /// Assigns the guardian role, which may only pause the contract.
+    /// 
+    /// # Arguments
+    /// * `guardian` - Address of the guardian (e.g. a monitoring bot)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_guardian(env: Env, guardian: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&guardian)?;
+
+        Self::_set_config(&env, &admin, symbol_short!("guardian"), &ConfigKey::Guardian, &guardian);
+
+        Ok(())
+    }
+
+    /// Resumes trading activities after a pause.
+    /// Only admin can unpause the contract.
+    /// 
+    /// # Security Consideration
+    /// - Admin should verify all issues are resolved before unpausing
+    /// - Existing trades continue normally after unpause
+    /// - New trading activities become available immediately
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of unpause operation
+    fn unpause(env: Env) -> Result<(), Error> {
+        // Verify admin authorization - only admin can unpause
+        let admin = Self::_require_admin(&env)?;
+
+        // A migrated or winding-down marketplace must stay halted
+        if Self::get_successor(env.clone()).is_some() || Self::get_wind_down(env.clone()).is_some() {
+            return Err(Error::Unauthorized);
+        }
+        
+        // Remove pause flag to resume trading operations
+        Self::_set_paused(&env, &admin, false);
+        
+        Ok(())
+    }
+
+    /// Starts the wind-down of a deprecated or compromised marketplace.
+    /// The contract is paused for good, and anyone can then settle each offer's escrow
+    /// with `emergency_refund`.
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidTradeStatus: If the wind-down has already started
+    fn begin_wind_down(env: Env) -> Result<(), Error> {
+        let admin = Self::_require_admin(&env)?;
+        if Self::get_wind_down(env.clone()).is_some() {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("winddown"), &ConfigKey::WindDown, &env.ledger().timestamp());
+        Self::_set_paused(&env, &admin, true);
+
+        Ok(())
+    }
+
+    /// Returns an offer's escrow and fee reserve to the seller during a wind-down.
+    /// Callable by anyone, since the funds can only go where the trade's state already points.
+    /// 
+    /// # Business Flow
+    /// 1. A payment-confirmed trade against the offer, including one still in its first-trade
+    ///    hold, is released to the buyer as usual, since both parties agreed the KES was paid
+    /// 2. Otherwise every open trade is cancelled, closing disputes without a ruling, and
+    ///    initiation deposits are returned to the buyers
+    /// 3. The escrow and fee reserve are refunded to the seller, and the offer is cancelled
+    /// 
+    /// An offer or open trade frozen by the compliance officer is left untouched: nothing
+    /// moves until the freeze is lifted, after which the refund can be retried.
+    /// 
+    /// # Arguments
+    /// * `offer_id` - The offer to refund; trades are refunded through their offer
+    /// 
+    /// # Returns
+    /// The amount of USDC returned to the seller, 0 if the escrow was released to the buyer
+    /// 
+    /// # Errors
+    /// - WindDownInactive: If the wind-down hasn't started
+    /// - OfferNotFound: If the offer doesn't exist or holds no escrow
+    /// - Frozen: If the offer or one of its open trades is frozen
+    /// - Unauthorized: If a token call is in progress
+    /// - TokenTransferFailed: If a release, refund or deposit transfer fails
+    fn emergency_refund(env: Env, offer_id: u64) -> Result<i128, Error> {
+        Self::_require_initialized(&env)?;
+
+        if Self::get_wind_down(env.clone()).is_none() {
+            return Err(Error::WindDownInactive);
+        }
+        Self::_require_not_executing(&env)?;
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        if !Self::_is_active_offer(&env, &offer.seller, offer_id) {
+            return Err(Error::OfferNotFound);
+        }
+
+        // A standing offer must not pull another round of escrow from the seller
+        let mut recurring: Map<u64, i128> = env.storage().instance().get(&DataKey::RecurringOffers).unwrap_or(Map::new(&env));
+        if recurring.remove(offer_id).is_some() {
+            env.storage().instance().set(&DataKey::RecurringOffers, &recurring);
+        }
+
+        // A compliance hold means neither party may be paid, so the escrow waits for the freeze to lift
+        Self::_require_not_frozen(&env, &DataKey::FrozenOffers, offer_id)?;
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut open: Vec<u64> = Vec::new(&env);
+        for (trade_id, trade) in trades.iter() {
+            if trade.offer_id != offer_id || state_machine::is_final(&trade.status) {
+                continue;
+            }
+            Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+            // Both parties confirmed the KES moved, so the escrow belongs to the buyer
+            if trade.status == TradeStatus::PaymentConfirmed {
+                Self::release_usdc(env.clone(), trade_id)?;
+                return Ok(0);
+            }
+            open.push_back(trade_id);
+        }
+
+        // Cancel the open trades; each is settled on its own, so the order doesn't matter
+        let mut disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap();
+        let mut queue: Vec<u64> = env.storage().instance().get(&DataKey::DisputeQueue).unwrap();
+        for trade_id in open.iter() {
+            let mut trade = trades.get(trade_id).unwrap();
+            transition(&env, &mut trade, TradeStatus::Cancelled)?;
+            trades.set(trade_id, trade.clone());
+            Self::_adjust_counter(&env, &DataKey::OpenTrades, -1);
+
+            // Keep the dispute record for audit, closed without a ruling
+            if let Some(mut dispute) = disputes.get(trade_id) {
+                if dispute.resolution == DisputeOutcome::Pending {
+                    dispute.resolution = DisputeOutcome::Closed;
+                    disputes.set(trade_id, dispute);
+                }
+                if let Some(index) = queue.first_index_of(trade_id) {
+                    queue.remove(index);
+                }
+            }
+            Self::_set_executing(&env, true);
+            let deposit = Self::_pay_initiation_deposit(&env, trade_id, &trade.buyer);
+            Self::_set_executing(&env, false);
+            deposit?;
+            Self::_publish_to_parties(&env, TRADE_CANCELLED, &trade.buyer, &trade.seller, (trade_id, env.current_contract_address()));
+        }
+        env.storage().instance().set(&DataKey::Trades, &trades);
+        env.storage().instance().set(&DataKey::Disputes, &disputes);
+        env.storage().instance().set(&DataKey::DisputeQueue, &queue);
+
+        // Return the escrowed USDC and any fee reserve to the seller
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        let refund_to = offer.refund_to.clone().unwrap_or(offer.seller.clone());
+        let refund_amount = offer.usdc_amount + Self::_offer_fee_reserve(&env, offer_id, offer.usdc_amount);
+        Self::_set_executing(&env, true);
+        let refund = Self::_ensure_liquid(&env, &usdc_client, refund_amount).map(|_| {
+            usdc_client.try_transfer(&env.current_contract_address(), &refund_to, &refund_amount).is_ok()
+        });
+        Self::_set_executing(&env, false);
+        if refund != Ok(true) {
+            log!(&env, "Failed to return {} to seller", refund_amount);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        Self::_clear_offer_settings(&env, offer_id);
+        Self::_remove_active_offer(&env, &offer.seller, offer_id, offer.usdc_amount);
+        Self::_set_reservation(&env, offer_id, None);
+        Self::_set_offer_status(&env, offer_id, OfferStatus::Cancelled);
+
+        // Proposals on an offer leaving the book can't be accepted, so their deposits go back
+        Self::_set_executing(&env, true);
+        Self::_clear_proposals(&env, offer_id);
+        Self::_set_executing(&env, false);
+
+        Self::_publish_to(&env, EMERGENCY_REFUND, &offer.seller, (offer_id, refund_amount));
+
+        Ok(refund_amount)
+    }
+
+    // ========== Disputes ==========
+
+    /// Raises a dispute for a trade when payment confirmation conflicts arise.
+    /// This function allows trade participants to escalate issues that cannot be resolved
+    /// through normal payment confirmation flow.
+    /// 
+    /// # Business Logic
+    /// - Either buyer or seller can raise a dispute
+    /// - Disputes can be raised on initiated or payment-confirmed trades
+    /// - Once disputed, trades require admin intervention to resolve
+    /// - Prevents automatic trade completion until dispute is resolved
+    /// 
+    /// # Security Features
+    /// - Only trade participants can raise disputes
+    /// - Validates trade exists and is in appropriate state
+    /// - Prevents abuse by limiting who can dispute
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade to dispute
+    /// * `caller` - The address raising the dispute (buyer or seller)
+    /// * `reason` - Short explanation of the dispute, at most 256 bytes
+    /// * `evidence` - Hashes of off-chain evidence supporting the dispute, at most 50 (may be empty)
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of dispute creation
+    /// 
+    /// # Errors
+    /// - InvalidAmount: If the reason is too long or there are too many evidence hashes
+    /// - TradeNotFound: If trade doesn't exist
+    /// - Unauthorized: If caller is not a trade participant
+    /// - InvalidTradeStatus: If trade is not in disputable state
+    fn raise_dispute(
+        env: Env,
+        trade_id: u64,
+        caller: Address,
+        reason: String,
+        evidence: Vec<BytesN<32>>,
+    ) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify the caller has signed this transaction
+        caller.require_auth();
+
+        // The reason and evidence are stored on the dispute record, so keep them bounded
+        if reason.len() > MAX_DISPUTE_REASON_LEN || evidence.len() > MAX_DISPUTE_EVIDENCE {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Retrieve and validate the trade
+        let mut trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let mut trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id)?;
+
+
+        // Security check: Only trade participants can raise disputes
+        if caller != trade.buyer && caller != trade.seller {
+            return Err(Error::Unauthorized);
+        }
+
+        // Business rule: Only initiated or payment-confirmed trades can be disputed
+        // Completed and cancelled trades cannot be disputed
+        if trade.status != TradeStatus::Initiated && trade.status != TradeStatus::PaymentConfirmed {
+            return Err(Error::InvalidTradeStatus);
+        }
+
+        // Update trade status to disputed
+        transition(&env, &mut trade, TradeStatus::Disputed)?;
+        trades.set(trade_id, trade.clone());
+        env.storage().instance().set(&DataKey::Trades, &trades);
+
+        Self::_open_dispute(&env, trade_id, &trade, &caller, reason, evidence);
+
+        Ok(())
+    }
+
+    /// Adds evidence to an open dispute during its evidence window.
+    /// 
+    /// # Business Logic
+    /// - Either participant can submit evidence hashes until the window closes
+    /// - Marking evidence complete ends that participant's submissions; once both
+    ///   participants have, the dispute can be resolved before the window closes
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the disputed trade
+    /// * `participant` - The buyer or seller submitting evidence (must sign transaction)
+    /// * `evidence` - Hashes of off-chain evidence to add (may be empty)
+    /// * `complete` - Whether the participant has nothing further to submit
+    /// 
+    /// # Errors
+    /// - TradeNotFound: If no dispute was raised for the trade
+    /// - Unauthorized: If caller is not a trade participant
+    /// - InvalidTradeStatus: If the dispute is already resolved
+    /// - EvidenceWindowClosed: If the window has closed or the participant already marked evidence complete
+    /// - InvalidAmount: If the dispute would hold more than 50 evidence hashes
+    fn submit_evidence(
+        env: Env,
+        trade_id: u64,
+        participant: Address,
+        evidence: Vec<BytesN<32>>,
+        complete: bool,
+    ) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify the participant has signed this transaction
+        participant.require_auth();
+
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+        let mut disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap();
+        let mut dispute = disputes.get(trade_id).ok_or(Error::TradeNotFound)?;
+
+        if participant != trade.buyer && participant != trade.seller {
+            return Err(Error::Unauthorized);
+        }
+        if dispute.resolution != DisputeOutcome::Pending {
+            return Err(Error::InvalidTradeStatus);
+        }
+        if env.ledger().timestamp() >= dispute.evidence_until || dispute.evidence_complete.contains(&participant) {
+            return Err(Error::EvidenceWindowClosed);
+        }
+        if dispute.evidence.len() + evidence.len() > MAX_DISPUTE_EVIDENCE {
+            return Err(Error::InvalidAmount);
+        }
+
+        dispute.evidence.append(&evidence);
+        if complete {
+            dispute.evidence_complete.push_back(participant.clone());
+        }
+        disputes.set(trade_id, dispute);
+        env.storage().instance().set(&DataKey::Disputes, &disputes);
+
+        Self::_publish_to_parties(&env, EVIDENCE_SUBMITTED, &trade.buyer, &trade.seller, (trade_id, participant, evidence, complete));
+
+        Ok(())
+    }
+
+    /// Resolves a disputed trade with admin intervention.
+    /// Only the admin can resolve disputes by choosing to release USDC to buyer or refund to seller.
+    /// 
+    /// # Business Logic
+    /// - Admin reviews dispute details off-chain
+    /// - Admin decides whether buyer or seller is correct
+    /// - USDC is transferred based on admin's resolution decision
+    /// - Fees are still collected on successful trades (release to buyer)
+    /// - No fees on refunds to seller
+    /// 
+    /// # Admin Authority
+    /// - Only admin can resolve disputes
+    /// - Admin decisions are final and irreversible
+    /// - Admin should have off-chain verification process
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the disputed trade to resolve
+    /// * `resolution` - The admin's decision (ReleaseToBuyer or RefundToSeller)
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of dispute resolution
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not the admin
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If trade is not in disputed state
+    /// - GracePeriodActive: If the evidence window is open and either party may still submit evidence
+    /// - TokenTransferFailed: If USDC transfer fails
+    /// - Unauthorized: If multisig admin is enabled (use `propose_admin_action`),
+    ///   or resolution is delegated to an arbitration contract
+    fn resolve_dispute(env: Env, trade_id: u64, resolution: DisputeResolution) -> Result<(), Error> {
+        // Verify admin authorization - only admin can resolve disputes
+        let admin = Self::_require_admin(&env)?;
+        Self::_require_single_admin(&env)?;
+        Self::_require_not_delegated(&env)?;
+
+        Self::_log_admin_action(&env, &admin, symbol_short!("resolve"), Bytes::new(&env), Self::_log_value(&env, &(trade_id, resolution.clone())));
+        Self::_resolve_dispute(env, trade_id, resolution, admin)
+    }
+
+    /// Applies the ruling of the arbitration contract disputes are delegated to.
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the disputed trade to resolve
+    /// * `resolution` - The arbitrator's decision
+    /// 
+    /// # Errors
+    /// - Unauthorized: If no arbitration contract is registered
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If trade is not in disputed state
+    /// - GracePeriodActive: If the evidence window is open and either party may still submit evidence
+    /// - TokenTransferFailed: If USDC transfer fails
+    fn submit_ruling(env: Env, trade_id: u64, resolution: DisputeResolution) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Only the registered arbitration contract may rule
+        let arbitrator = Self::get_arbitrator(env.clone()).ok_or(Error::Unauthorized)?;
+        arbitrator.require_auth();
+
+        Self::_resolve_dispute(env, trade_id, resolution, arbitrator)
+    }
+
+    /// Resolves a disputed trade assigned to the calling panel arbitrator.
+    /// 
+    /// # Arguments
+    /// * `arbitrator` - The panel arbitrator the dispute was assigned to (must sign transaction)
+    /// * `trade_id` - The ID of the disputed trade to resolve
+    /// * `resolution` - The arbitrator's decision
+    /// 
+    /// # Errors
+    /// - Unauthorized: If the dispute isn't assigned to the caller, or resolution is
+    ///   delegated to an arbitration contract
+    /// - TradeNotFound: If trade doesn't exist
+    /// - InvalidTradeStatus: If trade is not in disputed state
+    /// - GracePeriodActive: If the evidence window is open and either party may still submit evidence
+    /// - TokenTransferFailed: If USDC transfer fails
+    fn arbitrate_dispute(env: Env, arbitrator: Address, trade_id: u64, resolution: DisputeResolution) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        arbitrator.require_auth();
+        Self::_require_not_delegated(&env)?;
+
+        let disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap();
+        let dispute = disputes.get(trade_id).ok_or(Error::TradeNotFound)?;
+        if dispute.arbitrator != Some(arbitrator.clone()) {
+            return Err(Error::Unauthorized);
+        }
+
+        Self::_resolve_dispute(env.clone(), trade_id, resolution, arbitrator.clone())?;
+        Self::_accrue_arbitrator_fee(&env, &arbitrator);
+        Ok(())
+    }
+
+    /// Withdraws the fees a panel arbitrator has accrued for resolving disputes.
+    /// 
+    /// # Arguments
+    /// * `arbitrator` - The arbitrator the fees were accrued to (must sign transaction)
+    /// 
+    /// # Returns
+    /// The amount of USDC withdrawn
+    /// 
+    /// # Errors
+    /// - Unauthorized: If a token call is in progress
+    /// - InvalidAmount: If no fees are accrued to the arbitrator
+    /// - TokenTransferFailed: If the USDC transfer fails
+    fn claim_arbitrator_fees(env: Env, arbitrator: Address) -> Result<i128, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify the arbitrator has signed this transaction
+        arbitrator.require_auth();
+        Self::_require_not_executing(&env)?;
+
+        let mut arbitrator_fees: Map<Address, (u32, i128)> = env.storage().instance().get(&DataKey::ArbitratorFees)
+            .unwrap_or(Map::new(&env));
+        let (resolved, amount) = arbitrator_fees.get(arbitrator.clone()).unwrap_or((0, 0));
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Clear the balance before the transfer (checks-effects-interactions), keeping the resolution count
+        arbitrator_fees.set(arbitrator.clone(), (resolved, 0));
+        env.storage().instance().set(&DataKey::ArbitratorFees, &arbitrator_fees);
+
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        Self::_set_executing(&env, true);
+        let paid = Self::_ensure_liquid(&env, &usdc_client, amount).is_ok()
+            && usdc_client.try_transfer(&env.current_contract_address(), &arbitrator, &amount).is_ok();
+        Self::_set_executing(&env, false);
+        if !paid {
+            log!(&env, "Failed to pay {} arbitrator fees", amount);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        Self::_publish_to(&env, ARBITRATOR_PAID, &arbitrator, (amount,));
+
+        Ok(amount)
+    }
+
+    // ========== Relayed calls ==========
+
+    /// Creates an offer on behalf of a seller who has no XLM for fees.
+    /// 
+    /// # Arguments
+    /// * `relayer` - The allowlisted relayer submitting the transaction (must sign)
+    /// * Remaining arguments as for `create_offer`; the seller must sign an authorization entry.
+    ///   Refunds go to the seller.
+    /// 
+    /// # Errors
+    /// - Unauthorized: If the relayer isn't allowlisted
+    /// - Any error returned by `create_offer`
+    fn relay_create_offer(
+        env: Env,
+        relayer: Address,
+        seller: Address,
+        usdc_amount: i128,
+        kes_amount: i128,
+        terms_hash: Option<BytesN<32>>,
+        settlement: SettlementMode,
+    ) -> Result<u64, Error> {
+        Self::_require_initialized(&env)?;
+
+        Self::_require_relayer(&env, &relayer)?;
+        Self::create_offer(env, seller, usdc_amount, kes_amount, terms_hash, settlement, None, None, None)
+    }
+
+    /// Initiates a trade on behalf of a buyer who has no XLM for fees.
+    /// 
+    /// # Arguments
+    /// * `relayer` - The allowlisted relayer submitting the transaction (must sign)
+    /// * Remaining arguments as for `initiate_trade`; the buyer must sign an authorization entry.
+    ///   USDC is paid out to the buyer.
+    /// 
+    /// # Errors
+    /// - Unauthorized: If the relayer isn't allowlisted
+    /// - Any error returned by `initiate_trade`
+    fn relay_initiate_trade(
+        env: Env,
+        relayer: Address,
+        buyer: Address,
+        offer_id: u64,
+        operator: Option<Address>,
+        operator_fee_bps: u32,
+        external_ref: Option<BytesN<32>>,
+    ) -> Result<u64, Error> {
+        Self::_require_initialized(&env)?;
+
+        Self::_require_relayer(&env, &relayer)?;
+        Self::initiate_trade(env, buyer, offer_id, None, operator, operator_fee_bps, external_ref)
+    }
+
+    /// Confirms payment on behalf of a trade participant who has no XLM for fees.
+    /// 
+    /// # Arguments
+    /// * `relayer` - The allowlisted relayer submitting the transaction (must sign)
+    /// * `trade_id` - The ID of the trade
+    /// * `participant` - The buyer or seller confirming (must sign an authorization entry)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If the relayer isn't allowlisted
+    /// - Any error returned by `confirm_payment`
+    fn relay_confirm_payment(env: Env, relayer: Address, trade_id: u64, participant: Address) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        Self::_require_relayer(&env, &relayer)?;
+        Self::confirm_payment(env, trade_id, participant)
+    }
+
+    // ========== Administration ==========
+
+    /// Upgrades the contract to a new Wasm hash.
+    /// This function can only be called by the contract admin.
+    ///
+    /// # Arguments
+    /// * `new_wasm_hash` - The hash of the new contract Wasm to upgrade to.
+    ///
+    /// # Security
+    /// - Requires admin authorization.
+    /// - The new Wasm hash must be valid.
+    /// - Must go through `propose_admin_action` when multisig admin is enabled.
+    fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        let admin = Self::_require_admin(&env)?;
+        Self::_require_single_admin(&env)?;
+        Self::_upgrade(&env, &admin, new_wasm_hash);
+        Ok(())
+    }
+
+    /// Schedules escrow migration to a successor contract, or cancels it with None.
+    /// Starts a two-day timelock so users can exit before their escrow moves.
+    /// 
+    /// # Arguments
+    /// * `successor` - The successor deployment (see `migration::Successor`), or None
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin, or multisig admin is enabled
+    fn schedule_migration(env: Env, successor: Option<Address>) -> Result<(), Error> {
+        let admin = Self::_require_admin(&env)?;
+        Self::_require_single_admin(&env)?;
+
+        match successor {
+            Some(successor) => {
+                Self::_validate_address(&successor)?;
+                let eta = env.ledger().timestamp() + MIGRATION_DELAY;
+                Self::_set_config(&env, &admin, symbol_short!("migration"), &ConfigKey::PendingMigration, &(successor, eta));
+            }
+            None => Self::_remove_config(&env, &admin, symbol_short!("migration"), &ConfigKey::PendingMigration),
+        }
+
+        Ok(())
+    }
+
+    /// Moves the escrow of every active offer to the scheduled successor contract.
+    /// For upgrades that can't go through `upgrade`, e.g. storage layout changes.
+    /// 
+    /// # Business Flow
+    /// 1. Admin schedules the successor with `schedule_migration` and pauses the contract
+    /// 2. Once the timelock has passed, this transfers the escrow and fee reserves of all
+    ///    active offers, and hands their records and open trades to `import_escrow`
+    /// 3. The offers are delisted here and the contract can't be unpaused again
+    /// 
+    /// # Notes
+    /// - Parked payouts, the insurance fund and arbitrator fees stay in this contract and
+    ///   remain withdrawable
+    /// - Records stay readable here for audit
+    /// 
+    /// # Arguments
+    /// * `successor` - Must match the scheduled successor
+    /// 
+    /// # Returns
+    /// The amount of USDC transferred
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin, multisig admin is enabled, or `successor`
+    ///   isn't the scheduled one
+    /// - ContractNotPaused: If the contract isn't paused
+    /// - GracePeriodActive: If the timelock hasn't passed
+    /// - TokenTransferFailed: If the USDC transfer fails
+    fn migrate_escrow(env: Env, successor: Address) -> Result<i128, Error> {
+        let admin = Self::_require_admin(&env)?;
+        Self::_require_single_admin(&env)?;
+        Self::_require_not_executing(&env)?;
+
+        let (scheduled, eta): (Address, u64) = env.storage().persistent().get(&ConfigKey::PendingMigration)
+            .ok_or(Error::Unauthorized)?;
+        if scheduled != successor {
+            return Err(Error::Unauthorized);
+        }
+        if !Self::_is_paused(&env) {
+            return Err(Error::ContractNotPaused);
+        }
+        if env.ledger().timestamp() < eta {
+            return Err(Error::GracePeriodActive);
+        }
+
+        // Collect every listed offer together with its open trades
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let active_offers: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::ActiveOffers).unwrap();
+        let mut exported_offers = Vec::new(&env);
+        let mut amount: i128 = 0;
+        for offer_ids in active_offers.values() {
+            for offer_id in offer_ids.iter() {
+                if let Some(offer) = offers.get(offer_id) {
+                    amount += offer.usdc_amount + Self::_offer_fee_reserve(&env, offer_id, offer.usdc_amount);
+                    exported_offers.push_back((offer_id, offer));
+                }
+            }
+        }
+        let mut exported_trades = Vec::new(&env);
+        for (trade_id, trade) in trades.iter() {
+            if !state_machine::is_final(&trade.status) {
+                exported_trades.push_back((trade_id, trade));
+            }
+        }
+
+        // Delist before the transfer (checks-effects-interactions); the successor now owns the escrow
+        env.storage().instance().set(&DataKey::ActiveOffers, &Map::<Address, Vec<u64>>::new(&env));
+        env.storage().instance().remove(&DataKey::OfferActivity);
+        env.storage().instance().set(&DataKey::OfferCount, &0i128);
+        env.storage().instance().set(&DataKey::TotalEscrow, &0i128);
+        env.storage().persistent().remove(&ConfigKey::PendingMigration);
+        env.storage().persistent().set(&ConfigKey::Successor, &successor);
+
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        // Hold the guard across both calls so the successor can't reenter mid-migration
+        Self::_set_executing(&env, true);
+        let sent = Self::_ensure_liquid(&env, &usdc_client, amount).map(|_| {
+            usdc_client.try_transfer(&env.current_contract_address(), &successor, &amount).is_ok()
+        });
+        if sent != Ok(true) {
+            Self::_set_executing(&env, false);
+            log!(&env, "Failed to migrate {} to successor", amount);
+            return Err(Error::TokenTransferFailed);
+        }
+        SuccessorClient::new(&env, &successor).import_escrow(
+            &env.current_contract_address(),
+            &exported_offers,
+            &exported_trades,
+            &amount,
+        );
+        Self::_set_executing(&env, false);
+
+        Self::_log_admin_action(&env, &admin, symbol_short!("migrate"), Bytes::new(&env), Self::_log_value(&env, &(successor.clone(), amount)));
+        Self::_publish_to(&env, ESCROW_MIGRATED, &successor, (exported_offers.len(), exported_trades.len(), amount));
+
+        Ok(amount)
+    }
+
+    /// Updates the admin address to a new address.
+    /// This is a critical security function that transfers administrative control.
+    /// 
+    /// # Security Features
+    /// - Requires current admin authorization
+    /// - Requires new admin to sign transaction (prevents unauthorized transfers)
+    /// - Emits event for transparency and audit trail
+    /// - Immediate effect - new admin can perform admin functions right away
+    /// 
+    /// # Use Cases
+    /// - Transferring control to a new administrator
+    /// - Moving to a multi-sig admin address
+    /// - Emergency admin change for security reasons
+    /// 
+    /// # Arguments
+    /// * `new_admin` - The new admin address (must sign transaction)
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of admin update
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not current admin, or multisig admin is enabled
+    fn update_admin(env: Env, new_admin: Address) -> Result<(), Error> {
+        // Verify current admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_require_single_admin(&env)?;
+
+        Self::_update_admin(&env, &admin, new_admin)
+    }
+
+    /// Enables multisig admin by configuring an M-of-N signer set.
+    /// Once enabled, `update_admin`, `upgrade` and `resolve_dispute` must go through
+    /// `propose_admin_action` / `approve_action` / `execute_action`.
+    /// 
+    /// # Arguments
+    /// * `signers` - Addresses allowed to propose and approve admin actions
+    /// * `threshold` - Number of approvals required (1 to number of signers)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin, or multisig is already enabled
+    ///   (change signers with an `AdminAction::SetSigners` action instead)
+    /// - InvalidAmount: If threshold is out of range or signers contain duplicates
+    fn set_admin_signers(env: Env, signers: Vec<Address>, threshold: u32) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_require_single_admin(&env)?;
+
+        Self::_set_signers(&env, &admin, signers, threshold)
+    }
+
+    /// Proposes a high-impact admin action for multisig approval.
+    /// The proposer's approval is counted immediately.
+    /// 
+    /// # Arguments
+    /// * `signer` - The proposing signer (must sign transaction)
+    /// * `action` - The operation to perform once approved
+    /// 
+    /// # Returns
+    /// The unique ID of the pending action
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not in the signer set
+    fn propose_admin_action(env: Env, signer: Address, action: AdminAction) -> Result<u64, Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify signer authorization
+        Self::_require_signer(&env, &signer)?;
+
+        let mut actions: Map<u64, PendingAction> = env.storage().instance().get(&DataKey::AdminActions)
+            .unwrap_or(Map::new(&env));
+        let action_id: u64 = env.storage().instance().get(&DataKey::NextActionId).unwrap_or(0);
+
+        actions.set(action_id, PendingAction {
+            action: action.clone(),
+            proposer: signer.clone(),
+            approvals: Vec::from_array(&env, [signer]),
+            created_at: env.ledger().timestamp(),
+        });
+        env.storage().instance().set(&DataKey::AdminActions, &actions);
+        env.storage().instance().set(&DataKey::NextActionId, &(action_id + 1));
+
+        env.events().publish((ACTION_PROPOSED,), (action_id, action));
+
+        Ok(action_id)
+    }
+
+    /// Approves a pending admin action. Repeat approvals by the same signer are ignored.
+    /// 
+    /// # Arguments
+    /// * `signer` - The approving signer (must sign transaction)
+    /// * `action_id` - The ID of the pending action
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not in the signer set
+    /// - ActionNotFound: If the action doesn't exist or was already executed
+    fn approve_action(env: Env, signer: Address, action_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify signer authorization
+        Self::_require_signer(&env, &signer)?;
+
+        let mut actions: Map<u64, PendingAction> = env.storage().instance().get(&DataKey::AdminActions)
+            .unwrap_or(Map::new(&env));
+        let mut pending = actions.get(action_id).ok_or(Error::ActionNotFound)?;
+
+        if !pending.approvals.contains(&signer) {
+            pending.approvals.push_back(signer);
+            let approvals = pending.approvals.len();
+            actions.set(action_id, pending);
+            env.storage().instance().set(&DataKey::AdminActions, &actions);
+
+            env.events().publish((ACTION_APPROVED,), (action_id, approvals));
+        }
+
+        Ok(())
+    }
+
+    /// Executes an admin action once it has enough approvals.
+    /// Callable by anyone; the approvals are the authorization.
+    /// 
+    /// # Business Logic
+    /// - Approvals are counted against the current signer set, so removed signers don't count
+    /// - The pending record is removed before the operation runs
+    /// 
+    /// # Arguments
+    /// * `action_id` - The ID of the pending action
+    /// 
+    /// # Errors
+    /// - ActionNotFound: If the action doesn't exist or was already executed
+    /// - ThresholdNotMet: If the action lacks enough approvals
+    /// - Any error from the underlying operation
+    fn execute_action(env: Env, action_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        let mut actions: Map<u64, PendingAction> = env.storage().instance().get(&DataKey::AdminActions)
+            .unwrap_or(Map::new(&env));
+        let pending = actions.get(action_id).ok_or(Error::ActionNotFound)?;
+
+        let signers: Vec<Address> = env.storage().persistent().get(&ConfigKey::AdminSigners).unwrap_or(Vec::new(&env));
+        let threshold: u32 = env.storage().persistent().get(&ConfigKey::AdminThreshold).unwrap_or(0);
+        let valid_approvals = pending.approvals.iter().filter(|a| signers.contains(a)).count() as u32;
+        if signers.is_empty() || valid_approvals < threshold {
+            return Err(Error::ThresholdNotMet);
+        }
+
+        // Consume the action before running it
+        actions.remove(action_id);
+        env.storage().instance().set(&DataKey::AdminActions, &actions);
+
+        // The proposer stands in as the actor for actions approved by the signer set
+        let proposer = pending.proposer;
+        match pending.action {
+            AdminAction::UpdateAdmin(new_admin) => Self::_update_admin(&env, &proposer, new_admin)?,
+            AdminAction::Upgrade(new_wasm_hash) => Self::_upgrade(&env, &proposer, new_wasm_hash),
+            AdminAction::ResolveDispute(trade_id, resolution) => {
+                Self::_require_not_delegated(&env)?;
+                Self::_log_admin_action(&env, &proposer, symbol_short!("resolve"), Bytes::new(&env), Self::_log_value(&env, &(trade_id, resolution.clone())));
+                Self::_resolve_dispute(env.clone(), trade_id, resolution, Self::get_admin(env.clone())?)?
+            }
+            AdminAction::SetSigners(new_signers, new_threshold) => {
+                Self::_set_signers(&env, &proposer, new_signers, new_threshold)?
+            }
+        }
+
+        env.events().publish((ACTION_EXECUTED,), (action_id,));
+
+        Ok(())
+    }
+
+    /// Updates the fee collector address where trading fees are sent.
+    /// This allows admin to change where marketplace fees are collected.
+    /// 
+    /// # Business Logic
+    /// - Fee collector receives a percentage of each completed trade
+    /// - Can be set to treasury, DAO, or operational address
+    /// - Takes effect immediately for new trades
+    /// - Does not affect ongoing trades
+    /// 
+    /// # Arguments
+    /// * `new_fee_collector` - The new address to receive trading fees
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of fee collector update
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn update_fee_collector(env: Env, new_fee_collector: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        
+        // SECURITY FIX: Validate new fee collector address
+        Self::_validate_address(&new_fee_collector)?;
+        
+        // Update fee collector address in persistent storage
+        Self::_set_config(&env, &admin, symbol_short!("fee_coll"), &ConfigKey::FeeCollector, &new_fee_collector);
+        
+        Ok(())
+    }
+
+    /// Switches fees to a size-based curve where the rate falls as trades grow.
+    /// Distinct from the flat fee rate, which applies again once the curve is cleared.
+    /// 
+    /// # Business Logic
+    /// - Brackets must be sorted by strictly increasing `min_amount`, starting at 0
+    /// - Rates must not increase with size and may not exceed 10%
+    /// - An empty curve reverts to the flat fee rate
+    /// - Applies to trades settled after the update, including open ones
+    /// 
+    /// # Arguments
+    /// * `curve` - Up to 10 fee brackets
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin, or not the governance executor once one is set
+    /// - InvalidAmount: If the curve is malformed or a rate exceeds 10%
+    fn update_fee_curve(env: Env, curve: Vec<FeeBracket>) -> Result<(), Error> {
+        // Verify admin authorization, or the governance executor once parameters are decentralized
+        let authority = Self::_require_parameter_authority(&env)?;
+
+        if curve.len() > MAX_FEE_BRACKETS {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut previous: Option<FeeBracket> = None;
+        for bracket in curve.iter() {
+            Self::_validate_fee_rate(&env, bracket.rate)?;
+            let valid = match &previous {
+                None => bracket.min_amount == 0,
+                Some(prev) => bracket.min_amount > prev.min_amount && bracket.rate <= prev.rate,
+            };
+            if !valid {
+                return Err(Error::InvalidAmount);
+            }
+            previous = Some(bracket);
+        }
+
+        Self::_set_config(&env, &authority, symbol_short!("fee_curve"), &ConfigKey::FeeCurve, &curve);
+
+        Ok(())
+    }
+
+    /// Sets the maximum operator fee integrating wallets may add to a trade.
+    /// 
+    /// # Business Logic
+    /// - 0 disables operator fees (default)
+    /// - Applies to trades initiated after the update
+    /// 
+    /// # Arguments
+    /// * `new_cap` - Maximum operator fee in basis points (max 1000 = 10%)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If cap exceeds 10%
+    fn update_operator_fee_cap(env: Env, new_cap: u32) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if new_cap > MAX_FEE_RATE {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("op_feecap"), &ConfigKey::OperatorFeeCap, &new_cap);
+
+        Ok(())
+    }
+
+    /// Replaces the single fee collector with a weighted list of fee recipients.
+    /// Applies to the part of each fee not routed to the insurance fund.
+    /// 
+    /// # Business Logic
+    /// - Weights are basis points and must sum to exactly 10000
+    /// - An empty list reverts to the single fee collector
+    /// - Applies to `release_usdc` and dispute releases from the next settlement on
+    /// 
+    /// # Arguments
+    /// * `splits` - Up to 10 recipients with non-zero weights
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If a weight is zero, weights don't sum to 10000,
+    ///   a recipient is repeated, or there are too many recipients
+    fn update_fee_splits(env: Env, splits: Vec<FeeSplit>) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if splits.len() > MAX_FEE_SPLITS {
+            return Err(Error::InvalidAmount);
+        }
+
+        let mut total: u32 = 0;
+        let mut recipients: Vec<Address> = Vec::new(&env);
+        for split in splits.iter() {
+            Self::_validate_address(&split.recipient)?;
+            if split.weight == 0 || recipients.contains(&split.recipient) {
+                return Err(Error::InvalidAmount);
+            }
+            total = total.saturating_add(split.weight);
+            recipients.push_back(split.recipient);
+        }
+        if !splits.is_empty() && total != BASIS_POINTS_DIVISOR {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("fee_split"), &ConfigKey::FeeSplits, &splits);
+
+        Ok(())
+    }
+
+    /// Updates the trading fee rate charged on completed trades.
+    /// Fee rate is specified in basis points (1/100th of a percent).
+    /// 
+    /// # Fee Structure
+    /// - Basis points: 1 = 0.01%, 100 = 1%, 1000 = 10%
+    /// - Maximum allowed fee is 10% (1000 basis points)
+    /// - Reasonable marketplace fees are typically 0.1% - 1%
+    /// - Fees are only collected on successful trades
+    /// 
+    /// # Arguments
+    /// * `new_fee_rate` - New fee rate in basis points (max 1000 = 10%)
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of fee rate update
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin, or not the governance executor once one is set
+    /// - InvalidAmount: If fee rate exceeds 10%
+    fn update_fee_rate(env: Env, new_fee_rate: u32) -> Result<(), Error> {
+        // Verify admin authorization, or the governance executor once parameters are decentralized
+        let authority = Self::_require_parameter_authority(&env)?;
+        
+        // Validate fee rate is reasonable (max 10%) and within this instance's bounds
+        Self::_validate_fee_rate(&env, new_fee_rate)?;
+        
+        // Update fee rate in persistent storage
+        Self::_set_config(&env, &authority, symbol_short!("fee_rate"), &ConfigKey::FeeRate, &new_fee_rate);
+        
+        Ok(())
+    }
+
+    /// Registers the cKES token used for on-chain settlement.
+    /// Once registered, sellers may create offers whose KES leg settles on-chain.
+    /// 
+    /// # Business Logic
+    /// - Buyer escrows cKES at `initiate_trade`
+    /// - Both legs are swapped atomically, removing the off-chain trust gap
+    /// - Fees are charged on the USDC leg exactly as for off-chain trades
+    /// 
+    /// # Arguments
+    /// * `kes_token_id` - The contract address of the cKES token
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidTokenAddress: If the address is the USDC token itself
+    fn set_kes_token(env: Env, kes_token_id: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&kes_token_id)?;
+
+        // Both legs must be different assets
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        if kes_token_id == usdc_token_id {
+            return Err(Error::InvalidTokenAddress);
+        }
+
+        // Validate the address implements the token interface
+        let _ = token::Client::new(&env, &kes_token_id).decimals();
+
+        Self::_set_config(&env, &admin, symbol_short!("kes_token"), &ConfigKey::KesToken, &kes_token_id);
+
+        Ok(())
+    }
+
+    /// Adds a token to the registry with default settings: enabled, no limits, no fee override.
+    /// Decimals are read from the token contract.
+    /// 
+    /// # Arguments
+    /// * `token_id` - The token contract to register
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidTokenAddress: If the token is already registered
+    fn register_token(env: Env, token_id: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&token_id)?;
+
+        let mut configs: Map<Address, TokenConfig> = env.storage().persistent().get(&ConfigKey::TokenConfigs)
+            .unwrap_or(Map::new(&env));
+        if configs.contains_key(token_id.clone()) {
+            return Err(Error::InvalidTokenAddress);
+        }
+
+        // Reading decimals also validates the address implements the token interface
+        let decimals = token::Client::new(&env, &token_id).decimals();
+        let config = TokenConfig { decimals, min_amount: 0, max_amount: 0, fee_rate: None, enabled: true };
+        configs.set(token_id.clone(), config.clone());
+        env.storage().persistent().set(&ConfigKey::TokenConfigs, &configs);
+        Self::_log_admin_action(&env, &admin, symbol_short!("reg_token"), Bytes::new(&env), Self::_log_value(&env, &(token_id, config)));
+
+        Ok(())
+    }
+
+    /// Updates a registered token's limits, fee override and enabled flag.
+    /// Disabling a token stops new offers from using it; existing trades settle normally.
+    /// 
+    /// # Arguments
+    /// * `token_id` - The registered token
+    /// * `min_amount` - Smallest amount per trade in token base units, 0 = no minimum
+    /// * `max_amount` - Largest amount per trade in token base units, 0 = no maximum
+    /// * `fee_rate` - Fee rate in basis points overriding the flat rate, if any
+    /// * `enabled` - Whether new offers may use the token
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidTokenAddress: If the token isn't registered
+    /// - InvalidAmount: If the limits are negative or inverted, or the fee rate exceeds 10%
+    fn set_token_config(
+        env: Env,
+        token_id: Address,
+        min_amount: i128,
+        max_amount: i128,
+        fee_rate: Option<u32>,
+        enabled: bool,
+    ) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        let mut configs: Map<Address, TokenConfig> = env.storage().persistent().get(&ConfigKey::TokenConfigs)
+            .unwrap_or(Map::new(&env));
+        let mut config = configs.get(token_id.clone()).ok_or(Error::InvalidTokenAddress)?;
+        let old_value = Self::_log_value(&env, &(token_id.clone(), config.clone()));
+
+        if min_amount < 0 || max_amount < 0 || (max_amount > 0 && max_amount < min_amount) {
+            return Err(Error::InvalidAmount);
+        }
+        if let Some(rate) = fee_rate {
+            Self::_validate_fee_rate(&env, rate)?;
+        }
+
+        config.min_amount = min_amount;
+        config.max_amount = max_amount;
+        config.fee_rate = fee_rate;
+        config.enabled = enabled;
+        configs.set(token_id.clone(), config.clone());
+        env.storage().persistent().set(&ConfigKey::TokenConfigs, &configs);
+        Self::_log_admin_action(&env, &admin, symbol_short!("token_cfg"), old_value, Self::_log_value(&env, &(token_id, config)));
+
+        Ok(())
+    }
+
+    /// Registers the payment oracle allowed to attest off-chain KES payments.
+    /// 
+    /// # Use Cases
+    /// - M-Pesa callback relayer confirming payments on behalf of sellers
+    /// - Rotating the oracle key after a compromise
+    /// 
+    /// # Arguments
+    /// * `oracle` - The oracle address
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_payment_oracle(env: Env, oracle: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&oracle)?;
+
+        Self::_set_config(&env, &admin, symbol_short!("oracle"), &ConfigKey::PaymentOracle, &oracle);
+
+        Ok(())
+    }
+
+    /// Adds or removes a relayer from the gasless-trading allowlist.
+    /// 
+    /// # Use Cases
+    /// - Wallet backends sponsoring fees for mobile users without XLM
+    /// - Revoking a relayer that misbehaves or rotates keys
+    /// 
+    /// # Arguments
+    /// * `relayer` - The relayer address
+    /// * `allowed` - true to allowlist, false to remove
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_relayer(env: Env, relayer: Address, allowed: bool) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&relayer)?;
+
+        let mut relayers: Vec<Address> = env.storage().persistent().get(&ConfigKey::Relayers).unwrap_or(Vec::new(&env));
+        match (relayers.first_index_of(&relayer), allowed) {
+            (None, true) => relayers.push_back(relayer),
+            (Some(index), false) => { relayers.remove(index); }
+            _ => return Ok(()),
+        }
+        Self::_set_config(&env, &admin, symbol_short!("relayers"), &ConfigKey::Relayers, &relayers);
+
+        Ok(())
+    }
+
+    /// Registers the Ed25519 public key that signs buyer payment receipts.
+    /// 
+    /// # Use Cases
+    /// - Payment provider or relayer that issues signed receipts for KES transfers
+    /// - Rotating the attester key
+    /// 
+    /// # Arguments
+    /// * `public_key` - The attester's Ed25519 public key
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_receipt_attester(env: Env, public_key: BytesN<32>) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        Self::_set_config(&env, &admin, symbol_short!("attester"), &ConfigKey::ReceiptAttester, &public_key);
+
+        Ok(())
+    }
+
+    /// Updates the grace period between an oracle attestation and release.
+    /// 
+    /// # Business Logic
+    /// - Gives the seller time to dispute a wrong attestation
+    /// - Bounded to at most 24 hours so buyers aren't held hostage
+    /// 
+    /// # Arguments
+    /// * `grace_seconds` - Grace period in seconds (0 to 86400)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If grace period exceeds 24 hours
+    fn update_attestation_grace_period(env: Env, grace_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if grace_seconds > 86400 {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("att_grace"), &ConfigKey::AttestationGrace, &grace_seconds);
+
+        Ok(())
+    }
+
+    /// Enables or tunes auto-release of buyer-confirmed trades the seller never acts on.
+    /// 
+    /// # Business Logic
+    /// - 0 disables auto-release (default)
+    /// - Must be at least the trade expiration so sellers keep their normal confirmation time
+    /// - While enabled, buyer-confirmed trades can't be refunded through expiry;
+    ///   the seller has to dispute instead
+    /// 
+    /// # Arguments
+    /// * `window_seconds` - Seconds after the buyer's confirmation before anyone may release
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the window is non-zero but shorter than the trade expiration
+    fn update_auto_release_window(env: Env, window_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if window_seconds != 0 && window_seconds < Self::get_trade_expiration(env.clone()) {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("auto_rel"), &ConfigKey::AutoReleaseWindow, &window_seconds);
+
+        Ok(())
+    }
+
+    /// Sets the cooling-off period applied to each buyer's first completed trade.
+    /// 
+    /// # Business Logic
+    /// - 0 disables the hold (default)
+    /// - Guards against chargeback-style fraud from freshly funded M-Pesa lines
+    /// - Held trades are released with `release_held_trade` or disputed by the seller
+    /// - Seller-initiated releases and oracle-attested releases are not held
+    /// 
+    /// # Arguments
+    /// * `hold_seconds` - Seconds between the second confirmation and the release (max 7 days)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the hold exceeds the maximum
+    fn update_first_trade_hold(env: Env, hold_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if hold_seconds > MAX_FIRST_TRADE_HOLD {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("hold"), &ConfigKey::FirstTradeHold, &hold_seconds);
+
+        Ok(())
+    }
+
+    /// Sets the cooldown imposed on buyers who cancel or let trades expire.
+    /// 
+    /// # Business Logic
+    /// - 0 disables cooldowns and walkaway tracking (default)
+    /// - Each consecutive walkaway doubles the cooldown, up to 7 days
+    /// - Seller cancellations don't count against the buyer
+    /// - A completed trade clears the buyer's streak
+    /// 
+    /// # Arguments
+    /// * `base_seconds` - Cooldown after the first walkaway (max 7 days)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the cooldown exceeds the maximum
+    fn update_walkaway_cooldown(env: Env, base_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if base_seconds > MAX_WALKAWAY_COOLDOWN {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("cooldown"), &ConfigKey::WalkawayCooldown, &base_seconds);
+
+        Ok(())
+    }
+
+    /// Updates the share of trading fees routed into the insurance fund.
+    /// Share is specified in basis points of the fee, not of the trade amount.
+    /// 
+    /// # Business Logic
+    /// - 0 sends all fees to the fee collector (default)
+    /// - 10000 keeps all fees in the insurance fund
+    /// - Applies to fees collected after the update
+    /// 
+    /// # Arguments
+    /// * `new_share` - Insurance share in basis points of each fee (max 10000)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If share exceeds 100%
+    fn update_insurance_share(env: Env, new_share: u32) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        // Share cannot exceed the whole fee
+        if new_share > BASIS_POINTS_DIVISOR {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("ins_share"), &ConfigKey::InsuranceShare, &new_share);
+
+        Ok(())
+    }
+
+    /// Updates the fee a panel arbitrator accrues per dispute they resolve.
+    /// 
+    /// # Business Logic
+    /// - Paid out of the insurance fund, limited to what the fund holds at resolution
+    /// - Accrued fees are withdrawn with `claim_arbitrator_fees`
+    /// - Admin resolutions and arbitration-contract rulings accrue nothing
+    /// 
+    /// # Arguments
+    /// * `fee` - USDC per resolution, 0 = unpaid (default)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the fee is negative
+    fn update_arbitrator_fee(env: Env, fee: i128) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if fee < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("arb_fee"), &ConfigKey::ArbitratorFee, &fee);
+
+        Ok(())
+    }
+
+    /// Updates the reputation score deducted from the party a dispute is resolved against.
+    /// 
+    /// # Business Logic
+    /// - Defaults to 5, so one lost dispute outweighs five completed trades
+    /// - 0 still counts lost disputes without touching the score
+    /// - Lowered scores apply to offers' buyer requirements immediately
+    /// 
+    /// # Arguments
+    /// * `penalty` - Score deducted per dispute lost
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the penalty is negative
+    fn update_dispute_penalty(env: Env, penalty: i128) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if penalty < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("penalty"), &ConfigKey::DisputePenalty, &penalty);
+
+        Ok(())
+    }
+
+    /// Updates when repeat dispute losers are suspended.
+    /// 
+    /// # Business Logic
+    /// - An address losing more than `max_losses` disputes within `window` seconds is suspended
+    /// - Suspended addresses can't list offers or initiate trades until reinstated
+    /// - Open trades and offers of a suspended address settle normally
+    /// 
+    /// # Arguments
+    /// * `max_losses` - Dispute losses tolerated within the window, 0 = never suspend
+    /// * `window` - Window length in seconds
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the policy is enabled with a zero window
+    fn update_suspension_policy(env: Env, max_losses: u32, window: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if max_losses > 0 && window == 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("suspend"), &ConfigKey::SuspensionPolicy, &(max_losses, window));
+
+        Ok(())
+    }
+
+    /// Lifts a suspension, clearing the address's recent dispute losses.
+    /// Reputation and the lifetime `disputes_lost` count are kept.
+    /// 
+    /// # Arguments
+    /// * `address` - The suspended address
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the address is not suspended
+    fn reinstate(env: Env, address: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        let mut suspended: Map<Address, u64> = env.storage().instance().get(&DataKey::Suspended)
+            .unwrap_or(Map::new(&env));
+        if suspended.remove(address.clone()).is_none() {
+            return Err(Error::InvalidAmount);
+        }
+        env.storage().instance().set(&DataKey::Suspended, &suspended);
+
+        Self::_log_admin_action(&env, &admin, symbol_short!("reinstate"), Bytes::new(&env), Self::_log_value(&env, &address));
+        Self::_publish_to(&env, ADDRESS_REINSTATED, &address, ());
+
+        Ok(())
+    }
+
+    /// Updates how long both parties can submit evidence after a dispute is raised.
+    /// 
+    /// # Business Logic
+    /// - 0 disables the window: disputes can be resolved immediately (default)
+    /// - Resolution waits for the window to close, unless both parties mark their evidence complete
+    /// - Applies to disputes raised after the update
+    /// 
+    /// # Arguments
+    /// * `window_seconds` - Evidence window in seconds (max 14 days)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the window exceeds the maximum
+    fn update_evidence_window(env: Env, window_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if window_seconds > MAX_EVIDENCE_WINDOW {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("evidence"), &ConfigKey::EvidenceWindow, &window_seconds);
+
+        Ok(())
+    }
+
+    /// Assigns the compliance officer role.
+    /// The compliance officer can freeze individual trades and offers during AML reviews.
+    /// 
+    /// # Arguments
+    /// * `officer` - Address of the compliance officer
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_compliance_officer(env: Env, officer: Address) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&officer)?;
+
+        Self::_set_config(&env, &admin, symbol_short!("officer"), &ConfigKey::ComplianceOfficer, &officer);
+
+        Ok(())
+    }
+
+    /// Freezes a trade pending a compliance review.
+    /// Blocks every state transition on the trade without pausing the contract.
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The trade to freeze
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not the compliance officer
+    /// - TradeNotFound: If trade doesn't exist
+    fn freeze_trade(env: Env, trade_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify compliance officer authorization
+        Self::_require_compliance_officer(&env)?;
+
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+
+        Self::_set_frozen(&env, &DataKey::FrozenTrades, trade_id, true);
+        Self::_publish_to_parties(&env, TRADE_FROZEN, &trade.buyer, &trade.seller, (trade_id,));
+
+        Ok(())
+    }
+
+    /// Lifts a compliance freeze from a trade.
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The trade to unfreeze
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not the compliance officer
+    fn unfreeze_trade(env: Env, trade_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify compliance officer authorization
+        Self::_require_compliance_officer(&env)?;
+
+        Self::_set_frozen(&env, &DataKey::FrozenTrades, trade_id, false);
+        if let Some(trade) = Self::get_trade(env.clone(), trade_id) {
+            Self::_publish_to_parties(&env, TRADE_UNFROZEN, &trade.buyer, &trade.seller, (trade_id,));
+        }
+
+        Ok(())
+    }
+
+    /// Freezes an offer pending a compliance review.
+    /// Blocks new trades against the offer and its cancellation.
+    /// 
+    /// # Arguments
+    /// * `offer_id` - The offer to freeze
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not the compliance officer
+    /// - OfferNotFound: If offer doesn't exist
+    fn freeze_offer(env: Env, offer_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify compliance officer authorization
+        Self::_require_compliance_officer(&env)?;
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+
+        Self::_set_frozen(&env, &DataKey::FrozenOffers, offer_id, true);
+        if matches!(offer.status, OfferStatus::Active | OfferStatus::Reserved) {
+            Self::_set_offer_status(&env, offer_id, OfferStatus::Paused);
+        }
+        Self::_publish_to(&env, OFFER_FROZEN, &offer.seller, (offer_id,));
+
+        Ok(())
+    }
+
+    /// Lifts a compliance freeze from an offer.
+    /// 
+    /// # Arguments
+    /// * `offer_id` - The offer to unfreeze
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not the compliance officer
+    fn unfreeze_offer(env: Env, offer_id: u64) -> Result<(), Error> {
+        Self::_require_initialized(&env)?;
+
+        // Verify compliance officer authorization
+        Self::_require_compliance_officer(&env)?;
+
+        Self::_set_frozen(&env, &DataKey::FrozenOffers, offer_id, false);
+        if let Some(offer) = Self::get_offer(env.clone(), offer_id) {
+            if offer.status == OfferStatus::Paused {
+                let status = if offer.reserved_by.is_some() { OfferStatus::Reserved } else { OfferStatus::Active };
+                Self::_set_offer_status(&env, offer_id, status);
+            }
+            Self::_publish_to(&env, OFFER_UNFROZEN, &offer.seller, (offer_id,));
+        }
+
+        Ok(())
+    }
+
+    /// Updates the threshold above which completed trades emit a `large_trd` event.
+    /// Lets compliance pipelines subscribe to large trades only.
+    /// 
+    /// # Arguments
+    /// * `threshold` - USDC amount in the token's smallest unit; 0 disables reporting
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If threshold is negative
+    fn update_large_trade_threshold(env: Env, threshold: i128) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if threshold < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("large_trd"), &ConfigKey::LargeTradeThreshold, &threshold);
+
+        Ok(())
+    }
+
+    /// Sets the refundable native XLM deposit buyers post when initiating a trade.
+    /// 
+    /// # Business Logic
+    /// - Returned to the buyer when the trade completes, is cancelled or is resolved
+    /// - Paid to the caller of `resolve_expired_trade` when the trade expires, so the
+    ///   buyer who let it lapse funds the cleanup rather than the seller or protocol
+    /// - Not charged on on-chain settlement trades, which settle in the same call
+    /// - Price proposals post it when proposed, refunded on rejection or withdrawal
+    /// - Open trades keep the deposit they were initiated with
+    /// 
+    /// # Arguments
+    /// * `xlm_token` - The native XLM Stellar Asset Contract address
+    /// * `amount` - Deposit in stroops; 0 disables the deposit
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If amount is negative
+    fn set_initiation_deposit(env: Env, xlm_token: Address, amount: i128) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if amount < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        if amount == 0 {
+            Self::_remove_config(&env, &admin, symbol_short!("init_dep"), &ConfigKey::InitiationDeposit);
+        } else {
+            Self::_validate_address(&xlm_token)?;
+            Self::_set_config(&env, &admin, symbol_short!("init_dep"), &ConfigKey::InitiationDeposit, &(xlm_token, amount));
+        }
+
+        Ok(())
+    }
+
+    /// Updates the per-address daily cap on traded USDC.
+    /// 
+    /// # Business Logic
+    /// - Sellers are counted when escrowing offers, buyers when initiating trades
+    /// - Usage resets on ledger-time day boundaries
+    /// - 0 disables the cap (default)
+    /// 
+    /// # Arguments
+    /// * `daily_limit` - Maximum USDC per address per day, in the token's smallest unit
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If limit is negative
+    fn update_daily_limit(env: Env, daily_limit: i128) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if daily_limit < 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("daily_lim"), &ConfigKey::DailyLimit, &daily_limit);
+
+        Ok(())
+    }
+
+    /// Updates the reward points emission rate for completed trades.
+    /// 
+    /// # Business Logic
+    /// - Buyer and seller each earn `rate` points per whole USDC traded
+    /// - 0 disables emission (default)
+    /// - Applies to trades completed after the update
+    /// 
+    /// # Arguments
+    /// * `new_rate` - Points per whole USDC of completed volume
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn update_points_rate(env: Env, new_rate: u32) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        Self::_set_config(&env, &admin, symbol_short!("points"), &ConfigKey::PointsRate, &new_rate);
+
+        Ok(())
+    }
+
+    /// Pays compensation out of the insurance fund.
+    /// Used to make victims whole when a dispute reveals fraud that escrow alone can't cover.
+    /// 
+    /// # Admin Only
+    /// - Requires admin authorization
+    /// - Can only pay out what the insurance fund holds
+    /// 
+    /// # Arguments
+    /// * `recipient` - The address to compensate
+    /// * `amount` - Amount of USDC to pay out of the fund
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If amount is not positive or exceeds the fund balance
+    /// - TokenTransferFailed: If the USDC transfer fails
+    fn compensate(env: Env, recipient: Address, amount: i128) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&recipient)?;
+
+        let fund: i128 = env.storage().persistent().get(&ConfigKey::InsuranceFund).unwrap_or(0);
+        if amount <= 0 || amount > fund {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Debit the fund before the transfer (checks-effects-interactions)
+        env.storage().persistent().set(&ConfigKey::InsuranceFund, &(fund - amount));
+
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        Self::_ensure_liquid(&env, &usdc_client, amount)?;
+        if usdc_client.try_transfer(&env.current_contract_address(), &recipient, &amount).is_err() {
+            log!(&env, "Failed to pay {} insurance compensation", amount);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        Self::_publish_to(&env, INSURANCE_COMPENSATED, &recipient, (amount,));
+        Self::_log_admin_action(&env, &admin, symbol_short!("compens"), Bytes::new(&env), Self::_log_value(&env, &(recipient, amount)));
+
+        Ok(())
+    }
+
+    /// Sweeps USDC held above the contract's liabilities to the given address.
+    /// Recovers tokens that were accidentally sent directly to the contract.
+    /// 
+    /// # Admin Only
+    /// - Requires admin authorization
+    /// - Only the surplus reported by `verify_escrow` can be swept; escrow is never touched
+    /// 
+    /// # Arguments
+    /// * `to` - The address to receive the surplus
+    /// 
+    /// # Returns
+    /// The amount of USDC swept
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If there is no surplus to sweep
+    /// - TokenTransferFailed: If the USDC transfer fails
+    fn sweep_surplus(env: Env, to: Address) -> Result<i128, Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&to)?;
+
+        let surplus = Self::verify_escrow(env.clone())?;
+        if surplus <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        Self::_ensure_liquid(&env, &usdc_client, surplus)?;
+        if usdc_client.try_transfer(&env.current_contract_address(), &to, &surplus).is_err() {
+            log!(&env, "Failed to sweep surplus {}", surplus);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        Self::_publish_to(&env, SURPLUS_SWEPT, &to, (surplus,));
+        Self::_log_admin_action(&env, &admin, symbol_short!("sweep"), Bytes::new(&env), Self::_log_value(&env, &(to, surplus)));
+
+        Ok(surplus)
+    }
+
+    /// Switches protocol fee collection on or off for this instance.
+    /// Operator fees chosen by integrators are unaffected.
+    /// 
+    /// # Arguments
+    /// * `enabled` - Whether protocol fees are collected on completed trades
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_fees_enabled(env: Env, enabled: bool) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        Self::_set_config(&env, &admin, symbol_short!("fees_on"), &ConfigKey::FeesEnabled, &enabled);
+
+        Ok(())
+    }
+
+    /// Hands fee and trade-limit parameters to a governance executor, e.g. a token-voting
+    /// contract. Once set, `update_fee_rate`, `update_fee_curve` and `update_trade_limits`
+    /// accept updates only from the executor.
+    /// 
+    /// # Admin Only
+    /// - Requires admin authorization
+    /// - Handing control back requires the executor's authorization too, so the admin
+    ///   can't unilaterally revoke a decentralized setup
+    /// 
+    /// # Arguments
+    /// * `executor` - The governance executor address, or None to return control to the admin
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_governance_executor(env: Env, executor: Option<Address>) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        // The current executor must agree to any change of parameter authority.
+        // An executor that is also the admin has already signed; a second require_auth
+        // would need a second signature from the same (possibly smart) wallet.
+        if let Some(current) = Self::get_governance_executor(env.clone()) {
+            if current != admin {
+                current.require_auth();
+            }
+        }
+
+        match executor {
+            Some(executor) => {
+                Self::_validate_address(&executor)?;
+                Self::_set_config(&env, &admin, symbol_short!("executor"), &ConfigKey::GovernanceExecutor, &executor);
+            }
+            None => Self::_remove_config(&env, &admin, symbol_short!("executor"), &ConfigKey::GovernanceExecutor),
+        }
+
+        Ok(())
+    }
+
+    /// Delegates dispute resolution to an external arbitration contract, or returns it to the admin.
+    /// 
+    /// # Admin Only
+    /// - Requires admin authorization
+    /// - While delegated, new disputes are forwarded to the arbitrator and only its
+    ///   `submit_ruling` callback can resolve them
+    /// - Clearing the arbitrator hands open disputes back to the admin
+    /// 
+    /// # Arguments
+    /// * `arbitrator` - The arbitration contract (see `arbitration::Arbitration`), or None
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_arbitrator(env: Env, arbitrator: Option<Address>) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        match arbitrator {
+            Some(arbitrator) => {
+                Self::_validate_address(&arbitrator)?;
+                Self::_set_config(&env, &admin, symbol_short!("arbiter"), &ConfigKey::Arbitrator, &arbitrator);
+            }
+            None => Self::_remove_config(&env, &admin, symbol_short!("arbiter"), &ConfigKey::Arbitrator),
+        }
+
+        Ok(())
+    }
+
+    /// Adds or removes an arbitrator from the dispute panel.
+    /// 
+    /// # Business Logic
+    /// - New disputes are assigned to panel members in round-robin order
+    /// - Only the assigned arbitrator (or the admin) can resolve a dispute
+    /// - Removing an arbitrator doesn't reassign disputes already assigned to them;
+    ///   the admin can still resolve those
+    /// 
+    /// # Arguments
+    /// * `arbitrator` - The arbitrator address
+    /// * `allowed` - true to add to the panel, false to remove
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn set_panel_arbitrator(env: Env, arbitrator: Address, allowed: bool) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_validate_address(&arbitrator)?;
+
+        let mut panel = Self::get_arbitrator_panel(env.clone());
+        match (panel.first_index_of(&arbitrator), allowed) {
+            (None, true) => panel.push_back(arbitrator),
+            (Some(index), false) => { panel.remove(index); }
+            _ => return Ok(()),
+        }
+        Self::_set_config(&env, &admin, symbol_short!("panel"), &ConfigKey::ArbitratorPanel, &panel);
+
+        Ok(())
+    }
+
+    /// Sets or clears the yield adapter that idle escrow can be deposited with.
+    /// 
+    /// # Admin Only
+    /// - Requires admin authorization
+    /// - The current adapter must hold no principal before it can be replaced
+    /// 
+    /// # Arguments
+    /// * `adapter` - The adapter contract (see `yield_adapter::YieldAdapter`), or None to disable
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If principal is still deposited with the current adapter
+    fn set_yield_adapter(env: Env, adapter: Option<Address>) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        let deposited: i128 = env.storage().instance().get(&DataKey::YieldDeposited).unwrap_or(0);
+        if deposited > 0 {
+            return Err(Error::InvalidAmount);
+        }
+
+        match adapter {
+            Some(adapter) => {
+                Self::_validate_address(&adapter)?;
+                Self::_set_config(&env, &admin, symbol_short!("yield_adp"), &ConfigKey::YieldAdapter, &adapter);
+            }
+            None => Self::_remove_config(&env, &admin, symbol_short!("yield_adp"), &ConfigKey::YieldAdapter),
+        }
+
+        Ok(())
+    }
+
+    /// Deposits idle escrow with the yield adapter.
+    /// Deposits are withdrawn just in time whenever a release or refund needs them.
+    /// 
+    /// # Admin Only
+    /// - Requires admin authorization
+    /// - Can only deposit USDC the contract currently holds
+    /// 
+    /// # Arguments
+    /// * `amount` - Amount of USDC to deposit
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidTokenAddress: If no yield adapter is set
+    /// - InvalidAmount: If amount is not positive or exceeds the USDC on hand
+    /// - TokenTransferFailed: If the transfer to the adapter fails
+    fn deposit_to_yield(env: Env, amount: i128) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        Self::_require_not_executing(&env)?;
+
+        let adapter: Address = env.storage().persistent().get(&ConfigKey::YieldAdapter).ok_or(Error::InvalidTokenAddress)?;
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        let contract = env.current_contract_address();
+        if amount <= 0 || amount > usdc_client.balance(&contract) {
+            return Err(Error::InvalidAmount);
+        }
+
+        // Record the principal before handing funds to the adapter
+        let deposited: i128 = env.storage().instance().get(&DataKey::YieldDeposited).unwrap_or(0);
+        env.storage().instance().set(&DataKey::YieldDeposited, &(deposited + amount));
+
+        Self::_set_executing(&env, true);
+        let moved = usdc_client.try_transfer(&contract, &adapter, &amount).is_ok()
+            && YieldAdapterClient::new(&env, &adapter).try_deposit(&contract, &amount).is_ok();
+        Self::_set_executing(&env, false);
+        if !moved {
+            log!(&env, "Failed to deposit {} with yield adapter", amount);
+            return Err(Error::TokenTransferFailed);
+        }
+        Self::_log_admin_action(
+            &env,
+            &admin,
+            symbol_short!("yield_dep"),
+            Self::_log_value(&env, &deposited),
+            Self::_log_value(&env, &(deposited + amount)),
+        );
+
+        Ok(())
+    }
+
+    /// Withdraws yield accrued on deposited escrow into the insurance fund.
+    /// Permissionless, so anyone can keep the fund topped up.
+    /// 
+    /// # Returns
+    /// The amount of yield harvested (0 if nothing has accrued)
+    /// 
+    /// # Errors
+    /// - InvalidTokenAddress: If no yield adapter is set
+    /// - TokenTransferFailed: If the adapter withdrawal fails or comes back short
+    fn harvest_yield(env: Env) -> Result<i128, Error> {
+        Self::_require_initialized(&env)?;
+
+        Self::_require_not_executing(&env)?;
+
+        let adapter: Address = env.storage().persistent().get(&ConfigKey::YieldAdapter).ok_or(Error::InvalidTokenAddress)?;
+        let adapter_client = YieldAdapterClient::new(&env, &adapter);
+        let contract = env.current_contract_address();
+        let deposited: i128 = env.storage().instance().get(&DataKey::YieldDeposited).unwrap_or(0);
+        let accrued = adapter_client.balance(&contract) - deposited;
+        if accrued <= 0 {
+            return Ok(0);
+        }
+
+        let usdc_token_id: Address = env.storage().persistent().get(&ConfigKey::UsdcToken).unwrap();
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        let on_hand = usdc_client.balance(&contract);
+        Self::_set_executing(&env, true);
+        let withdrawn = adapter_client.try_withdraw(&contract, &accrued).is_ok();
+        Self::_set_executing(&env, false);
+        if !withdrawn || usdc_client.balance(&contract) - on_hand != accrued {
+            log!(&env, "Failed to harvest {} of yield", accrued);
+            return Err(Error::TokenTransferFailed);
+        }
+
+        // Yield backs the insurance fund rather than any one seller
+        let fund: i128 = env.storage().persistent().get(&ConfigKey::InsuranceFund).unwrap_or(0);
+        env.storage().persistent().set(&ConfigKey::InsuranceFund, &(fund + accrued));
+
+        env.events().publish((YIELD_HARVESTED,), (accrued,));
+
+        Ok(accrued)
+    }
+
+    /// Updates the minimum and maximum trade amounts for USDC trades.
+    /// These limits help prevent spam trades and excessive exposure.
+    /// 
+    /// # Business Logic
+    /// - Minimum amount prevents spam with tiny trades
+    /// - Maximum amount limits exposure per trade
+    /// - Amounts are in USDC with 6 decimal places
+    /// - Applies to new offers only, existing offers unchanged
+    /// 
+    /// # Arguments
+    /// * `min_amount` - Minimum USDC amount for trades (in the token's smallest unit)
+    /// * `max_amount` - Maximum USDC amount for trades (in the token's smallest unit)
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of limits update
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin, or not the governance executor once one is set
+    /// - InvalidAmount: If amounts are invalid or min > max
+    fn update_trade_limits(env: Env, min_amount: i128, max_amount: i128) -> Result<(), Error> {
+        // Verify admin authorization, or the governance executor once parameters are decentralized
+        let authority = Self::_require_parameter_authority(&env)?;
+        
+        // Validate amount parameters
+        if min_amount <= 0 || max_amount <= 0 || min_amount > max_amount {
+            return Err(Error::InvalidAmount);
+        }
+        
+        // SECURITY FIX: Additional bounds checking to prevent extreme values
+        // Maximum reasonable amount is 1 trillion USDC, scaled to the token's decimals
+        if max_amount > MAX_REASONABLE_UNITS * Self::_token_unit(&env) {
+            return Err(Error::InvalidAmount);
+        }
+        
+        // Update trade limits in persistent storage
+        let old_limits = Self::get_trade_limits(env.clone());
+        env.storage().persistent().set(&ConfigKey::MinTradeAmount, &min_amount);
+        env.storage().persistent().set(&ConfigKey::MaxTradeAmount, &max_amount);
+        Self::_log_admin_action(
+            &env,
+            &authority,
+            symbol_short!("trd_lims"),
+            Self::_log_value(&env, &old_limits),
+            Self::_log_value(&env, &(min_amount, max_amount)),
+        );
+        
+        Ok(())
+    }
+
+    /// Updates the KES-side limits applied to fiat offers.
+    /// Swap offers are bounded by the token registry instead.
+    /// 
+    /// # Business Logic
+    /// - The KES minimum keeps offers from pricing the fiat side at dust
+    /// - The rate range catches amounts mistyped by orders of magnitude
+    /// - Applies to new offers only, existing offers unchanged
+    /// 
+    /// # Arguments
+    /// * `min_kes_amount` - Minimum KES amount per offer, 0 = no minimum
+    /// * `min_rate` - Minimum implied rate (same format as `get_offer_rate`), 0 = unbounded
+    /// * `max_rate` - Maximum implied rate (same format as `get_offer_rate`), 0 = unbounded
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If a value is negative or the rate range is inverted
+    fn update_fiat_limits(env: Env, min_kes_amount: i128, min_rate: i128, max_rate: i128) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if min_kes_amount < 0 || min_rate < 0 || max_rate < 0 || (max_rate > 0 && max_rate < min_rate) {
+            return Err(Error::InvalidAmount);
+        }
+
+        let old_limits = Self::get_fiat_limits(env.clone());
+        env.storage().persistent().set(&ConfigKey::MinKesAmount, &min_kes_amount);
+        env.storage().persistent().set(&ConfigKey::RateBounds, &(min_rate, max_rate));
+        Self::_log_admin_action(
+            &env,
+            &admin,
+            symbol_short!("fiat_lims"),
+            Self::_log_value(&env, &old_limits),
+            Self::_log_value(&env, &(min_kes_amount, min_rate, max_rate)),
+        );
+
+        Ok(())
+    }
+
+    /// Updates the caps on active offers, bounding instance storage growth and query cost.
+    /// 
+    /// # Business Logic
+    /// - The total cap limits active offers across the marketplace
+    /// - The per-seller cap limits ladders and direct escrows from one seller
+    /// - Applies to new listings only; recurring relists replace a filled offer and are exempt
+    /// 
+    /// # Arguments
+    /// * `max_total` - Maximum active offers overall, 0 = unlimited
+    /// * `max_per_seller` - Maximum active offers per seller, 0 = unlimited
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn update_offer_caps(env: Env, max_total: u32, max_per_seller: u32) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        Self::_set_config(&env, &admin, symbol_short!("offr_caps"), &ConfigKey::OfferCaps, &(max_total, max_per_seller));
+
+        Ok(())
+    }
+
+    /// Switches how new offer and trade IDs are assigned.
+    /// 
+    /// # Business Logic
+    /// - Sequential IDs (default) count up from 0 and are easy to enumerate
+    /// - Hashed IDs take the first 8 bytes of sha256(owner, nonce, ledger sequence),
+    ///   where the owner is the seller for offers and the buyer for trades
+    ///   and the nonce is the owner's own count from `get_id_nonce`
+    /// - Hashed IDs stop callers walking offers and trades by ID and keep IDs from revealing
+    ///   marketplace volume; the next-ID getters go quiet while they are on. They are not a
+    ///   secret: contract storage and events are public, so anyone indexing the ledger still sees every ID
+    /// - The counters keep advancing under both schemes, so switching back and forth never reuses an ID
+    /// - Existing IDs are untouched; `find_offer_id` and `find_trade_id` resolve either scheme
+    /// 
+    /// # Arguments
+    /// * `enabled` - Whether new IDs are hashed
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn update_hashed_ids(env: Env, enabled: bool) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        // The flag sits beside the ID counters in instance storage, so log it by hand
+        let old_value = Self::_log_value(&env, &Self::get_hashed_ids(env.clone()));
+        env.storage().instance().set(&DataKey::HashedIds, &enabled);
+        Self::_log_admin_action(&env, &admin, symbol_short!("hash_ids"), old_value, Self::_log_value(&env, &enabled));
+
+        Ok(())
+    }
+
+    /// Updates the ceiling on concurrent open trades across the marketplace.
+    /// A safety valve for abuse incidents: new trades are rejected while the ceiling is reached.
+    /// 
+    /// # Business Logic
+    /// - Open trades are initiated, payment-confirmed, or disputed
+    /// - Existing trades are unaffected and settle normally
+    /// - Lowering the ceiling below the current count only blocks new trades
+    /// 
+    /// # Arguments
+    /// * `max_open_trades` - Maximum open trades, 0 = unlimited
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    fn update_max_open_trades(env: Env, max_open_trades: u32) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        Self::_set_config(&env, &admin, symbol_short!("max_open"), &ConfigKey::MaxOpenTrades, &max_open_trades);
+
+        Ok(())
+    }
+
+    /// Updates the trade expiration time for new trades.
+    /// This controls how long buyers have to confirm payment before trades expire.
+    /// 
+    /// # Business Logic
+    /// - Expired trades automatically return USDC to seller
+    /// - Shorter times reduce seller risk but may rush buyers
+    /// - Longer times give buyers more flexibility but increase seller risk
+    /// - Typical values: 10 minutes to 24 hours
+    /// 
+    /// # Arguments
+    /// * `expiration_seconds` - New expiration time in seconds (60 to 86400)
+    /// 
+    /// # Returns
+    /// Result indicating success or failure of expiration update
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If expiration is outside allowed range
+    fn update_trade_expiration(env: Env, expiration_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+        
+        // Validate expiration time is reasonable (1 minute to 24 hours)
+        if expiration_seconds < 60 || expiration_seconds > 86400 { // Min 1 minute, max 24 hours
+            return Err(Error::InvalidAmount);
+        }
+        
+        // Update trade expiration in persistent storage
+        Self::_set_config(&env, &admin, symbol_short!("trade_exp"), &ConfigKey::TradeExpiration, &expiration_seconds);
+        
+        Ok(())
+    }
+
+    /// Updates the grace period after a trade's nominal deadline.
+    /// 
+    /// # Business Logic
+    /// - During the grace period confirmations are still accepted and
+    ///   `resolve_expired_trade` is rejected
+    /// - Absorbs clock skew and submission latency, so trades aren't expired seconds
+    ///   after a legitimate confirmation was sent
+    /// - Deadlines shown to clients stay nominal; 0 disables the grace period (default)
+    /// 
+    /// # Arguments
+    /// * `grace_seconds` - Grace period in seconds (max 10 minutes)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the grace period exceeds the maximum
+    fn update_expiry_grace(env: Env, grace_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if grace_seconds > MAX_EXPIRY_GRACE {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("exp_grace"), &ConfigKey::ExpiryGrace, &grace_seconds);
+
+        Ok(())
+    }
+
+    /// Updates how long completed and cancelled records are kept before they can be pruned.
+    /// 
+    /// # Business Logic
+    /// - Operators set this to match their record-keeping obligations
+    /// - Applies to pruning from the moment of the update, including existing records
+    /// - Pruned records remain available off-chain through archival events
+    /// 
+    /// # Arguments
+    /// * `retention_seconds` - Retention period in seconds (max 10 years)
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If retention exceeds the maximum
+    fn update_record_retention(env: Env, retention_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if retention_seconds > MAX_RECORD_RETENTION {
+            return Err(Error::InvalidAmount);
+        }
+
+        Self::_set_config(&env, &admin, symbol_short!("retention"), &ConfigKey::RecordRetention, &retention_seconds);
+
+        Ok(())
+    }
+
+    /// Sets how long an offer may go without trade activity before anyone can expire it.
+    /// 
+    /// # Business Logic
+    /// - Expired offers are refunded to their seller through `expire_offer`
+    /// - 0 disables expiry (default)
+    /// - Offers get at least one full TTL from the update before they can be expired
+    /// 
+    /// # Arguments
+    /// * `ttl_seconds` - TTL in seconds, 0 or between 1 day and 1 year
+    /// 
+    /// # Errors
+    /// - Unauthorized: If caller is not admin
+    /// - InvalidAmount: If the TTL is outside the allowed range
+    fn update_offer_ttl(env: Env, ttl_seconds: u64) -> Result<(), Error> {
+        // Verify admin authorization
+        let admin = Self::_require_admin(&env)?;
+
+        if ttl_seconds == 0 {
+            Self::_remove_config(&env, &admin, symbol_short!("offer_ttl"), &ConfigKey::OfferTtl);
+            return Ok(());
+        }
+        if !(MIN_OFFER_TTL..=MAX_OFFER_TTL).contains(&ttl_seconds) {
+            return Err(Error::InvalidAmount);
+        }
+
+        let ttl = (ttl_seconds, env.ledger().timestamp());
+        Self::_set_config(&env, &admin, symbol_short!("offer_ttl"), &ConfigKey::OfferTtl, &ttl);
+
+        Ok(())
+    }
+
+    // ========== Queries ==========
+
+    /// Returns the current admin address.
+    /// 
+    /// # Usage
+    /// - Check who has administrative privileges
+    /// - Verify admin address in UI applications
+    /// - Audit administrative access
+    /// 
+    /// # Returns
+    /// The address of the current contract administrator
+    /// 
+    /// # Errors
+    /// - NotInitialized: If the contract has not been initialized
+    fn get_admin(env: Env) -> Result<Address, Error> {
+        env.storage().persistent().get(&ConfigKey::Admin).ok_or(Error::NotInitialized)
+    }
+
+    /// Returns the USDC token contract address.
+    /// 
+    /// # Usage
+    /// - Verify which token is used for trading
+    /// - Build token transfer authorizations in client applications
+    /// - Validate contract configuration
+    /// 
+    /// # Returns
+    /// The address of the USDC token contract
+    /// 
+    /// # Errors
+    /// - NotInitialized: If the contract has not been initialized
+    fn get_usdc_token_id(env: Env) -> Result<Address, Error> {
+        env.storage().persistent().get(&ConfigKey::UsdcToken).ok_or(Error::NotInitialized)
+    }
+
+    /// Returns the fee collector address.
+    /// 
+    /// # Usage
+    /// - See where trading fees are sent
+    /// - Verify fee collection setup
+    /// - Audit fee distribution
+    /// 
+    /// # Returns
+    /// The address that receives trading fees
+    /// 
+    /// # Errors
+    /// - NotInitialized: If the contract has not been initialized
+    fn get_fee_collector(env: Env) -> Result<Address, Error> {
+        env.storage().persistent().get(&ConfigKey::FeeCollector).ok_or(Error::NotInitialized)
+    }
+
+    /// Returns the current trading fee rate in basis points.
+    /// 
+    /// # Fee Calculation
+    /// - Basis points: 25 = 0.25%, 100 = 1%
+    /// - To calculate fee: (trade_amount * fee_rate) / 10000
+    /// - Example: 1000 USDC trade with 25 basis points = 2.5 USDC fee
+    /// 
+    /// # Returns
+    /// Current fee rate in basis points (e.g., 25 = 0.25%)
+    fn get_fee_rate(env: Env) -> u32 {
+        env.storage().persistent().get(&ConfigKey::FeeRate).unwrap_or(DEFAULT_FEE_RATE)
+    }
+
+    /// Returns the maximum operator fee in basis points; 0 means operator fees are disabled.
+    fn get_operator_fee_cap(env: Env) -> u32 {
+        env.storage().persistent().get(&ConfigKey::OperatorFeeCap).unwrap_or(DEFAULT_OPERATOR_FEE_CAP)
+    }
+
+    /// Returns the size-based fee curve.
+    /// 
+    /// # Returns
+    /// The configured brackets; empty when the flat fee rate applies
+    fn get_fee_curve(env: Env) -> Vec<FeeBracket> {
+        env.storage().persistent().get(&ConfigKey::FeeCurve).unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the fee rate that would apply to a trade of the given size.
+    /// 
+    /// # Arguments
+    /// * `usdc_amount` - USDC trade amount in token base units
+    /// 
+    /// # Returns
+    /// Fee rate in basis points from the fee curve, or the flat rate if none is set
+    fn get_fee_rate_for(env: Env, usdc_amount: i128) -> u32 {
+        Self::_fee_rate_for(&env, usdc_amount)
+    }
+
+    /// Returns the weighted fee recipients.
+    /// 
+    /// # Returns
+    /// The configured splits; empty when all fees go to the single fee collector
+    fn get_fee_splits(env: Env) -> Vec<FeeSplit> {
+        env.storage().persistent().get(&ConfigKey::FeeSplits).unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the share of each trading fee routed to the insurance fund.
+    /// 
+    /// # Returns
+    /// Insurance share in basis points of the fee (e.g., 2000 = 20% of fees)
+    fn get_insurance_share(env: Env) -> u32 {
+        env.storage().persistent().get(&ConfigKey::InsuranceShare).unwrap_or(DEFAULT_INSURANCE_SHARE)
+    }
+
+    /// Returns the multisig admin signer set and approval threshold.
+    /// 
+    /// # Returns
+    /// (signers, threshold); an empty signer set means single-admin mode
+    fn get_admin_signers(env: Env) -> (Vec<Address>, u32) {
+        (
+            env.storage().persistent().get(&ConfigKey::AdminSigners).unwrap_or(Vec::new(&env)),
+            env.storage().persistent().get(&ConfigKey::AdminThreshold).unwrap_or(0),
+        )
+    }
+
+    /// Returns a pending multisig admin action by its ID.
+    fn get_admin_action(env: Env, action_id: u64) -> Option<PendingAction> {
+        let actions: Map<u64, PendingAction> = env.storage().instance().get(&DataKey::AdminActions)
+            .unwrap_or(Map::new(&env));
+        actions.get(action_id)
+    }
+
+    /// Returns the guardian address, if assigned.
+    fn get_guardian(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ConfigKey::Guardian)
+    }
+
+    /// Returns the compliance officer address, if assigned.
+    fn get_compliance_officer(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ConfigKey::ComplianceOfficer)
+    }
+
+    /// Returns whether a trade is frozen pending compliance review.
+    fn is_trade_frozen(env: Env, trade_id: u64) -> bool {
+        Self::_require_not_frozen(&env, &DataKey::FrozenTrades, trade_id).is_err()
+    }
+
+    /// Returns whether an offer is frozen pending compliance review.
+    fn is_offer_frozen(env: Env, offer_id: u64) -> bool {
+        Self::_require_not_frozen(&env, &DataKey::FrozenOffers, offer_id).is_err()
+    }
+
+    /// Returns the large-trade reporting threshold.
+    /// 
+    /// # Returns
+    /// Threshold in the token's smallest unit, or 0 if reporting is disabled
+    fn get_large_trade_threshold(env: Env) -> i128 {
+        env.storage().persistent().get(&ConfigKey::LargeTradeThreshold).unwrap_or(0)
+    }
+
+    /// Returns the initiation deposit buyers currently post.
+    /// 
+    /// # Returns
+    /// (native XLM token, amount in stroops), or None if no deposit is required
+    fn get_initiation_deposit(env: Env) -> Option<(Address, i128)> {
+        env.storage().persistent().get(&ConfigKey::InitiationDeposit)
+    }
 
-        // Emit resolution event for transparency and audit trail
-        env.events().publish((DISPUTE_RESOLVED, env.current_contract_address()), (trade_id, resolution));
+    /// Returns the initiation deposit held for a trade.
+    /// 
+    /// # Returns
+    /// Amount in stroops, or 0 if none is held (never posted or already paid out)
+    fn get_trade_deposit(env: Env, trade_id: u64) -> i128 {
+        let deposits: Map<u64, (Address, i128)> = env.storage().instance().get(&DataKey::TradeDeposits)
+            .unwrap_or(Map::new(&env));
+        deposits.get(trade_id).map(|(_, deposit)| deposit).unwrap_or(0)
+    }
 
-        Ok(())
+    /// Returns the per-address daily cap on traded USDC.
+    /// 
+    /// # Returns
+    /// Daily cap in the token's smallest unit, or 0 if unlimited
+    fn get_daily_limit(env: Env) -> i128 {
+        env.storage().persistent().get(&ConfigKey::DailyLimit).unwrap_or(0)
     }
 
-    // ================================================================================================
-    // ADMINISTRATIVE FUNCTIONS
-    // ================================================================================================
-    // These functions allow the admin to configure and manage the marketplace
-    
-    /// Upgrades the contract to a new Wasm hash.
-    /// This function can only be called by the contract admin.
-    ///
+    /// Returns the USDC an address has traded so far today.
+    /// 
     /// # Arguments
-    /// * `new_wasm_hash` - The hash of the new contract Wasm to upgrade to.
-    ///
-    /// # Security
-    /// - Requires admin authorization.
-    /// - The new Wasm hash must be valid.
-    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
-        Self::_require_admin(&env)?;
-        env.deployer().update_current_contract_wasm(new_wasm_hash);
-        Ok(())
+    /// * `address` - The trader to look up
+    /// 
+    /// # Returns
+    /// Volume counted against the daily cap for the current ledger-time day
+    fn get_daily_volume(env: Env, address: Address) -> i128 {
+        match env.storage().persistent().get::<_, (u64, i128)>(&DataKey::DailyUsed(address)) {
+            Some((day, used)) if day == env.ledger().timestamp() / SECONDS_PER_DAY => used,
+            _ => 0,
+        }
     }
 
-    /// Updates the admin address to a new address.
-    /// This is a critical security function that transfers administrative control.
-    /// 
-    /// # Security Features
-    /// - Requires current admin authorization
-    /// - Requires new admin to sign transaction (prevents unauthorized transfers)
-    /// - Emits event for transparency and audit trail
-    /// - Immediate effect - new admin can perform admin functions right away
+    /// Returns the reward points emission rate.
     /// 
-    /// # Use Cases
-    /// - Transferring control to a new administrator
-    /// - Moving to a multi-sig admin address
-    /// - Emergency admin change for security reasons
+    /// # Returns
+    /// Points earned by each side per whole USDC of completed volume
+    fn get_points_rate(env: Env) -> u32 {
+        env.storage().persistent().get(&ConfigKey::PointsRate).unwrap_or(DEFAULT_POINTS_RATE)
+    }
+
+    /// Returns the reward points balance of an address.
     /// 
     /// # Arguments
-    /// * `new_admin` - The new admin address (must sign transaction)
+    /// * `address` - The trader to look up
     /// 
     /// # Returns
-    /// Result indicating success or failure of admin update
-    /// 
-    /// # Errors
-    /// - Unauthorized: If caller is not current admin
-    pub fn update_admin(env: Env, new_admin: Address) -> Result<(), Error> {
-        // Verify current admin authorization
-        Self::_require_admin(&env)?;
-        
-        // Require new admin to sign transaction - prevents accidental transfers
-        new_admin.require_auth();
-        
-        // SECURITY FIX: Validate new admin address
-        Self::_validate_address(&new_admin)?;
-        
-        // Update admin address in persistent storage
-        env.storage().persistent().set(&ADMIN_KEY, &new_admin);
-        
-        // Emit event for security audit trail
-        env.events().publish((symbol_short!("adm_upd"), env.current_contract_address()), &new_admin);
-        
-        Ok(())
+    /// Accumulated reward points, or 0 if the address has never traded
+    fn get_points(env: Env, address: Address) -> i128 {
+        env.storage().persistent().get(&DataKey::Points(address)).unwrap_or(0)
     }
-    
-    /// Updates the fee collector address where trading fees are sent.
-    /// This allows admin to change where marketplace fees are collected.
+
+    /// Returns the parked USDC an address can claim with `withdraw`.
+    fn get_claimable(env: Env, address: Address) -> i128 {
+        env.storage().persistent().get(&DataKey::Claimable(address)).unwrap_or(0)
+    }
+
+    /// Returns the reputation score deducted per dispute lost.
+    fn get_dispute_penalty(env: Env) -> i128 {
+        env.storage().persistent().get(&ConfigKey::DisputePenalty).unwrap_or(DEFAULT_DISPUTE_PENALTY)
+    }
+
+    /// Returns the repeat-offender suspension policy.
     /// 
-    /// # Business Logic
-    /// - Fee collector receives a percentage of each completed trade
-    /// - Can be set to treasury, DAO, or operational address
-    /// - Takes effect immediately for new trades
-    /// - Does not affect ongoing trades
+    /// # Returns
+    /// (max dispute losses, window seconds); 0 losses means suspension is disabled
+    fn get_suspension_policy(env: Env) -> (u32, u64) {
+        env.storage().persistent().get(&ConfigKey::SuspensionPolicy).unwrap_or((0, 0))
+    }
+
+    /// Returns when an address was suspended, or None if it isn't suspended.
+    fn get_suspended_at(env: Env, address: Address) -> Option<u64> {
+        let suspended: Map<Address, u64> = env.storage().instance().get(&DataKey::Suspended)
+            .unwrap_or(Map::new(&env));
+        suspended.get(address)
+    }
+
+    /// Returns the evidence window opened when a dispute is raised.
     /// 
-    /// # Arguments
-    /// * `new_fee_collector` - The new address to receive trading fees
+    /// # Returns
+    /// Window in seconds, 0 if disputes can be resolved immediately
+    fn get_evidence_window(env: Env) -> u64 {
+        env.storage().persistent().get(&ConfigKey::EvidenceWindow).unwrap_or(0)
+    }
+
+    /// Returns the fee a panel arbitrator accrues per resolved dispute.
+    fn get_arbitrator_fee(env: Env) -> i128 {
+        env.storage().persistent().get(&ConfigKey::ArbitratorFee).unwrap_or(0)
+    }
+
+    /// Returns a panel arbitrator's track record and unclaimed fees.
     /// 
     /// # Returns
-    /// Result indicating success or failure of fee collector update
+    /// (disputes resolved, USDC claimable with `claim_arbitrator_fees`)
+    fn get_arbitrator_stats(env: Env, arbitrator: Address) -> (u32, i128) {
+        let arbitrator_fees: Map<Address, (u32, i128)> = env.storage().instance().get(&DataKey::ArbitratorFees)
+            .unwrap_or(Map::new(&env));
+        arbitrator_fees.get(arbitrator).unwrap_or((0, 0))
+    }
+
+    /// Returns the USDC currently held in the insurance fund.
     /// 
-    /// # Errors
-    /// - Unauthorized: If caller is not admin
-    pub fn update_fee_collector(env: Env, new_fee_collector: Address) -> Result<(), Error> {
-        // Verify admin authorization
-        Self::_require_admin(&env)?;
-        
-        // SECURITY FIX: Validate new fee collector address
-        Self::_validate_address(&new_fee_collector)?;
-        
-        // Update fee collector address in persistent storage
-        env.storage().persistent().set(&FEE_COLLECTOR_KEY, &new_fee_collector);
-        
-        Ok(())
+    /// # Usage
+    /// - Monitor insurance coverage available for compensation
+    /// - Audit fee routing into the fund
+    /// 
+    /// # Returns
+    /// Insurance fund balance in the token's smallest unit
+    fn get_insurance_fund(env: Env) -> i128 {
+        env.storage().persistent().get(&ConfigKey::InsuranceFund).unwrap_or(0)
     }
-    
-    /// Updates the trading fee rate charged on completed trades.
-    /// Fee rate is specified in basis points (1/100th of a percent).
+
+    /// Returns whether protocol fees are collected on this instance.
+    fn get_fees_enabled(env: Env) -> bool {
+        env.storage().persistent().get(&ConfigKey::FeesEnabled).unwrap_or(true)
+    }
+
+    /// Returns the (floor, ceiling) fee rate bounds fixed at deployment, in basis points.
+    fn get_fee_bounds(env: Env) -> (u32, u32) {
+        env.storage().persistent().get(&ConfigKey::FeeBounds).unwrap_or((0, MAX_FEE_RATE))
+    }
+
+    /// Returns the governance executor that owns fee and limit parameters, if any.
+    fn get_governance_executor(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ConfigKey::GovernanceExecutor)
+    }
+
+    /// Returns the arbitration contract disputes are delegated to, if any.
+    fn get_arbitrator(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ConfigKey::Arbitrator)
+    }
+
+    /// Returns the arbitrators new disputes are assigned to, in assignment order.
+    fn get_arbitrator_panel(env: Env) -> Vec<Address> {
+        env.storage().persistent().get(&ConfigKey::ArbitratorPanel).unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the scheduled escrow migration, if any.
     /// 
-    /// # Fee Structure
-    /// - Basis points: 1 = 0.01%, 100 = 1%, 1000 = 10%
-    /// - Maximum allowed fee is 10% (1000 basis points)
-    /// - Reasonable marketplace fees are typically 0.1% - 1%
-    /// - Fees are only collected on successful trades
+    /// # Returns
+    /// (successor contract, earliest time `migrate_escrow` may run)
+    fn get_pending_migration(env: Env) -> Option<(Address, u64)> {
+        env.storage().persistent().get(&ConfigKey::PendingMigration)
+    }
+
+    /// Returns when the wind-down started, if it has.
+    fn get_wind_down(env: Env) -> Option<u64> {
+        env.storage().persistent().get(&ConfigKey::WindDown)
+    }
+
+    /// Returns the successor contract escrow was migrated to, if the migration has run.
+    fn get_successor(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ConfigKey::Successor)
+    }
+
+    /// Returns the yield adapter idle escrow can be deposited with, if any.
+    fn get_yield_adapter(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ConfigKey::YieldAdapter)
+    }
+
+    /// Returns the USDC principal currently deposited with the yield adapter.
+    fn get_yield_deposited(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::YieldDeposited).unwrap_or(0)
+    }
+
+    /// Reconciles the contract's USDC balance against what it owes.
+    /// Cheap on-chain solvency check for auditors and monitoring.
     /// 
-    /// # Arguments
-    /// * `new_fee_rate` - New fee rate in basis points (max 1000 = 10%)
+    /// # Calculation
+    /// - Liabilities = escrow of all active offers + insurance fund
+    /// - Result = actual token balance + yield principal - liabilities
     /// 
     /// # Returns
-    /// Result indicating success or failure of fee rate update
+    /// Surplus if positive, deficit if negative, zero when fully reconciled
     /// 
     /// # Errors
-    /// - Unauthorized: If caller is not admin
-    /// - InvalidAmount: If fee rate exceeds 10%
-    pub fn update_fee_rate(env: Env, new_fee_rate: u32) -> Result<(), Error> {
-        // Verify admin authorization
-        Self::_require_admin(&env)?;
-        
-        // Validate fee rate is reasonable (max 10%)
-        if new_fee_rate > 1000 { // Max 10%
-            return Err(Error::InvalidAmount);
-        }
-        
-        // Update fee rate in persistent storage
-        env.storage().persistent().set(&FEE_RATE_KEY, &new_fee_rate);
-        
-        Ok(())
+    /// - NotInitialized: If the contract has not been initialized
+    fn verify_escrow(env: Env) -> Result<i128, Error> {
+        let usdc_token_id = Self::get_usdc_token_id(env.clone())?;
+        let usdc_client = token::Client::new(&env, &usdc_token_id);
+        Ok(Self::_held_usdc(&env, &usdc_client) - Self::_total_liabilities(&env))
     }
-    
-    /// Updates the minimum and maximum trade amounts for USDC trades.
-    /// These limits help prevent spam trades and excessive exposure.
+
+    /// Previews the settlement of a trade against an offer without changing state.
+    /// Uses the same fee calculation as trade completion.
     /// 
     /// # Business Logic
-    /// - Minimum amount prevents spam with tiny trades
-    /// - Maximum amount limits exposure per trade
-    /// - Amounts are in USDC with 6 decimal places
-    /// - Applies to new offers only, existing offers unchanged
+    /// - KES due is pro-rated at the offer's rate for the quoted amount
+    /// - Trades currently fill the whole offer; quote `usdc_amount` = offer amount for exact figures
     /// 
     /// # Arguments
-    /// * `min_amount` - Minimum USDC amount for trades (with 6 decimals)
-    /// * `max_amount` - Maximum USDC amount for trades (with 6 decimals)
+    /// * `offer_id` - The offer to quote against
+    /// * `usdc_amount` - USDC amount to quote (up to the offer's escrowed amount)
     /// 
     /// # Returns
-    /// Result indicating success or failure of limits update
+    /// Quote with gross, fee, net to buyer, KES due and payment window
     /// 
     /// # Errors
-    /// - Unauthorized: If caller is not admin
-    /// - InvalidAmount: If amounts are invalid or min > max
-    pub fn update_trade_limits(env: Env, min_amount: i128, max_amount: i128) -> Result<(), Error> {
-        // Verify admin authorization
-        Self::_require_admin(&env)?;
-        
-        // Validate amount parameters
-        if min_amount <= 0 || max_amount <= 0 || min_amount > max_amount {
-            return Err(Error::InvalidAmount);
+    /// - OfferNotFound: If offer doesn't exist or is no longer active
+    /// - InvalidAmount: If amount is not positive or exceeds the offer
+    fn quote_trade(env: Env, offer_id: u64, usdc_amount: i128) -> Result<Quote, Error> {
+        Self::_require_initialized(&env)?;
+
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap();
+        let offer = offers.get(offer_id).ok_or(Error::OfferNotFound)?;
+        if !Self::_is_active_offer(&env, &offer.seller, offer_id) {
+            return Err(Error::OfferNotFound);
         }
-        
-        // SECURITY FIX: Additional bounds checking to prevent extreme values
-        // Maximum reasonable amount is 1 trillion USDC (with 6 decimals)
-        const MAX_REASONABLE_AMOUNT: i128 = 1_000_000_000_000_000_000; // 1 trillion USDC
-        if max_amount > MAX_REASONABLE_AMOUNT {
+
+        if usdc_amount <= 0 || usdc_amount > offer.usdc_amount {
             return Err(Error::InvalidAmount);
         }
-        
-        // Update trade limits in persistent storage
-        env.storage().persistent().set(&MIN_TRADE_AMOUNT_KEY, &min_amount);
-        env.storage().persistent().set(&MAX_TRADE_AMOUNT_KEY, &max_amount);
-        
-        Ok(())
+
+        let fee = Self::_calculate_fee(usdc_amount, Self::_fee_rate_for(&env, usdc_amount));
+        let fee = fee - Self::_seller_fee_share(Self::_fee_payer(&env, offer_id), fee);
+        let kes_due = offer.kes_amount * usdc_amount / offer.usdc_amount;
+
+        Ok(Quote {
+            gross: usdc_amount,
+            fee,
+            net_to_buyer: usdc_amount - fee,
+            kes_due,
+            expires_in: Self::get_trade_expiration(env),
+        })
     }
-    
-    /// Updates the trade expiration time for new trades.
-    /// This controls how long buyers have to confirm payment before trades expire.
+
+    /// Returns the current minimum and maximum trade amounts.
     /// 
-    /// # Business Logic
-    /// - Expired trades automatically return USDC to seller
-    /// - Shorter times reduce seller risk but may rush buyers
-    /// - Longer times give buyers more flexibility but increase seller risk
-    /// - Typical values: 10 minutes to 24 hours
+    /// # Usage
+    /// - Validate trade amounts before creating offers
+    /// - Display trading limits in UI
+    /// - Ensure compliance with platform rules
     /// 
-    /// # Arguments
-    /// * `expiration_seconds` - New expiration time in seconds (60 to 86400)
+    /// # Returns
+    /// Tuple of (minimum_amount, maximum_amount) in the token's smallest unit
+    fn get_trade_limits(env: Env) -> (i128, i128) {
+        let unit = Self::_token_unit(&env);
+        let min = env.storage().persistent().get(&ConfigKey::MinTradeAmount)
+            .unwrap_or(DEFAULT_MIN_TRADE_UNITS * unit);
+        let max = env.storage().persistent().get(&ConfigKey::MaxTradeAmount)
+            .unwrap_or(DEFAULT_MAX_TRADE_UNITS * unit);
+        (min, max)
+    }
+
+    /// Returns the active-offer caps.
     /// 
     /// # Returns
-    /// Result indicating success or failure of expiration update
+    /// Tuple of (max_total, max_per_seller); 0 means unlimited
+    fn get_offer_caps(env: Env) -> (u32, u32) {
+        env.storage().persistent().get(&ConfigKey::OfferCaps).unwrap_or((0, 0))
+    }
+
+    /// Returns the ceiling on concurrent open trades.
     /// 
-    /// # Errors
-    /// - Unauthorized: If caller is not admin
-    /// - InvalidAmount: If expiration is outside allowed range
-    pub fn update_trade_expiration(env: Env, expiration_seconds: u64) -> Result<(), Error> {
-        // Verify admin authorization
-        Self::_require_admin(&env)?;
-        
-        // Validate expiration time is reasonable (1 minute to 24 hours)
-        if expiration_seconds < 60 || expiration_seconds > 86400 { // Min 1 minute, max 24 hours
-            return Err(Error::InvalidAmount);
-        }
-        
-        // Update trade expiration in persistent storage
-        env.storage().persistent().set(&TRADE_EXPIRATION_KEY, &expiration_seconds);
-        
-        Ok(())
+    /// # Returns
+    /// Maximum open trades, 0 means unlimited
+    fn get_max_open_trades(env: Env) -> u32 {
+        env.storage().persistent().get(&ConfigKey::MaxOpenTrades).unwrap_or(0)
     }
 
-    // ================================================================================================
-    // QUERY FUNCTIONS (GETTERS)
-    // ================================================================================================
-    // These functions provide read-only access to contract state for external callers
-    
-    /// Returns the current admin address.
+    /// Returns the KES-side limits for fiat offers.
     /// 
-    /// # Usage
-    /// - Check who has administrative privileges
-    /// - Verify admin address in UI applications
-    /// - Audit administrative access
+    /// # Returns
+    /// Tuple of (min_kes_amount, min_rate, max_rate); 0 means the limit is off
+    fn get_fiat_limits(env: Env) -> (i128, i128, i128) {
+        let min_kes_amount = env.storage().persistent().get(&ConfigKey::MinKesAmount).unwrap_or(0);
+        let (min_rate, max_rate) = env.storage().persistent().get(&ConfigKey::RateBounds).unwrap_or((0, 0));
+        (min_kes_amount, min_rate, max_rate)
+    }
+
+    /// Returns the registered payment oracle, if any.
     /// 
     /// # Returns
-    /// The address of the current contract administrator
-    pub fn get_admin(env: Env) -> Address {
-        env.storage().persistent().get(&ADMIN_KEY).unwrap()
+    /// The oracle address, or None if attestations are disabled
+    fn get_payment_oracle(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ConfigKey::PaymentOracle)
     }
 
-    /// Returns the USDC token contract address.
+    /// Returns the relayers allowed to submit trades on behalf of users.
+    fn get_relayers(env: Env) -> Vec<Address> {
+        env.storage().persistent().get(&ConfigKey::Relayers).unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the registered receipt attester public key, if any.
     /// 
-    /// # Usage
-    /// - Verify which token is used for trading
-    /// - Set up token approvals in client applications
-    /// - Validate contract configuration
+    /// # Returns
+    /// The attester's Ed25519 public key, or None if signed receipts are disabled
+    fn get_receipt_attester(env: Env) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&ConfigKey::ReceiptAttester)
+    }
+
+    /// Returns the cooling-off period for a buyer's first trade.
     /// 
     /// # Returns
-    /// The address of the USDC token contract
-    pub fn get_usdc_token_id(env: Env) -> Address {
-        env.storage().persistent().get(&USDC_TOKEN_KEY).unwrap()
+    /// Period in seconds, 0 if the hold is disabled
+    fn get_first_trade_hold(env: Env) -> u64 {
+        env.storage().persistent().get(&ConfigKey::FirstTradeHold).unwrap_or(0)
     }
-    
-    /// Returns the fee collector address.
+
+    /// Returns the base cooldown applied after a buyer walks away from a trade.
     /// 
-    /// # Usage
-    /// - See where trading fees are sent
-    /// - Verify fee collection setup
-    /// - Audit fee distribution
+    /// # Returns
+    /// Cooldown in seconds, 0 if cooldowns are disabled
+    fn get_walkaway_cooldown(env: Env) -> u64 {
+        env.storage().persistent().get(&ConfigKey::WalkawayCooldown).unwrap_or(0)
+    }
+
+    /// Returns a buyer's walkaway streak and when their cooldown ends.
     /// 
     /// # Returns
-    /// The address that receives trading fees
-    pub fn get_fee_collector(env: Env) -> Address {
-        env.storage().persistent().get(&FEE_COLLECTOR_KEY).unwrap()
+    /// (consecutive walkaways, timestamp the buyer may trade again), with 0 meaning no cooldown applies
+    fn get_buyer_cooldown(env: Env, buyer: Address) -> (u32, u64) {
+        let count = env.storage().persistent().get::<_, (u32, u64)>(&DataKey::Walkaways(buyer.clone()))
+            .map_or(0, |(count, _)| count);
+        (count, Self::_cooldown_until(&env, &buyer))
     }
-    
-    /// Returns the current trading fee rate in basis points.
+
+    /// Returns the auto-release window for buyer-confirmed trades.
     /// 
-    /// # Fee Calculation
-    /// - Basis points: 25 = 0.25%, 100 = 1%
-    /// - To calculate fee: (trade_amount * fee_rate) / 10000
-    /// - Example: 1000 USDC trade with 25 basis points = 2.5 USDC fee
+    /// # Returns
+    /// Window in seconds, 0 if auto-release is disabled
+    fn get_auto_release_window(env: Env) -> u64 {
+        env.storage().persistent().get(&ConfigKey::AutoReleaseWindow).unwrap_or(0)
+    }
+
+    /// Returns the grace period between an oracle attestation and release.
+    /// 
+    /// # Returns
+    /// Grace period in seconds
+    fn get_attestation_grace_period(env: Env) -> u64 {
+        env.storage().persistent().get(&ConfigKey::AttestationGrace).unwrap_or(DEFAULT_ATTESTATION_GRACE)
+    }
+
+    /// Returns the registered cKES token used for on-chain settlement, if any.
     /// 
     /// # Returns
-    /// Current fee rate in basis points (e.g., 25 = 0.25%)
-    pub fn get_fee_rate(env: Env) -> u32 {
-        env.storage().persistent().get(&FEE_RATE_KEY).unwrap_or(DEFAULT_FEE_RATE)
+    /// The cKES token address, or None if on-chain settlement is not enabled
+    fn get_kes_token(env: Env) -> Option<Address> {
+        env.storage().persistent().get(&ConfigKey::KesToken)
     }
-    
-    /// Returns the current minimum and maximum trade amounts.
+
+    /// Returns a token's registry entry.
+    /// 
+    /// # Returns
+    /// The token's TokenConfig, or None if it isn't registered
+    fn get_token_config(env: Env, token_id: Address) -> Option<TokenConfig> {
+        Self::_token_config(&env, &token_id)
+    }
+
+    /// Returns the decimals of the USDC token, cached at initialization.
     /// 
     /// # Usage
-    /// - Validate trade amounts before creating offers
-    /// - Display trading limits in UI
-    /// - Ensure compliance with platform rules
+    /// - Convert between whole tokens and contract amounts in client applications
+    /// - Interpret trade limits and fees correctly for non 6-decimal assets
     /// 
     /// # Returns
-    /// Tuple of (minimum_amount, maximum_amount) in USDC with 6 decimals
-    pub fn get_trade_limits(env: Env) -> (i128, i128) {
-        let min = env.storage().persistent().get(&MIN_TRADE_AMOUNT_KEY)
-            .unwrap_or(DEFAULT_MIN_TRADE_AMOUNT);
-        let max = env.storage().persistent().get(&MAX_TRADE_AMOUNT_KEY)
-            .unwrap_or(DEFAULT_MAX_TRADE_AMOUNT);
-        (min, max)
+    /// Number of decimal places used by the traded token
+    fn get_token_decimals(env: Env) -> u32 {
+        env.storage().persistent().get(&ConfigKey::TokenDecimals).unwrap_or(DEFAULT_TOKEN_DECIMALS)
     }
-    
+
     /// Returns the current trade expiration time in seconds.
     /// 
     /// # Usage
@@ -1397,11 +7615,19 @@ impl P2PMarketplaceContract {
     /// 
     /// # Returns
     /// Trade expiration time in seconds
-    pub fn get_trade_expiration(env: Env) -> u64 {
-        env.storage().persistent().get(&TRADE_EXPIRATION_KEY)
+    fn get_trade_expiration(env: Env) -> u64 {
+        env.storage().persistent().get(&ConfigKey::TradeExpiration)
             .unwrap_or(DEFAULT_TRADE_EXPIRATION)
     }
 
+    /// Returns the grace period after a trade's nominal deadline before it counts as expired.
+    /// 
+    /// # Returns
+    /// Grace period in seconds, 0 if disabled
+    fn get_expiry_grace(env: Env) -> u64 {
+        env.storage().persistent().get(&ConfigKey::ExpiryGrace).unwrap_or(0)
+    }
+
     /// Returns the next offer ID that will be assigned.
     /// 
     /// # Usage
@@ -1410,9 +7636,12 @@ impl P2PMarketplaceContract {
     /// - Debug offer creation issues
     /// 
     /// # Returns
-    /// The next available offer ID
-    pub fn get_next_offer_id(env: Env) -> u64 {
-        env.storage().instance().get(&NEXT_OFFER_ID).unwrap()
+    /// The next sequential offer ID, or None while hashed IDs are on so the count stays private
+    fn get_next_offer_id(env: Env) -> Option<u64> {
+        if Self::get_hashed_ids(env.clone()) {
+            return None;
+        }
+        Some(env.storage().instance().get(&DataKey::NextOfferId).unwrap_or(0))
     }
 
     /// Returns the next trade ID that will be assigned.
@@ -1423,9 +7652,62 @@ impl P2PMarketplaceContract {
     /// - Debug trade creation issues
     /// 
     /// # Returns
-    /// The next available trade ID
-    pub fn get_next_trade_id(env: Env) -> u64 {
-        env.storage().instance().get(&NEXT_TRADE_ID).unwrap()
+    /// The next sequential trade ID, or None while hashed IDs are on so the count stays private
+    fn get_next_trade_id(env: Env) -> Option<u64> {
+        if Self::get_hashed_ids(env.clone()) {
+            return None;
+        }
+        Some(env.storage().instance().get(&DataKey::NextTradeId).unwrap_or(0))
+    }
+
+    /// Returns whether new offer and trade IDs are derived from a hash.
+    fn get_hashed_ids(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::HashedIds).unwrap_or(false)
+    }
+
+    /// Returns the nonce the next hashed offer or trade ID of an owner will be derived from.
+    /// Offers and trades share the count, which only advances while hashed IDs are on.
+    fn get_id_nonce(env: Env, owner: Address) -> u64 {
+        env.storage().persistent().get(&DataKey::IdNonce(owner)).unwrap_or(0)
+    }
+
+    /// Resolves the offer a seller created with a given nonce, under either ID scheme.
+    /// 
+    /// # Usage
+    /// - Read `get_next_offer_id` (sequential) or `get_id_nonce` (hashed) before submitting
+    ///   to learn the nonce, then look the offer up with the ledger the transaction landed in
+    /// - Offers from batch calls use consecutive nonces
+    /// 
+    /// # Arguments
+    /// * `seller` - Seller who created the offer
+    /// * `nonce` - The offer counter, or the seller's ID nonce if hashed, when the offer was created
+    /// * `ledger` - Ledger sequence the offer was created in
+    /// 
+    /// # Returns
+    /// The offer ID if an offer by `seller` exists under the hashed or sequential ID
+    fn find_offer_id(env: Env, seller: Address, nonce: u64, ledger: u32) -> Option<u64> {
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env));
+        let hashed = Self::_hashed_id(&env, &seller, nonce, ledger);
+        [hashed, nonce].into_iter().find(|id| {
+            offers.get(*id).map(|offer| offer.seller == seller).unwrap_or(false)
+        })
+    }
+
+    /// Resolves the trade a buyer opened with a given nonce, under either ID scheme.
+    /// 
+    /// # Arguments
+    /// * `buyer` - Buyer who opened the trade
+    /// * `nonce` - The trade counter, or the buyer's ID nonce if hashed, when the trade was opened
+    /// * `ledger` - Ledger sequence the trade was opened in
+    /// 
+    /// # Returns
+    /// The trade ID if a trade by `buyer` exists under the hashed or sequential ID
+    fn find_trade_id(env: Env, buyer: Address, nonce: u64, ledger: u32) -> Option<u64> {
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap_or(Map::new(&env));
+        let hashed = Self::_hashed_id(&env, &buyer, nonce, ledger);
+        [hashed, nonce].into_iter().find(|id| {
+            trades.get(*id).map(|trade| trade.buyer == buyer).unwrap_or(false)
+        })
     }
 
     /// Returns all offers in the marketplace.
@@ -1439,8 +7721,8 @@ impl P2PMarketplaceContract {
     /// 
     /// # Returns
     /// Map of all offers keyed by offer ID
-    pub fn get_offers(env: Env) -> Map<u64, Offer> {
-        env.storage().instance().get(&OFFERS_KEY).unwrap()
+    fn get_offers(env: Env) -> Map<u64, Offer> {
+        env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env))
     }
 
     /// Returns a specific offer by its ID.
@@ -1455,11 +7737,203 @@ impl P2PMarketplaceContract {
     /// 
     /// # Returns
     /// The offer if it exists, None otherwise
-    pub fn get_offer(env: Env, offer_id: u64) -> Option<Offer> {
-        let offers: Map<u64, Offer> = env.storage().instance().get(&OFFERS_KEY).unwrap();
+    fn get_offer(env: Env, offer_id: u64) -> Option<Offer> {
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env));
         offers.get(offer_id)
     }
 
+    /// Returns who bears the protocol fee on an offer (Buyer unless the seller chose otherwise).
+    fn get_fee_payer(env: Env, offer_id: u64) -> FeePayer {
+        Self::_fee_payer(&env, offer_id)
+    }
+
+    /// Returns the buyer requirements set on an offer, if any.
+    fn get_buyer_requirements(env: Env, offer_id: u64) -> Option<BuyerRequirements> {
+        let requirements: Map<u64, BuyerRequirements> = env.storage().instance().get(&DataKey::OfferRequirements)
+            .unwrap_or(Map::new(&env));
+        requirements.get(offer_id)
+    }
+
+    /// Returns the per-buyer purchase cap set on an offer, if any.
+    /// 
+    /// # Returns
+    /// (max USDC per buyer, window seconds), or None if uncapped
+    fn get_buyer_cap(env: Env, offer_id: u64) -> Option<(i128, u64)> {
+        let caps: Map<u64, (i128, u64)> = env.storage().instance().get(&DataKey::BuyerCaps).unwrap_or(Map::new(&env));
+        caps.get(offer_id)
+    }
+
+    /// Returns how much USDC a buyer has filled from a capped offer in the current window.
+    /// 
+    /// # Returns
+    /// USDC filled, or 0 if the offer is uncapped or the window has elapsed
+    fn get_buyer_fill(env: Env, offer_id: u64, buyer: Address) -> i128 {
+        let caps: Map<u64, (i128, u64)> = env.storage().instance().get(&DataKey::BuyerCaps).unwrap_or(Map::new(&env));
+        let Some((_, window)) = caps.get(offer_id) else { return 0 };
+        Self::_buyer_fill(&env, offer_id, &buyer, window).1
+    }
+
+    /// Returns an address's reputation; zero for addresses with no completed trades or lost disputes.
+    fn get_reputation(env: Env, address: Address) -> Reputation {
+        env.storage().persistent().get(&DataKey::Reputation(address)).unwrap_or_default()
+    }
+
+    /// Returns the USDC a recurring offer may still re-escrow; 0 if it isn't recurring.
+    fn get_recurring_cap(env: Env, offer_id: u64) -> i128 {
+        let recurring: Map<u64, i128> = env.storage().instance().get(&DataKey::RecurringOffers).unwrap_or(Map::new(&env));
+        recurring.get(offer_id).unwrap_or(0)
+    }
+
+    /// Returns the implied exchange rate of an offer.
+    /// 
+    /// # Rate Format
+    /// KES per USDC as a fixed-point number with 7 decimal places (scale 10^7),
+    /// computed as `kes_amount * 10^7 / usdc_amount` over both amounts' smallest units.
+    /// Example: 120.5 KES per USDC = 1_205_000_000
+    /// 
+    /// # Arguments
+    /// * `offer_id` - The ID of the offer
+    /// 
+    /// # Returns
+    /// The offer's rate, or None if the offer doesn't exist or its KES amount is too large to scale
+    fn get_offer_rate(env: Env, offer_id: u64) -> Option<i128> {
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env));
+        offers.get(offer_id).and_then(|offer| Self::_offer_rate(&offer).ok())
+    }
+
+    /// Returns the volume-weighted average rate of trades completed in the last 24 hours.
+    /// 
+    /// # Usage
+    /// - Pricing anchor for new sellers
+    /// - Rate-band checks without an external oracle
+    /// 
+    /// # Returns
+    /// VWAP in the same fixed-point format as `get_offer_rate`, or None if no trades
+    /// completed within the window
+    fn get_reference_rate(env: Env) -> Option<i128> {
+        let volume: Map<u64, (i128, i128)> = env.storage().instance().get(&DataKey::Volume)
+            .unwrap_or(Map::new(&env));
+        let oldest = env.ledger().timestamp().saturating_sub(VWAP_WINDOW) / VWAP_BUCKET;
+
+        let (mut total_usdc, mut total_kes) = (0i128, 0i128);
+        for (bucket, (usdc, kes)) in volume.iter() {
+            if bucket >= oldest {
+                total_usdc += usdc;
+                total_kes += kes;
+            }
+        }
+
+        if total_usdc == 0 {
+            return None;
+        }
+        Self::_implied_rate(total_kes, total_usdc).ok()
+    }
+
+    /// Returns available offers whose implied rate falls within a range.
+    /// 
+    /// # Usage
+    /// - Filter the order book by price on-chain with consistent precision
+    /// - Find the best available offers near a target rate
+    /// 
+    /// # Arguments
+    /// * `min_rate` - Minimum rate, inclusive (same format as `get_offer_rate`)
+    /// * `max_rate` - Maximum rate, inclusive
+    /// * `limit` - Maximum number of offer IDs to return
+    /// 
+    /// # Returns
+    /// Matching active offer IDs in ascending ID order
+    fn get_offers_by_rate_range(env: Env, min_rate: i128, max_rate: i128, limit: u32) -> Vec<u64> {
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env));
+        let mut matches = Vec::new(&env);
+        for (offer_id, offer) in offers.iter() {
+            if matches.len() >= limit {
+                break;
+            }
+            let Ok(rate) = Self::_offer_rate(&offer) else { continue };
+            if rate >= min_rate && rate <= max_rate && Self::_is_available_offer(&env, offer_id, &offer) {
+                matches.push_back(offer_id);
+            }
+        }
+        matches
+    }
+
+    /// Returns an order-book depth snapshot: available USDC aggregated per price level.
+    /// 
+    /// # Usage
+    /// - Render a depth chart from a single call
+    /// - Gauge how much USDC is available near the best rate
+    /// 
+    /// # Business Logic
+    /// - Only available offers count (same rules as `get_available_offers`)
+    /// - Swap offers are excluded, as their rate isn't quoted in KES
+    /// 
+    /// # Arguments
+    /// * `levels` - Maximum number of price levels to return
+    /// 
+    /// # Returns
+    /// Vector of (rate, total USDC) pairs, best (lowest) rate first, rates in the same
+    /// format as `get_offer_rate`
+    fn get_depth(env: Env, levels: u32) -> Vec<(i128, i128)> {
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env));
+        let mut depth: Map<i128, i128> = Map::new(&env);
+        for (offer_id, offer) in offers.iter() {
+            if matches!(offer.settlement, SettlementMode::Swap(_)) || !Self::_is_available_offer(&env, offer_id, &offer) {
+                continue;
+            }
+            let Ok(rate) = Self::_offer_rate(&offer) else { continue };
+            depth.set(rate, depth.get(rate).unwrap_or(0) + offer.usdc_amount);
+        }
+
+        let mut snapshot = Vec::new(&env);
+        for level in depth.iter() {
+            if snapshot.len() >= levels {
+                break;
+            }
+            snapshot.push_back(level);
+        }
+        snapshot
+    }
+
+    /// Returns a page of offers that can be traded right now.
+    /// Applies the same availability rules `initiate_trade` enforces, so frontends
+    /// don't need to replicate them client-side.
+    /// 
+    /// # Business Logic
+    /// - Excludes offers that are cancelled, completed, or refunded
+    /// - Excludes offers reserved by an open trade
+    /// - Excludes offers frozen by the compliance officer
+    /// 
+    /// # Arguments
+    /// * `start` - Smallest offer ID to include (pass the last returned ID + 1 for the next page)
+    /// * `limit` - Maximum number of offers to return
+    /// 
+    /// # Returns
+    /// Vector of (offer_id, offer) pairs in ascending ID order
+    fn get_available_offers(env: Env, start: u64, limit: u32) -> Vec<(u64, Offer)> {
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env));
+        let mut page = Vec::new(&env);
+        for (offer_id, offer) in offers.iter() {
+            if page.len() >= limit {
+                break;
+            }
+            if offer_id >= start && Self::_is_available_offer(&env, offer_id, &offer) {
+                page.push_back((offer_id, offer));
+            }
+        }
+        page
+    }
+
+    /// Returns an open price proposal by its ID.
+    /// 
+    /// # Arguments
+    /// * `proposal_id` - The ID of the proposal to retrieve
+    /// 
+    /// # Returns
+    /// The proposal if it is still open, None otherwise
+    fn get_proposal(env: Env, proposal_id: u64) -> Option<Proposal> {
+        env.storage().persistent().get(&DataKey::Proposal(proposal_id))
+    }
+
     /// Returns all trades in the marketplace.
     /// Warning: This function can be expensive for large datasets.
     /// 
@@ -1471,10 +7945,10 @@ impl P2PMarketplaceContract {
     /// 
     /// # Returns
     /// Map of all trades keyed by trade ID
-    pub fn get_trades(env: Env) -> Map<u64, Trade> {
-        env.storage().instance().get(&TRADES_KEY).unwrap()
+    fn get_trades(env: Env) -> Map<u64, Trade> {
+        env.storage().instance().get(&DataKey::Trades).unwrap_or(Map::new(&env))
     }
-    
+
     /// Returns a specific trade by its ID.
     /// 
     /// # Usage
@@ -1487,11 +7961,208 @@ impl P2PMarketplaceContract {
     /// 
     /// # Returns
     /// The trade if it exists, None otherwise
-    pub fn get_trade(env: Env, trade_id: u64) -> Option<Trade> {
-        let trades: Map<u64, Trade> = env.storage().instance().get(&TRADES_KEY).unwrap();
+    fn get_trade(env: Env, trade_id: u64) -> Option<Trade> {
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap_or(Map::new(&env));
         trades.get(trade_id)
     }
 
+    /// Returns the payment hash the buyer committed for a trade, if any.
+    fn get_payment_hash(env: Env, trade_id: u64) -> Option<BytesN<32>> {
+        let payment_hashes: Map<u64, BytesN<32>> = env.storage().instance().get(&DataKey::PaymentHashes)
+            .unwrap_or(Map::new(&env));
+        payment_hashes.get(trade_id)
+    }
+
+    /// Returns the trade a client-supplied external reference was attached to.
+    /// 
+    /// # Usage
+    /// - Back offices reconcile by their own order ID instead of the on-chain trade ID
+    /// 
+    /// # Arguments
+    /// * `external_ref` - The reference passed to `initiate_trade`
+    /// 
+    /// # Returns
+    /// The trade if the reference is known and the trade hasn't been pruned, None otherwise
+    fn get_trade_by_ref(env: Env, external_ref: BytesN<32>) -> Option<Trade> {
+        let trade_refs: Map<BytesN<32>, u64> = env.storage().instance().get(&DataKey::TradeRefs)
+            .unwrap_or(Map::new(&env));
+        let trade_id = trade_refs.get(external_ref)?;
+        Self::get_trade(env, trade_id)
+    }
+
+    /// Returns the offer a seller created with a client idempotency key.
+    /// 
+    /// # Arguments
+    /// * `seller` - The seller who created the offer
+    /// * `idempotency_key` - The key passed to `create_offer`
+    /// 
+    /// # Returns
+    /// The offer ID if the key is known and the offer hasn't been pruned, None otherwise
+    fn get_offer_by_key(env: Env, seller: Address, idempotency_key: BytesN<32>) -> Option<u64> {
+        env.storage().persistent().get(&DataKey::OfferKey(seller, idempotency_key))
+    }
+
+    /// Returns when a trade expires and how long is left.
+    /// Applies the contract's own expiration rules, including the extension
+    /// installment trades get from each confirmed tranche.
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade
+    /// 
+    /// # Returns
+    /// (expires_at, seconds remaining), with 0 remaining once expired
+    /// 
+    /// # Errors
+    /// - TradeNotFound: If trade doesn't exist
+    fn get_trade_deadline(env: Env, trade_id: u64) -> Result<(u64, u64), Error> {
+        Self::_require_initialized(&env)?;
+
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+
+        let expires_at = Self::_trade_deadline(&env, &trade);
+        Ok((expires_at, expires_at.saturating_sub(env.ledger().timestamp())))
+    }
+
+    /// Returns the sequence number of the last event addressed to an address.
+    /// 
+    /// # Usage
+    /// - Indexers compare it with the last sequence they processed to detect gaps
+    /// 
+    /// # Arguments
+    /// * `address` - The address to look up
+    /// 
+    /// # Returns
+    /// The last sequence number, or 0 if no event has been addressed to it
+    fn get_event_seq(env: Env, address: Address) -> u64 {
+        env.storage().persistent().get(&DataKey::EventSeq(address)).unwrap_or(0)
+    }
+
+    /// Returns a trade together with everything needed to render it.
+    /// 
+    /// # Usage
+    /// - Mobile clients render a trade screen from a single RPC call
+    /// - Shows the expiry countdown and exact take-home amount
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the trade to retrieve
+    /// 
+    /// # Returns
+    /// Trade details with rate, expiry, fee preview and dispute info
+    /// 
+    /// # Errors
+    /// - TradeNotFound: If trade doesn't exist
+    /// - RateOutOfBounds: If the trade's KES amount is too large to compute a rate
+    fn get_trade_details(env: Env, trade_id: u64) -> Result<TradeDetails, Error> {
+        Self::_require_initialized(&env)?;
+
+        let trades: Map<u64, Trade> = env.storage().instance().get(&DataKey::Trades).unwrap();
+        let trade = trades.get(trade_id).ok_or(Error::TradeNotFound)?;
+
+        let expires_at = Self::_trade_deadline(&env, &trade);
+        let fee = Self::_calculate_fee(trade.usdc_amount, Self::_fee_rate_for(&env, trade.usdc_amount));
+        let fee = fee - Self::_seller_fee_share(Self::_fee_payer(&env, trade.offer_id), fee);
+        let operator_fee = Self::_calculate_fee(trade.usdc_amount, trade.operator_fee_bps);
+        let dispute = match Self::get_dispute(env.clone(), trade_id) {
+            Some(dispute) => TradeDispute::Disputed(dispute),
+            None => TradeDispute::NotDisputed,
+        };
+
+        Ok(TradeDetails {
+            rate: Self::_implied_rate(trade.kes_amount, trade.usdc_amount)?,
+            expires_at,
+            expires_in: expires_at.saturating_sub(env.ledger().timestamp()),
+            fee,
+            operator_fee,
+            net_to_buyer: trade.usdc_amount - fee - operator_fee,
+            dispute,
+            trade,
+        })
+    }
+
+    /// Returns the dispute record for a trade.
+    /// 
+    /// # Usage
+    /// - Display dispute reason and evidence to the admin
+    /// - Show resolution outcome to participants
+    /// 
+    /// # Arguments
+    /// * `trade_id` - The ID of the disputed trade
+    /// 
+    /// # Returns
+    /// The dispute if one was raised for the trade, None otherwise
+    fn get_dispute(env: Env, trade_id: u64) -> Option<Dispute> {
+        let disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap_or(Map::new(&env));
+        disputes.get(trade_id)
+    }
+
+    /// Returns all disputes that have not yet been resolved.
+    /// 
+    /// # Usage
+    /// - Admin dashboard of pending disputes
+    /// - Monitoring of dispute backlog
+    /// 
+    /// # Returns
+    /// Vector of open disputes, oldest first
+    fn get_open_disputes(env: Env) -> Vec<Dispute> {
+        let disputes: Map<u64, Dispute> = env.storage().instance().get(&DataKey::Disputes).unwrap_or(Map::new(&env));
+        let queue: Vec<u64> = env.storage().instance().get(&DataKey::DisputeQueue).unwrap_or(Vec::new(&env));
+        let mut open = Vec::new(&env);
+        for trade_id in queue.iter() {
+            if let Some(dispute) = disputes.get(trade_id) {
+                open.push_back(dispute);
+            }
+        }
+        open
+    }
+
+    /// Returns a page of disputed trade IDs ordered by dispute age (oldest first).
+    /// 
+    /// # Usage
+    /// - Arbitration team works disputes as a first-in, first-out queue
+    /// - Avoids scanning all trades or relying on an external indexer
+    /// 
+    /// # Arguments
+    /// * `start` - Zero-based position in the queue to start from
+    /// * `limit` - Maximum number of trade IDs to return
+    /// 
+    /// # Returns
+    /// Vector of disputed trade IDs awaiting resolution
+    fn get_disputed_trades(env: Env, start: u32, limit: u32) -> Vec<u64> {
+        let queue: Vec<u64> = env.storage().instance().get(&DataKey::DisputeQueue).unwrap_or(Vec::new(&env));
+        let end = start.saturating_add(limit).min(queue.len());
+        if start >= end {
+            return Vec::new(&env);
+        }
+        queue.slice(start..end)
+    }
+
+    /// Returns a page of the on-chain admin activity log.
+    /// 
+    /// # Usage
+    /// - Auditors reconstruct configuration history directly from contract state
+    /// - Only the most recent 200 entries are kept; older IDs are skipped
+    /// 
+    /// # Arguments
+    /// * `start` - Smallest log ID to include (pass the last returned ID + 1 for the next page)
+    /// * `limit` - Maximum number of entries to return
+    /// 
+    /// # Returns
+    /// Vector of admin log entries in ascending ID order
+    fn get_admin_log(env: Env, start: u64, limit: u32) -> Vec<AdminLogEntry> {
+        let next_id: u64 = env.storage().instance().get(&DataKey::NextAdminLogId).unwrap_or(0);
+        let oldest = next_id.saturating_sub(MAX_ADMIN_LOG_ENTRIES);
+        let mut page = Vec::new(&env);
+        let mut id = start.max(oldest);
+        while id < next_id && page.len() < limit {
+            if let Some(entry) = env.storage().persistent().get(&DataKey::AdminLog(id)) {
+                page.push_back(entry);
+            }
+            id += 1;
+        }
+        page
+    }
+
     /// Returns the mapping of sellers to their active offer IDs.
     /// 
     /// # Usage
@@ -1500,12 +8171,12 @@ impl P2PMarketplaceContract {
     /// - Display active offers by seller
     /// 
     /// # Returns
-    /// Map of seller addresses to their active offer IDs
-    pub fn get_active_offers(env: Env) -> Map<Address, u64> {
-        env.storage().instance().get(&ACTIVE_OFFERS).unwrap()
+    /// Map of seller addresses to their active offer IDs, oldest first
+    fn get_active_offers(env: Env) -> Map<Address, Vec<u64>> {
+        env.storage().instance().get(&DataKey::ActiveOffers).unwrap_or(Map::new(&env))
     }
-    
-    /// Returns the active offer ID for a specific seller.
+
+    /// Returns the oldest active offer ID for a specific seller.
     /// 
     /// # Usage
     /// - Check if seller has an active offer
@@ -1516,10 +8187,63 @@ impl P2PMarketplaceContract {
     /// * `seller` - The seller address to check
     /// 
     /// # Returns
-    /// The seller's active offer ID if they have one, None otherwise
-    pub fn get_seller_active_offer(env: Env, seller: Address) -> Option<u64> {
-        let active_offers: Map<Address, u64> = env.storage().instance().get(&ACTIVE_OFFERS).unwrap();
-        active_offers.get(seller)
+    /// The seller's oldest active offer ID if they have one, None otherwise
+    fn get_seller_active_offer(env: Env, seller: Address) -> Option<u64> {
+        Self::get_seller_active_offers(env, seller).first()
+    }
+
+    /// Returns every active offer ID for a specific seller.
+    /// Market makers may hold several price levels created through `create_offers`.
+    /// 
+    /// # Arguments
+    /// * `seller` - The seller address to check
+    /// 
+    /// # Returns
+    /// The seller's active offer IDs, oldest first (empty if none)
+    fn get_seller_active_offers(env: Env, seller: Address) -> Vec<u64> {
+        let active_offers: Map<Address, Vec<u64>> = env.storage().instance().get(&DataKey::ActiveOffers).unwrap_or(Map::new(&env));
+        active_offers.get(seller).unwrap_or(Vec::new(&env))
+    }
+
+    /// Returns the total USDC a seller has locked in escrow.
+    /// 
+    /// # Calculation
+    /// Sum of the escrow of every active offer. An offer stays active while a trade
+    /// against it is open or disputed, so amounts locked in open trades are included
+    /// exactly once.
+    /// 
+    /// # Arguments
+    /// * `seller` - The seller to reconcile
+    /// 
+    /// # Returns
+    /// Escrowed USDC in the token's smallest unit
+    fn get_escrowed_balance(env: Env, seller: Address) -> i128 {
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env));
+        Self::get_seller_active_offers(env, seller)
+            .iter()
+            .filter_map(|offer_id| offers.get(offer_id))
+            .map(|offer| offer.usdc_amount)
+            .sum()
+    }
+
+    /// Returns the number of active offers.
+    /// Maintained as a counter, so it is cheap for dashboards and monitoring.
+    fn get_active_offer_count(env: Env) -> u32 {
+        let count: i128 = env.storage().instance().get(&DataKey::OfferCount).unwrap_or(0);
+        count as u32
+    }
+
+    /// Returns the number of trades that are initiated, payment-confirmed, or disputed.
+    /// Maintained as a counter, so it is cheap for dashboards and monitoring.
+    fn get_open_trade_count(env: Env) -> u32 {
+        let count: i128 = env.storage().instance().get(&DataKey::OpenTrades).unwrap_or(0);
+        count as u32
+    }
+
+    /// Returns the USDC escrowed across all active offers.
+    /// Maintained as a counter; `verify_escrow` remains the full reconciliation.
+    fn get_total_escrowed(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::TotalEscrow).unwrap_or(0)
     }
 
     /// Returns whether the contract is currently paused.
@@ -1531,10 +8255,45 @@ impl P2PMarketplaceContract {
     /// 
     /// # Returns
     /// True if contract is paused, false if trading is active
-    pub fn is_paused(env: Env) -> bool {
-        env.storage().instance().get(&PAUSED_KEY).unwrap_or(false)
+    fn is_paused(env: Env) -> bool {
+        env.storage().instance().get(&DataKey::Paused).unwrap_or(false)
     }
-    
+
+    /// Returns how long final-state records are kept before they can be pruned.
+    /// 
+    /// # Returns
+    /// Retention period in seconds
+    fn get_record_retention(env: Env) -> u64 {
+        env.storage().persistent().get(&ConfigKey::RecordRetention).unwrap_or(DEFAULT_RECORD_RETENTION)
+    }
+
+    /// Returns how long an offer may go without trade activity before anyone can expire it.
+    /// 
+    /// # Returns
+    /// TTL in seconds, 0 if disabled
+    fn get_offer_ttl(env: Env) -> u64 {
+        let ttl: Option<(u64, u64)> = env.storage().persistent().get(&ConfigKey::OfferTtl);
+        ttl.map(|(ttl_seconds, _)| ttl_seconds).unwrap_or(0)
+    }
+
+    /// Returns when an active offer can be expired with `expire_offer`.
+    /// 
+    /// # Returns
+    /// Timestamp from which the offer can be expired, or None if the offer TTL is disabled
+    /// or the offer isn't active
+    fn get_offer_expiry(env: Env, offer_id: u64) -> Option<u64> {
+        let (ttl_seconds, set_at): (u64, u64) = env.storage().persistent().get(&ConfigKey::OfferTtl)?;
+        let activity: Map<u64, u64> = env.storage().instance().get(&DataKey::OfferActivity).unwrap_or(Map::new(&env));
+        let offers: Map<u64, Offer> = env.storage().instance().get(&DataKey::Offers).unwrap_or(Map::new(&env));
+        let offer = offers.get(offer_id)?;
+        if !matches!(offer.status, OfferStatus::Active | OfferStatus::Reserved) {
+            return None;
+        }
+        // Offers listed before activity was tracked count from the TTL update
+        let last_activity = activity.get(offer_id).unwrap_or(0).max(set_at);
+        Some(last_activity + ttl_seconds)
+    }
+
     /// Returns comprehensive contract configuration and status.
     /// This is a convenience function that aggregates multiple config values.
     /// 
@@ -1545,17 +8304,39 @@ impl P2PMarketplaceContract {
     /// 
     /// # Returns
     /// Tuple containing:
-    /// (admin, usdc_token, fee_collector, fee_rate, min_amount, max_amount, expiration, is_paused)
-    pub fn get_contract_info(env: Env) -> (Address, Address, Address, u32, i128, i128, u64, bool) {
-        (
-            Self::get_admin(env.clone()),
-            Self::get_usdc_token_id(env.clone()),
-            Self::get_fee_collector(env.clone()),
+    /// (admin, usdc_token, fee_collector, fee_rate, min_amount, max_amount, expiration, is_paused,
+    ///  record_retention)
+    /// 
+    /// # Errors
+    /// - NotInitialized: If the contract has not been initialized
+    #[allow(clippy::type_complexity)] // Contract functions can't return type aliases of tuples
+    fn get_contract_info(env: Env) -> Result<(Address, Address, Address, u32, i128, i128, u64, bool, u64), Error> {
+        Ok((
+            Self::get_admin(env.clone())?,
+            Self::get_usdc_token_id(env.clone())?,
+            Self::get_fee_collector(env.clone())?,
             Self::get_fee_rate(env.clone()),
             Self::get_trade_limits(env.clone()).0,
             Self::get_trade_limits(env.clone()).1,
             Self::get_trade_expiration(env.clone()),
-            Self::is_paused(env)
-        )
+            Self::is_paused(env.clone()),
+            Self::get_record_retention(env)
+        ))
+    }
+
+    /// Returns machine-readable metadata for an error code.
+    /// 
+    /// # Usage
+    /// - Wallets map a failed call's error code to a category, severity and retry hint
+    /// - `name` is the `Error` variant name, usable as a localization key
+    /// - `params` names the arguments to highlight, e.g. `usdc_amount` for BelowMinimum
+    /// 
+    /// # Arguments
+    /// * `code` - The `Error` discriminant
+    /// 
+    /// # Returns
+    /// The error's metadata, or None if no error has that code
+    fn get_error_info(env: Env, code: u32) -> Option<ErrorInfo> {
+        error_info::error_info(&env, code)
     }
 }
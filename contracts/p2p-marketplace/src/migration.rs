@@ -0,0 +1,17 @@
+//! Interface the marketplace expects from a successor deployment.
+//!
+//! When an upgrade can't go through `upgrade` (e.g. a storage layout change), the admin
+//! migrates escrow with `migrate_escrow`. The marketplace transfers the USDC backing every
+//! active offer to the successor before calling `import_escrow` with the offer and open
+//! trade records, so the successor can take over exactly where this deployment stopped.
+
+use soroban_sdk::{contractclient, Address, Env, Vec};
+
+use crate::types::{Offer, Trade};
+
+#[contractclient(name = "SuccessorClient")]
+pub trait Successor {
+    /// Takes over `offers` and the open `trades` against them from the `from` marketplace,
+    /// which has already transferred `amount` USDC (escrow plus fee reserves) to the successor
+    fn import_escrow(env: Env, from: Address, offers: Vec<(u64, Offer)>, trades: Vec<(u64, Trade)>, amount: i128);
+}
@@ -0,0 +1,61 @@
+//! Trade status state machine.
+//!
+//! Every change to a trade's status goes through [`transition`], which checks it against
+//! the table in [`can_transition`] and stamps the completion or cancellation time.
+//! Entrypoints still check their own preconditions (e.g. only initiated trades can be
+//! confirmed), but none of them can move a trade along an edge that isn't in the table.
+//!
+//! ```text
+//! Initiated ──► PaymentConfirmed ──► Completed
+//!   │   ▲              │   │             ▲
+//!   │   │ (reopen)     │   ▼             │
+//!   ├───┼──────────────┼─► Disputed ─────┤
+//!   │   │              │   │             │
+//!   ▼   │    (refund)  ▼   ▼
+//! Cancelled ◄──────────┴───┘
+//! ```
+//!
+//! The only way back out of Cancelled is `reopen_trade`, which both parties must sign
+//! shortly after an accidental cancellation. A payment-confirmed trade is only cancelled
+//! by `emergency_refund` while the marketplace winds down.
+
+use soroban_sdk::Env;
+
+use crate::types::{Error, Trade, TradeStatus};
+
+/// Whether a trade may move directly from one status to another.
+/// Completed is terminal and Cancelled can only be reopened; a status never transitions to itself.
+pub fn can_transition(from: &TradeStatus, to: &TradeStatus) -> bool {
+    use TradeStatus::*;
+    matches!(
+        (from, to),
+        (Initiated, PaymentConfirmed | Cancelled | Disputed)
+            | (PaymentConfirmed, Completed | Disputed | Cancelled)
+            | (Disputed, Completed | Cancelled)
+            | (Cancelled, Initiated)
+    )
+}
+
+/// Whether a trade has settled: no transitions are possible other than reopening a cancelled trade
+pub fn is_final(status: &TradeStatus) -> bool {
+    matches!(status, TradeStatus::Completed | TradeStatus::Cancelled)
+}
+
+/// Moves a trade to a new status, recording when it completed or was cancelled.
+///
+/// # Errors
+/// - InvalidTradeStatus: If the transition isn't allowed from the trade's current status
+pub fn transition(env: &Env, trade: &mut Trade, to: TradeStatus) -> Result<(), Error> {
+    if !can_transition(&trade.status, &to) {
+        return Err(Error::InvalidTradeStatus);
+    }
+
+    match to {
+        TradeStatus::Completed => trade.completed_at = Some(env.ledger().timestamp()),
+        TradeStatus::Cancelled => trade.cancelled_at = Some(env.ledger().timestamp()),
+        TradeStatus::Initiated => trade.cancelled_at = None,
+        _ => {}
+    }
+    trade.status = to;
+    Ok(())
+}
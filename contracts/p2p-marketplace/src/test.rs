@@ -1,10 +1,14 @@
 #![cfg(test)]
 
 use super::*;
+use crate::testutils::{
+    count_events, default_fee_policy, events_with_topic, setup_marketplace, setup_marketplace_with_fee_policy,
+};
 use soroban_sdk::{
-    testutils::{Address as TestAddress, Ledger, LedgerInfo},
-    token, Address, Env,
+    testutils::{Address as TestAddress, Events, Ledger, LedgerInfo, MockAuth, MockAuthInvoke},
+    token, vec, xdr::SorobanAuthorizationEntry, Address, Env, IntoVal, Symbol, TryFromVal,
 };
+use ed25519_dalek::{Signer, SigningKey};
 
 // Helper function to create a token contract for testing
 fn create_token_contract<'a>(
@@ -18,13 +22,301 @@ fn create_token_contract<'a>(
 }
 
 // Helper function to setup token balance for testing
-fn setup_token_balance(env: &Env, token_admin: &Address, token_id: &Address, user: &Address, amount: i128, marketplace_contract: &Address) {
+// No allowance is needed: create_offer transfers with the seller's own authorization
+fn setup_token_balance(env: &Env, token_admin: &Address, token_id: &Address, user: &Address, amount: i128, _marketplace_contract: &Address) {
     let token_admin_client = token::StellarAssetClient::new(env, token_id);
     token_admin_client.mint(user, &amount);
-    
-    // Also set up allowance for the marketplace contract
-    let token_client = token::Client::new(env, token_id);
-    token_client.approve(user, marketplace_contract, &amount, &99999);
+}
+
+// Minimal token double that burns 1% of every transfer, used to exercise
+// escrow accounting against non-standard tokens
+#[contract]
+pub struct FeeOnTransferToken;
+
+#[contractimpl]
+impl FeeOnTransferToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().persistent().set(&to, &(balance + amount));
+    }
+
+    pub fn decimals(_env: Env) -> u32 {
+        6
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().persistent().get(&id).unwrap_or(0)
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        let fee = amount / 100;
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage().persistent().set(&from, &(from_balance - amount));
+        let to_balance = Self::balance(env.clone(), to.clone());
+        env.storage().persistent().set(&to, &(to_balance + amount - fee));
+    }
+}
+
+// Hostile token double that misbehaves whenever the marketplace pays out of escrow:
+// it can fail the transfer, skim part of the amount, or call back into the marketplace
+// (in its own module so its contract spec doesn't clash with FeeOnTransferToken)
+mod hostile_token {
+    use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, Symbol, Val, Vec};
+
+    #[contracttype]
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum Hostility {
+        /// Behaves like a plain token
+        None,
+        /// Panics on every transfer out of the marketplace
+        FailPayouts,
+        /// Debits the full amount but credits the recipient this much less
+        Skim(i128),
+        /// Invokes the given marketplace function before moving funds
+        Reenter(Symbol, Vec<Val>),
+    }
+
+    #[contract]
+    pub struct HostileToken;
+
+    #[contractimpl]
+    impl HostileToken {
+        pub fn mint(env: Env, to: Address, amount: i128) {
+            let balance = Self::balance(env.clone(), to.clone());
+            env.storage().persistent().set(&to, &(balance + amount));
+        }
+
+        pub fn decimals(_env: Env) -> u32 {
+            7
+        }
+
+        pub fn balance(env: Env, id: Address) -> i128 {
+            env.storage().persistent().get(&id).unwrap_or(0)
+        }
+
+        /// Configures how the token treats transfers out of `market`
+        pub fn set_hostility(env: Env, market: Address, hostility: Hostility) {
+            env.storage().instance().set(&symbol_short!("market"), &market);
+            env.storage().instance().set(&symbol_short!("mode"), &hostility);
+            env.storage().instance().remove(&symbol_short!("reentered"));
+        }
+
+        /// Whether the re-entrant call succeeded, or None if none was attempted
+        pub fn reentered(env: Env) -> Option<bool> {
+            env.storage().instance().get(&symbol_short!("reentered"))
+        }
+
+        pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+            from.require_auth();
+            let mut credited = amount;
+            let market: Option<Address> = env.storage().instance().get(&symbol_short!("market"));
+            if market.as_ref() == Some(&from) {
+                let mode: Hostility = env.storage().instance().get(&symbol_short!("mode")).unwrap_or(Hostility::None);
+                match mode {
+                    Hostility::None => {}
+                    Hostility::FailPayouts => panic!("payout refused"),
+                    Hostility::Skim(skim) => credited -= skim,
+                    Hostility::Reenter(func, args) => {
+                        // Re-enter once, then behave
+                        env.storage().instance().set(&symbol_short!("mode"), &Hostility::None);
+                        let result = env.try_invoke_contract::<(), soroban_sdk::Error>(&from, &func, args);
+                        env.storage().instance().set(&symbol_short!("reentered"), &matches!(result, Ok(Ok(()))));
+                    }
+                }
+            }
+            let from_balance = Self::balance(env.clone(), from.clone());
+            env.storage().persistent().set(&from, &(from_balance - amount));
+            let to_balance = Self::balance(env.clone(), to.clone());
+            env.storage().persistent().set(&to, &(to_balance + credited));
+        }
+    }
+}
+use hostile_token::{Hostility, HostileTokenClient};
+
+// Lending pool double implementing the yield adapter interface; yield is simulated with `accrue`
+mod yield_pool {
+    use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env};
+
+    #[contract]
+    pub struct MockYieldPool;
+
+    #[contractimpl]
+    impl MockYieldPool {
+        pub fn __constructor(env: Env, token: Address) {
+            env.storage().instance().set(&symbol_short!("token"), &token);
+        }
+
+        pub fn deposit(env: Env, from: Address, amount: i128) {
+            let position = Self::balance(env.clone(), from.clone());
+            env.storage().persistent().set(&from, &(position + amount));
+        }
+
+        pub fn withdraw(env: Env, to: Address, amount: i128) {
+            let position = Self::balance(env.clone(), to.clone());
+            env.storage().persistent().set(&to, &(position - amount));
+            let token: Address = env.storage().instance().get(&symbol_short!("token")).unwrap();
+            token::Client::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+        }
+
+        pub fn balance(env: Env, owner: Address) -> i128 {
+            env.storage().persistent().get(&owner).unwrap_or(0)
+        }
+
+        /// Credits interest to a position; the test mints the matching tokens to the pool
+        pub fn accrue(env: Env, owner: Address, amount: i128) {
+            Self::deposit(env, owner, amount);
+        }
+    }
+}
+use yield_pool::MockYieldPoolClient;
+
+// Arbitration contract standing in for a juror pool
+mod juror_pool {
+    use crate::{types::DisputeResolution, P2PMarketplaceContractClient};
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    #[contract]
+    pub struct JurorPool;
+
+    #[contractimpl]
+    impl JurorPool {
+        pub fn open_case(env: Env, trade_id: u64, raised_by: Address, usdc_amount: i128) {
+            env.storage().persistent().set(&trade_id, &(raised_by, usdc_amount));
+        }
+
+        pub fn case(env: Env, trade_id: u64) -> Option<(Address, i128)> {
+            env.storage().persistent().get(&trade_id)
+        }
+
+        /// Delivers the jurors' verdict to the marketplace
+        pub fn rule(env: Env, market: Address, trade_id: u64, resolution: DisputeResolution) {
+            P2PMarketplaceContractClient::new(&env, &market).submit_ruling(&trade_id, &resolution);
+        }
+    }
+}
+use juror_pool::JurorPoolClient;
+
+// Custom account contract standing in for a smart wallet (e.g. passkey-controlled).
+// It approves every authorization and counts how often the host asked it to
+mod smart_wallet {
+    use soroban_sdk::{auth::{Context, CustomAccountInterface}, contract, contractimpl, crypto::Hash, symbol_short, Env, Vec};
+
+    #[contract]
+    pub struct SmartWallet;
+
+    #[contractimpl]
+    impl SmartWallet {
+        /// Number of `__check_auth` calls served so far
+        pub fn checks(env: Env) -> u32 {
+            env.storage().instance().get(&symbol_short!("checks")).unwrap_or(0)
+        }
+    }
+
+    #[contractimpl]
+    impl CustomAccountInterface for SmartWallet {
+        type Signature = ();
+        type Error = soroban_sdk::Error;
+
+        #[allow(non_snake_case)]
+        fn __check_auth(env: Env, _signature_payload: Hash<32>, _signatures: (), _auth_contexts: Vec<Context>) -> Result<(), soroban_sdk::Error> {
+            let checks = Self::checks(env.clone());
+            env.storage().instance().set(&symbol_short!("checks"), &(checks + 1));
+            Ok(())
+        }
+    }
+}
+use smart_wallet::{SmartWallet, SmartWalletClient};
+
+// Successor deployment that records what a migration handed over
+mod successor_double {
+    use crate::types::{Offer, Trade};
+    use soroban_sdk::{contract, contractimpl, symbol_short, Address, Env, Vec};
+
+    #[contract]
+    pub struct SuccessorDouble;
+
+    #[contractimpl]
+    impl SuccessorDouble {
+        pub fn import_escrow(env: Env, from: Address, offers: Vec<(u64, Offer)>, trades: Vec<(u64, Trade)>, amount: i128) {
+            env.storage().instance().set(&symbol_short!("import"), &(from, offers.len(), trades.len(), amount));
+        }
+
+        /// (from, offers, trades, amount) of the last import
+        pub fn imported(env: Env) -> Option<(Address, u32, u32, i128)> {
+            env.storage().instance().get(&symbol_short!("import"))
+        }
+    }
+}
+use successor_double::{SuccessorDouble, SuccessorDoubleClient};
+
+// Deployment whose storage the marketplace never initialized
+mod uninitialized {
+    use soroban_sdk::contract;
+
+    #[contract]
+    pub struct Uninitialized;
+}
+use uninitialized::Uninitialized;
+
+// Authorizes the next call with real authorization entries instead of mocks, so each
+// wallet's `__check_auth` runs. Mocking stays off until `mock_all_auths` is called again.
+fn authorize_wallets(env: &Env, auths: &[MockAuth]) {
+    extern crate std;
+    let entries: std::vec::Vec<SorobanAuthorizationEntry> = auths.iter().map(Into::into).collect();
+    env.set_auths(&entries);
+}
+
+// Deploys a marketplace over a fresh hostile token
+// Returns (env, client, token client, contract_id)
+fn setup_hostile_env() -> (Env, P2PMarketplaceContractClient<'static>, HostileTokenClient<'static>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = <Address as TestAddress>::generate(&env);
+    let fee_collector = <Address as TestAddress>::generate(&env);
+
+    let token_id = env.register(hostile_token::HostileToken, ());
+    let token_client = HostileTokenClient::new(&env, &token_id);
+    let contract_id = env.register(P2PMarketplaceContract, (&admin, &token_id, &fee_collector, default_fee_policy()));
+    let client = P2PMarketplaceContractClient::new(&env, &contract_id);
+
+    (env, client, token_client, contract_id)
+}
+
+// Creates a funded offer and a trade against it on a hostile token market
+// Returns (seller, buyer, offer_id, trade_id)
+fn setup_hostile_trade(
+    env: &Env,
+    client: &P2PMarketplaceContractClient,
+    token_client: &HostileTokenClient,
+    usdc_amount: i128,
+) -> (Address, Address, u64, u64) {
+    let seller = <Address as TestAddress>::generate(env);
+    let buyer = <Address as TestAddress>::generate(env);
+    token_client.mint(&seller, &usdc_amount);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    (seller, buyer, offer_id, trade_id)
+}
+
+// Helper function to create a funded offer and a trade against it
+// Returns (seller, buyer, offer_id, trade_id)
+fn setup_trade(
+    env: &Env,
+    client: &P2PMarketplaceContractClient,
+    admin: &Address,
+    usdc_token_id: &Address,
+    contract_id: &Address,
+    usdc_amount: i128,
+) -> (Address, Address, u64, u64) {
+    let seller = <Address as TestAddress>::generate(env);
+    let buyer = <Address as TestAddress>::generate(env);
+    let kes_amount = usdc_amount * 120;
+
+    setup_token_balance(env, admin, usdc_token_id, &seller, usdc_amount, contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    (seller, buyer, offer_id, trade_id)
 }
 
 // Main test setup function
@@ -37,33 +329,20 @@ fn setup_test_env() -> (
     Address,
 ) {
     let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = <Address as TestAddress>::generate(&env);
-    let fee_collector = <Address as TestAddress>::generate(&env);
-
-    // Setup the P2P marketplace contract
-    let contract_id = env.register(P2PMarketplaceContract, ());
-    let client = P2PMarketplaceContractClient::new(&env, &contract_id);
-
-    // Setup the USDC token contract
-    let (usdc_token_id, usdc_client) = create_token_contract(&env, &admin);
-
-    // Initialize the P2P marketplace
-    client.initialize(&admin, &usdc_token_id, &fee_collector);
+    let market = setup_marketplace(&env);
 
-    (env, client, admin, usdc_token_id, usdc_client, contract_id)
+    (env, market.client, market.admin, market.usdc_token_id, market.usdc, market.contract_id)
 }
 
 #[test]
 fn test_initialize() {
     let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
-    let (read_admin, read_usdc_token, _, _, _, _, _, _) = client.get_contract_info();
+    let (read_admin, read_usdc_token, _, _, _, _, _, _, _) = client.get_contract_info();
 
     assert_eq!(read_admin, admin);
     assert_eq!(read_usdc_token, usdc_token_id);
-    assert_eq!(client.get_next_offer_id(), 0);
-    assert_eq!(client.get_next_trade_id(), 0);
+    assert_eq!(client.get_next_offer_id(), Some(0));
+    assert_eq!(client.get_next_trade_id(), Some(0));
     assert_eq!(client.get_offers(), Map::new(&env));
     assert!(!client.is_paused());
 }
@@ -84,7 +363,7 @@ fn test_create_offer() {
     let kes_amount = 12_000_000_000; // 12,000 KES
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
 
     assert_eq!(offer_id, 0);
     let offer = client.get_offer(&offer_id).unwrap();
@@ -101,8 +380,8 @@ fn test_create_offer_already_has_active_offer() {
     let kes_amount = 12_000_000_000;
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 2, &contract_id);
-    client.create_offer(&seller, &usdc_amount, &kes_amount);
-    client.create_offer(&seller, &usdc_amount, &kes_amount);
+    client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
 }
 
 #[test]
@@ -115,7 +394,7 @@ fn test_create_offer_paused() {
 
     client.pause();
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    client.create_offer(&seller, &usdc_amount, &kes_amount);
+    client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
 }
 
 #[test]
@@ -127,8 +406,8 @@ fn test_initiate_trade() {
     let kes_amount = 12_000_000_000;
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
-    let trade_id = client.initiate_trade(&buyer, &offer_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
 
     assert_eq!(trade_id, 0);
     let trade = client.get_trade(&trade_id).unwrap();
@@ -141,7 +420,7 @@ fn test_initiate_trade() {
 fn test_initiate_trade_offer_not_found() {
     let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
     let buyer = <Address as TestAddress>::generate(&env);
-    client.initiate_trade(&buyer, &999);
+    client.initiate_trade(&buyer, &999, &None, &None, &0, &None);
 }
 
 #[test]
@@ -154,9 +433,9 @@ fn test_initiate_trade_already_initiated() {
     let kes_amount = 12_000_000_000;
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
-    client.initiate_trade(&buyer, &offer_id);
-    client.initiate_trade(&buyer, &offer_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
 }
 
 #[test]
@@ -167,12 +446,15 @@ fn test_cancel_offer() {
     let kes_amount = 12_000_000_000;
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
     assert_eq!(usdc_client.balance(&contract_id), usdc_amount);
 
     client.cancel_offer(&seller, &offer_id);
-    assert_eq!(client.get_offers().len(), 0);
+    // The record stays for the audit trail, but the offer is no longer listed
+    assert_eq!(client.get_offer(&offer_id).unwrap().status, OfferStatus::Cancelled);
+    assert_eq!(client.get_seller_active_offer(&seller), None);
     assert_eq!(usdc_client.balance(&seller), usdc_amount);
+    assert_eq!(client.try_cancel_offer(&seller, &offer_id), Err(Ok(Error::OfferNotFound)));
 }
 
 #[test]
@@ -185,8 +467,8 @@ fn test_cancel_offer_trade_already_initiated() {
     let kes_amount = 12_000_000_000;
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
-    client.initiate_trade(&buyer, &offer_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
     client.cancel_offer(&seller, &offer_id);
 }
 
@@ -200,8 +482,8 @@ fn test_confirm_payment_and_release() {
     let kes_amount = 12_000_000_000;
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
-    let trade_id = client.initiate_trade(&buyer, &offer_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
 
     client.confirm_payment(&trade_id, &buyer);
     let trade = client.get_trade(&trade_id).unwrap();
@@ -228,8 +510,8 @@ fn test_confirm_payment_trade_expired() {
     let kes_amount = 12_000_000_000;
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
-    let trade_id = client.initiate_trade(&buyer, &offer_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
 
     let expiration = client.get_trade_expiration();
     env.ledger().set(LedgerInfo {
@@ -256,8 +538,8 @@ fn test_confirm_payment_invalid_trade_status() {
     let kes_amount = 12_000_000_000;
 
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
-    let trade_id = client.initiate_trade(&buyer, &offer_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
 
     client.confirm_payment(&trade_id, &buyer);
     client.confirm_payment(&trade_id, &seller);
@@ -277,11 +559,11 @@ fn test_trade_completion_after_fix() {
     setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
 
     // 1. Seller creates an offer
-    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
     assert_eq!(usdc_client.balance(&contract_id), usdc_amount);
 
     // 2. Buyer initiates a trade
-    let trade_id = client.initiate_trade(&buyer, &offer_id);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
     assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Initiated);
 
     // 3. Buyer confirms payment
@@ -311,3 +593,3449 @@ fn test_trade_completion_after_fix() {
     // 7. Verify offer is no longer active
     assert!(!client.get_active_offers().contains_key(seller));
 }
+
+#[test]
+fn test_raise_dispute_records_dispute() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    let kes_amount = 12_000_000_000;
+
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+
+    let reason = String::from_str(&env, "Payment sent but not acknowledged");
+    let mut evidence = Vec::new(&env);
+    evidence.push_back(BytesN::from_array(&env, &[7u8; 32]));
+    let essay = String::from_bytes(&env, &[b'x'; 257]);
+    assert_eq!(client.try_raise_dispute(&trade_id, &buyer, &essay, &evidence), Err(Ok(Error::InvalidAmount)));
+    let mut pile = Vec::new(&env);
+    for i in 0..51u8 {
+        pile.push_back(BytesN::from_array(&env, &[i; 32]));
+    }
+    assert_eq!(client.try_raise_dispute(&trade_id, &buyer, &reason, &pile), Err(Ok(Error::InvalidAmount)));
+    client.raise_dispute(&trade_id, &buyer, &reason, &evidence);
+
+    let dispute = client.get_dispute(&trade_id).unwrap();
+    assert_eq!(dispute.trade_id, trade_id);
+    assert_eq!(dispute.raised_by, buyer);
+    assert_eq!(dispute.reason, reason);
+    assert_eq!(dispute.evidence, evidence);
+    assert_eq!(dispute.resolved_by, None);
+    assert_eq!(client.get_open_disputes().len(), 1);
+}
+
+#[test]
+fn test_resolve_dispute_records_resolution() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    let kes_amount = 12_000_000_000;
+
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+
+    let reason = String::from_str(&env, "No payment received");
+    client.raise_dispute(&trade_id, &seller, &reason, &Vec::new(&env));
+    client.resolve_dispute(&trade_id, &DisputeResolution::RefundToSeller);
+
+    let dispute = client.get_dispute(&trade_id).unwrap();
+    assert_eq!(dispute.resolved_by, Some(admin));
+    assert_eq!(dispute.resolution, DisputeOutcome::Resolved(DisputeResolution::RefundToSeller));
+    assert_eq!(client.get_open_disputes().len(), 0);
+    assert_eq!(usdc_client.balance(&seller), usdc_amount);
+}
+
+#[test]
+fn test_disputes_assigned_round_robin_to_panel() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let first = <Address as TestAddress>::generate(&env);
+    let second = <Address as TestAddress>::generate(&env);
+    client.set_panel_arbitrator(&first, &true);
+    client.set_panel_arbitrator(&second, &true);
+    client.set_panel_arbitrator(&first, &true);
+    assert_eq!(client.get_arbitrator_panel(), vec![&env, first.clone(), second.clone()]);
+
+    let reason = String::from_str(&env, "No payment received");
+    let mut trades = Vec::new(&env);
+    for _ in 0..3 {
+        let (seller, _, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+        client.raise_dispute(&trade_id, &seller, &reason, &Vec::new(&env));
+        trades.push_back((trade_id, seller));
+    }
+    for ((trade_id, _), expected) in trades.iter().zip([&first, &second, &first]) {
+        assert_eq!(client.get_dispute(&trade_id).unwrap().arbitrator, Some(expected.clone()));
+    }
+
+    // Only the assigned arbitrator can rule
+    let (trade_id, seller) = trades.get(0).unwrap();
+    assert_eq!(
+        client.try_arbitrate_dispute(&second, &trade_id, &DisputeResolution::RefundToSeller),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.arbitrate_dispute(&first, &trade_id, &DisputeResolution::RefundToSeller);
+    assert_eq!(client.get_dispute(&trade_id).unwrap().resolved_by, Some(first.clone()));
+    assert_eq!(usdc_client.balance(&seller), 100_000_000);
+
+    // The admin can still resolve any dispute, including after an arbitrator leaves the panel
+    client.set_panel_arbitrator(&second, &false);
+    let (trade_id, _) = trades.get(1).unwrap();
+    client.resolve_dispute(&trade_id, &DisputeResolution::RefundToSeller);
+    assert_eq!(client.get_dispute(&trade_id).unwrap().resolved_by, Some(admin));
+}
+
+#[test]
+fn test_evidence_window_delays_resolution() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let window = 86_400;
+    client.update_evidence_window(&window);
+    let reason = String::from_str(&env, "No payment received");
+    let receipt = BytesN::from_array(&env, &[1; 32]);
+    let reply = BytesN::from_array(&env, &[2; 32]);
+
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    client.raise_dispute(&trade_id, &seller, &reason, &vec![&env, receipt.clone()]);
+    assert_eq!(client.get_dispute(&trade_id).unwrap().evidence_until, env.ledger().timestamp() + window);
+
+    // No ruling while the accused party can still respond
+    assert_eq!(
+        client.try_resolve_dispute(&trade_id, &DisputeResolution::RefundToSeller),
+        Err(Ok(Error::GracePeriodActive))
+    );
+    let stranger = <Address as TestAddress>::generate(&env);
+    assert_eq!(
+        client.try_submit_evidence(&trade_id, &stranger, &Vec::new(&env), &true),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.submit_evidence(&trade_id, &buyer, &vec![&env, reply.clone()], &true);
+    assert_eq!(
+        client.try_submit_evidence(&trade_id, &buyer, &Vec::new(&env), &false),
+        Err(Ok(Error::EvidenceWindowClosed))
+    );
+    assert_eq!(client.get_dispute(&trade_id).unwrap().evidence, vec![&env, receipt, reply]);
+
+    // Both sides finishing early unlocks resolution
+    client.submit_evidence(&trade_id, &seller, &Vec::new(&env), &true);
+    client.resolve_dispute(&trade_id, &DisputeResolution::RefundToSeller);
+
+    // Otherwise resolution waits for the window to close
+    let (seller, _, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    client.raise_dispute(&trade_id, &seller, &reason, &Vec::new(&env));
+    env.ledger().with_mut(|li| li.timestamp += window);
+    assert_eq!(
+        client.try_submit_evidence(&trade_id, &seller, &Vec::new(&env), &false),
+        Err(Ok(Error::EvidenceWindowClosed))
+    );
+    client.resolve_dispute(&trade_id, &DisputeResolution::RefundToSeller);
+}
+
+#[test]
+fn test_lost_dispute_slashes_reputation() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let reason = String::from_str(&env, "No payment received");
+    assert_eq!(client.get_dispute_penalty(), 5);
+
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    client.raise_dispute(&trade_id, &seller, &reason, &Vec::new(&env));
+    client.resolve_dispute(&trade_id, &DisputeResolution::RefundToSeller);
+    assert_eq!(client.get_reputation(&buyer), Reputation { completed_trades: 0, score: -5, disputes_lost: 1 });
+    assert_eq!(client.get_reputation(&seller), Reputation::default());
+
+    // The weight is configurable and applies to whoever the ruling goes against
+    client.update_dispute_penalty(&2);
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    client.raise_dispute(&trade_id, &buyer, &reason, &Vec::new(&env));
+    client.resolve_dispute(&trade_id, &DisputeResolution::ReleaseToBuyer);
+    assert_eq!(client.get_reputation(&seller), Reputation { completed_trades: 0, score: -2, disputes_lost: 1 });
+    assert_eq!(client.get_reputation(&buyer).disputes_lost, 0);
+    assert_eq!(client.try_update_dispute_penalty(&-1), Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_repeat_dispute_losers_suspended_until_reinstated() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let reason = String::from_str(&env, "No payment received");
+    let window = 30 * 86_400;
+    client.update_suspension_policy(&1, &window);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let lose_dispute = |buyer: &Address| {
+        let seller = <Address as TestAddress>::generate(&env);
+        setup_token_balance(&env, &admin, &usdc_token_id, &seller, 100_000_000, &contract_id);
+        let offer_id = client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+        let trade_id = client.initiate_trade(buyer, &offer_id, &None, &None, &0, &None);
+        client.raise_dispute(&trade_id, &seller, &reason, &Vec::new(&env));
+        client.resolve_dispute(&trade_id, &DisputeResolution::RefundToSeller);
+    };
+
+    // Losses spread beyond the window are tolerated
+    lose_dispute(&buyer);
+    env.ledger().with_mut(|li| li.timestamp += window);
+    lose_dispute(&buyer);
+    assert_eq!(client.get_suspended_at(&buyer), None);
+
+    // A second loss within the window suspends the buyer
+    lose_dispute(&buyer);
+    assert_eq!(client.get_suspended_at(&buyer), Some(env.ledger().timestamp()));
+
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 100_000_000, &contract_id);
+    let offer_id = client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::Suspended))
+    );
+    setup_token_balance(&env, &admin, &usdc_token_id, &buyer, 100_000_000, &contract_id);
+    assert_eq!(
+        client.try_create_offer(&buyer, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None),
+        Err(Ok(Error::Suspended))
+    );
+
+    // Reinstatement restores access but keeps the track record
+    client.reinstate(&buyer);
+    assert_eq!(client.try_reinstate(&buyer), Err(Ok(Error::InvalidAmount)));
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    assert_eq!(client.get_reputation(&buyer).disputes_lost, 3);
+}
+
+#[test]
+fn test_get_disputed_trades_ordered_by_age() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (_, buyer_a, _, trade_a) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    let (seller_b, _, _, trade_b) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 200_000_000);
+    let (_, buyer_c, _, trade_c) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 300_000_000);
+    let reason = String::from_str(&env, "dispute");
+
+    // Raise disputes out of trade ID order
+    client.raise_dispute(&trade_c, &buyer_c, &reason, &Vec::new(&env));
+    client.raise_dispute(&trade_a, &buyer_a, &reason, &Vec::new(&env));
+    client.raise_dispute(&trade_b, &seller_b, &reason, &Vec::new(&env));
+
+    assert_eq!(client.get_disputed_trades(&0, &10), vec![&env, trade_c, trade_a, trade_b]);
+    assert_eq!(client.get_disputed_trades(&1, &1), vec![&env, trade_a]);
+    assert_eq!(client.get_disputed_trades(&5, &10).len(), 0);
+
+    // Resolved disputes leave the queue
+    client.resolve_dispute(&trade_a, &DisputeResolution::RefundToSeller);
+    assert_eq!(client.get_disputed_trades(&0, &10), vec![&env, trade_c, trade_b]);
+}
+
+#[test]
+fn test_insurance_fund_collects_fee_share_and_compensates() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let fee_collector = client.get_fee_collector();
+    let usdc_amount = 100_000_000;
+
+    // Route 40% of every fee into the insurance fund
+    client.update_insurance_share(&4_000);
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    let insurance = fee * 4_000 / 10_000;
+    assert_eq!(client.get_insurance_fund(), insurance);
+    assert_eq!(usdc_client.balance(&fee_collector), fee - insurance);
+    assert_eq!(usdc_client.balance(&contract_id), insurance);
+
+    let victim = <Address as TestAddress>::generate(&env);
+    client.compensate(&victim, &insurance);
+    assert_eq!(usdc_client.balance(&victim), insurance);
+    assert_eq!(client.get_insurance_fund(), 0);
+}
+
+#[test]
+fn test_arbitrator_fees_accrue_from_insurance_fund() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let arbitrator = <Address as TestAddress>::generate(&env);
+    client.set_panel_arbitrator(&arbitrator, &true);
+    client.update_insurance_share(&10_000);
+    assert_eq!(client.try_update_arbitrator_fee(&-1), Err(Ok(Error::InvalidAmount)));
+
+    // Fund the insurance pool with the fees of a completed trade
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 1_000_000_000);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    let fund = client.get_insurance_fund();
+    let fee = fund * 3 / 4;
+    client.update_arbitrator_fee(&fee);
+
+    // The first ruling is paid in full, the second only gets what the fund has left
+    let reason = String::from_str(&env, "No payment received");
+    for expected in [fee, fund - fee] {
+        let (seller, _, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+        client.raise_dispute(&trade_id, &seller, &reason, &Vec::new(&env));
+        let before = client.get_arbitrator_stats(&arbitrator).1;
+        client.arbitrate_dispute(&arbitrator, &trade_id, &DisputeResolution::RefundToSeller);
+        assert_eq!(client.get_arbitrator_stats(&arbitrator).1 - before, expected);
+    }
+    assert_eq!(client.get_arbitrator_stats(&arbitrator), (2, fund));
+    assert_eq!(client.get_insurance_fund(), 0);
+    assert_eq!(client.verify_escrow(), 0);
+
+    assert_eq!(client.claim_arbitrator_fees(&arbitrator), fund);
+    assert_eq!(usdc_client.balance(&arbitrator), fund);
+    assert_eq!(client.get_arbitrator_stats(&arbitrator), (2, 0));
+    assert_eq!(client.try_claim_arbitrator_fees(&arbitrator), Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #11)")] // InvalidAmount
+fn test_compensate_exceeds_insurance_fund() {
+    let (env, client, _, _, _, _) = setup_test_env();
+    let victim = <Address as TestAddress>::generate(&env);
+    client.compensate(&victim, &1);
+}
+
+#[test]
+fn test_terms_hash_copied_onto_trade() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    let kes_amount = 12_000_000_000;
+    let terms_hash = BytesN::from_array(&env, &[42u8; 32]);
+
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &Some(terms_hash.clone()), &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+
+    assert_eq!(client.get_offer(&offer_id).unwrap().terms_hash, Some(terms_hash.clone()));
+    assert_eq!(client.get_trade(&trade_id).unwrap().terms_hash, Some(terms_hash));
+}
+
+#[test]
+fn test_trade_snapshots_offer_terms() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let usdc_amount = 250_000_000;
+    let (seller, _, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+
+    let offer = client.get_offer(&offer_id).unwrap();
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.seller, seller);
+    assert_eq!(trade.usdc_amount, offer.usdc_amount);
+    assert_eq!(trade.kes_amount, offer.kes_amount);
+}
+
+#[test]
+fn test_trade_lifecycle_timestamps() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 900);
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.confirm_payment(&trade_id, &buyer);
+    env.ledger().with_mut(|li| li.timestamp = 1_120);
+    client.confirm_payment(&trade_id, &seller);
+
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.buyer_confirmed_at, Some(1_000));
+    assert_eq!(trade.seller_confirmed_at, Some(1_120));
+    assert_eq!(trade.completed_at, Some(1_120));
+    assert_eq!(trade.cancelled_at, None);
+}
+
+#[test]
+fn test_create_offer_escrows_amount_actually_received() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = <Address as TestAddress>::generate(&env);
+    let fee_collector = <Address as TestAddress>::generate(&env);
+    let seller = <Address as TestAddress>::generate(&env);
+
+    let token_id = env.register(FeeOnTransferToken, ());
+    let token_client = FeeOnTransferTokenClient::new(&env, &token_id);
+    let contract_id = env.register(P2PMarketplaceContract, (&admin, &token_id, &fee_collector, default_fee_policy()));
+    let client = P2PMarketplaceContractClient::new(&env, &contract_id);
+
+    let usdc_amount = 100_000_000;
+    token_client.mint(&seller, &usdc_amount);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    // 1% is burned in transit, so only 99 USDC is actually held in escrow
+    let offer = client.get_offer(&offer_id).unwrap();
+    assert_eq!(offer.usdc_amount, 99_000_000);
+    assert_eq!(token_client.balance(&contract_id), offer.usdc_amount);
+}
+
+#[test]
+fn test_verify_escrow_and_sweep_surplus() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    assert_eq!(client.verify_escrow(), 0);
+
+    // Someone sends USDC straight to the contract by mistake
+    let donation = 5_000_000;
+    token::StellarAssetClient::new(&env, &usdc_token_id).mint(&contract_id, &donation);
+    assert_eq!(client.verify_escrow(), donation);
+
+    let treasury = <Address as TestAddress>::generate(&env);
+    assert_eq!(client.sweep_surplus(&treasury), donation);
+    assert_eq!(usdc_client.balance(&treasury), donation);
+    assert_eq!(usdc_client.balance(&contract_id), usdc_amount);
+    assert_eq!(client.verify_escrow(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #24)")] // InsufficientBalance
+fn test_create_offer_insufficient_balance() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount - 1, &contract_id);
+    client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+}
+
+#[test]
+fn test_trade_limits_scaled_to_token_decimals() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+
+    // Stellar Asset Contracts use 7 decimals
+    assert_eq!(client.get_token_decimals(), 7);
+    assert_eq!(client.get_trade_limits(), (10_000_000, 10_000_000_000_000));
+
+    // Half a token is below the 1-token minimum
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 5_000_000, &contract_id);
+    let result = client.try_create_offer(&seller, &5_000_000, &600_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(result, Err(Ok(Error::BelowMinimum)));
+
+    // Above the 1M-token maximum
+    let result = client.try_create_offer(&seller, &10_000_000_000_001, &600_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(result, Err(Ok(Error::AboveMaximum)));
+}
+
+#[test]
+fn test_onchain_settlement_swaps_both_legs() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let (kes_token_id, kes_client) = create_token_contract(&env, &admin);
+    client.set_kes_token(&kes_token_id);
+
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    let kes_amount = 12_000_000_000;
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    setup_token_balance(&env, &admin, &kes_token_id, &buyer, kes_amount, &contract_id);
+
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OnChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+
+    // The trade completes in the same invocation with no confirmations
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.status, TradeStatus::Completed);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount - fee);
+    assert_eq!(kes_client.balance(&seller), kes_amount);
+    assert_eq!(kes_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_swap_settlement_exchanges_two_onchain_assets() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let (eurc_token_id, eurc_client) = create_token_contract(&env, &admin);
+
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    let eurc_amount = 92_000_000;
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 2, &contract_id);
+    setup_token_balance(&env, &admin, &eurc_token_id, &buyer, eurc_amount, &contract_id);
+
+    // The swap asset can't be the escrowed token itself
+    assert_eq!(
+        client.try_create_offer(&seller, &usdc_amount, &eurc_amount, &None, &SettlementMode::Swap(usdc_token_id.clone()), &None, &None, &None),
+        Err(Ok(Error::InvalidTokenAddress))
+    );
+
+    let settlement = SettlementMode::Swap(eurc_token_id.clone());
+    client.register_token(&eurc_token_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &eurc_amount, &None, &settlement, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+
+    // Both legs move in the initiating invocation, with no confirmations
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount - fee);
+    assert_eq!(eurc_client.balance(&seller), eurc_amount);
+    assert_eq!(eurc_client.balance(&contract_id), 0);
+}
+
+#[test]
+fn test_offer_caps_bound_active_offers() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let maker = <Address as TestAddress>::generate(&env);
+    let seller = <Address as TestAddress>::generate(&env);
+    let late_seller = <Address as TestAddress>::generate(&env);
+    for user in [&maker, &seller, &late_seller] {
+        setup_token_balance(&env, &admin, &usdc_token_id, user, 1_000_000_000, &contract_id);
+    }
+
+    client.update_offer_caps(&4, &3);
+    assert_eq!(client.get_offer_caps(), (4, 3));
+
+    // A ladder larger than the per-seller cap is rejected as a whole
+    let mut levels = Vec::new(&env);
+    for i in 0..4 {
+        levels.push_back((100_000_000, 13_000_000_000 + i * 10_000_000));
+    }
+    assert_eq!(client.try_create_offers(&maker, &levels), Err(Ok(Error::OfferLimitReached)));
+    levels.pop_back();
+    client.create_offers(&maker, &levels);
+
+    // The last global slot goes to another seller, then the marketplace is full
+    client.create_offer(&seller, &100_000_000, &13_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(
+        client.try_create_offer(&late_seller, &100_000_000, &13_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None),
+        Err(Ok(Error::OfferLimitReached))
+    );
+
+    // Lifting the caps reopens listing
+    client.update_offer_caps(&0, &0);
+    client.create_offer(&late_seller, &100_000_000, &13_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(client.get_active_offer_count(), 5);
+}
+
+#[test]
+fn test_max_open_trades_ceiling() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    client.update_max_open_trades(&2);
+    assert_eq!(client.get_max_open_trades(), 2);
+
+    let (_, first_buyer, _, first_trade) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+
+    // The third trade is rejected while two are open
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 100_000_000, &contract_id);
+    let offer_id = client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::TradeLimitReached))
+    );
+
+    // Closing a trade frees a slot
+    client.cancel_trade(&first_trade, &first_buyer);
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    assert_eq!(client.get_open_trade_count(), 2);
+}
+
+#[test]
+fn test_fiat_limits_reject_mistyped_offers() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 300_000_000, &contract_id);
+
+    // 100-160 KES per USDC, at least 500 KES per offer
+    let (min_rate, max_rate) = (100 * 10_000_000, 160 * 10_000_000);
+    client.update_fiat_limits(&50_000, &min_rate, &max_rate);
+    assert_eq!(client.get_fiat_limits(), (50_000, min_rate, max_rate));
+
+    // 100 USDC for 1 KES
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &100, &None, &SettlementMode::OffChain, &None, &None, &None),
+        Err(Ok(Error::BelowMinimum))
+    );
+    // An extra zero on the KES side
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &130_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None),
+        Err(Ok(Error::RateOutOfBounds))
+    );
+    // A missing zero on the KES side
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &1_300_000_000, &None, &SettlementMode::OffChain, &None, &None, &None),
+        Err(Ok(Error::RateOutOfBounds))
+    );
+    // A KES amount too large to scale into a rate
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &(i128::MAX / 1_000), &None, &SettlementMode::OffChain, &None, &None, &None),
+        Err(Ok(Error::RateOutOfBounds))
+    );
+    client.create_offer(&seller, &100_000_000, &13_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    assert_eq!(client.try_update_fiat_limits(&0, &max_rate, &min_rate), Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_token_registry_gates_swap_assets() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (eurc_token_id, _) = create_token_contract(&env, &admin);
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 300_000_000, &contract_id);
+    let settlement = SettlementMode::Swap(eurc_token_id.clone());
+
+    // Unregistered assets can't be swapped
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &92_000_000, &None, &settlement, &None, &None, &None),
+        Err(Ok(Error::InvalidTokenAddress))
+    );
+
+    client.register_token(&eurc_token_id);
+    let config = client.get_token_config(&eurc_token_id).unwrap();
+    assert_eq!(config.decimals, 7);
+    assert!(config.enabled);
+    assert_eq!(client.try_register_token(&eurc_token_id), Err(Ok(Error::InvalidTokenAddress)));
+
+    // Per-asset limits bound the swap leg
+    client.set_token_config(&eurc_token_id, &50_000_000, &200_000_000, &None, &true);
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &10_000_000, &None, &settlement, &None, &None, &None),
+        Err(Ok(Error::BelowMinimum))
+    );
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &300_000_000, &None, &settlement, &None, &None, &None),
+        Err(Ok(Error::AboveMaximum))
+    );
+    client.create_offer(&seller, &100_000_000, &92_000_000, &None, &settlement, &None, &None, &None);
+
+    // Disabled assets stop accepting new offers
+    client.set_token_config(&eurc_token_id, &0, &0, &None, &false);
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &92_000_000, &None, &settlement, &None, &None, &None),
+        Err(Ok(Error::InvalidTokenAddress))
+    );
+    assert_eq!(
+        client.try_set_token_config(&eurc_token_id, &10, &5, &None, &true),
+        Err(Ok(Error::InvalidAmount))
+    );
+
+    // A fee override on the escrow token replaces the flat rate
+    client.register_token(&usdc_token_id);
+    client.set_token_config(&usdc_token_id, &0, &0, &Some(50), &true);
+    assert_eq!(client.get_fee_rate_for(&100_000_000), 50);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #13)")] // InvalidTokenAddress
+fn test_onchain_offer_requires_kes_token() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 100_000_000, &contract_id);
+    client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OnChain, &None, &None, &None);
+}
+
+#[test]
+fn test_oracle_attestation_releases_after_grace_period() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let oracle = <Address as TestAddress>::generate(&env);
+    client.set_payment_oracle(&oracle);
+    client.update_attestation_grace_period(&300);
+
+    let usdc_amount = 100_000_000;
+    let (_, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+    client.attest_payment(&trade_id, &BytesN::from_array(&env, &[9u8; 32]));
+
+    // Release is blocked until the seller's dispute window has passed
+    assert_eq!(client.try_release_attested_trade(&trade_id), Err(Ok(Error::GracePeriodActive)));
+
+    // Even once expired, attested trades cannot be clawed back through expiry
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration() + 1);
+    assert_eq!(client.try_resolve_expired_trade(&trade_id, &buyer), Err(Ok(Error::InvalidTradeStatus)));
+
+    client.release_attested_trade(&trade_id);
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.status, TradeStatus::Completed);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount - fee);
+}
+
+fn sign_receipt(
+    env: &Env,
+    key: &SigningKey,
+    market: &Address,
+    buyer: &Address,
+    trade_id: u64,
+    kes_amount: i128,
+    payment_ref: &[u8; 32],
+) -> BytesN<64> {
+    let mut message = market.clone().to_xdr(env);
+    message.append(&buyer.clone().to_xdr(env));
+    message.extend_from_array(&trade_id.to_be_bytes());
+    message.extend_from_array(&kes_amount.to_be_bytes());
+    message.extend_from_array(payment_ref);
+    let message: std::vec::Vec<u8> = message.iter().collect();
+    BytesN::from_array(env, &key.sign(&message).to_bytes())
+}
+
+#[test]
+fn test_signed_receipt_confirms_buyer_payment() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    client.set_receipt_attester(&BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let kes_amount = client.get_trade(&trade_id).unwrap().kes_amount;
+    let payment_ref = [3u8; 32];
+    let signature = sign_receipt(&env, &key, &contract_id, &buyer, trade_id, kes_amount, &payment_ref);
+
+    client.confirm_payment_with_receipt(&trade_id, &buyer, &BytesN::from_array(&env, &payment_ref), &signature);
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert!(trade.buyer_confirmed_payment);
+    assert_eq!(trade.buyer_receipt, Some(BytesN::from_array(&env, &payment_ref)));
+
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount - fee);
+}
+
+#[test]
+#[should_panic]
+fn test_signed_receipt_rejects_wrong_amount() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    client.set_receipt_attester(&BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+
+    let (_, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    let payment_ref = [3u8; 32];
+    // Receipt signed for a smaller KES payment than the trade requires
+    let signature = sign_receipt(&env, &key, &contract_id, &buyer, trade_id, 1, &payment_ref);
+    client.confirm_payment_with_receipt(&trade_id, &buyer, &BytesN::from_array(&env, &payment_ref), &signature);
+}
+
+#[test]
+#[should_panic]
+fn test_signed_receipt_rejects_other_market() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let key = SigningKey::from_bytes(&[7u8; 32]);
+    client.set_receipt_attester(&BytesN::from_array(&env, &key.verifying_key().to_bytes()));
+
+    let (_, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    let kes_amount = client.get_trade(&trade_id).unwrap().kes_amount;
+    let payment_ref = [3u8; 32];
+    // Receipt signed for the same trade ID and amount on another corridor's market
+    let other_market = <Address as TestAddress>::generate(&env);
+    let signature = sign_receipt(&env, &key, &other_market, &buyer, trade_id, kes_amount, &payment_ref);
+    client.confirm_payment_with_receipt(&trade_id, &buyer, &BytesN::from_array(&env, &payment_ref), &signature);
+}
+
+#[test]
+fn test_completed_trade_awards_points() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    client.update_points_rate(&10);
+
+    // 100 whole USDC at 7 decimals
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 1_000_000_000);
+    assert_eq!(client.get_points(&buyer), 0);
+
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_points(&buyer), 1_000);
+    assert_eq!(client.get_points(&seller), 1_000);
+
+    // Balances live in their own entries, kept alive as they grow
+    let ttl = env.as_contract(&contract_id, || env.storage().persistent().get_ttl(&DataKey::Points(buyer.clone())));
+    assert!(ttl > ENTRY_TTL_THRESHOLD);
+}
+
+#[test]
+fn test_create_offers_ladder_single_escrow() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 600_000_000, &contract_id);
+
+    let levels = vec![&env, (100_000_000i128, 12_000_000_000i128), (200_000_000, 24_200_000_000), (300_000_000, 36_600_000_000)];
+    let offer_ids = client.create_offers(&seller, &levels);
+    assert_eq!(offer_ids, vec![&env, 0u64, 1, 2]);
+    assert_eq!(client.get_seller_active_offers(&seller), offer_ids);
+    assert_eq!(usdc_client.balance(&contract_id), 600_000_000);
+    assert_eq!(client.get_offer(&1).unwrap().kes_amount, 24_200_000_000);
+
+    // Filling one level leaves the others listed
+    let trade_id = client.initiate_trade(&buyer, &1, &None, &None, &0, &None);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_seller_active_offers(&seller), vec![&env, 0u64, 2]);
+    assert_eq!(client.verify_escrow(), 0);
+}
+
+#[test]
+fn test_create_offers_rejects_invalid_level() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 200_000_000, &contract_id);
+
+    let levels = vec![&env, (100_000_000i128, 12_000_000_000i128), (0, 1)];
+    assert_eq!(client.try_create_offers(&seller, &levels), Err(Ok(Error::InvalidAmount)));
+    assert_eq!(client.try_create_offers(&seller, &vec![&env]), Err(Ok(Error::InvalidAmount)));
+    assert_eq!(usdc_client.balance(&seller), 200_000_000);
+}
+
+#[test]
+fn test_quote_trade_matches_settlement() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, offer_id, _) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+
+    let quote = client.quote_trade(&offer_id, &usdc_amount);
+    assert_eq!(quote.gross, usdc_amount);
+    assert_eq!(quote.kes_due, usdc_amount * 120);
+    assert_eq!(quote.expires_in, client.get_trade_expiration());
+    assert_eq!(quote.fee + quote.net_to_buyer, usdc_amount);
+
+    // Pro-rated preview for half the offer
+    assert_eq!(client.quote_trade(&offer_id, &(usdc_amount / 2)).kes_due, usdc_amount * 60);
+    assert_eq!(client.try_quote_trade(&offer_id, &(usdc_amount + 1)), Err(Ok(Error::InvalidAmount)));
+
+    let trade_id = client.get_next_trade_id().unwrap() - 1;
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(usdc_client.balance(&buyer), quote.net_to_buyer);
+}
+
+#[test]
+fn test_offer_rates_and_rate_filter() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 600_000_000, &contract_id);
+
+    // 120.0, 121.0 and 122.0 KES per USDC
+    let levels = vec![&env, (100_000_000i128, 12_000_000_000i128), (200_000_000, 24_200_000_000), (300_000_000, 36_600_000_000)];
+    client.create_offers(&seller, &levels);
+
+    assert_eq!(client.get_offer_rate(&0), Some(1_200_000_000));
+    assert_eq!(client.get_offer_rate(&2), Some(1_220_000_000));
+    assert_eq!(client.get_offer_rate(&9), None);
+
+    assert_eq!(client.get_offers_by_rate_range(&1_205_000_000, &1_250_000_000, &10), vec![&env, 1u64, 2]);
+    assert_eq!(client.get_offers_by_rate_range(&0, &i128::MAX, &2), vec![&env, 0u64, 1]);
+
+    client.cancel_offer(&seller, &1);
+    assert_eq!(client.get_offers_by_rate_range(&1_205_000_000, &1_250_000_000, &10), vec![&env, 2u64]);
+}
+
+#[test]
+fn test_depth_aggregates_available_usdc_per_rate() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let other_seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 600_000_000, &contract_id);
+    setup_token_balance(&env, &admin, &usdc_token_id, &other_seller, 150_000_000, &contract_id);
+
+    // 121.0, 120.0 and 122.0 KES per USDC, plus a second 121.0 offer from another seller
+    let levels = vec![&env, (200_000_000i128, 24_200_000_000i128), (100_000_000, 12_000_000_000), (300_000_000, 36_600_000_000)];
+    client.create_offers(&seller, &levels);
+    client.create_offer(&other_seller, &150_000_000, &18_150_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    assert_eq!(
+        client.get_depth(&10),
+        vec![&env, (1_200_000_000i128, 100_000_000i128), (1_210_000_000, 350_000_000), (1_220_000_000, 300_000_000)]
+    );
+    assert_eq!(client.get_depth(&1), vec![&env, (1_200_000_000i128, 100_000_000i128)]);
+
+    // Reserved offers drop out of the book
+    client.initiate_trade(&buyer, &1, &None, &None, &0, &None);
+    assert_eq!(
+        client.get_depth(&10),
+        vec![&env, (1_210_000_000i128, 350_000_000i128), (1_220_000_000, 300_000_000)]
+    );
+}
+
+#[test]
+fn test_reference_rate_tracks_recent_volume() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 10_000);
+    assert_eq!(client.get_reference_rate(), None);
+
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 400_000_000, &contract_id);
+    // 100 USDC at 120 and 300 USDC at 124 => VWAP 123
+    let levels = vec![&env, (100_000_000i128, 12_000_000_000i128), (300_000_000, 37_200_000_000)];
+    client.create_offers(&seller, &levels);
+    for offer_id in 0..2u64 {
+        let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+        client.confirm_payment(&trade_id, &buyer);
+        client.confirm_payment(&trade_id, &seller);
+    }
+    assert_eq!(client.get_reference_rate(), Some(1_230_000_000));
+
+    // Trades older than the 24h window no longer count
+    env.ledger().with_mut(|li| li.timestamp += 2 * 86_400);
+    assert_eq!(client.get_reference_rate(), None);
+}
+
+#[test]
+fn test_daily_limit_blocks_and_resets() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    client.update_daily_limit(&150_000_000);
+
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 300_000_000, &contract_id);
+    let offer_id = client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(client.get_daily_volume(&seller), 100_000_000);
+
+    // Second listing the same day would exceed the cap
+    client.cancel_offer(&seller, &offer_id);
+    assert_eq!(
+        client.try_create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None),
+        Err(Ok(Error::DailyLimitExceeded))
+    );
+
+    // A new day resets usage
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    assert_eq!(client.get_daily_volume(&seller), 0);
+    client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+}
+
+#[test]
+fn test_daily_limit_applies_to_buyer() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    client.update_daily_limit(&50_000_000);
+
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 50_000_000, &contract_id);
+    let offer_id = client.create_offer(&seller, &50_000_000, &6_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    assert_eq!(client.get_daily_volume(&buyer), 50_000_000);
+}
+
+#[test]
+fn test_compliance_freeze_blocks_trade_transitions() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let officer = <Address as TestAddress>::generate(&env);
+    client.set_compliance_officer(&officer);
+
+    let (seller, buyer, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    client.freeze_trade(&trade_id);
+    assert!(client.is_trade_frozen(&trade_id));
+    assert_eq!(client.try_confirm_payment(&trade_id, &buyer), Err(Ok(Error::Frozen)));
+    assert_eq!(client.try_cancel_trade(&trade_id, &seller), Err(Ok(Error::Frozen)));
+
+    client.freeze_offer(&offer_id);
+    assert_eq!(client.try_cancel_offer(&seller, &offer_id), Err(Ok(Error::Frozen)));
+
+    // Unfreezing restores normal flow
+    client.unfreeze_trade(&trade_id);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+}
+
+#[test]
+fn test_frozen_offer_rejects_new_trades() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let officer = <Address as TestAddress>::generate(&env);
+    client.set_compliance_officer(&officer);
+
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 100_000_000, &contract_id);
+    let offer_id = client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    client.freeze_offer(&offer_id);
+    assert_eq!(client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None), Err(Ok(Error::Frozen)));
+    client.unfreeze_offer(&offer_id);
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+}
+
+#[test]
+fn test_large_trade_event_above_threshold() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    client.update_large_trade_threshold(&500_000_000);
+
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(count_events(&env, LARGE_TRADE), 0);
+
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 500_000_000);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(count_events(&env, LARGE_TRADE), 1);
+}
+
+#[test]
+fn test_payout_and_refund_addresses() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let cold_wallet = <Address as TestAddress>::generate(&env);
+    let seller_vault = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 2 * usdc_amount, &contract_id);
+
+    // Buyer payout goes to the designated cold wallet
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &Some(seller_vault.clone()), &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &Some(cold_wallet.clone()), &None, &0, &None);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(usdc_client.balance(&cold_wallet), usdc_amount - fee);
+    assert_eq!(usdc_client.balance(&buyer), 0);
+
+    // Cancelled offer escrow returns to the seller's refund address
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &Some(seller_vault.clone()), &None, &None);
+    client.cancel_offer(&seller, &offer_id);
+    assert_eq!(usdc_client.balance(&seller_vault), usdc_amount);
+}
+
+#[test]
+fn test_price_negotiation_flow() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let other_buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 100_000_000, &contract_id);
+    let offer_id = client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    let lowball = client.propose_price(&other_buyer, &offer_id, &10_000_000_000, &None);
+    let proposal_id = client.propose_price(&buyer, &offer_id, &11_800_000_000, &None);
+
+    // A buyer can only keep a few proposals open on one offer
+    for _ in 1..3 {
+        client.propose_price(&other_buyer, &offer_id, &10_000_000_000, &None);
+    }
+    assert_eq!(client.try_propose_price(&other_buyer, &offer_id, &10_000_000_000, &None), Err(Ok(Error::RateLimitExceeded)));
+
+    // ...and the offer only a bounded number overall
+    for _ in 4..MAX_OFFER_PROPOSALS {
+        client.propose_price(&<Address as TestAddress>::generate(&env), &offer_id, &10_000_000_000, &None);
+    }
+    let latecomer = <Address as TestAddress>::generate(&env);
+    assert_eq!(client.try_propose_price(&latecomer, &offer_id, &10_000_000_000, &None), Err(Ok(Error::RateLimitExceeded)));
+
+    // Only the seller can accept
+    assert_eq!(client.try_accept_proposal(&buyer, &proposal_id), Err(Ok(Error::Unauthorized)));
+    client.reject_proposal(&seller, &lowball);
+    assert_eq!(client.get_proposal(&lowball), None);
+
+    let trade_id = client.accept_proposal(&seller, &proposal_id);
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.buyer, buyer);
+    assert_eq!(trade.kes_amount, 11_800_000_000);
+    assert_eq!(trade.status, TradeStatus::Initiated);
+    assert_eq!(client.try_accept_proposal(&seller, &proposal_id), Err(Ok(Error::ProposalNotFound)));
+}
+
+#[test]
+fn test_stale_proposals_expire() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 100_000_000, &contract_id);
+    let offer_id = client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    let mut proposal_ids: Vec<u64> = Vec::new(&env);
+    for _ in 0..MAX_OFFER_PROPOSALS {
+        proposal_ids.push_back(client.propose_price(&<Address as TestAddress>::generate(&env), &offer_id, &11_000_000_000, &None));
+    }
+    let stale = proposal_ids.get(0).unwrap();
+    assert_eq!(client.get_proposal(&stale).unwrap().expires_at, 1_000 + PROPOSAL_LIFETIME);
+
+    // The seller can't take up a price the buyer offered long ago
+    env.ledger().with_mut(|li| li.timestamp = 1_000 + PROPOSAL_LIFETIME + 1);
+    assert_eq!(client.try_accept_proposal(&seller, &stale), Err(Ok(Error::ProposalExpired)));
+
+    // Expired proposals give up their slots to new ones
+    let buyer = <Address as TestAddress>::generate(&env);
+    let proposal_id = client.propose_price(&buyer, &offer_id, &11_800_000_000, &None);
+    for expired in proposal_ids.iter() {
+        assert_eq!(client.get_proposal(&expired), None);
+    }
+    let trade_id = client.accept_proposal(&seller, &proposal_id);
+    assert_eq!(client.get_trade(&trade_id).unwrap().kes_amount, 11_800_000_000);
+}
+
+#[test]
+fn test_installment_tranches_release_at_full_payment() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let kes_amount = client.get_trade(&trade_id).unwrap().kes_amount;
+
+    let first = client.mark_tranche(&trade_id, &buyer, &(kes_amount / 2));
+    client.confirm_tranche(&trade_id, &seller, &first);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Initiated);
+    assert_eq!(client.try_confirm_tranche(&trade_id, &seller, &first), Err(Ok(Error::InvalidTradeStatus)));
+
+    // Confirmed tranche restarts the clock, so the next installment can land later
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration() - 1);
+    let second = client.mark_tranche(&trade_id, &buyer, &(kes_amount / 2));
+    client.confirm_tranche(&trade_id, &seller, &second);
+
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.status, TradeStatus::Completed);
+    assert_eq!(trade.tranches.len(), 2);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount - fee);
+}
+
+#[test]
+fn test_tranches_per_trade_are_capped() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (_, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+
+    for index in 0..20 {
+        assert_eq!(client.mark_tranche(&trade_id, &buyer, &1), index);
+    }
+    assert_eq!(client.try_mark_tranche(&trade_id, &buyer, &1), Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_prune_final_records_after_retention() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (seller, buyer, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+
+    // In-progress records cannot be pruned
+    assert_eq!(client.try_prune_trade(&trade_id), Err(Ok(Error::InvalidTradeStatus)));
+    assert_eq!(client.try_prune_offer(&offer_id), Err(Ok(Error::InvalidTradeStatus)));
+
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.try_prune_trade(&trade_id), Err(Ok(Error::GracePeriodActive)));
+
+    env.ledger().with_mut(|li| li.timestamp += 30 * 86_400);
+    client.prune_offer(&offer_id);
+    client.prune_trade(&trade_id);
+    assert_eq!(client.get_trade(&trade_id), None);
+    assert_eq!(client.get_offer(&offer_id), None);
+}
+
+#[test]
+fn test_record_retention_is_configurable() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    client.update_record_retention(&3_600);
+    assert_eq!(client.get_contract_info().8, 3_600);
+
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    client.prune_trade(&trade_id);
+    assert_eq!(client.get_trade(&trade_id), None);
+}
+
+#[test]
+fn test_escrowed_balance_per_seller() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 300_000_000, &contract_id);
+    client.create_offers(&seller, &vec![&env, (100_000_000i128, 12_000_000_000i128), (200_000_000, 24_000_000_000)]);
+    assert_eq!(client.get_escrowed_balance(&seller), 300_000_000);
+
+    // Open trade still counts; completion releases it
+    let trade_id = client.initiate_trade(&buyer, &0, &None, &None, &0, &None);
+    assert_eq!(client.get_escrowed_balance(&seller), 300_000_000);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_escrowed_balance(&seller), 200_000_000);
+    assert_eq!(client.get_escrowed_balance(&buyer), 0);
+}
+
+#[test]
+fn test_guardian_can_only_pause() {
+    let (env, client, admin, _, _, _) = setup_test_env();
+    let guardian = <Address as TestAddress>::generate(&env);
+    assert_eq!(client.try_guardian_pause(), Err(Ok(Error::Unauthorized)));
+
+    client.set_guardian(&guardian);
+    client.guardian_pause();
+    assert_eq!(env.auths()[0].0, guardian);
+    assert!(client.is_paused());
+
+    // Unpausing still requires the admin
+    client.unpause();
+    assert_eq!(env.auths()[0].0, admin);
+    assert!(!client.is_paused());
+}
+
+#[test]
+fn test_multisig_admin_actions_require_threshold() {
+    let (env, client, _, _, _, _) = setup_test_env();
+    let signers = vec![
+        &env,
+        <Address as TestAddress>::generate(&env),
+        <Address as TestAddress>::generate(&env),
+        <Address as TestAddress>::generate(&env),
+    ];
+    let new_admin = <Address as TestAddress>::generate(&env);
+
+    assert_eq!(client.try_set_admin_signers(&signers, &4), Err(Ok(Error::InvalidAmount)));
+    client.set_admin_signers(&signers, &2);
+    assert_eq!(client.get_admin_signers(), (signers.clone(), 2));
+
+    // Direct high-impact calls are closed once multisig is enabled
+    assert_eq!(client.try_update_admin(&new_admin), Err(Ok(Error::Unauthorized)));
+
+    let action_id = client.propose_admin_action(&signers.get(0).unwrap(), &AdminAction::UpdateAdmin(new_admin.clone()));
+    assert_eq!(client.try_execute_action(&action_id), Err(Ok(Error::ThresholdNotMet)));
+
+    client.approve_action(&signers.get(0).unwrap(), &action_id);
+    assert_eq!(client.get_admin_action(&action_id).unwrap().approvals.len(), 1);
+    client.approve_action(&signers.get(2).unwrap(), &action_id);
+
+    client.execute_action(&action_id);
+    assert_eq!(client.get_admin(), new_admin);
+    assert!(client.get_admin_action(&action_id).is_none());
+    assert_eq!(client.try_execute_action(&action_id), Err(Ok(Error::ActionNotFound)));
+}
+
+#[test]
+fn test_fee_split_across_weighted_recipients() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let treasury = <Address as TestAddress>::generate(&env);
+    let arbitrators = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+
+    let bad = vec![&env, FeeSplit { recipient: treasury.clone(), weight: 7_000 }];
+    assert_eq!(client.try_update_fee_splits(&bad), Err(Ok(Error::InvalidAmount)));
+
+    // 70% treasury, 10% arbitrator pool on top of a 20% insurance share
+    client.update_insurance_share(&2_000);
+    let splits = vec![
+        &env,
+        FeeSplit { recipient: treasury.clone(), weight: 8_750 },
+        FeeSplit { recipient: arbitrators.clone(), weight: 1_250 },
+    ];
+    client.update_fee_splits(&splits);
+    assert_eq!(client.get_fee_splits(), splits);
+
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(client.get_insurance_fund(), fee * 2 / 10);
+    assert_eq!(usdc_client.balance(&treasury), fee * 7 / 10);
+    assert_eq!(usdc_client.balance(&arbitrators), fee / 10);
+    assert_eq!(usdc_client.balance(&client.get_fee_collector()), 0);
+}
+
+#[test]
+fn test_fee_curve_lowers_rate_for_large_trades() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let fee_collector = client.get_fee_collector();
+
+    let rising = vec![
+        &env,
+        FeeBracket { min_amount: 0, rate: 50 },
+        FeeBracket { min_amount: 1_000_000_000, rate: 100 },
+    ];
+    assert_eq!(client.try_update_fee_curve(&rising), Err(Ok(Error::InvalidAmount)));
+
+    // 0.5% below 100 USDC, 0.1% from 100 USDC up
+    let curve = vec![
+        &env,
+        FeeBracket { min_amount: 0, rate: 50 },
+        FeeBracket { min_amount: 1_000_000_000, rate: 10 },
+    ];
+    client.update_fee_curve(&curve);
+    assert_eq!(client.get_fee_rate_for(&100_000_000), 50);
+    assert_eq!(client.get_fee_rate_for(&1_000_000_000), 10);
+
+    let usdc_amount = 2_000_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(usdc_client.balance(&fee_collector), usdc_amount * 10 / 10_000);
+
+    // Clearing the curve restores the flat rate
+    client.update_fee_curve(&vec![&env]);
+    assert_eq!(client.get_fee_rate_for(&usdc_amount), client.get_fee_rate());
+}
+
+#[test]
+fn test_fee_policy_switch_and_bounds() {
+    let env = Env::default();
+    let market = setup_marketplace_with_fee_policy(&env, FeePolicy { fees_enabled: false, fee_floor: 10, fee_ceiling: 100 });
+    let client = &market.client;
+    assert_eq!(client.get_fee_bounds(), (10, 100));
+    assert!(!client.get_fees_enabled());
+
+    // Community instances complete trades without protocol fees
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, client, &market.admin, &market.usdc_token_id, &market.contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(market.usdc.balance(&buyer), usdc_amount);
+    assert_eq!(market.usdc.balance(&market.fee_collector), 0);
+
+    // Once switched on, the admin can only choose rates inside the deployment's range
+    client.set_fees_enabled(&true);
+    assert_eq!(client.try_update_fee_rate(&5), Err(Ok(Error::InvalidAmount)));
+    assert_eq!(client.try_update_fee_rate(&200), Err(Ok(Error::InvalidAmount)));
+    client.update_fee_rate(&100);
+    assert_eq!(client.get_fee_rate_for(&usdc_amount), 100);
+}
+
+#[test]
+fn test_operator_fee_paid_at_completion() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let wallet = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    // Operator fees are disabled until the admin sets a cap
+    let operator = Some(wallet.clone());
+    assert_eq!(client.try_initiate_trade(&buyer, &offer_id, &None, &operator, &50, &None), Err(Ok(Error::InvalidAmount)));
+    client.update_operator_fee_cap(&100);
+    assert_eq!(client.try_initiate_trade(&buyer, &offer_id, &None, &operator, &150, &None), Err(Ok(Error::InvalidAmount)));
+    assert_eq!(client.try_initiate_trade(&buyer, &offer_id, &None, &None, &50, &None), Err(Ok(Error::InvalidAmount)));
+
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &operator, &50, &None);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    let operator_fee = usdc_amount * 50 / 10_000;
+    assert_eq!(usdc_client.balance(&wallet), operator_fee);
+    assert_eq!(usdc_client.balance(&client.get_fee_collector()), fee);
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount - fee - operator_fee);
+}
+
+#[test]
+fn test_trade_reserves_offer_until_cancelled() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (_, buyer, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+
+    // Reserved offers drop out of availability queries
+    assert_eq!(client.get_offer(&offer_id).unwrap().reserved_by, Some(trade_id));
+    assert_eq!(client.get_offers_by_rate_range(&0, &i128::MAX, &10), vec![&env]);
+
+    client.cancel_trade(&trade_id, &buyer);
+    assert_eq!(client.get_offer(&offer_id).unwrap().reserved_by, None);
+    assert_eq!(client.get_offers_by_rate_range(&0, &i128::MAX, &10), vec![&env, offer_id]);
+
+    // Another buyer can now take the offer
+    let other_buyer = <Address as TestAddress>::generate(&env);
+    let next_trade = client.initiate_trade(&other_buyer, &offer_id, &None, &None, &0, &None);
+    assert_eq!(client.get_offer(&offer_id).unwrap().reserved_by, Some(next_trade));
+}
+
+#[test]
+fn test_available_offers_excludes_reserved_and_frozen() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let officer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 400_000_000, &contract_id);
+
+    let levels = vec![&env, (100_000_000i128, 12_000_000_000i128), (100_000_000, 12_100_000_000), (100_000_000, 12_200_000_000), (100_000_000, 12_300_000_000)];
+    client.create_offers(&seller, &levels);
+
+    client.initiate_trade(&buyer, &0, &None, &None, &0, &None);
+    client.set_compliance_officer(&officer);
+    client.freeze_offer(&2);
+    client.cancel_offer(&seller, &3);
+
+    let page = client.get_available_offers(&0, &10);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().0, 1);
+
+    client.unfreeze_offer(&2);
+    let page = client.get_available_offers(&0, &1);
+    assert_eq!(page.get(0).unwrap().0, 1);
+    let next = client.get_available_offers(&2, &1);
+    assert_eq!(next.get(0).unwrap().0, 2);
+}
+
+#[test]
+fn test_open_interest_counters() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    let (_, _, other_offer, other_trade) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 50_000_000);
+
+    assert_eq!(client.get_active_offer_count(), 2);
+    assert_eq!(client.get_open_trade_count(), 2);
+    assert_eq!(client.get_total_escrowed(), 150_000_000);
+
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_active_offer_count(), 1);
+    assert_eq!(client.get_open_trade_count(), 1);
+    assert_eq!(client.get_total_escrowed(), 50_000_000);
+
+    let other_seller = client.get_offer(&other_offer).unwrap().seller;
+    client.cancel_trade(&other_trade, &other_seller);
+    client.cancel_offer(&other_seller, &other_offer);
+    assert_eq!(client.get_active_offer_count(), 0);
+    assert_eq!(client.get_open_trade_count(), 0);
+    assert_eq!(client.get_total_escrowed(), 0);
+}
+
+#[test]
+fn test_trade_details_aggregate_view() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (_, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    let details = client.get_trade_details(&trade_id);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(details.trade, client.get_trade(&trade_id).unwrap());
+    assert_eq!(details.rate, 120 * 10_000_000);
+    assert_eq!(details.expires_in, client.get_trade_expiration() - 100);
+    assert_eq!(details.fee, fee);
+    assert_eq!(details.net_to_buyer, usdc_amount - fee);
+    assert_eq!(details.dispute, TradeDispute::NotDisputed);
+
+    client.raise_dispute(&trade_id, &buyer, &String::from_str(&env, "no release"), &vec![&env]);
+    let details = client.get_trade_details(&trade_id);
+    assert_eq!(details.dispute, TradeDispute::Disputed(client.get_dispute(&trade_id).unwrap()));
+    assert_eq!(client.try_get_trade_details(&99), Err(Ok(Error::TradeNotFound)));
+}
+
+#[test]
+fn test_expired_trade_emits_trade_expired_event() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, _, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let keeper = <Address as TestAddress>::generate(&env);
+
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration());
+    client.resolve_expired_trade(&trade_id, &keeper);
+
+    // One copy is addressed to each party
+    assert_eq!(count_events(&env, TRADE_EXPIRED), 2);
+    assert_eq!(count_events(&env, TRADE_CANCELLED), 0);
+    let (_, _, data) = env.events().all().last().unwrap();
+    let data = <(u64, u64, i128, Address)>::try_from_val(&env, &data).unwrap();
+    assert_eq!(data, (trade_id, offer_id, usdc_amount, keeper));
+    assert_eq!(usdc_client.balance(&seller), usdc_amount);
+}
+
+#[test]
+fn test_reopen_trade_after_accidental_cancellation() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let keeper = <Address as TestAddress>::generate(&env);
+
+    // A cancelled trade still has its escrow on the offer
+    let (seller, buyer, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.cancel_trade(&trade_id, &buyer);
+    env.ledger().with_mut(|li| li.timestamp += 600);
+    assert_eq!(client.reopen_trade(&trade_id), 0);
+    let auths = env.auths();
+    assert_eq!((auths.len(), &auths[0].0, &auths[1].0), (2, &buyer, &seller));
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.status, TradeStatus::Initiated);
+    assert_eq!((trade.start_time, trade.cancelled_at), (env.ledger().timestamp(), None));
+    assert_eq!(client.get_offer(&offer_id).unwrap().status, OfferStatus::Reserved);
+    assert_eq!(client.get_open_trade_count(), 1);
+
+    // An expired trade needs the seller to escrow again
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration());
+    client.resolve_expired_trade(&trade_id, &keeper);
+    assert_eq!(usdc_client.balance(&seller), usdc_amount);
+    assert_eq!(client.reopen_trade(&trade_id), usdc_amount);
+    assert_eq!(usdc_client.balance(&seller), 0);
+    assert_eq!(client.verify_escrow(), 0);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+
+    // The window is short, and completed trades stay completed
+    assert_eq!(client.try_reopen_trade(&trade_id), Err(Ok(Error::InvalidTradeStatus)));
+    let (_, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.cancel_trade(&trade_id, &buyer);
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    assert_eq!(client.try_reopen_trade(&trade_id), Err(Ok(Error::TradeExpired)));
+
+    // A seller who has listed again since can't end up with two active offers
+    let (seller, _, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration());
+    client.resolve_expired_trade(&trade_id, &keeper);
+    client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(client.try_reopen_trade(&trade_id), Err(Ok(Error::AlreadyHasActiveOffer)));
+}
+
+#[test]
+fn test_reopened_trade_is_counted_and_funded_again() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    client.update_walkaway_cooldown(&3_600);
+    let (_, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let (other_seller, _, _, other_trade) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+
+    // Walking away counts against the buyer until the trade is reopened
+    client.cancel_trade(&trade_id, &buyer);
+    assert_eq!(client.get_buyer_cooldown(&buyer).0, 1);
+
+    // A reopened trade counts against the open-trade ceiling like a new one
+    client.update_max_open_trades(&1);
+    assert_eq!(client.try_reopen_trade(&trade_id), Err(Ok(Error::TradeLimitReached)));
+    client.cancel_trade(&other_trade, &other_seller);
+
+    // The cancellation returned the deposit, so the buyer funds the cleanup bounty again
+    let (xlm_token_id, xlm_client) = create_token_contract(&env, &admin);
+    let deposit = 5_000_000;
+    client.set_initiation_deposit(&xlm_token_id, &deposit);
+    token::StellarAssetClient::new(&env, &xlm_token_id).mint(&buyer, &deposit);
+    assert_eq!(client.reopen_trade(&trade_id), 0);
+    assert_eq!(client.get_trade_deposit(&trade_id), deposit);
+    assert_eq!(xlm_client.balance(&buyer), 0);
+    assert_eq!(client.get_buyer_cooldown(&buyer), (0, 0));
+    assert_eq!(client.get_open_trade_count(), 1);
+}
+
+#[test]
+fn test_expiry_grace_absorbs_late_confirmations() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let grace = 60;
+    client.update_expiry_grace(&grace);
+    assert_eq!(client.try_update_expiry_grace(&601), Err(Ok(Error::InvalidAmount)));
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    let keeper = <Address as TestAddress>::generate(&env);
+
+    // Just past the nominal deadline the trade can't be expired, and confirmations still land
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration());
+    assert_eq!(client.get_trade_deadline(&trade_id).1, 0);
+    assert_eq!(client.try_resolve_expired_trade(&trade_id, &keeper), Err(Ok(Error::TradeNotExpired)));
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+
+    // Once the grace period is over the trade expires as usual
+    let (_, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration() + grace);
+    assert_eq!(client.try_confirm_payment(&trade_id, &buyer), Err(Ok(Error::TradeExpired)));
+    client.resolve_expired_trade(&trade_id, &keeper);
+}
+
+#[test]
+fn test_expiry_after_buyer_confirmation_opens_dispute() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let keeper = <Address as TestAddress>::generate(&env);
+    client.confirm_payment(&trade_id, &buyer);
+
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration());
+    client.resolve_expired_trade(&trade_id, &keeper);
+    assert_eq!(count_events(&env, DISPUTE_RAISED), 2);
+
+    // The seller is not refunded; the buyer's claim goes to arbitration
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Disputed);
+    assert_eq!(usdc_client.balance(&seller), 0);
+    let dispute = client.get_dispute(&trade_id).unwrap();
+    assert_eq!(dispute.raised_by, buyer);
+    assert_eq!(dispute.resolution, DisputeOutcome::Pending);
+    assert_eq!(client.get_open_disputes(), vec![&env, dispute]);
+
+    client.resolve_dispute(&trade_id, &DisputeResolution::ReleaseToBuyer);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+}
+
+#[test]
+fn test_seller_cannot_cancel_after_buyer_confirmation() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+
+    assert_eq!(client.try_cancel_trade(&trade_id, &seller), Err(Ok(Error::InvalidTradeStatus)));
+    assert_eq!(client.try_cancel_offer(&seller, &offer_id), Err(Ok(Error::TradeAlreadyInitiated)));
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Initiated);
+    assert_eq!(usdc_client.balance(&seller), 0);
+
+    // The buyer's claim still reaches arbitration once the trade expires
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration());
+    client.resolve_expired_trade(&trade_id, &buyer);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Disputed);
+}
+
+#[test]
+fn test_auto_release_after_silent_seller() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+
+    // Disabled by default
+    assert_eq!(client.try_auto_release_trade(&trade_id), Err(Ok(Error::InvalidTradeStatus)));
+    assert_eq!(client.try_update_auto_release_window(&60), Err(Ok(Error::InvalidAmount)));
+    client.update_auto_release_window(&86_400);
+
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    assert_eq!(client.try_auto_release_trade(&trade_id), Err(Ok(Error::GracePeriodActive)));
+    // The seller can no longer take a refund through expiry once the buyer has paid
+    assert_eq!(client.try_resolve_expired_trade(&trade_id, &seller), Err(Ok(Error::InvalidTradeStatus)));
+
+    env.ledger().with_mut(|li| li.timestamp += 86_400);
+    client.auto_release_trade(&trade_id);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount - fee);
+}
+
+#[test]
+fn test_seller_releases_without_buyer_confirmation() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+
+    assert_eq!(client.try_release_to_buyer(&trade_id, &buyer), Err(Ok(Error::Unauthorized)));
+
+    client.release_to_buyer(&trade_id, &seller);
+    assert_eq!(env.auths()[0].0, seller);
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.status, TradeStatus::Completed);
+    assert!(!trade.buyer_confirmed_payment);
+
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount - fee);
+    assert_eq!(client.try_release_to_buyer(&trade_id, &seller), Err(Ok(Error::InvalidTradeStatus)));
+}
+
+#[test]
+fn test_double_confirmation_rejected() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+
+    client.confirm_payment(&trade_id, &buyer);
+    assert_eq!(client.try_confirm_payment(&trade_id, &buyer), Err(Ok(Error::BuyerAlreadyConfirmed)));
+
+    // Seller's repeat is caught too when the buyer hasn't confirmed yet
+    let (seller_b, _, _, trade_b) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    client.confirm_payment(&trade_b, &seller_b);
+    assert_eq!(client.try_confirm_payment(&trade_b, &seller_b), Err(Ok(Error::SellerAlreadyConfirmed)));
+
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+}
+
+#[test]
+fn test_hostile_token_failed_payouts_are_parked_or_rejected() {
+    let (env, client, token_client, contract_id) = setup_hostile_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_hostile_trade(&env, &client, &token_client, usdc_amount);
+    let (other_seller, other_buyer, other_offer, expiring_id) = setup_hostile_trade(&env, &client, &token_client, usdc_amount);
+    token_client.set_hostility(&contract_id, &Hostility::FailPayouts);
+
+    // Release: the trade completes and the undeliverable payout is parked
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    let fee = (usdc_amount * client.get_fee_rate() as i128) / 10000;
+    assert_eq!(client.get_claimable(&buyer), usdc_amount - fee);
+    assert_eq!(client.try_withdraw(&buyer), Err(Ok(Error::TokenTransferFailed)));
+    assert_eq!(client.get_claimable(&buyer), usdc_amount - fee);
+
+    // Expiry refund fails outright and the trade stays open
+    env.ledger().with_mut(|li| li.timestamp += 601);
+    assert_eq!(client.try_resolve_expired_trade(&expiring_id, &other_buyer), Err(Ok(Error::TokenTransferFailed)));
+    assert_eq!(client.get_trade(&expiring_id).unwrap().status, TradeStatus::Initiated);
+
+    // Cancellation moves no tokens, so it still works and the escrow stays with the offer
+    client.cancel_trade(&expiring_id, &other_seller);
+    assert_eq!(client.get_trade(&expiring_id).unwrap().status, TradeStatus::Cancelled);
+    assert_eq!(client.get_offer(&other_offer).unwrap().usdc_amount, usdc_amount);
+
+    // Once the token behaves again the buyer can claim; the failed fee stays as surplus
+    token_client.set_hostility(&contract_id, &Hostility::None);
+    assert_eq!(client.withdraw(&buyer), usdc_amount - fee);
+    assert_eq!(token_client.balance(&buyer), usdc_amount - fee);
+    assert_eq!(client.get_claimable(&buyer), 0);
+    assert_eq!(client.verify_escrow(), fee);
+}
+
+#[test]
+fn test_hostile_token_skimmed_payouts_keep_escrow_reconciled() {
+    let (env, client, token_client, contract_id) = setup_hostile_env();
+    let usdc_amount = 100_000_000;
+    let skim = 1_000;
+    let (seller, buyer, _, trade_id) = setup_hostile_trade(&env, &client, &token_client, usdc_amount);
+    let (_, _, _, expiring_id) = setup_hostile_trade(&env, &client, &token_client, usdc_amount);
+    token_client.set_hostility(&contract_id, &Hostility::Skim(skim));
+
+    // Release completes; the skim is the token's loss, not a marketplace liability
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    assert!(token_client.balance(&buyer) < usdc_amount - skim);
+    assert_eq!(client.verify_escrow(), 0);
+
+    // Expiry refund also completes and only the remaining escrow is owed
+    env.ledger().with_mut(|li| li.timestamp += 601);
+    client.resolve_expired_trade(&expiring_id, &buyer);
+    assert_eq!(client.get_trade(&expiring_id).unwrap().status, TradeStatus::Cancelled);
+    assert_eq!(token_client.balance(&contract_id), 0);
+    assert_eq!(client.verify_escrow(), 0);
+}
+
+#[test]
+fn test_hostile_token_reentry_rejected_during_payouts() {
+    let (env, client, token_client, contract_id) = setup_hostile_env();
+    let usdc_amount = 100_000_000;
+
+    // Release: the token tries to cancel the trade while the buyer is being paid
+    let (seller, buyer, _, trade_id) = setup_hostile_trade(&env, &client, &token_client, usdc_amount);
+    let args = vec![&env, trade_id.into_val(&env), buyer.into_val(&env)];
+    token_client.set_hostility(&contract_id, &Hostility::Reenter(Symbol::new(&env, "cancel_trade"), args));
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+
+    assert_eq!(token_client.reentered(), Some(false));
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+
+    // Expiry refund: the token tries to cancel the offer and claim a second refund
+    let (seller, buyer, offer_id, trade_id) = setup_hostile_trade(&env, &client, &token_client, usdc_amount);
+    env.ledger().with_mut(|li| li.timestamp += 601);
+    let args = vec![&env, seller.into_val(&env), offer_id.into_val(&env)];
+    token_client.set_hostility(&contract_id, &Hostility::Reenter(Symbol::new(&env, "cancel_offer"), args));
+    client.resolve_expired_trade(&trade_id, &buyer);
+
+    assert_eq!(token_client.reentered(), Some(false));
+    assert_eq!(token_client.balance(&seller), usdc_amount);
+    assert_eq!(token_client.balance(&contract_id), 0);
+}
+
+// ================================================================================================
+// STATE MACHINE FUZZING
+// ================================================================================================
+// Random sequences of user operations against a live contract, checking invariants after
+// every step. Failed operations are expected and must leave state untouched.
+
+mod fuzz {
+    extern crate std;
+
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use soroban_sdk::testutils::EnvTestConfig;
+
+    const SEEDS: u64 = 24;
+    const STEPS: u32 = 60;
+
+    // Whether a trade may move from one status to another in a single operation
+    fn allowed_transition(from: &TradeStatus, to: &TradeStatus) -> bool {
+        use TradeStatus::*;
+        from == to
+            || matches!(
+                (from, to),
+                (Initiated, PaymentConfirmed | Completed | Cancelled | Disputed)
+                    | (PaymentConfirmed, Completed | Disputed | Cancelled)
+                    | (Disputed, Completed | Cancelled)
+                    | (Cancelled, Initiated)
+            )
+    }
+
+    fn run(seed: u64) {
+        // Same setup as setup_test_env, minus the per-run ledger snapshot
+        let env = Env::new_with_config(EnvTestConfig { capture_snapshot_at_drop: false });
+        env.mock_all_auths();
+        let admin = <Address as TestAddress>::generate(&env);
+        let fee_collector = <Address as TestAddress>::generate(&env);
+        let (usdc_token_id, usdc_client) = create_token_contract(&env, &admin);
+        let contract_id = env.register(P2PMarketplaceContract, (&admin, &usdc_token_id, &fee_collector, default_fee_policy()));
+        let client = P2PMarketplaceContractClient::new(&env, &contract_id);
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let sellers: [Address; 2] = core::array::from_fn(|_| <Address as TestAddress>::generate(&env));
+        let buyers: [Address; 2] = core::array::from_fn(|_| <Address as TestAddress>::generate(&env));
+        for seller in &sellers {
+            setup_token_balance(&env, &admin, &usdc_token_id, seller, 10_000_000_000, &contract_id);
+        }
+
+        let mut offer_ids: std::vec::Vec<u64> = std::vec::Vec::new();
+        let mut trades: std::vec::Vec<(u64, Trade)> = std::vec::Vec::new();
+
+        for step in 0..STEPS {
+            let op = rng.gen_range(0..9);
+            let pick = |rng: &mut StdRng, len: usize| if len == 0 { None } else { Some(rng.gen_range(0..len)) };
+            match op {
+                0 => {
+                    let seller = &sellers[rng.gen_range(0..sellers.len())];
+                    let usdc_amount = rng.gen_range(1..=50) * 10_000_000;
+                    if let Ok(Ok(id)) = client.try_create_offer(seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None) {
+                        offer_ids.push(id);
+                    }
+                }
+                1 => {
+                    if let Some(i) = pick(&mut rng, offer_ids.len()) {
+                        let buyer = &buyers[rng.gen_range(0..buyers.len())];
+                        let _ = client.try_initiate_trade(buyer, &offer_ids[i], &None, &None, &0, &None);
+                    }
+                }
+                2 | 3 => {
+                    if let Some(i) = pick(&mut rng, trades.len()) {
+                        let trade = &trades[i].1;
+                        let who = if op == 2 { &trade.buyer } else { &trade.seller };
+                        let _ = client.try_confirm_payment(&trades[i].0, who);
+                    }
+                }
+                4 => {
+                    if let Some(i) = pick(&mut rng, trades.len()) {
+                        let who = if rng.gen_bool(0.5) { &trades[i].1.buyer } else { &trades[i].1.seller };
+                        let _ = client.try_cancel_trade(&trades[i].0, who);
+                    }
+                }
+                5 => {
+                    if let Some(i) = pick(&mut rng, trades.len()) {
+                        let _ = client.try_resolve_expired_trade(&trades[i].0, &trades[i].1.buyer);
+                    }
+                }
+                6 => {
+                    if let Some(i) = pick(&mut rng, trades.len()) {
+                        let reason = String::from_str(&env, "fuzz");
+                        let _ = client.try_raise_dispute(&trades[i].0, &trades[i].1.buyer, &reason, &Vec::new(&env));
+                    }
+                }
+                7 => {
+                    if let Some(i) = pick(&mut rng, trades.len()) {
+                        let resolution = if rng.gen_bool(0.5) { DisputeResolution::ReleaseToBuyer } else { DisputeResolution::RefundToSeller };
+                        let _ = client.try_resolve_dispute(&trades[i].0, &resolution);
+                    }
+                }
+                _ => {
+                    let secs = rng.gen_range(1..=400);
+                    env.ledger().with_mut(|li| li.timestamp += secs);
+                }
+            }
+
+            // Pick up trades created by this step
+            let next_trade_id = trades.last().map(|(id, _)| id + 1).unwrap_or(0);
+            for trade_id in next_trade_id.. {
+                match client.get_trade(&trade_id) {
+                    Some(trade) => trades.push((trade_id, trade)),
+                    None => break,
+                }
+            }
+
+            // Invariant: trades only move along allowed edges; finished trades never change
+            let mut open = 0;
+            for (trade_id, before) in trades.iter_mut() {
+                let after = client.get_trade(trade_id).unwrap();
+                assert!(
+                    allowed_transition(&before.status, &after.status),
+                    "seed {seed} step {step}: trade {trade_id} moved {:?} -> {:?}", before.status, after.status
+                );
+                if before.status == TradeStatus::Completed {
+                    assert_eq!(*before, after, "seed {seed} step {step}: completed trade {trade_id} changed");
+                }
+                if matches!(after.status, TradeStatus::Initiated | TradeStatus::PaymentConfirmed | TradeStatus::Disputed) {
+                    open += 1;
+                }
+                *before = after;
+            }
+
+            // Invariant: the escrow balance equals the sum of locked amounts
+            let locked: i128 = client.get_active_offers().values().iter()
+                .flat_map(|ids| ids.iter())
+                .map(|id| client.get_offer(&id).unwrap().usdc_amount)
+                .sum();
+            assert_eq!(usdc_client.balance(&contract_id), locked + client.get_insurance_fund(), "seed {seed} step {step}: escrow mismatch");
+            assert_eq!(client.get_total_escrowed(), locked, "seed {seed} step {step}: escrow counter drifted");
+            assert_eq!(client.get_open_trade_count(), open, "seed {seed} step {step}: open trade counter drifted");
+        }
+    }
+
+    #[test]
+    fn test_random_operation_sequences_preserve_invariants() {
+        for seed in 0..SEEDS {
+            run(seed);
+        }
+    }
+}
+
+#[test]
+fn test_direct_escrow_bound_to_named_buyer() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let outsider = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+
+    let (offer_id, trade_id) = client.create_direct_escrow(&seller, &buyer, &usdc_amount, &12_000_000_000);
+    // Both parties authorize the one invocation that escrows and opens the trade
+    let auths = env.auths();
+    assert!(auths.iter().any(|(address, _)| *address == seller));
+    assert!(auths.iter().any(|(address, _)| *address == buyer));
+    let trade = client.get_trade(&trade_id).unwrap();
+    assert_eq!(trade.offer_id, offer_id);
+    assert_eq!(trade.buyer, buyer);
+    assert_eq!(client.get_offer(&offer_id).unwrap().counterparty, Some(buyer.clone()));
+    assert_eq!(usdc_client.balance(&contract_id), usdc_amount);
+
+    // Never listed, and still off-limits to others once the trade is cancelled
+    client.cancel_trade(&trade_id, &buyer);
+    assert_eq!(client.get_available_offers(&0, &10).len(), 0);
+    assert_eq!(
+        client.try_initiate_trade(&outsider, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    // The named buyer can pick the deal back up and complete it
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    assert!(usdc_client.balance(&buyer) > 0);
+}
+
+#[test]
+fn test_recurring_offer_relists_until_cap() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 4, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    // Two relists are allowed, funded from the seller's allowance
+    usdc_client.approve(&seller, &contract_id, &(usdc_amount * 2), &1_000);
+    client.set_recurring(&seller, &offer_id, &(usdc_amount * 2));
+
+    for round in 0..3 {
+        let buyer = <Address as TestAddress>::generate(&env);
+        let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+        client.confirm_payment(&trade_id, &buyer);
+        client.confirm_payment(&trade_id, &seller);
+
+        let relisted = round < 2;
+        if relisted {
+            assert_eq!(count_events(&env, OFFER_RELISTED), 1);
+        }
+        assert_eq!(client.get_seller_active_offers(&seller).contains(offer_id), relisted);
+        assert_eq!(client.get_recurring_cap(&offer_id), if round == 0 { usdc_amount } else { 0 });
+    }
+
+    // Two relists drew exactly the capped amount back into escrow
+    assert_eq!(usdc_client.balance(&seller), usdc_amount);
+    assert_eq!(usdc_client.balance(&contract_id), 0);
+    assert_eq!(client.verify_escrow(), 0);
+}
+
+#[test]
+fn test_buyer_requirements_gate_initiate_trade() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    client.set_buyer_requirements(&seller, &offer_id, &1, &1, &Vec::new(&env));
+
+    // A first-time account is turned away
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::RequirementsNotMet))
+    );
+
+    // One completed trade elsewhere builds enough reputation
+    let other_seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &other_seller, usdc_amount, &contract_id);
+    let other_offer = client.create_offer(&other_seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &other_offer, &None, &None, &0, &None);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &other_seller);
+    assert_eq!(client.get_reputation(&buyer), Reputation { completed_trades: 1, score: 1, disputes_lost: 0 });
+
+    // An allowlist overrides a good track record
+    let trusted = <Address as TestAddress>::generate(&env);
+    client.set_buyer_requirements(&seller, &offer_id, &1, &1, &vec![&env, trusted]);
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::RequirementsNotMet))
+    );
+
+    client.set_buyer_requirements(&seller, &offer_id, &1, &1, &Vec::new(&env));
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+
+    // All-zero requirements remove the entry
+    client.set_buyer_requirements(&seller, &offer_id, &0, &0, &Vec::new(&env));
+    assert_eq!(client.get_buyer_requirements(&offer_id), None);
+}
+
+#[test]
+fn test_buyer_cap_limits_fills_per_window() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let window = 86_400;
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 4, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    usdc_client.approve(&seller, &contract_id, &(usdc_amount * 3), &1_000);
+    client.set_recurring(&seller, &offer_id, &(usdc_amount * 3));
+
+    // Only the seller can cap, and a positive cap needs a window
+    assert_eq!(
+        client.try_set_buyer_cap(&buyer, &offer_id, &usdc_amount, &window),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_set_buyer_cap(&seller, &offer_id, &usdc_amount, &0),
+        Err(Ok(Error::InvalidAmount))
+    );
+    client.set_buyer_cap(&seller, &offer_id, &usdc_amount, &window);
+    assert_eq!(client.get_buyer_cap(&offer_id), Some((usdc_amount, window)));
+
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_buyer_fill(&offer_id, &buyer), usdc_amount);
+
+    // The same buyer is turned away from the relisted offer, others are not
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::BuyerCapExceeded))
+    );
+    let other = <Address as TestAddress>::generate(&env);
+    let trade_id = client.initiate_trade(&other, &offer_id, &None, &None, &0, &None);
+    client.confirm_payment(&trade_id, &other);
+    client.confirm_payment(&trade_id, &seller);
+
+    // The cap frees up once the window has elapsed
+    env.ledger().with_mut(|li| li.timestamp += window);
+    assert_eq!(client.get_buyer_fill(&offer_id, &buyer), 0);
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+
+    // A zero cap removes it
+    client.set_buyer_cap(&seller, &offer_id, &0, &0);
+    assert_eq!(client.get_buyer_cap(&offer_id), None);
+}
+
+#[test]
+fn test_walkaway_cooldown_escalates_until_completion() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let base = 3_600;
+    client.update_walkaway_cooldown(&base);
+    assert_eq!(
+        client.try_update_walkaway_cooldown(&(8 * 86_400)),
+        Err(Ok(Error::InvalidAmount))
+    );
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    // A seller cancellation doesn't count against the buyer
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.cancel_trade(&trade_id, &seller);
+    assert_eq!(client.get_buyer_cooldown(&buyer), (0, 0));
+
+    // The first walkaway costs the base cooldown
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.cancel_trade(&trade_id, &buyer);
+    let now = env.ledger().timestamp();
+    assert_eq!(client.get_buyer_cooldown(&buyer), (1, now + base));
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::CooldownActive))
+    );
+
+    // The second doubles it
+    env.ledger().with_mut(|li| li.timestamp += base);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.cancel_trade(&trade_id, &buyer);
+    let now = env.ledger().timestamp();
+    assert_eq!(client.get_buyer_cooldown(&buyer), (2, now + 2 * base));
+    env.ledger().with_mut(|li| li.timestamp += base);
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::CooldownActive))
+    );
+
+    // Letting a trade expire counts as well
+    env.ledger().with_mut(|li| li.timestamp += base);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration() + 1);
+    client.resolve_expired_trade(&trade_id, &seller);
+    assert_eq!(client.get_buyer_cooldown(&buyer).0, 3);
+
+    // A completed trade clears the streak
+    env.ledger().with_mut(|li| li.timestamp += 4 * base);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_buyer_cooldown(&buyer), (0, 0));
+}
+
+#[test]
+fn test_first_trade_held_through_cooling_off_period() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    client.update_first_trade_hold(&3_600);
+    let usdc_amount = 100_000_000;
+
+    // The buyer's first trade waits out the hold after both confirmations
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(count_events(&env, RELEASE_HELD), 2);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::PaymentConfirmed);
+    assert_eq!(client.try_release_held_trade(&trade_id), Err(Ok(Error::GracePeriodActive)));
+
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    client.release_held_trade(&trade_id);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    assert!(usdc_client.balance(&buyer) > 0);
+
+    // With a track record, the next trade settles immediately
+    let next_seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &next_seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&next_seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    let next_trade = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.confirm_payment(&next_trade, &buyer);
+    client.confirm_payment(&next_trade, &next_seller);
+    assert_eq!(client.get_trade(&next_trade).unwrap().status, TradeStatus::Completed);
+
+    // A seller whose payment was reversed disputes during the hold
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    client.raise_dispute(&trade_id, &seller, &String::from_str(&env, "M-Pesa reversed"), &Vec::new(&env));
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    assert_eq!(client.try_release_held_trade(&trade_id), Err(Ok(Error::InvalidTradeStatus)));
+}
+
+#[test]
+fn test_payment_details_disclosed_on_trade_initiation() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, 100_000_000, &contract_id);
+    let offer_id = client.create_offer(&seller, &100_000_000, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    let details = Bytes::from_array(&env, &[0xA5; 48]);
+    client.set_payment_details(&seller, &offer_id, &details);
+    assert_eq!(count_events(&env, PAYMENT_DETAILS), 0);
+    assert_eq!(
+        client.try_set_payment_details(&seller, &offer_id, &Bytes::from_array(&env, &[0; 257])),
+        Err(Ok(Error::InvalidAmount))
+    );
+
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    let disclosed = events_with_topic(&env, PAYMENT_DETAILS);
+    assert_eq!(disclosed.len(), 1);
+    let disclosed_data = <(u64, Bytes)>::try_from_val(&env, &disclosed[0].1).unwrap();
+    assert_eq!(disclosed_data, (trade_id, details));
+}
+
+#[test]
+fn test_disputes_delegated_to_arbitration_contract() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let pool_id = env.register(juror_pool::JurorPool, ());
+    let pool = JurorPoolClient::new(&env, &pool_id);
+    client.set_arbitrator(&Some(pool_id.clone()));
+
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.raise_dispute(&trade_id, &buyer, &String::from_str(&env, "Paid, no release"), &Vec::new(&env));
+    assert_eq!(pool.case(&trade_id), Some((buyer.clone(), usdc_amount)));
+
+    // The admin no longer resolves, and nobody else can submit a ruling
+    assert_eq!(
+        client.try_resolve_dispute(&trade_id, &DisputeResolution::RefundToSeller),
+        Err(Ok(Error::Unauthorized))
+    );
+    env.set_auths(&[]);
+    assert!(client.try_submit_ruling(&trade_id, &DisputeResolution::RefundToSeller).is_err());
+
+    pool.rule(&contract_id, &trade_id, &DisputeResolution::RefundToSeller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Cancelled);
+    assert_eq!(client.get_dispute(&trade_id).unwrap().resolved_by, Some(pool_id));
+    assert_eq!(usdc_client.balance(&seller), usdc_amount);
+
+    // Revoking the delegation hands resolution back to the admin
+    env.mock_all_auths();
+    client.set_arbitrator(&None);
+    assert_eq!(client.try_submit_ruling(&trade_id, &DisputeResolution::RefundToSeller), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_yield_adapter_deposits_and_withdraws_just_in_time() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let pool_id = env.register(yield_pool::MockYieldPool, (&usdc_token_id,));
+    let pool = MockYieldPoolClient::new(&env, &pool_id);
+    client.set_yield_adapter(&Some(pool_id.clone()));
+
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let (_, _, _, expiring_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+
+    // All idle escrow goes to the pool and is still fully accounted for
+    client.deposit_to_yield(&(usdc_amount * 2));
+    assert_eq!(usdc_client.balance(&contract_id), 0);
+    assert_eq!(client.get_yield_deposited(), usdc_amount * 2);
+    assert_eq!(client.verify_escrow(), 0);
+    assert_eq!(client.try_set_yield_adapter(&None), Err(Ok(Error::InvalidAmount)));
+
+    // Accrued interest is harvested into the insurance fund
+    let interest = 3_000_000;
+    token::StellarAssetClient::new(&env, &usdc_token_id).mint(&pool_id, &interest);
+    pool.accrue(&contract_id, &interest);
+    assert_eq!(client.harvest_yield(), interest);
+    assert_eq!(client.get_insurance_fund(), interest);
+    assert_eq!(client.verify_escrow(), 0);
+
+    // Release and expiry refunds pull their escrow back out of the pool
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+
+    env.ledger().with_mut(|li| li.timestamp += 601);
+    client.resolve_expired_trade(&expiring_id, &buyer);
+    // Only the insurance fund is left, still deposited since payouts used the harvest first
+    assert_eq!(client.get_yield_deposited(), interest);
+    assert_eq!(pool.balance(&contract_id), interest);
+    assert_eq!(usdc_client.balance(&contract_id), 0);
+    assert_eq!(client.verify_escrow(), 0);
+}
+
+#[test]
+fn test_admin_actions_recorded_in_capped_log() {
+    let (env, client, admin, _, _, _) = setup_test_env();
+    let old_rate = client.get_fee_rate();
+
+    client.update_fee_rate(&40);
+    client.pause();
+
+    let log = client.get_admin_log(&0, &10);
+    assert_eq!(log.len(), 2);
+    let fee_entry = log.get(0).unwrap();
+    assert_eq!(fee_entry.id, 0);
+    assert_eq!(fee_entry.actor, admin);
+    assert_eq!(fee_entry.action, Symbol::new(&env, "fee_rate"));
+    assert_eq!(fee_entry.old_value, old_rate.to_xdr(&env));
+    assert_eq!(fee_entry.new_value, 40u32.to_xdr(&env));
+    let pause_entry = log.get(1).unwrap();
+    assert_eq!(pause_entry.action, Symbol::new(&env, "pause"));
+    assert_eq!(pause_entry.old_value, false.to_xdr(&env));
+    assert_eq!(pause_entry.new_value, true.to_xdr(&env));
+
+    // Unset settings log empty bytes as their old value
+    let officer = <Address as TestAddress>::generate(&env);
+    client.set_compliance_officer(&officer);
+    let officer_entry = client.get_admin_log(&2, &1).get(0).unwrap();
+    assert!(officer_entry.old_value.is_empty());
+    assert_eq!(officer_entry.new_value, officer.to_xdr(&env));
+
+    // Beyond the cap the oldest entries are dropped but IDs keep counting
+    for i in 0..200u32 {
+        client.update_points_rate(&i);
+    }
+    let log = client.get_admin_log(&0, &5);
+    assert_eq!(log.len(), 5);
+    assert_eq!(log.get(0).unwrap().id, 3);
+    let tail = client.get_admin_log(&202, &5);
+    assert_eq!(tail.len(), 1);
+    assert_eq!(tail.get(0).unwrap().new_value, 199u32.to_xdr(&env));
+}
+
+#[test]
+fn test_seller_absorbs_fee_from_reserve() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    let (_, ceiling) = client.get_fee_bounds();
+    let reserve = usdc_amount * ceiling as i128 / 10_000;
+
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount + reserve, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    // Seller escrows a reserve sized for the fee ceiling
+    client.set_fee_payer(&seller, &offer_id, &FeePayer::Seller);
+    assert_eq!(client.get_fee_payer(&offer_id), FeePayer::Seller);
+    assert_eq!(usdc_client.balance(&seller), 0);
+    assert_eq!(client.verify_escrow(), 0);
+    assert_eq!(client.quote_trade(&offer_id, &usdc_amount).net_to_buyer, usdc_amount);
+
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    assert_eq!(
+        client.try_set_fee_payer(&seller, &offer_id, &FeePayer::Buyer),
+        Err(Ok(Error::TradeAlreadyInitiated))
+    );
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+
+    // Buyer receives face value; the unused reserve goes back to the seller
+    let fee = usdc_amount * client.get_fee_rate() as i128 / 10_000;
+    assert_eq!(usdc_client.balance(&buyer), usdc_amount);
+    assert_eq!(usdc_client.balance(&seller), reserve - fee);
+    assert_eq!(usdc_client.balance(&client.get_fee_collector()), fee);
+    assert_eq!(client.verify_escrow(), 0);
+
+    // A split offer returns its whole reserve on cancellation
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount + reserve / 2 - (reserve - fee), &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    client.set_fee_payer(&seller, &offer_id, &FeePayer::Split);
+    assert_eq!(usdc_client.balance(&seller), 0);
+    client.cancel_offer(&seller, &offer_id);
+    assert_eq!(usdc_client.balance(&seller), usdc_amount + reserve / 2);
+    assert_eq!(client.get_fee_payer(&offer_id), FeePayer::Buyer);
+    assert_eq!(client.verify_escrow(), 0);
+}
+
+#[test]
+fn test_trade_lookup_by_external_ref() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    let order_id = BytesN::from_array(&env, &[7; 32]);
+
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 2, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &Some(order_id.clone()));
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+
+    let trade = client.get_trade_by_ref(&order_id).unwrap();
+    assert_eq!(trade.status, TradeStatus::Completed);
+    assert_eq!(trade.external_ref, Some(order_id.clone()));
+    assert_eq!(client.get_trade_by_ref(&BytesN::from_array(&env, &[8; 32])), None);
+
+    // A reference can't be attached to a second trade
+    let seller2 = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller2, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller2, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &Some(order_id.clone())),
+        Err(Ok(Error::DuplicateReference))
+    );
+
+    // Pruning the trade drops the reference with it
+    env.ledger().with_mut(|li| li.timestamp += client.get_record_retention());
+    client.prune_trade(&trade_id);
+    assert_eq!(client.get_trade_by_ref(&order_id), None);
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &Some(order_id.clone()));
+}
+
+#[test]
+fn test_trade_deadline_counts_down() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (_, _, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    let expiration = client.get_trade_expiration();
+    let expires_at = env.ledger().timestamp() + expiration;
+
+    assert_eq!(client.get_trade_deadline(&trade_id), (expires_at, expiration));
+    env.ledger().with_mut(|li| li.timestamp += 100);
+    assert_eq!(client.get_trade_deadline(&trade_id), (expires_at, expiration - 100));
+    env.ledger().with_mut(|li| li.timestamp += expiration);
+    assert_eq!(client.get_trade_deadline(&trade_id), (expires_at, 0));
+    assert_eq!(client.try_get_trade_deadline(&99), Err(Ok(Error::TradeNotFound)));
+}
+
+#[test]
+fn test_trade_events_addressed_to_both_counterparties() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    let completed = events_with_topic(&env, TRADE_COMPLETED);
+    assert_eq!(completed.len(), 2);
+    let expected = (trade_id, offer_id, usdc_amount, usdc_amount * 120);
+
+    // Each party gets a copy with itself as the second topic and the counterparty as the third
+    for ((topics, data), (party, counterparty)) in completed.iter().zip([(&buyer, &seller), (&seller, &buyer)]) {
+        assert_eq!(Address::try_from_val(&env, &topics.get(1).unwrap()).unwrap(), *party);
+        assert_eq!(Address::try_from_val(&env, &topics.get(2).unwrap()).unwrap(), *counterparty);
+        assert_eq!(<(u64, u64, i128, i128)>::try_from_val(&env, data).unwrap(), expected);
+    }
+
+    // Initiation carries the same counterparties and terms
+    let seller2 = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller2, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller2, &usdc_amount, &(usdc_amount * 110), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    let initiated = events_with_topic(&env, TRADE_INITIATED);
+    assert_eq!(initiated.len(), 2);
+    let (topics, data) = &initiated[1];
+    assert_eq!(Address::try_from_val(&env, &topics.get(1).unwrap()).unwrap(), seller2);
+    let data = <(u64, u64, i128, i128)>::try_from_val(&env, data).unwrap();
+    assert_eq!(data, (trade_id, offer_id, usdc_amount, usdc_amount * 110));
+}
+
+#[test]
+fn test_event_sequence_numbers_are_contiguous_per_address() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    let before = client.get_event_seq(&buyer);
+    assert!(before > 0);
+
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+
+    // Every event addressed to the buyer in the last call continues its sequence without gaps
+    let mut seqs: Vec<u64> = Vec::new(&env);
+    for (_, topics, _) in env.events().all().iter() {
+        if topics.len() < 3 {
+            continue;
+        }
+        if let Ok(party) = Address::try_from_val(&env, &topics.get(1).unwrap()) {
+            if party == buyer {
+                seqs.push_back(u64::try_from_val(&env, &topics.get(topics.len() - 1).unwrap()).unwrap());
+            }
+        }
+    }
+    assert!(!seqs.is_empty());
+    let last = client.get_event_seq(&buyer);
+    assert_eq!(seqs.last().unwrap(), last);
+    for (i, seq) in seqs.iter().enumerate() {
+        assert_eq!(seq, last - (seqs.len() as u64 - 1 - i as u64));
+    }
+    assert!(seqs.first().unwrap() > before + 1);
+
+    // Each event addressed to the buyer keeps their sequence entry from being archived
+    let ttl = env.as_contract(&contract_id, || env.storage().persistent().get_ttl(&DataKey::EventSeq(buyer.clone())));
+    assert!(ttl > ENTRY_TTL_THRESHOLD);
+
+    let stranger = <Address as TestAddress>::generate(&env);
+    assert_eq!(client.get_event_seq(&stranger), 0);
+}
+
+#[test]
+fn test_initiation_deposit_refunded_or_paid_to_keeper() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (xlm_token_id, xlm_client) = create_token_contract(&env, &admin);
+    let deposit = 5_000_000;
+    client.set_initiation_deposit(&xlm_token_id, &deposit);
+    assert_eq!(client.get_initiation_deposit(), Some((xlm_token_id.clone(), deposit)));
+
+    // A buyer without XLM can't initiate
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 2, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(
+        client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None),
+        Err(Ok(Error::InsufficientBalance))
+    );
+
+    // Completing the trade returns the deposit
+    token::StellarAssetClient::new(&env, &xlm_token_id).mint(&buyer, &(deposit * 2));
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    assert_eq!(client.get_trade_deposit(&trade_id), deposit);
+    assert_eq!(xlm_client.balance(&buyer), deposit);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade_deposit(&trade_id), 0);
+    assert_eq!(xlm_client.balance(&buyer), deposit * 2);
+
+    // Letting a trade expire pays the deposit to whoever resolves it
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    let keeper = <Address as TestAddress>::generate(&env);
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration());
+    client.resolve_expired_trade(&trade_id, &keeper);
+    assert_eq!(xlm_client.balance(&keeper), deposit);
+    assert_eq!(xlm_client.balance(&buyer), deposit);
+    assert_eq!(xlm_client.balance(&contract_id), 0);
+
+    client.set_initiation_deposit(&xlm_token_id, &0);
+    assert_eq!(client.get_initiation_deposit(), None);
+}
+
+#[test]
+fn test_allowlisted_relayer_submits_on_behalf_of_users() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let relayer = <Address as TestAddress>::generate(&env);
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+
+    assert_eq!(
+        client.try_relay_create_offer(&relayer, &seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    client.set_relayer(&relayer, &true);
+    assert_eq!(client.get_relayers(), vec![&env, relayer.clone()]);
+    let offer_id = client.relay_create_offer(&relayer, &seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain);
+    let trade_id = client.relay_initiate_trade(&relayer, &buyer, &offer_id, &None, &0, &None);
+    // Both the relayer and the user authorize the relayed call
+    let auths = env.auths();
+    assert!(auths.iter().any(|(address, _)| *address == relayer));
+    assert!(auths.iter().any(|(address, _)| *address == buyer));
+
+    client.relay_confirm_payment(&relayer, &trade_id, &buyer);
+    client.relay_confirm_payment(&relayer, &trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+
+    client.set_relayer(&relayer, &false);
+    assert!(client.get_relayers().is_empty());
+}
+
+#[test]
+fn test_smart_wallets_trade_end_to_end() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = env.register(SmartWallet, ());
+    let buyer = env.register(SmartWallet, ());
+    let usdc_amount = 100_000_000;
+    let kes_amount = usdc_amount * 120;
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+
+    // The seller's wallet authorizes the escrow transfer as a sub-invocation of create_offer
+    let escrow = MockAuthInvoke {
+        contract: &usdc_token_id,
+        fn_name: "transfer",
+        args: (&seller, &contract_id, usdc_amount).into_val(&env),
+        sub_invokes: &[],
+    };
+    authorize_wallets(&env, &[MockAuth {
+        address: &seller,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "create_offer",
+            args: (&seller, usdc_amount, kes_amount, None::<BytesN<32>>, SettlementMode::OffChain, None::<Address>, None::<String>, None::<BytesN<32>>).into_val(&env),
+            sub_invokes: &[escrow],
+        },
+    }]);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    authorize_wallets(&env, &[MockAuth {
+        address: &buyer,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "initiate_trade",
+            args: (&buyer, offer_id, None::<Address>, None::<Address>, 0u32, None::<BytesN<32>>).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+
+    for participant in [&buyer, &seller] {
+        authorize_wallets(&env, &[MockAuth {
+            address: participant,
+            invoke: &MockAuthInvoke {
+                contract: &contract_id,
+                fn_name: "confirm_payment",
+                args: (trade_id, participant).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.confirm_payment(&trade_id, participant);
+    }
+
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    assert!(usdc_client.balance(&buyer) > 0);
+    assert_eq!(SmartWalletClient::new(&env, &seller).checks(), 2);
+    assert_eq!(SmartWalletClient::new(&env, &buyer).checks(), 2);
+}
+
+#[test]
+fn test_smart_wallet_admin_handover_and_governance() {
+    let (env, client, _, _, _, contract_id) = setup_test_env();
+    let wallet_admin = env.register(SmartWallet, ());
+    let next_admin = env.register(SmartWallet, ());
+    client.update_admin(&wallet_admin);
+
+    // A smart wallet admin can configure the marketplace
+    authorize_wallets(&env, &[MockAuth {
+        address: &wallet_admin,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "update_large_trade_threshold",
+            args: (5_000_000_000i128,).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    client.update_large_trade_threshold(&5_000_000_000);
+    assert_eq!(client.get_large_trade_threshold(), 5_000_000_000);
+
+    // Handover needs both wallets to sign the same invocation
+    let handover = MockAuthInvoke {
+        contract: &contract_id,
+        fn_name: "update_admin",
+        args: (&next_admin,).into_val(&env),
+        sub_invokes: &[],
+    };
+    authorize_wallets(&env, &[
+        MockAuth { address: &wallet_admin, invoke: &handover },
+        MockAuth { address: &next_admin, invoke: &handover },
+    ]);
+    client.update_admin(&next_admin);
+    assert_eq!(client.get_admin(), next_admin);
+
+    // An admin that is also the governance executor signs each change once
+    env.mock_all_auths();
+    client.set_governance_executor(&Some(next_admin.clone()));
+    authorize_wallets(&env, &[MockAuth {
+        address: &next_admin,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "set_governance_executor",
+            args: (None::<Address>,).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    client.set_governance_executor(&None);
+    assert_eq!(client.get_governance_executor(), None);
+    assert_eq!(SmartWalletClient::new(&env, &next_admin).checks(), 2);
+}
+
+#[test]
+fn test_simulations_report_failures_without_changing_state() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    let kes_amount = usdc_amount * 120;
+
+    assert_eq!(
+        client.try_simulate_create_offer(&seller, &usdc_amount, &kes_amount, &SettlementMode::OffChain),
+        Err(Ok(Error::InsufficientBalance))
+    );
+    assert_eq!(
+        client.try_simulate_create_offer(&seller, &0, &kes_amount, &SettlementMode::OffChain),
+        Err(Ok(Error::InvalidAmount))
+    );
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    client.simulate_create_offer(&seller, &usdc_amount, &kes_amount, &SettlementMode::OffChain);
+    assert_eq!(usdc_client.balance(&seller), usdc_amount);
+    assert_eq!(client.get_active_offer_count(), 0);
+
+    let offer_id = client.create_offer(&seller, &usdc_amount, &kes_amount, &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(
+        client.try_simulate_create_offer(&seller, &usdc_amount, &kes_amount, &SettlementMode::OffChain),
+        Err(Ok(Error::AlreadyHasActiveOffer))
+    );
+
+    assert_eq!(
+        client.try_simulate_initiate_trade(&seller, &offer_id, &None, &0, &None),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_simulate_initiate_trade(&buyer, &offer_id, &None, &100, &None),
+        Err(Ok(Error::InvalidAmount))
+    );
+    client.simulate_initiate_trade(&buyer, &offer_id, &None, &0, &None);
+    assert!(client.get_offer(&offer_id).unwrap().reserved_by.is_none());
+
+    client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    let other_buyer = <Address as TestAddress>::generate(&env);
+    assert_eq!(
+        client.try_simulate_initiate_trade(&other_buyer, &offer_id, &None, &0, &None),
+        Err(Ok(Error::TradeAlreadyInitiated))
+    );
+    assert_eq!(client.try_simulate_initiate_trade(&buyer, &99, &None, &0, &None), Err(Ok(Error::OfferNotFound)));
+
+    client.pause();
+    assert_eq!(
+        client.try_simulate_create_offer(&seller, &usdc_amount, &kes_amount, &SettlementMode::OffChain),
+        Err(Ok(Error::ContractPaused))
+    );
+}
+
+#[test]
+fn test_state_machine_transition_table_is_exhaustive() {
+    use crate::state_machine::{can_transition, is_final, transition};
+    use TradeStatus::*;
+
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (_, _, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, 100_000_000);
+    let template = client.get_trade(&trade_id).unwrap();
+
+    let statuses = [Initiated, PaymentConfirmed, Completed, Cancelled, Disputed];
+    let allowed = [
+        (Initiated, PaymentConfirmed),
+        (Initiated, Cancelled),
+        (Initiated, Disputed),
+        (PaymentConfirmed, Completed),
+        (PaymentConfirmed, Disputed),
+        (PaymentConfirmed, Cancelled),
+        (Disputed, Completed),
+        (Disputed, Cancelled),
+        (Cancelled, Initiated),
+    ];
+
+    env.ledger().with_mut(|li| li.timestamp += 42);
+    for from in statuses.iter() {
+        for to in statuses.iter() {
+            let expected = allowed.contains(&(from.clone(), to.clone()));
+            assert_eq!(can_transition(from, to), expected, "{:?} -> {:?}", from, to);
+
+            let mut trade = template.clone();
+            trade.status = from.clone();
+            let result = transition(&env, &mut trade, to.clone());
+            if expected {
+                assert_eq!(result, Ok(()));
+                assert_eq!(trade.status, *to);
+                assert_eq!(trade.completed_at.is_some(), *to == Completed);
+                assert_eq!(trade.cancelled_at.is_some(), *to == Cancelled);
+            } else {
+                assert_eq!(result, Err(Error::InvalidTradeStatus));
+                assert_eq!(trade.status, *from);
+            }
+        }
+
+        // Final statuses have no way out, other than reopening a cancelled trade
+        let reopens = |to: &TradeStatus| (from, to) == (&Cancelled, &Initiated);
+        assert_eq!(is_final(from), statuses.iter().all(|to| !can_transition(from, to) || reopens(to)));
+    }
+}
+
+#[test]
+fn test_offer_status_follows_lifecycle() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let status = |offer_id: u64| client.get_offer(&offer_id).unwrap().status;
+    assert_eq!(status(offer_id), OfferStatus::Reserved);
+
+    // A compliance freeze pauses the offer and unfreezing restores the reservation
+    let officer = <Address as TestAddress>::generate(&env);
+    client.set_compliance_officer(&officer);
+    client.freeze_offer(&offer_id);
+    assert_eq!(status(offer_id), OfferStatus::Paused);
+    client.unfreeze_offer(&offer_id);
+    assert_eq!(status(offer_id), OfferStatus::Reserved);
+
+    client.cancel_trade(&trade_id, &buyer);
+    assert_eq!(status(offer_id), OfferStatus::Active);
+    assert_eq!(client.get_available_offers(&0, &10).len(), 1);
+
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(status(offer_id), OfferStatus::Filled);
+    // A filled offer has no escrow left to refund
+    assert_eq!(client.try_cancel_offer(&seller, &offer_id), Err(Ok(Error::OfferNotFound)));
+
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    env.ledger().with_mut(|li| li.timestamp += client.get_trade_expiration());
+    client.resolve_expired_trade(&trade_id, &buyer);
+    assert_eq!(status(offer_id), OfferStatus::Expired);
+    assert!(client.get_available_offers(&0, &10).is_empty());
+}
+
+#[test]
+fn test_migrate_escrow_to_successor_after_timelock() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (_, _, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let successor_id = env.register(SuccessorDouble, ());
+    let successor = SuccessorDoubleClient::new(&env, &successor_id);
+    let impostor = env.register(SuccessorDouble, ());
+
+    // Only the scheduled successor, only while paused, and only after the timelock
+    assert_eq!(client.try_migrate_escrow(&successor_id), Err(Ok(Error::Unauthorized)));
+    client.schedule_migration(&Some(successor_id.clone()));
+    let (_, eta) = client.get_pending_migration().unwrap();
+    assert_eq!(client.try_migrate_escrow(&successor_id), Err(Ok(Error::ContractNotPaused)));
+    client.pause();
+    assert_eq!(client.try_migrate_escrow(&impostor), Err(Ok(Error::Unauthorized)));
+    assert_eq!(client.try_migrate_escrow(&successor_id), Err(Ok(Error::GracePeriodActive)));
+
+    env.ledger().with_mut(|li| li.timestamp = eta);
+    assert_eq!(client.migrate_escrow(&successor_id), usdc_amount);
+    assert_eq!(usdc_client.balance(&successor_id), usdc_amount);
+    assert_eq!(successor.imported(), Some((contract_id.clone(), 1, 1, usdc_amount)));
+    assert_eq!(client.get_successor(), Some(successor_id));
+    assert_eq!(client.get_pending_migration(), None);
+    assert_eq!(client.get_total_escrowed(), 0);
+    assert_eq!(client.verify_escrow(), 0);
+
+    // Records stay readable, but the old deployment stays halted
+    assert!(client.get_trade(&trade_id).is_some());
+    assert_eq!(client.try_unpause(), Err(Ok(Error::Unauthorized)));
+}
+
+#[test]
+fn test_wind_down_refunds_escrow_to_sellers() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    client.update_first_trade_hold(&3_600);
+    let usdc_amount = 100_000_000;
+    let (confirmed_seller, buyer, confirmed_offer, confirmed_trade) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&confirmed_trade, &buyer);
+    let (disputed_seller, buyer, disputed_offer, disputed_trade) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.raise_dispute(&disputed_trade, &buyer, &String::from_str(&env, "No payment received"), &Vec::new(&env));
+    let idle_seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &idle_seller, usdc_amount, &contract_id);
+    let idle_offer = client.create_offer(&idle_seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    // A first trade both parties confirmed, still waiting out its hold
+    let (held_seller, held_buyer, held_offer, held_trade) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    client.confirm_payment(&held_trade, &held_buyer);
+    client.confirm_payment(&held_trade, &held_seller);
+    assert_eq!(client.get_trade(&held_trade).unwrap().status, TradeStatus::PaymentConfirmed);
+    let officer = <Address as TestAddress>::generate(&env);
+    client.set_compliance_officer(&officer);
+    client.freeze_trade(&held_trade);
+
+    // Refunds need the wind-down, which halts everything else for good
+    assert_eq!(client.try_emergency_refund(&idle_offer), Err(Ok(Error::WindDownInactive)));
+    client.begin_wind_down();
+    assert!(client.is_paused());
+    assert_eq!(client.try_unpause(), Err(Ok(Error::Unauthorized)));
+    assert_eq!(client.try_begin_wind_down(), Err(Ok(Error::InvalidTradeStatus)));
+
+    // Offers without an agreed payment go back to their seller, whatever state their trade is in
+    for (seller, offer_id) in [(&confirmed_seller, confirmed_offer), (&disputed_seller, disputed_offer), (&idle_seller, idle_offer)] {
+        assert_eq!(client.emergency_refund(&offer_id), usdc_amount);
+        assert_eq!(count_events(&env, EMERGENCY_REFUND), 1);
+        assert_eq!(usdc_client.balance(seller), usdc_amount);
+        assert_eq!(client.get_offer(&offer_id).unwrap().status, OfferStatus::Cancelled);
+        assert_eq!(client.try_emergency_refund(&offer_id), Err(Ok(Error::OfferNotFound)));
+    }
+    for trade_id in [confirmed_trade, disputed_trade] {
+        assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Cancelled);
+    }
+
+    // The dispute stays on record, closed without a ruling
+    let dispute = client.get_dispute(&disputed_trade).unwrap();
+    assert_eq!(dispute.resolution, DisputeOutcome::Closed);
+    assert_eq!(dispute.resolved_by, None);
+    assert!(client.get_open_disputes().is_empty());
+
+    // A compliance hold keeps the escrow where it is until the freeze lifts
+    assert_eq!(client.try_emergency_refund(&held_offer), Err(Ok(Error::Frozen)));
+    assert_eq!(client.get_trade(&held_trade).unwrap().status, TradeStatus::PaymentConfirmed);
+    client.unfreeze_trade(&held_trade);
+
+    // The agreed trade settles to the buyer instead
+    assert_eq!(client.emergency_refund(&held_offer), 0);
+    assert_eq!(client.get_trade(&held_trade).unwrap().status, TradeStatus::Completed);
+    assert_eq!(client.get_offer(&held_offer).unwrap().status, OfferStatus::Filled);
+    assert!(usdc_client.balance(&held_buyer) > 0);
+    assert_eq!(usdc_client.balance(&held_seller), 0);
+    assert_eq!(client.try_emergency_refund(&held_offer), Err(Ok(Error::OfferNotFound)));
+
+    assert_eq!((client.get_open_trade_count(), client.get_total_escrowed()), (0, 0));
+    assert_eq!(client.verify_escrow(), 0);
+}
+
+#[test]
+fn test_entrypoints_on_uninitialized_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(Uninitialized, ());
+    let a = <Address as TestAddress>::generate(&env);
+
+    env.as_contract(&contract_id, || {
+        // Every fallible entrypoint rejects the call before touching storage
+        let results = [
+            ("create_offer", P2PMarketplaceContract::create_offer(env.clone(), a.clone(), 0, 0, None, SettlementMode::OffChain, None, None, None).err()),
+            ("simulate_create_offer", P2PMarketplaceContract::simulate_create_offer(env.clone(), a.clone(), 0, 0, SettlementMode::OffChain).err()),
+            ("set_buyer_requirements", P2PMarketplaceContract::set_buyer_requirements(env.clone(), a.clone(), 0, 0, 0, Vec::new(&env)).err()),
+            ("set_buyer_cap", P2PMarketplaceContract::set_buyer_cap(env.clone(), a.clone(), 0, 0, 0).err()),
+            ("set_payment_details", P2PMarketplaceContract::set_payment_details(env.clone(), a.clone(), 0, Bytes::new(&env)).err()),
+            ("set_fee_payer", P2PMarketplaceContract::set_fee_payer(env.clone(), a.clone(), 0, FeePayer::Buyer).err()),
+            ("set_recurring", P2PMarketplaceContract::set_recurring(env.clone(), a.clone(), 0, 0).err()),
+            ("create_offers", P2PMarketplaceContract::create_offers(env.clone(), a.clone(), Vec::new(&env)).err()),
+            ("create_direct_escrow", P2PMarketplaceContract::create_direct_escrow(env.clone(), a.clone(), a.clone(), 0, 0).err()),
+            ("initiate_trade", P2PMarketplaceContract::initiate_trade(env.clone(), a.clone(), 0, None, None, 0, None).err()),
+            ("initiate_trade_fok", P2PMarketplaceContract::initiate_trade_fok(env.clone(), a.clone(), 0, 0, 0).err()),
+            ("simulate_initiate_trade", P2PMarketplaceContract::simulate_initiate_trade(env.clone(), a.clone(), 0, None, 0, None).err()),
+            ("propose_price", P2PMarketplaceContract::propose_price(env.clone(), a.clone(), 0, 0, None).err()),
+            ("accept_proposal", P2PMarketplaceContract::accept_proposal(env.clone(), a.clone(), 0).err()),
+            ("reject_proposal", P2PMarketplaceContract::reject_proposal(env.clone(), a.clone(), 0).err()),
+            ("claim_proposal_deposit", P2PMarketplaceContract::claim_proposal_deposit(env.clone(), a.clone(), 0).err()),
+            ("mark_tranche", P2PMarketplaceContract::mark_tranche(env.clone(), 0, a.clone(), 0).err()),
+            ("confirm_tranche", P2PMarketplaceContract::confirm_tranche(env.clone(), 0, a.clone(), 0).err()),
+            ("confirm_payment", P2PMarketplaceContract::confirm_payment(env.clone(), 0, a.clone()).err()),
+            ("release_to_buyer", P2PMarketplaceContract::release_to_buyer(env.clone(), 0, a.clone()).err()),
+            ("confirm_payment_with_receipt", P2PMarketplaceContract::confirm_payment_with_receipt(env.clone(), 0, a.clone(), BytesN::from_array(&env, &[0; 32]), BytesN::from_array(&env, &[0; 64])).err()),
+            ("commit_payment_hash", P2PMarketplaceContract::commit_payment_hash(env.clone(), 0, a.clone(), BytesN::from_array(&env, &[0; 32])).err()),
+            ("confirm_payment_with_preimage", P2PMarketplaceContract::confirm_payment_with_preimage(env.clone(), 0, a.clone(), Bytes::new(&env)).err()),
+            ("attest_payment", P2PMarketplaceContract::attest_payment(env.clone(), 0, BytesN::from_array(&env, &[0; 32])).err()),
+            ("release_attested_trade", P2PMarketplaceContract::release_attested_trade(env.clone(), 0).err()),
+            ("release_held_trade", P2PMarketplaceContract::release_held_trade(env.clone(), 0).err()),
+            ("auto_release_trade", P2PMarketplaceContract::auto_release_trade(env.clone(), 0).err()),
+            ("cancel_trade", P2PMarketplaceContract::cancel_trade(env.clone(), 0, a.clone()).err()),
+            ("reopen_trade", P2PMarketplaceContract::reopen_trade(env.clone(), 0).err()),
+            ("resolve_expired_trade", P2PMarketplaceContract::resolve_expired_trade(env.clone(), 0, a.clone()).err()),
+            ("cancel_offer", P2PMarketplaceContract::cancel_offer(env.clone(), a.clone(), 0).err()),
+            ("expire_offer", P2PMarketplaceContract::expire_offer(env.clone(), 0).err()),
+            ("withdraw", P2PMarketplaceContract::withdraw(env.clone(), a.clone()).err()),
+            ("prune_trade", P2PMarketplaceContract::prune_trade(env.clone(), 0).err()),
+            ("prune_offer", P2PMarketplaceContract::prune_offer(env.clone(), 0).err()),
+            ("pause", P2PMarketplaceContract::pause(env.clone()).err()),
+            ("guardian_pause", P2PMarketplaceContract::guardian_pause(env.clone()).err()),
+            ("set_guardian", P2PMarketplaceContract::set_guardian(env.clone(), a.clone()).err()),
+            ("unpause", P2PMarketplaceContract::unpause(env.clone()).err()),
+            ("begin_wind_down", P2PMarketplaceContract::begin_wind_down(env.clone()).err()),
+            ("emergency_refund", P2PMarketplaceContract::emergency_refund(env.clone(), 0).err()),
+            ("raise_dispute", P2PMarketplaceContract::raise_dispute(env.clone(), 0, a.clone(), String::from_str(&env, ""), Vec::new(&env)).err()),
+            ("submit_evidence", P2PMarketplaceContract::submit_evidence(env.clone(), 0, a.clone(), Vec::new(&env), false).err()),
+            ("resolve_dispute", P2PMarketplaceContract::resolve_dispute(env.clone(), 0, DisputeResolution::RefundToSeller).err()),
+            ("submit_ruling", P2PMarketplaceContract::submit_ruling(env.clone(), 0, DisputeResolution::RefundToSeller).err()),
+            ("arbitrate_dispute", P2PMarketplaceContract::arbitrate_dispute(env.clone(), a.clone(), 0, DisputeResolution::RefundToSeller).err()),
+            ("claim_arbitrator_fees", P2PMarketplaceContract::claim_arbitrator_fees(env.clone(), a.clone()).err()),
+            ("relay_create_offer", P2PMarketplaceContract::relay_create_offer(env.clone(), a.clone(), a.clone(), 0, 0, None, SettlementMode::OffChain).err()),
+            ("relay_initiate_trade", P2PMarketplaceContract::relay_initiate_trade(env.clone(), a.clone(), a.clone(), 0, None, 0, None).err()),
+            ("relay_confirm_payment", P2PMarketplaceContract::relay_confirm_payment(env.clone(), a.clone(), 0, a.clone()).err()),
+            ("upgrade", P2PMarketplaceContract::upgrade(env.clone(), BytesN::from_array(&env, &[0; 32])).err()),
+            ("schedule_migration", P2PMarketplaceContract::schedule_migration(env.clone(), None).err()),
+            ("migrate_escrow", P2PMarketplaceContract::migrate_escrow(env.clone(), a.clone()).err()),
+            ("update_admin", P2PMarketplaceContract::update_admin(env.clone(), a.clone()).err()),
+            ("set_admin_signers", P2PMarketplaceContract::set_admin_signers(env.clone(), Vec::new(&env), 0).err()),
+            ("propose_admin_action", P2PMarketplaceContract::propose_admin_action(env.clone(), a.clone(), AdminAction::UpdateAdmin(a.clone())).err()),
+            ("approve_action", P2PMarketplaceContract::approve_action(env.clone(), a.clone(), 0).err()),
+            ("execute_action", P2PMarketplaceContract::execute_action(env.clone(), 0).err()),
+            ("update_fee_collector", P2PMarketplaceContract::update_fee_collector(env.clone(), a.clone()).err()),
+            ("update_fee_curve", P2PMarketplaceContract::update_fee_curve(env.clone(), Vec::new(&env)).err()),
+            ("update_operator_fee_cap", P2PMarketplaceContract::update_operator_fee_cap(env.clone(), 0).err()),
+            ("update_fee_splits", P2PMarketplaceContract::update_fee_splits(env.clone(), Vec::new(&env)).err()),
+            ("update_fee_rate", P2PMarketplaceContract::update_fee_rate(env.clone(), 0).err()),
+            ("set_kes_token", P2PMarketplaceContract::set_kes_token(env.clone(), a.clone()).err()),
+            ("register_token", P2PMarketplaceContract::register_token(env.clone(), a.clone()).err()),
+            ("set_token_config", P2PMarketplaceContract::set_token_config(env.clone(), a.clone(), 0, 0, None, false).err()),
+            ("set_payment_oracle", P2PMarketplaceContract::set_payment_oracle(env.clone(), a.clone()).err()),
+            ("set_relayer", P2PMarketplaceContract::set_relayer(env.clone(), a.clone(), false).err()),
+            ("set_receipt_attester", P2PMarketplaceContract::set_receipt_attester(env.clone(), BytesN::from_array(&env, &[0; 32])).err()),
+            ("update_attestation_grace_period", P2PMarketplaceContract::update_attestation_grace_period(env.clone(), 0).err()),
+            ("update_auto_release_window", P2PMarketplaceContract::update_auto_release_window(env.clone(), 0).err()),
+            ("update_first_trade_hold", P2PMarketplaceContract::update_first_trade_hold(env.clone(), 0).err()),
+            ("update_walkaway_cooldown", P2PMarketplaceContract::update_walkaway_cooldown(env.clone(), 0).err()),
+            ("update_insurance_share", P2PMarketplaceContract::update_insurance_share(env.clone(), 0).err()),
+            ("update_arbitrator_fee", P2PMarketplaceContract::update_arbitrator_fee(env.clone(), 0).err()),
+            ("update_dispute_penalty", P2PMarketplaceContract::update_dispute_penalty(env.clone(), 0).err()),
+            ("update_suspension_policy", P2PMarketplaceContract::update_suspension_policy(env.clone(), 0, 0).err()),
+            ("reinstate", P2PMarketplaceContract::reinstate(env.clone(), a.clone()).err()),
+            ("update_evidence_window", P2PMarketplaceContract::update_evidence_window(env.clone(), 0).err()),
+            ("set_compliance_officer", P2PMarketplaceContract::set_compliance_officer(env.clone(), a.clone()).err()),
+            ("freeze_trade", P2PMarketplaceContract::freeze_trade(env.clone(), 0).err()),
+            ("unfreeze_trade", P2PMarketplaceContract::unfreeze_trade(env.clone(), 0).err()),
+            ("freeze_offer", P2PMarketplaceContract::freeze_offer(env.clone(), 0).err()),
+            ("unfreeze_offer", P2PMarketplaceContract::unfreeze_offer(env.clone(), 0).err()),
+            ("update_large_trade_threshold", P2PMarketplaceContract::update_large_trade_threshold(env.clone(), 0).err()),
+            ("set_initiation_deposit", P2PMarketplaceContract::set_initiation_deposit(env.clone(), a.clone(), 0).err()),
+            ("update_daily_limit", P2PMarketplaceContract::update_daily_limit(env.clone(), 0).err()),
+            ("update_points_rate", P2PMarketplaceContract::update_points_rate(env.clone(), 0).err()),
+            ("compensate", P2PMarketplaceContract::compensate(env.clone(), a.clone(), 0).err()),
+            ("sweep_surplus", P2PMarketplaceContract::sweep_surplus(env.clone(), a.clone()).err()),
+            ("set_fees_enabled", P2PMarketplaceContract::set_fees_enabled(env.clone(), false).err()),
+            ("set_governance_executor", P2PMarketplaceContract::set_governance_executor(env.clone(), None).err()),
+            ("set_arbitrator", P2PMarketplaceContract::set_arbitrator(env.clone(), None).err()),
+            ("set_panel_arbitrator", P2PMarketplaceContract::set_panel_arbitrator(env.clone(), a.clone(), false).err()),
+            ("set_yield_adapter", P2PMarketplaceContract::set_yield_adapter(env.clone(), None).err()),
+            ("deposit_to_yield", P2PMarketplaceContract::deposit_to_yield(env.clone(), 0).err()),
+            ("harvest_yield", P2PMarketplaceContract::harvest_yield(env.clone()).err()),
+            ("update_trade_limits", P2PMarketplaceContract::update_trade_limits(env.clone(), 0, 0).err()),
+            ("update_fiat_limits", P2PMarketplaceContract::update_fiat_limits(env.clone(), 0, 0, 0).err()),
+            ("update_offer_caps", P2PMarketplaceContract::update_offer_caps(env.clone(), 0, 0).err()),
+            ("update_hashed_ids", P2PMarketplaceContract::update_hashed_ids(env.clone(), true).err()),
+            ("update_max_open_trades", P2PMarketplaceContract::update_max_open_trades(env.clone(), 0).err()),
+            ("update_trade_expiration", P2PMarketplaceContract::update_trade_expiration(env.clone(), 0).err()),
+            ("update_expiry_grace", P2PMarketplaceContract::update_expiry_grace(env.clone(), 0).err()),
+            ("update_record_retention", P2PMarketplaceContract::update_record_retention(env.clone(), 0).err()),
+            ("update_offer_ttl", P2PMarketplaceContract::update_offer_ttl(env.clone(), 0).err()),
+            ("get_admin", P2PMarketplaceContract::get_admin(env.clone()).err()),
+            ("get_usdc_token_id", P2PMarketplaceContract::get_usdc_token_id(env.clone()).err()),
+            ("get_fee_collector", P2PMarketplaceContract::get_fee_collector(env.clone()).err()),
+            ("verify_escrow", P2PMarketplaceContract::verify_escrow(env.clone()).err()),
+            ("quote_trade", P2PMarketplaceContract::quote_trade(env.clone(), 0, 0).err()),
+            ("get_trade_deadline", P2PMarketplaceContract::get_trade_deadline(env.clone(), 0).err()),
+            ("get_trade_details", P2PMarketplaceContract::get_trade_details(env.clone(), 0).err()),
+            ("get_contract_info", P2PMarketplaceContract::get_contract_info(env.clone()).err()),
+        ];
+        for (name, result) in results {
+            assert_eq!(result, Some(Error::NotInitialized), "{}", name);
+        }
+
+        // Queries fall back to empty results instead of panicking on missing storage
+        P2PMarketplaceContract::get_fee_rate(env.clone());
+        P2PMarketplaceContract::get_operator_fee_cap(env.clone());
+        P2PMarketplaceContract::get_fee_curve(env.clone());
+        P2PMarketplaceContract::get_fee_rate_for(env.clone(), 0);
+        P2PMarketplaceContract::get_fee_splits(env.clone());
+        P2PMarketplaceContract::get_insurance_share(env.clone());
+        P2PMarketplaceContract::get_admin_signers(env.clone());
+        P2PMarketplaceContract::get_admin_action(env.clone(), 0);
+        P2PMarketplaceContract::get_guardian(env.clone());
+        P2PMarketplaceContract::get_compliance_officer(env.clone());
+        P2PMarketplaceContract::is_trade_frozen(env.clone(), 0);
+        P2PMarketplaceContract::is_offer_frozen(env.clone(), 0);
+        P2PMarketplaceContract::get_large_trade_threshold(env.clone());
+        P2PMarketplaceContract::get_initiation_deposit(env.clone());
+        P2PMarketplaceContract::get_trade_deposit(env.clone(), 0);
+        P2PMarketplaceContract::get_daily_limit(env.clone());
+        P2PMarketplaceContract::get_daily_volume(env.clone(), a.clone());
+        P2PMarketplaceContract::get_points_rate(env.clone());
+        P2PMarketplaceContract::get_points(env.clone(), a.clone());
+        P2PMarketplaceContract::get_claimable(env.clone(), a.clone());
+        P2PMarketplaceContract::get_dispute_penalty(env.clone());
+        P2PMarketplaceContract::get_suspension_policy(env.clone());
+        P2PMarketplaceContract::get_suspended_at(env.clone(), a.clone());
+        P2PMarketplaceContract::get_evidence_window(env.clone());
+        P2PMarketplaceContract::get_arbitrator_fee(env.clone());
+        P2PMarketplaceContract::get_arbitrator_stats(env.clone(), a.clone());
+        P2PMarketplaceContract::get_insurance_fund(env.clone());
+        P2PMarketplaceContract::get_fees_enabled(env.clone());
+        P2PMarketplaceContract::get_fee_bounds(env.clone());
+        P2PMarketplaceContract::get_governance_executor(env.clone());
+        P2PMarketplaceContract::get_arbitrator(env.clone());
+        P2PMarketplaceContract::get_arbitrator_panel(env.clone());
+        P2PMarketplaceContract::get_pending_migration(env.clone());
+        P2PMarketplaceContract::get_wind_down(env.clone());
+        P2PMarketplaceContract::get_successor(env.clone());
+        P2PMarketplaceContract::get_yield_adapter(env.clone());
+        P2PMarketplaceContract::get_yield_deposited(env.clone());
+        P2PMarketplaceContract::get_trade_limits(env.clone());
+        P2PMarketplaceContract::get_offer_caps(env.clone());
+        P2PMarketplaceContract::get_max_open_trades(env.clone());
+        P2PMarketplaceContract::get_fiat_limits(env.clone());
+        P2PMarketplaceContract::get_payment_oracle(env.clone());
+        P2PMarketplaceContract::get_relayers(env.clone());
+        P2PMarketplaceContract::get_receipt_attester(env.clone());
+        P2PMarketplaceContract::get_first_trade_hold(env.clone());
+        P2PMarketplaceContract::get_walkaway_cooldown(env.clone());
+        P2PMarketplaceContract::get_buyer_cooldown(env.clone(), a.clone());
+        P2PMarketplaceContract::get_auto_release_window(env.clone());
+        P2PMarketplaceContract::get_attestation_grace_period(env.clone());
+        P2PMarketplaceContract::get_kes_token(env.clone());
+        P2PMarketplaceContract::get_token_config(env.clone(), a.clone());
+        P2PMarketplaceContract::get_token_decimals(env.clone());
+        P2PMarketplaceContract::get_trade_expiration(env.clone());
+        P2PMarketplaceContract::get_expiry_grace(env.clone());
+        P2PMarketplaceContract::get_next_offer_id(env.clone());
+        P2PMarketplaceContract::get_next_trade_id(env.clone());
+        P2PMarketplaceContract::get_hashed_ids(env.clone());
+        P2PMarketplaceContract::get_id_nonce(env.clone(), a.clone());
+        P2PMarketplaceContract::find_offer_id(env.clone(), a.clone(), 0, 0);
+        P2PMarketplaceContract::find_trade_id(env.clone(), a.clone(), 0, 0);
+        P2PMarketplaceContract::get_offers(env.clone());
+        P2PMarketplaceContract::get_offer(env.clone(), 0);
+        P2PMarketplaceContract::get_fee_payer(env.clone(), 0);
+        P2PMarketplaceContract::get_buyer_requirements(env.clone(), 0);
+        P2PMarketplaceContract::get_buyer_cap(env.clone(), 0);
+        P2PMarketplaceContract::get_buyer_fill(env.clone(), 0, a.clone());
+        P2PMarketplaceContract::get_reputation(env.clone(), a.clone());
+        P2PMarketplaceContract::get_recurring_cap(env.clone(), 0);
+        P2PMarketplaceContract::get_offer_rate(env.clone(), 0);
+        P2PMarketplaceContract::get_reference_rate(env.clone());
+        P2PMarketplaceContract::get_offers_by_rate_range(env.clone(), 0, 0, 0);
+        P2PMarketplaceContract::get_depth(env.clone(), 0);
+        P2PMarketplaceContract::get_available_offers(env.clone(), 0, 0);
+        P2PMarketplaceContract::get_proposal(env.clone(), 0);
+        P2PMarketplaceContract::get_trades(env.clone());
+        P2PMarketplaceContract::get_trade(env.clone(), 0);
+        P2PMarketplaceContract::get_payment_hash(env.clone(), 0);
+        P2PMarketplaceContract::get_offer_by_key(env.clone(), a.clone(), BytesN::from_array(&env, &[0; 32]));
+        P2PMarketplaceContract::get_trade_by_ref(env.clone(), BytesN::from_array(&env, &[0; 32]));
+        P2PMarketplaceContract::get_event_seq(env.clone(), a.clone());
+        P2PMarketplaceContract::get_dispute(env.clone(), 0);
+        P2PMarketplaceContract::get_open_disputes(env.clone());
+        P2PMarketplaceContract::get_disputed_trades(env.clone(), 0, 0);
+        P2PMarketplaceContract::get_admin_log(env.clone(), 0, 0);
+        P2PMarketplaceContract::get_active_offers(env.clone());
+        P2PMarketplaceContract::get_seller_active_offer(env.clone(), a.clone());
+        P2PMarketplaceContract::get_seller_active_offers(env.clone(), a.clone());
+        P2PMarketplaceContract::get_escrowed_balance(env.clone(), a.clone());
+        P2PMarketplaceContract::get_active_offer_count(env.clone());
+        P2PMarketplaceContract::get_open_trade_count(env.clone());
+        P2PMarketplaceContract::get_total_escrowed(env.clone());
+        P2PMarketplaceContract::is_paused(env.clone());
+        P2PMarketplaceContract::get_record_retention(env.clone());
+        P2PMarketplaceContract::get_offer_ttl(env.clone());
+        P2PMarketplaceContract::get_offer_expiry(env.clone(), 0);
+        P2PMarketplaceContract::get_error_info(env.clone(), 0);
+        assert!(P2PMarketplaceContract::get_offers(env.clone()).is_empty());
+        assert_eq!(P2PMarketplaceContract::get_trade(env.clone(), 0), None);
+    });
+}
+
+#[test]
+fn test_interface_client_drives_trade_lifecycle() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let market = p2p_marketplace_interface::P2PMarketplaceClient::new(&env, &contract_id);
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+
+    // Integrators get the same calls and errors through the interface crate
+    let offer_id = market.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(market.get_offer(&offer_id), client.get_offer(&offer_id));
+    let trade_id = market.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    assert_eq!(market.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None), Err(Ok(Error::TradeAlreadyInitiated)));
+    market.confirm_payment(&trade_id, &buyer);
+    market.confirm_payment(&trade_id, &seller);
+    assert_eq!(market.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    assert!(usdc_client.balance(&buyer) > 0);
+    assert_eq!(market.get_admin(), admin);
+}
+
+#[test]
+fn test_fill_or_kill_initiation_checks_quote() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let rate = client.get_offer_rate(&offer_id).unwrap();
+
+    // A worse rate or a bigger fill than quoted kills the call without reserving the offer
+    assert_eq!(client.try_initiate_trade_fok(&buyer, &offer_id, &(rate - 1), &usdc_amount), Err(Ok(Error::FillConditionsNotMet)));
+    assert_eq!(client.try_initiate_trade_fok(&buyer, &offer_id, &rate, &(usdc_amount - 1)), Err(Ok(Error::FillConditionsNotMet)));
+    assert_eq!(client.try_initiate_trade_fok(&buyer, &offer_id, &0, &usdc_amount), Err(Ok(Error::InvalidAmount)));
+    assert_eq!(client.try_initiate_trade_fok(&buyer, &99, &rate, &usdc_amount), Err(Ok(Error::OfferNotFound)));
+    assert_eq!(client.get_offer(&offer_id).unwrap().status, OfferStatus::Active);
+
+    // Within the quote it fills like initiate_trade, and an offer already taken fails cleanly
+    let trade_id = client.initiate_trade_fok(&buyer, &offer_id, &rate, &usdc_amount);
+    assert_eq!(client.get_trade(&trade_id).unwrap().buyer, buyer);
+    assert_eq!(client.get_offer(&offer_id).unwrap().status, OfferStatus::Reserved);
+    let other_buyer = <Address as TestAddress>::generate(&env);
+    assert_eq!(client.try_initiate_trade_fok(&other_buyer, &offer_id, &rate, &usdc_amount), Err(Ok(Error::TradeAlreadyInitiated)));
+}
+
+#[test]
+fn test_recurring_offer_requires_live_allowance() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let seller = <Address as TestAddress>::generate(&env);
+    let usdc_amount = 100_000_000;
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 2, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    // No approval, or one too small for a single relist
+    assert_eq!(client.try_set_recurring(&seller, &offer_id, &usdc_amount), Err(Ok(Error::InsufficientAllowance)));
+    let live_until = env.ledger().sequence() + 100;
+    usdc_client.approve(&seller, &contract_id, &(usdc_amount - 1), &live_until);
+    assert_eq!(client.try_set_recurring(&seller, &offer_id, &usdc_amount), Err(Ok(Error::InsufficientAllowance)));
+    usdc_client.approve(&seller, &contract_id, &usdc_amount, &live_until);
+    client.set_recurring(&seller, &offer_id, &usdc_amount);
+
+    // A lapsed approval is caught up front; turning relisting off needs none
+    env.ledger().with_mut(|li| li.sequence_number = live_until + 1);
+    assert_eq!(client.try_set_recurring(&seller, &offer_id, &usdc_amount), Err(Ok(Error::InsufficientAllowance)));
+    client.set_recurring(&seller, &offer_id, &0);
+    assert_eq!(client.get_recurring_cap(&offer_id), 0);
+}
+
+#[test]
+fn test_failed_relist_leaves_daily_limit_unused() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    client.update_daily_limit(&(usdc_amount * 3));
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 2, &contract_id);
+    let offer_id = client.create_offer(&seller, &usdc_amount, &12_000_000_000, &None, &SettlementMode::OffChain, &None, &None, &None);
+    usdc_client.approve(&seller, &contract_id, &usdc_amount, &1_000);
+    client.set_recurring(&seller, &offer_id, &(usdc_amount * 2));
+    let trade_id = client.initiate_trade(&buyer, &offer_id, &None, &None, &0, &None);
+    let used = client.get_daily_volume(&seller);
+
+    // The seller revokes the allowance, so the relist can't pull the next round
+    usdc_client.approve(&seller, &contract_id, &0, &1_000);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    assert_eq!(client.get_recurring_cap(&offer_id), 0);
+    assert_eq!(client.get_daily_volume(&seller), used);
+}
+
+#[test]
+fn test_create_offer_retry_with_idempotency_key() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 2, &contract_id);
+    let key = BytesN::from_array(&env, &[7; 32]);
+
+    // The retry returns the first offer without escrowing again or tripping the one-offer rule
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &Some(key.clone()));
+    assert_eq!(client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &Some(key.clone())), offer_id);
+    assert_eq!(count_events(&env, OFFER_CREATED), 0);
+    assert_eq!(usdc_client.balance(&seller), usdc_amount);
+    assert_eq!(client.get_offer_by_key(&seller, &key), Some(offer_id));
+
+    // Keys are scoped to the seller
+    let other_seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &other_seller, usdc_amount, &contract_id);
+    let other_offer = client.create_offer(&other_seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &Some(key.clone()));
+    assert_ne!(other_offer, offer_id);
+
+    // A cancelled offer still answers the retry until it is pruned
+    client.cancel_offer(&seller, &offer_id);
+    assert_eq!(client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &Some(key.clone())), offer_id);
+    client.prune_offer(&offer_id);
+    assert_eq!(client.get_offer_by_key(&seller, &key), None);
+    assert!(!env.as_contract(&contract_id, || env.storage().persistent().has(&DataKey::OfferKeyOf(offer_id))));
+    assert_eq!(client.get_offer_by_key(&other_seller, &key), Some(other_offer));
+}
+
+#[test]
+fn test_hashed_ids_resolve_under_both_schemes() {
+    let (env, client, admin, usdc_token_id, _usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let sequential_seller = <Address as TestAddress>::generate(&env);
+    let hashed_seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &sequential_seller, usdc_amount, &contract_id);
+    setup_token_balance(&env, &admin, &usdc_token_id, &hashed_seller, usdc_amount, &contract_id);
+    let ledger = env.ledger().sequence();
+
+    let sequential_id = client.create_offer(&sequential_seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(sequential_id, 0);
+
+    // Hashed IDs come from the owner's own nonce, and the global counter stops being served
+    assert!(!client.get_hashed_ids());
+    client.update_hashed_ids(&true);
+    assert!(client.get_hashed_ids());
+    assert_eq!(client.get_id_nonce(&hashed_seller), 0);
+    let hashed_id = client.create_offer(&hashed_seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_ne!(hashed_id, 1);
+    assert_eq!(client.get_id_nonce(&hashed_seller), 1);
+    assert_eq!((client.get_next_offer_id(), client.get_next_trade_id()), (None, None));
+    assert_eq!(client.get_offer(&hashed_id).unwrap().seller, hashed_seller);
+
+    // Lookups resolve either scheme and check the owner
+    assert_eq!(client.find_offer_id(&sequential_seller, &0, &ledger), Some(sequential_id));
+    assert_eq!(client.find_offer_id(&hashed_seller, &0, &ledger), Some(hashed_id));
+    assert_eq!(client.find_offer_id(&hashed_seller, &1, &ledger), None);
+    assert_eq!(client.find_offer_id(&hashed_seller, &0, &(ledger + 1)), None);
+
+    let trade_id = client.initiate_trade(&buyer, &hashed_id, &None, &None, &0, &None);
+    assert_ne!(trade_id, 0);
+    assert_eq!(client.find_trade_id(&buyer, &0, &ledger), Some(trade_id));
+
+    // Switching back resumes sequential IDs without reusing a nonce
+    client.update_hashed_ids(&false);
+    setup_token_balance(&env, &admin, &usdc_token_id, &sequential_seller, usdc_amount, &contract_id);
+    client.cancel_offer(&sequential_seller, &sequential_id);
+    assert_eq!(client.get_next_offer_id(), Some(2));
+    let next_id = client.create_offer(&sequential_seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    assert_eq!(next_id, 2);
+}
+
+#[test]
+fn test_offer_metadata_uri_is_stored_and_announced() {
+    let (env, client, admin, usdc_token_id, _usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+
+    // Empty or oversized URIs are rejected before any escrow moves
+    let too_long = String::from_bytes(&env, &[b'a'; 129]);
+    assert_eq!(
+        client.try_create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &Some(too_long), &None),
+        Err(Ok(Error::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &Some(String::from_str(&env, "")), &None),
+        Err(Ok(Error::InvalidAmount))
+    );
+
+    let uri = String::from_str(&env, "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi");
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &Some(uri.clone()), &None);
+    let created = events_with_topic(&env, OFFER_CREATED);
+    assert_eq!(created.len(), 1);
+    let data = <(u64, i128, i128, Option<String>)>::try_from_val(&env, &created[0].1).unwrap();
+    assert_eq!(data, (offer_id, usdc_amount, usdc_amount * 120, Some(uri.clone())));
+    assert_eq!(client.get_offer(&offer_id).unwrap().metadata_uri, Some(uri));
+}
+
+#[test]
+fn test_hash_locked_payment_settles_on_preimage() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let (seller, buyer, _, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+    let secret = Bytes::from_slice(&env, b"MPESA-REF-QX7K2");
+    let payment_hash: BytesN<32> = env.crypto().sha256(&secret).into();
+
+    // Only the buyer commits, and committing counts as their confirmation
+    assert_eq!(client.try_commit_payment_hash(&trade_id, &seller, &payment_hash), Err(Ok(Error::Unauthorized)));
+    client.commit_payment_hash(&trade_id, &buyer, &payment_hash);
+    assert_eq!(count_events(&env, PAYMENT_CONFIRMED), 2);
+    assert!(client.get_trade(&trade_id).unwrap().buyer_confirmed_payment);
+    assert_eq!(client.get_payment_hash(&trade_id), Some(payment_hash.clone()));
+    assert_eq!(client.try_commit_payment_hash(&trade_id, &buyer, &payment_hash), Err(Ok(Error::BuyerAlreadyConfirmed)));
+
+    // The seller can no longer confirm without revealing the secret
+    assert_eq!(client.try_confirm_payment(&trade_id, &seller), Err(Ok(Error::InvalidTradeStatus)));
+    assert_eq!(
+        client.try_confirm_payment_with_preimage(&trade_id, &seller, &Bytes::from_slice(&env, b"MPESA-REF-WRONG")),
+        Err(Ok(Error::PreimageMismatch))
+    );
+
+    client.confirm_payment_with_preimage(&trade_id, &seller, &secret);
+    let revealed = events_with_topic(&env, PAYMENT_REVEALED);
+    assert_eq!(revealed.len(), 2);
+    let data = <(u64, Bytes)>::try_from_val(&env, &revealed[0].1).unwrap();
+    assert_eq!(data, (trade_id, secret));
+    assert_eq!(client.get_trade(&trade_id).unwrap().status, TradeStatus::Completed);
+    assert!(usdc_client.balance(&buyer) > 0);
+}
+
+#[test]
+fn test_error_info_covers_every_error() {
+    extern crate std;
+    let (env, client, _, _, _, _) = setup_test_env();
+    let published = include_str!("../../../contract_errors.json");
+
+    // Every code up to the newest variant has a row, named as in the published table
+    let last = Error::ProposalExpired as u32;
+    for code in 1..=last {
+        let info = client.get_error_info(&code).unwrap();
+        assert_eq!(info.code, code);
+        let row = published.split(&std::format!("\"code\": {code},")).nth(1).unwrap();
+        let name = row.split('"').nth(3).unwrap();
+        assert_eq!(info.name, Symbol::new(&env, name), "code {code}");
+    }
+    assert_eq!(client.get_error_info(&0), None);
+    assert_eq!(client.get_error_info(&(last + 1)), None);
+
+    let info = client.get_error_info(&(Error::BelowMinimum as u32)).unwrap();
+    assert_eq!(info.name, Symbol::new(&env, "BelowMinimum"));
+    assert_eq!(info.category, types::ErrorCategory::Limits);
+    assert_eq!(info.severity, types::ErrorSeverity::Warning);
+    assert!(!info.retryable);
+    assert_eq!(info.params, vec![&env, Symbol::new(&env, "usdc_amount")]);
+    assert!(client.get_error_info(&(Error::ContractPaused as u32)).unwrap().retryable);
+}
+
+#[test]
+fn test_untouched_offer_expires_after_ttl() {
+    let (env, client, admin, usdc_token_id, usdc_client, contract_id) = setup_test_env();
+    let usdc_amount = 100_000_000;
+    let ttl = 30 * 86_400;
+    let (seller, buyer, offer_id, trade_id) = setup_trade(&env, &client, &admin, &usdc_token_id, &contract_id, usdc_amount);
+
+    // Disabled by default, and a non-zero TTL must leave sellers at least a day
+    assert_eq!(client.get_offer_ttl(), 0);
+    assert_eq!(client.get_offer_expiry(&offer_id), None);
+    assert_eq!(client.try_update_offer_ttl(&3_600), Err(Ok(Error::InvalidAmount)));
+    client.update_offer_ttl(&ttl);
+    assert_eq!(client.get_offer_ttl(), ttl);
+
+    // An offer with a trade in progress can't be expired
+    env.ledger().with_mut(|li| li.timestamp += ttl);
+    assert_eq!(client.try_expire_offer(&offer_id), Err(Ok(Error::TradeAlreadyInitiated)));
+
+    // Closing the trade counts as activity and restarts the TTL
+    client.cancel_trade(&trade_id, &buyer);
+    let expires_at = env.ledger().timestamp() + ttl;
+    assert_eq!(client.get_offer_expiry(&offer_id), Some(expires_at));
+    env.ledger().with_mut(|li| li.timestamp = expires_at - 1);
+    assert_eq!(client.try_expire_offer(&offer_id), Err(Ok(Error::GracePeriodActive)));
+
+    // Once the TTL has passed the offer can't be traded, and anyone can expire it, refunding the seller
+    env.ledger().with_mut(|li| li.timestamp = expires_at);
+    assert_eq!(client.try_initiate_trade(&buyer, &offer_id, &None, &None, &0, &None), Err(Ok(Error::OfferExpired)));
+    assert_eq!(client.expire_offer(&offer_id), usdc_amount);
+    assert_eq!(count_events(&env, OFFER_EXPIRED), 1);
+    assert_eq!(usdc_client.balance(&seller), usdc_amount);
+    assert_eq!(client.get_offer(&offer_id).unwrap().status, OfferStatus::Expired);
+    assert_eq!(client.get_active_offer_count(), 0);
+    assert_eq!(client.get_offer_expiry(&offer_id), None);
+    assert_eq!(client.try_expire_offer(&offer_id), Err(Ok(Error::OfferNotFound)));
+}
+
+#[test]
+fn test_undeliverable_proposal_refund_is_parked() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let xlm_token_id = env.register(hostile_token::HostileToken, ());
+    let xlm_client = HostileTokenClient::new(&env, &xlm_token_id);
+    let deposit = 5_000_000;
+    client.set_initiation_deposit(&xlm_token_id, &deposit);
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    let proposer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    xlm_client.mint(&proposer, &deposit);
+
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let proposal_id = client.propose_price(&proposer, &offer_id, &(usdc_amount * 118), &None);
+    assert_eq!(client.try_claim_proposal_deposit(&proposer, &proposal_id), Err(Ok(Error::InvalidTradeStatus)));
+
+    // A proposer who can't take the refund (e.g. a merged account) doesn't hold the seller's escrow hostage
+    xlm_client.set_hostility(&contract_id, &Hostility::FailPayouts);
+    client.cancel_offer(&seller, &offer_id);
+    assert_eq!(client.get_offer(&offer_id).unwrap().status, OfferStatus::Cancelled);
+    assert_eq!(client.get_proposal(&proposal_id), None);
+    assert_eq!(count_events(&env, DEPOSIT_PARKED), 1);
+    assert_eq!(xlm_client.balance(&contract_id), deposit);
+
+    // The deposit stays parked until the proposer can receive it
+    assert_eq!(client.try_claim_proposal_deposit(&proposer, &proposal_id), Err(Ok(Error::TokenTransferFailed)));
+    assert_eq!(client.try_claim_proposal_deposit(&seller, &proposal_id), Err(Ok(Error::Unauthorized)));
+    xlm_client.set_hostility(&contract_id, &Hostility::None);
+    assert_eq!(client.claim_proposal_deposit(&proposer, &proposal_id), deposit);
+    assert_eq!(xlm_client.balance(&proposer), deposit);
+    assert_eq!(client.try_claim_proposal_deposit(&proposer, &proposal_id), Err(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn test_accepted_proposal_carries_initiation_deposit() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (xlm_token_id, xlm_client) = create_token_contract(&env, &admin);
+    let deposit = 5_000_000;
+    client.set_initiation_deposit(&xlm_token_id, &deposit);
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount, &contract_id);
+    token::StellarAssetClient::new(&env, &xlm_token_id).mint(&buyer, &(deposit * 2));
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+
+    // The buyer posts the deposit when proposing; withdrawing returns it
+    let withdrawn = client.propose_price(&buyer, &offer_id, &(usdc_amount * 118), &None);
+    assert_eq!(xlm_client.balance(&buyer), deposit);
+    client.reject_proposal(&buyer, &withdrawn);
+    assert_eq!(xlm_client.balance(&buyer), deposit * 2);
+
+    // Acceptance is signed by the seller alone and moves the deposit onto the trade
+    let proposal_id = client.propose_price(&buyer, &offer_id, &(usdc_amount * 118), &None);
+    env.mock_auths(&[MockAuth {
+        address: &seller,
+        invoke: &MockAuthInvoke {
+            contract: &contract_id,
+            fn_name: "accept_proposal",
+            args: (&seller, proposal_id).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    let trade_id = client.accept_proposal(&seller, &proposal_id);
+    env.mock_all_auths();
+    assert_eq!(client.get_trade_deposit(&trade_id), deposit);
+    assert_eq!(xlm_client.balance(&buyer), deposit);
+    assert_eq!(xlm_client.balance(&contract_id), deposit);
+}
+
+#[test]
+fn test_proposals_refunded_when_offer_leaves_book() {
+    let (env, client, admin, usdc_token_id, _, contract_id) = setup_test_env();
+    let (xlm_token_id, xlm_client) = create_token_contract(&env, &admin);
+    let deposit = 5_000_000;
+    client.set_initiation_deposit(&xlm_token_id, &deposit);
+    let usdc_amount = 100_000_000;
+    let seller = <Address as TestAddress>::generate(&env);
+    let buyer = <Address as TestAddress>::generate(&env);
+    let outbid = <Address as TestAddress>::generate(&env);
+    setup_token_balance(&env, &admin, &usdc_token_id, &seller, usdc_amount * 2, &contract_id);
+    for address in [&buyer, &outbid] {
+        token::StellarAssetClient::new(&env, &xlm_token_id).mint(address, &deposit);
+    }
+
+    // Filling the offer drops the proposal that lost out
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let proposal_id = client.propose_price(&buyer, &offer_id, &(usdc_amount * 118), &None);
+    let lost = client.propose_price(&outbid, &offer_id, &(usdc_amount * 117), &None);
+    let trade_id = client.accept_proposal(&seller, &proposal_id);
+    client.confirm_payment(&trade_id, &buyer);
+    client.confirm_payment(&trade_id, &seller);
+    assert_eq!(client.get_proposal(&lost), None);
+    assert_eq!(xlm_client.balance(&outbid), deposit);
+    assert_eq!(xlm_client.balance(&contract_id), 0);
+
+    // So does cancelling it
+    let offer_id = client.create_offer(&seller, &usdc_amount, &(usdc_amount * 120), &None, &SettlementMode::OffChain, &None, &None, &None);
+    let cancelled = client.propose_price(&outbid, &offer_id, &(usdc_amount * 117), &None);
+    client.cancel_offer(&seller, &offer_id);
+    assert_eq!(client.get_proposal(&cancelled), None);
+    assert_eq!(xlm_client.balance(&outbid), deposit);
+    assert_eq!(client.try_reject_proposal(&outbid, &cancelled), Err(Ok(Error::ProposalNotFound)));
+}
@@ -0,0 +1,80 @@
+//! Test helpers for contracts and apps integrating with the marketplace.
+//!
+//! Enabled with the `testutils` feature so downstream integration tests can deploy a
+//! funded marketplace and inspect its events without copying this crate's test setup.
+
+extern crate std;
+
+use soroban_sdk::{
+    testutils::{Address as TestAddress, Events},
+    token, Address, Env, Symbol, TryFromVal, Val, Vec,
+};
+
+use crate::{types::FeePolicy, P2PMarketplaceContract, P2PMarketplaceContractClient};
+
+/// A marketplace deployed over a fresh Stellar asset contract standing in for USDC
+pub struct Marketplace<'a> {
+    pub client: P2PMarketplaceContractClient<'a>,
+    pub contract_id: Address,
+    pub admin: Address,
+    pub fee_collector: Address,
+    pub usdc_token_id: Address,
+    pub usdc: token::Client<'a>,
+}
+
+/// Fee policy with fees enabled and the full 0-10% range open to the admin
+pub fn default_fee_policy() -> FeePolicy {
+    FeePolicy { fees_enabled: true, fee_floor: 0, fee_ceiling: 1_000 }
+}
+
+/// Deploys and initializes a marketplace with generated admin and fee collector addresses.
+/// All authorizations are mocked on `env`.
+pub fn setup_marketplace<'a>(env: &Env) -> Marketplace<'a> {
+    setup_marketplace_with_fee_policy(env, default_fee_policy())
+}
+
+/// Like `setup_marketplace`, deploying the marketplace with the given fee policy
+pub fn setup_marketplace_with_fee_policy<'a>(env: &Env, fee_policy: FeePolicy) -> Marketplace<'a> {
+    env.mock_all_auths();
+
+    let admin = <Address as TestAddress>::generate(env);
+    let fee_collector = <Address as TestAddress>::generate(env);
+
+    let usdc_token_id = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    let usdc = token::Client::new(env, &usdc_token_id);
+
+    // The constructor initializes the marketplace atomically
+    let contract_id = env.register(P2PMarketplaceContract, (&admin, &usdc_token_id, &fee_collector, fee_policy));
+    let client = P2PMarketplaceContractClient::new(env, &contract_id);
+
+    Marketplace { client, contract_id, admin, fee_collector, usdc_token_id, usdc }
+}
+
+impl Marketplace<'_> {
+    /// Mints `amount` USDC to `user` so they can escrow it in offers.
+    /// No allowance is set: `create_offer` transfers with the seller's own authorization.
+    pub fn fund_and_approve(&self, user: &Address, amount: i128) {
+        token::StellarAssetClient::new(&self.usdc.env, &self.usdc_token_id).mint(user, &amount);
+    }
+}
+
+/// Returns the topics and data of events from the last invocation whose first topic is `topic`
+pub fn events_with_topic(env: &Env, topic: Symbol) -> std::vec::Vec<(Vec<Val>, Val)> {
+    env.events().all().iter()
+        .filter(|(_, topics, _)| {
+            topics.get(0).map(|t| Symbol::try_from_val(env, &t).ok() == Some(topic.clone())).unwrap_or(false)
+        })
+        .map(|(_, topics, data)| (topics, data))
+        .collect()
+}
+
+/// Counts events from the last invocation whose first topic is `topic`
+pub fn count_events(env: &Env, topic: Symbol) -> usize {
+    events_with_topic(env, topic).len()
+}
+
+/// Asserts that the last invocation emitted exactly `expected` events with `topic`
+pub fn assert_event_count(env: &Env, topic: Symbol, expected: usize) {
+    let found = count_events(env, topic.clone());
+    assert_eq!(found, expected, "expected {expected} {topic:?} event(s), found {found}");
+}
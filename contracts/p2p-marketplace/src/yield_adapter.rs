@@ -0,0 +1,19 @@
+//! Interface the marketplace expects from a yield adapter.
+//!
+//! An adapter wraps a lending pool (e.g. Blend) so idle escrow can earn interest.
+//! The marketplace transfers USDC to the adapter before calling `deposit`, and the
+//! adapter pays withdrawals straight back to the marketplace.
+
+use soroban_sdk::{contractclient, Address, Env};
+
+#[contractclient(name = "YieldAdapterClient")]
+pub trait YieldAdapter {
+    /// Credits `amount` of USDC, already transferred to the adapter, to `from`'s position
+    fn deposit(env: Env, from: Address, amount: i128);
+
+    /// Pays `amount` of USDC out of `to`'s position back to `to`
+    fn withdraw(env: Env, to: Address, amount: i128);
+
+    /// Returns the value of `owner`'s position, including accrued yield
+    fn balance(env: Env, owner: Address) -> i128;
+}